@@ -1,8 +1,15 @@
+pub mod asm;
 pub mod bench;
+pub mod blockchaintest;
 pub mod bytecode;
+pub mod debug;
 pub mod eofvalidation;
 pub mod evmrunner;
+pub mod node;
 pub mod statetest;
+pub mod t8n;
+pub mod tracediff;
+pub mod txtest;
 
 use clap::Parser;
 
@@ -12,14 +19,28 @@ use clap::Parser;
 pub enum MainCmd {
     /// Execute Ethereum state tests.
     Statetest(statetest::Cmd),
+    /// Execute Ethereum blockchain tests.
+    Blockchaintest(blockchaintest::Cmd),
+    /// Execute Ethereum transaction tests.
+    Txtest(txtest::Cmd),
+    /// Apply transactions to a state, compatible with geth's `evm t8n`.
+    T8n(t8n::Cmd),
+    /// Compare two EIP-3155 traces and report their first divergence.
+    Tracediff(tracediff::Cmd),
     /// Execute EOF validation tests.
     EofValidation(eofvalidation::Cmd),
     /// Run arbitrary EVM bytecode.
     Evm(evmrunner::Cmd),
+    /// Step through the execution of bytecode or a statetest.
+    Debug(debug::Cmd),
+    /// Run a local JSON-RPC node.
+    Node(node::Cmd),
     /// Print the structure of an EVM bytecode.
     Bytecode(bytecode::Cmd),
     /// Run bench from specified list.
     Bench(bench::Cmd),
+    /// Assemble EVM mnemonics into bytecode.
+    Asm(asm::Cmd),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -27,7 +48,21 @@ pub enum Error {
     #[error(transparent)]
     Statetest(#[from] statetest::Error),
     #[error(transparent)]
+    Blockchaintest(#[from] Box<blockchaintest::Error>),
+    #[error(transparent)]
+    Txtest(#[from] Box<txtest::Error>),
+    #[error(transparent)]
+    T8n(#[from] t8n::Error),
+    #[error(transparent)]
+    Tracediff(#[from] Box<tracediff::Error>),
+    #[error(transparent)]
+    Asm(#[from] asm::Error),
+    #[error(transparent)]
     EvmRunnerErrors(#[from] evmrunner::Errors),
+    #[error(transparent)]
+    Debug(#[from] debug::Error),
+    #[error(transparent)]
+    Node(#[from] node::Error),
     #[error("Eof validation failed: {:?}/{total_tests}", total_tests-failed_test)]
     EofValidation {
         failed_test: usize,
@@ -41,8 +76,14 @@ impl MainCmd {
     pub fn run(&self) -> Result<(), Error> {
         match self {
             Self::Statetest(cmd) => cmd.run().map_err(Into::into),
+            Self::Blockchaintest(cmd) => cmd.run().map_err(|e| Box::new(e).into()),
+            Self::Txtest(cmd) => cmd.run().map_err(|e| Box::new(e).into()),
+            Self::T8n(cmd) => cmd.run().map_err(Into::into),
+            Self::Tracediff(cmd) => cmd.run().map_err(|e| Box::new(e).into()),
             Self::EofValidation(cmd) => cmd.run().map_err(Into::into),
             Self::Evm(cmd) => cmd.run().map_err(Into::into),
+            Self::Debug(cmd) => cmd.run().map_err(Into::into),
+            Self::Node(cmd) => cmd.run().map_err(Into::into),
             Self::Bytecode(cmd) => {
                 cmd.run();
                 Ok(())
@@ -51,6 +92,7 @@ impl MainCmd {
                 cmd.run();
                 Ok(())
             }
+            Self::Asm(cmd) => cmd.run().map_err(Into::into),
         }
     }
 }
@@ -0,0 +1,51 @@
+use clap::Parser;
+use revm::{
+    bytecode::assembler::{assemble, assemble_eof, AsmError},
+    primitives::hex,
+};
+use std::{fs, io, io::Read, path::PathBuf};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Asm(#[from] AsmError),
+}
+
+/// `asm` subcommand.
+///
+/// Assembles EVM mnemonics into hex-encoded bytecode.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Path to the assembly source.
+    ///
+    /// If not provided, the source is read from stdin.
+    #[arg()]
+    path: Option<PathBuf>,
+    /// Assemble an EOF container instead of legacy bytecode.
+    #[arg(long)]
+    eof: bool,
+}
+
+impl Cmd {
+    /// Runs asm command.
+    pub fn run(&self) -> Result<(), Error> {
+        let source = match &self.path {
+            Some(path) => fs::read_to_string(path)?,
+            None => {
+                let mut source = String::new();
+                io::stdin().read_to_string(&mut source)?;
+                source
+            }
+        };
+
+        let code = if self.eof {
+            assemble_eof(&source)?.raw
+        } else {
+            assemble(&source)?
+        };
+        println!("{}", hex::encode_prefixed(code));
+        Ok(())
+    }
+}
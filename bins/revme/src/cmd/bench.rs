@@ -1,6 +1,8 @@
 pub mod analysis;
 pub mod burntpix;
+pub mod dispatch;
 pub mod snailtracer;
+pub mod stack;
 pub mod transfer;
 
 use clap::{Parser, ValueEnum};
@@ -9,7 +11,9 @@ use clap::{Parser, ValueEnum};
 pub enum BenchName {
     Analysis,
     Burntpix,
+    Dispatch,
     Snailtracer,
+    Stack,
     Transfer,
 }
 
@@ -17,7 +21,9 @@ impl BenchName {
     pub const ALL: &[BenchName] = &[
         BenchName::Analysis,
         BenchName::Burntpix,
+        BenchName::Dispatch,
         BenchName::Snailtracer,
+        BenchName::Stack,
         BenchName::Transfer,
     ];
 
@@ -25,7 +31,9 @@ impl BenchName {
         match self {
             BenchName::Analysis => "analysis",
             BenchName::Burntpix => "burntpix",
+            BenchName::Dispatch => "dispatch",
             BenchName::Snailtracer => "snailtracer",
+            BenchName::Stack => "stack",
             BenchName::Transfer => "transfer",
         }
     }
@@ -44,7 +52,9 @@ impl Cmd {
         match self.name {
             BenchName::Analysis => analysis::run(),
             BenchName::Burntpix => burntpix::run(),
+            BenchName::Dispatch => dispatch::run(),
             BenchName::Snailtracer => snailtracer::run(),
+            BenchName::Stack => stack::run(),
             BenchName::Transfer => transfer::run(),
         }
     }
@@ -0,0 +1,55 @@
+use std::time::{Duration, Instant};
+
+use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+use revm::{
+    bytecode::Bytecode,
+    primitives::{bytes, hex, Bytes, TxKind},
+    transact_main, Context,
+};
+
+/// Counts down from 100000 in a loop.
+const COUNTDOWN: &str = "620186a05b600190038060045700";
+
+/// Number of rounds of every benchmark, the fastest one is reported.
+const ROUNDS: u32 = 20;
+
+pub fn run() {
+    compare("Countdown", COUNTDOWN, Bytes::new());
+    compare("Snailtracer", super::snailtracer::BYTES, bytes!("30627b7c"));
+}
+
+/// Compares the dispatch loop with decoded dispatch, without and with block precharging.
+///
+/// Rounds of all of them are interleaved so that they run under the same load.
+fn compare(name: &str, code: &str, data: Bytes) {
+    let bytecode = Bytecode::new_raw(Bytes::from(hex::decode(code.trim()).unwrap()));
+    let modes = [(false, false), (true, false), (true, true)];
+    let mut contexts = modes.map(|(decoded_dispatch, block_precharge)| {
+        Context::builder()
+            .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
+            .modify_tx_chained(|tx| {
+                tx.caller = BENCH_CALLER;
+                tx.kind = TxKind::Call(BENCH_TARGET);
+                tx.data = data.clone();
+                tx.gas_limit = 1_000_000_000;
+            })
+            .modify_cfg_chained(|cfg| {
+                cfg.decoded_dispatch = decoded_dispatch;
+                cfg.block_precharge = block_precharge;
+            })
+    });
+
+    let mut fastest = [Duration::MAX; 3];
+    for _ in 0..ROUNDS {
+        for (context, fastest) in contexts.iter_mut().zip(&mut fastest) {
+            let time = Instant::now();
+            let result = transact_main(context).unwrap().result;
+            *fastest = (*fastest).min(time.elapsed());
+            assert!(result.is_success(), "{name} failed: {result:?}");
+        }
+    }
+    let [dispatch_loop, decoded_dispatch, block_precharge] = fastest;
+    println!("{name}, dispatch loop: {dispatch_loop:?}");
+    println!("{name}, decoded dispatch: {decoded_dispatch:?}");
+    println!("{name}, decoded dispatch with block precharge: {block_precharge:?}");
+}
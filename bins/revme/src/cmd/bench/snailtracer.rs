@@ -27,4 +27,4 @@ pub fn run() {
     println!("elapsed: {:?}", elapsed);
 }
 
-const BYTES: &str = include_str!("snailtracer.hex");
+pub(super) const BYTES: &str = include_str!("snailtracer.hex");
@@ -0,0 +1,92 @@
+use std::time::Instant;
+
+use database::{BenchmarkDB, BENCH_CALLER, BENCH_TARGET};
+use revm::{
+    bytecode::Bytecode,
+    interpreter::{SharedStack, Stack, STACK_LIMIT},
+    primitives::{hex, Bytes, TxKind, U256},
+    transact_main, Context,
+};
+
+/// Calls itself until the call depth limit is reached.
+const DEEP_CALLS: &str = "5f5f5f5f5f305af100";
+
+/// Calls itself 10000 times in a loop. The inner calls have non empty calldata and stop.
+const SIBLING_CALLS: &str = "36601b576127105b5f5f60015f5f305af1506001900380600757005b00";
+
+/// Depth of the call chain in the allocation benchmark.
+const DEPTH: usize = 1024;
+
+/// Number of words every frame pushes in the allocation benchmark.
+const WORDS: usize = 8;
+
+/// Number of rounds of every benchmark.
+const ROUNDS: u32 = 100;
+
+pub fn run() {
+    allocation();
+    execution("Deep calls", DEEP_CALLS);
+    execution("Sibling calls", SIBLING_CALLS);
+}
+
+/// Compares allocating a fresh stack per frame with taking it from a [`SharedStack`]
+/// in a chain of nested calls.
+fn allocation() {
+    let time = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut stacks = Vec::with_capacity(DEPTH);
+        for _ in 0..DEPTH {
+            let mut stack = Stack::with_limit(STACK_LIMIT);
+            push_words(&mut stack);
+            stacks.push(stack);
+        }
+        drop(stacks);
+    }
+    println!("Stack per frame: {:?}", time.elapsed() / ROUNDS);
+
+    let mut shared = SharedStack::new();
+    let time = Instant::now();
+    for _ in 0..ROUNDS {
+        let mut stacks = Vec::with_capacity(DEPTH);
+        for _ in 0..DEPTH {
+            if let Some(caller) = stacks.last_mut() {
+                shared.suspend(caller);
+            }
+            let mut stack = shared.new_stack(STACK_LIMIT);
+            push_words(&mut stack);
+            stacks.push(stack);
+        }
+        while let Some(mut stack) = stacks.pop() {
+            shared.free_stack(&mut stack);
+            if let Some(caller) = stacks.last_mut() {
+                shared.resume(caller);
+            }
+        }
+    }
+    println!("Shared stack: {:?}", time.elapsed() / ROUNDS);
+}
+
+fn push_words(stack: &mut Stack) {
+    for i in 0..WORDS {
+        assert!(stack.push(U256::from(i)));
+    }
+}
+
+fn execution(name: &str, code: &str) {
+    let bytecode = Bytecode::new_raw(Bytes::from(hex::decode(code).unwrap()));
+
+    let mut context = Context::builder()
+        .with_db(BenchmarkDB::new_bytecode(bytecode))
+        .modify_tx_chained(|tx| {
+            tx.caller = BENCH_CALLER;
+            tx.kind = TxKind::Call(BENCH_TARGET);
+            tx.gas_limit = u64::MAX;
+        });
+
+    let time = Instant::now();
+    for _ in 0..ROUNDS {
+        let result = transact_main(&mut context).unwrap().result;
+        assert!(result.is_success(), "{name} failed: {result:?}");
+    }
+    println!("{name}: {:?}", time.elapsed() / ROUNDS);
+}
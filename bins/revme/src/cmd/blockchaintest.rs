@@ -0,0 +1,48 @@
+pub mod block;
+pub mod rlp;
+mod runner;
+
+pub use runner::TestError as Error;
+
+use crate::cmd::statetest::find_all_json_tests;
+use clap::Parser;
+use runner::{run, TestError};
+use std::path::PathBuf;
+
+/// `blockchaintest` subcommand
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Path to folder or file containing the tests
+    ///
+    /// If multiple paths are specified they will be run in sequence.
+    ///
+    /// Folders will be searched recursively for files with the extension `.json`.
+    #[clap(required = true, num_args = 1..)]
+    paths: Vec<PathBuf>,
+    /// Run tests in a single thread
+    #[clap(short = 's', long)]
+    single_thread: bool,
+    /// Output outcome of every test in JSON format
+    #[clap(short = 'o', long)]
+    json_outcome: bool,
+    /// Keep going after a test failure
+    #[clap(long, alias = "no-fail-fast")]
+    keep_going: bool,
+}
+
+impl Cmd {
+    /// Runs `blockchaintest` command.
+    pub fn run(&self) -> Result<(), TestError> {
+        for path in &self.paths {
+            println!("\nRunning tests in {}...", path.display());
+            let test_files = find_all_json_tests(path);
+            run(
+                test_files,
+                self.single_thread,
+                self.json_outcome,
+                self.keep_going,
+            )?
+        }
+        Ok(())
+    }
+}
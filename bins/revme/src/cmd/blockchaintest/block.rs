@@ -0,0 +1,975 @@
+use crate::cmd::statetest::merkle_trie::{ordered_trie_root, state_merkle_trie_root};
+use alloy_rlp::Encodable;
+use alloy_sol_macro::sol;
+use alloy_sol_types::SolEvent;
+use builder::{max_blob_gas, Receipt, ReceiptOutcome};
+use database::{CacheState, State};
+use revm::{
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::{
+        block::calc_excess_blob_gas,
+        result::{ExecutionResult, ResultAndState},
+    },
+    database_interface::EmptyDB,
+    primitives::{address, alloy_primitives::Bloom, keccak256, Address, Bytes, TxKind, B256, U256},
+    specification::{
+        eip4844::{
+            GAS_PER_BLOB, TARGET_BLOB_GAS_PER_BLOCK_CANCUN, TARGET_BLOB_GAS_PER_BLOCK_PRAGUE,
+        },
+        hardfork::SpecId,
+    },
+    Context, Database, DatabaseCommit, ExecuteCommitEvm, ExecuteEvm,
+};
+use sha2::{Digest, Sha256};
+use statetest_types::{BlockHeader, BlockTransaction, DecodedBlock, SpecName, Withdrawal};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Caller of the system contracts.
+const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+/// EIP-4788 beacon block roots contract.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+/// EIP-2935 block hashes contract.
+pub const HISTORY_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
+/// EIP-7002 withdrawal requests contract.
+const WITHDRAWAL_REQUEST_ADDRESS: Address = address!("00000961Ef480Eb55e80D19ad83579A64c007002");
+/// EIP-7251 consolidation requests contract.
+const CONSOLIDATION_REQUEST_ADDRESS: Address = address!("0000BBdDc7CE488642fb579F8B00f3a590007251");
+/// EIP-6110 deposit contract, the mainnet address is used by the tests.
+const DEPOSIT_CONTRACT_ADDRESS: Address = address!("00000000219ab540356cBB839Cbe05303d7705Fa");
+/// Gas limit of a system call, it is not counted in the block gas.
+const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// EIP-1559 base fee of the first London block.
+pub const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+const ELASTICITY_MULTIPLIER: u64 = 2;
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+const MIN_GAS_LIMIT: u64 = 5000;
+/// Maximum number of ommers of a block.
+const MAX_OMMERS: usize = 2;
+/// Maximum distance of the number of an ommer to the number of the block including it.
+pub const MAX_OMMER_DEPTH: u64 = 6;
+
+const ONE_ETHER: u128 = 1_000_000_000_000_000_000;
+const GWEI_TO_WEI: u128 = 1_000_000_000;
+
+sol! {
+    event DepositEvent(
+        bytes pubkey,
+        bytes withdrawal_credentials,
+        bytes amount,
+        bytes signature,
+        bytes index
+    );
+}
+
+/// Reason for rejecting a block.
+#[derive(Debug, Error)]
+pub enum BlockError {
+    #[error("invalid RLP: {0}")]
+    Rlp(alloy_rlp::Error),
+    #[error("invalid header: {0}")]
+    InvalidHeader(&'static str),
+    #[error("invalid ommer {index}: {error}")]
+    InvalidOmmer {
+        index: usize,
+        error: Box<BlockError>,
+    },
+    #[error("{field} mismatch: got {got}, expected {expected}")]
+    Mismatch {
+        field: &'static str,
+        got: String,
+        expected: String,
+    },
+    #[error("transaction {index} has no sender")]
+    MissingSender { index: usize },
+    #[error("transaction {index} exceeds the block gas limit")]
+    BlockGasLimitExceeded { index: usize },
+    #[error("transaction {index} exceeds the block blob gas limit")]
+    BlobGasLimitExceeded { index: usize },
+    #[error("transaction {index} is invalid: {error}")]
+    Transaction { index: usize, error: String },
+    #[error("system call to {address} failed: {error}")]
+    SystemCall { address: Address, error: String },
+    #[error("invalid deposit event")]
+    InvalidDepositEvent,
+}
+
+impl BlockError {
+    fn mismatch(field: &'static str, got: impl ToString, expected: impl ToString) -> Self {
+        Self::Mismatch {
+            field,
+            got: got.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+}
+
+impl From<alloy_rlp::Error> for BlockError {
+    fn from(error: alloy_rlp::Error) -> Self {
+        Self::Rlp(error)
+    }
+}
+
+/// Block with the environment of its transactions.
+#[derive(Clone, Debug)]
+pub struct ExecutableBlock {
+    pub header: BlockHeader,
+    /// Transaction environments, `None` if the sender is unknown.
+    pub transactions: Vec<Option<TxEnv>>,
+    pub uncle_headers: Vec<BlockHeader>,
+    /// Withdrawals, present since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl ExecutableBlock {
+    /// Returns the block of the decoded fields, `None` if there is no header.
+    pub fn from_decoded(block: &DecodedBlock) -> Option<Self> {
+        Some(Self {
+            header: block.block_header.clone()?,
+            transactions: block.transactions.iter().map(tx_env).collect(),
+            uncle_headers: block.uncle_headers.clone(),
+            withdrawals: block.withdrawals.clone(),
+        })
+    }
+}
+
+/// Recent block of the chain.
+#[derive(Clone, Debug)]
+pub struct Ancestor {
+    pub header: BlockHeader,
+    /// Hashes of the ommers included by the block.
+    pub ommers: Vec<B256>,
+}
+
+/// Chain state the next block is executed on.
+#[derive(Clone, Debug)]
+pub struct Chain {
+    pub network: SpecName,
+    pub chain_id: u64,
+    pub cache: CacheState,
+    pub parent: BlockHeader,
+    pub block_hashes: BTreeMap<u64, B256>,
+    /// Last blocks up to the parent, ommers must be children of one of them.
+    ///
+    /// Only the last [`MAX_OMMER_DEPTH`] + 1 blocks are needed.
+    pub ancestors: Vec<Ancestor>,
+}
+
+impl Chain {
+    /// Executes the block on top of the chain, returns the state after the block.
+    ///
+    /// The chain is not modified, an invalid block doesn't change anything.
+    pub fn execute_block(&self, block: &ExecutableBlock) -> Result<CacheState, BlockError> {
+        let header = &block.header;
+        let number = header.number.saturating_to();
+        let spec = self
+            .network
+            .spec_at(number, header.timestamp.saturating_to());
+        let parent_spec = self.network.spec_at(
+            self.parent.number.saturating_to(),
+            self.parent.timestamp.saturating_to(),
+        );
+        validate_header(header, &self.parent, spec, parent_spec)?;
+        self.validate_ommers(block, spec)?;
+
+        let mut cache = self.cache.clone();
+        cache.set_state_clear_flag(spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+        let mut state = State::builder()
+            .with_cached_prestate(cache)
+            .with_block_hashes(self.block_hashes.clone())
+            .build();
+
+        let mut cfg = CfgEnv::default();
+        cfg.chain_id = self.chain_id;
+        cfg.spec = spec;
+        let block_env = block_env(header, spec);
+
+        // Pre-block system calls.
+        if spec.is_enabled_in(SpecId::CANCUN) {
+            let root = header
+                .parent_beacon_block_root
+                .ok_or(BlockError::InvalidHeader(
+                    "missing parent beacon block root",
+                ))?;
+            system_call(
+                &mut state,
+                &block_env,
+                &cfg,
+                BEACON_ROOTS_ADDRESS,
+                root.into(),
+            )?;
+        }
+        if spec.is_enabled_in(SpecId::PRAGUE) {
+            system_call(
+                &mut state,
+                &block_env,
+                &cfg,
+                HISTORY_STORAGE_ADDRESS,
+                header.parent_hash.into(),
+            )?;
+        }
+
+        // Transactions.
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        let mut gas_used = 0u64;
+        let mut blob_gas_used = 0u64;
+        let max_blob_gas = max_blob_gas(spec);
+        for (index, tx) in block.transactions.iter().enumerate() {
+            let tx = tx.as_ref().ok_or(BlockError::MissingSender { index })?;
+            if gas_used.saturating_add(tx.gas_limit) > block_env.gas_limit {
+                return Err(BlockError::BlockGasLimitExceeded { index });
+            }
+            blob_gas_used += tx.blob_hashes.len() as u64 * GAS_PER_BLOB;
+            if blob_gas_used > max_blob_gas {
+                return Err(BlockError::BlobGasLimitExceeded { index });
+            }
+
+            let result = Context::builder()
+                .with_block(&block_env)
+                .with_tx(tx)
+                .with_cfg(&cfg)
+                .with_db(&mut state)
+                .exec_commit_previous()
+                .map_err(|error| BlockError::Transaction {
+                    index,
+                    error: error.to_string(),
+                })?;
+            gas_used += result.gas_used();
+
+            // Receipts before Byzantium have the intermediate state root instead of the status.
+            let outcome = if spec.is_enabled_in(SpecId::BYZANTIUM) {
+                ReceiptOutcome::Status(result.is_success())
+            } else {
+                ReceiptOutcome::StateRoot(state_merkle_trie_root(state.cache.trie_account()))
+            };
+            receipts.push(Receipt::new(
+                tx.tx_type,
+                outcome,
+                gas_used,
+                result.into_logs(),
+            ));
+        }
+
+        if spec.is_enabled_in(SpecId::CANCUN) {
+            let expected = header.blob_gas_used.map(|gas| gas.saturating_to());
+            if Some(blob_gas_used) != expected {
+                return Err(BlockError::mismatch(
+                    "blob gas used",
+                    blob_gas_used,
+                    format!("{expected:?}"),
+                ));
+            }
+        }
+
+        // Block and uncle rewards before the Merge, withdrawals after Shanghai. Ommers are
+        // validated to be below the block.
+        let ommers = block.uncle_headers.iter().map(|uncle| {
+            (
+                uncle.coinbase,
+                (header.number - uncle.number).saturating_to::<u64>(),
+            )
+        });
+        let rewards = rewards(header.coinbase, block_reward(spec), ommers);
+        state
+            .increment_balances(rewards)
+            .expect("EmptyDB is infallible");
+        match (&block.withdrawals, header.withdrawals_root) {
+            (Some(withdrawals), Some(expected)) if spec.is_enabled_in(SpecId::SHANGHAI) => {
+                let root = withdrawals_root(withdrawals);
+                if root != expected {
+                    return Err(BlockError::mismatch("withdrawals root", root, expected));
+                }
+                state
+                    .increment_balances(withdrawal_balances(withdrawals))
+                    .expect("EmptyDB is infallible");
+            }
+            (None, None) if !spec.is_enabled_in(SpecId::SHANGHAI) => {}
+            _ => return Err(BlockError::InvalidHeader("withdrawals")),
+        }
+
+        // Post-block requests.
+        if spec.is_enabled_in(SpecId::PRAGUE) {
+            let requests = block_requests(&mut state, &block_env, &cfg, &receipts)?;
+            let hash = requests_hash(&requests);
+            if Some(hash) != header.requests_hash {
+                return Err(BlockError::mismatch(
+                    "requests hash",
+                    hash,
+                    format!("{:?}", header.requests_hash),
+                ));
+            }
+        }
+
+        let expected_gas_used = header.gas_used.saturating_to::<u64>();
+        if gas_used != expected_gas_used {
+            return Err(BlockError::mismatch(
+                "gas used",
+                gas_used,
+                expected_gas_used,
+            ));
+        }
+        let bloom = receipts
+            .iter()
+            .fold(Bloom::ZERO, |bloom, receipt| bloom | receipt.bloom);
+        if bloom != header.bloom {
+            return Err(BlockError::mismatch("logs bloom", bloom, header.bloom));
+        }
+        let receipts_root = ordered_trie_root(receipts.iter().map(Receipt::encoded));
+        if receipts_root != header.receipt_trie {
+            return Err(BlockError::mismatch(
+                "receipts root",
+                receipts_root,
+                header.receipt_trie,
+            ));
+        }
+        let state_root = state_merkle_trie_root(state.cache.trie_account());
+        if state_root != header.state_root {
+            return Err(BlockError::mismatch(
+                "state root",
+                state_root,
+                header.state_root,
+            ));
+        }
+
+        Ok(state.cache)
+    }
+
+    /// Checks the ommers of the block against its `uncle_hash` and the recent blocks.
+    ///
+    /// An ommer is a valid header of a child of one of the last [`MAX_OMMER_DEPTH`] blocks
+    /// that is neither one of those blocks nor included by them or twice by this block.
+    fn validate_ommers(&self, block: &ExecutableBlock, spec: SpecId) -> Result<(), BlockError> {
+        let header = &block.header;
+        let ommers = &block.uncle_headers;
+        let uncle_hash = keccak256(rlp_list(ommers.iter().flat_map(header_rlp).collect()));
+        if uncle_hash != header.uncle_hash {
+            return Err(BlockError::mismatch(
+                "uncle hash",
+                uncle_hash,
+                header.uncle_hash,
+            ));
+        }
+        if ommers.is_empty() {
+            return Ok(());
+        }
+        if spec.is_enabled_in(SpecId::MERGE) {
+            return Err(BlockError::InvalidHeader("ommers after the Merge"));
+        }
+        if ommers.len() > MAX_OMMERS {
+            return Err(BlockError::InvalidHeader("too many ommers"));
+        }
+
+        let mut included = self
+            .ancestors
+            .iter()
+            .flat_map(|ancestor| ancestor.ommers.iter().copied())
+            .collect::<Vec<_>>();
+        for (index, ommer) in ommers.iter().enumerate() {
+            let invalid = |error| BlockError::InvalidOmmer {
+                index,
+                error: Box::new(error),
+            };
+            if included.contains(&ommer.hash) {
+                return Err(invalid(BlockError::InvalidHeader("already included")));
+            }
+            if self
+                .ancestors
+                .iter()
+                .any(|ancestor| ancestor.header.hash == ommer.hash)
+            {
+                return Err(invalid(BlockError::InvalidHeader("ommer is an ancestor")));
+            }
+            let is_in_depth = ommer.number < header.number
+                && header.number - ommer.number <= U256::from(MAX_OMMER_DEPTH);
+            if !is_in_depth {
+                return Err(invalid(BlockError::InvalidHeader("depth")));
+            }
+            let parent = self
+                .ancestors
+                .iter()
+                .map(|ancestor| &ancestor.header)
+                .find(|ancestor| ancestor.hash == ommer.parent_hash)
+                .ok_or_else(|| invalid(BlockError::InvalidHeader("parent is not an ancestor")))?;
+            let spec_at = |header: &BlockHeader| {
+                self.network.spec_at(
+                    header.number.saturating_to(),
+                    header.timestamp.saturating_to(),
+                )
+            };
+            validate_header(ommer, parent, spec_at(ommer), spec_at(parent)).map_err(invalid)?;
+            included.push(ommer.hash);
+        }
+        Ok(())
+    }
+}
+
+/// Checks the header fields that follow from the parent header.
+fn validate_header(
+    header: &BlockHeader,
+    parent: &BlockHeader,
+    spec: SpecId,
+    parent_spec: SpecId,
+) -> Result<(), BlockError> {
+    let hash = header_hash(header);
+    if hash != header.hash {
+        return Err(BlockError::mismatch("header hash", hash, header.hash));
+    }
+    if header.parent_hash != parent.hash {
+        return Err(BlockError::InvalidHeader("parent hash"));
+    }
+    if header.number != parent.number + U256::from(1) {
+        return Err(BlockError::InvalidHeader("number"));
+    }
+    if header.timestamp <= parent.timestamp {
+        return Err(BlockError::InvalidHeader("timestamp"));
+    }
+    if header.extra_data.len() > 32 {
+        return Err(BlockError::InvalidHeader("extra data"));
+    }
+    if header.gas_used > header.gas_limit {
+        return Err(BlockError::InvalidHeader("gas used"));
+    }
+
+    // The gas target is kept at the London transition, so the limit is doubled.
+    let is_london_transition =
+        spec.is_enabled_in(SpecId::LONDON) && !parent_spec.is_enabled_in(SpecId::LONDON);
+    let mut parent_gas_limit = parent.gas_limit.saturating_to::<u64>();
+    if is_london_transition {
+        parent_gas_limit *= ELASTICITY_MULTIPLIER;
+    }
+    let gas_limit = header.gas_limit.saturating_to::<u64>();
+    if gas_limit.abs_diff(parent_gas_limit) >= parent_gas_limit / GAS_LIMIT_BOUND_DIVISOR
+        || gas_limit < MIN_GAS_LIMIT
+    {
+        return Err(BlockError::InvalidHeader("gas limit"));
+    }
+
+    if spec.is_enabled_in(SpecId::LONDON) {
+        let base_fee = if is_london_transition {
+            INITIAL_BASE_FEE
+        } else {
+            next_base_fee(
+                parent.base_fee_per_gas.unwrap_or_default().saturating_to(),
+                parent.gas_used.saturating_to(),
+                parent.gas_limit.saturating_to(),
+            )
+        };
+        if header.base_fee_per_gas != Some(U256::from(base_fee)) {
+            return Err(BlockError::InvalidHeader("base fee"));
+        }
+    }
+
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        let excess_blob_gas = excess_blob_gas(
+            parent.excess_blob_gas.unwrap_or_default().saturating_to(),
+            parent.blob_gas_used.unwrap_or_default().saturating_to(),
+            spec,
+        );
+        if header.excess_blob_gas != Some(U256::from(excess_blob_gas)) {
+            return Err(BlockError::InvalidHeader("excess blob gas"));
+        }
+    }
+
+    let is_merge = spec.is_enabled_in(SpecId::MERGE);
+    if is_merge && (!header.difficulty.is_zero() || !header.nonce.is_zero()) {
+        return Err(BlockError::InvalidHeader("difficulty"));
+    }
+    Ok(())
+}
+
+/// Returns the keccak hash of the RLP encoded header.
+pub fn header_hash(header: &BlockHeader) -> B256 {
+    keccak256(header_rlp(header))
+}
+
+/// Returns the RLP encoding of the header.
+pub fn header_rlp(header: &BlockHeader) -> Vec<u8> {
+    let mut payload = Vec::new();
+    header.parent_hash.encode(&mut payload);
+    header.uncle_hash.encode(&mut payload);
+    header.coinbase.encode(&mut payload);
+    header.state_root.encode(&mut payload);
+    header.transactions_trie.encode(&mut payload);
+    header.receipt_trie.encode(&mut payload);
+    header.bloom.encode(&mut payload);
+    header.difficulty.encode(&mut payload);
+    header.number.encode(&mut payload);
+    header.gas_limit.encode(&mut payload);
+    header.gas_used.encode(&mut payload);
+    header.timestamp.encode(&mut payload);
+    header.extra_data.encode(&mut payload);
+    header.mix_hash.encode(&mut payload);
+    header.nonce.encode(&mut payload);
+    // Fields added by forks are appended in order.
+    let optional = [
+        header.base_fee_per_gas.map(alloy_rlp::encode),
+        header.withdrawals_root.map(alloy_rlp::encode),
+        header.blob_gas_used.map(alloy_rlp::encode),
+        header.excess_blob_gas.map(alloy_rlp::encode),
+        header.parent_beacon_block_root.map(alloy_rlp::encode),
+        header.requests_hash.map(alloy_rlp::encode),
+    ];
+    for field in optional.into_iter().flatten() {
+        payload.extend_from_slice(&field);
+    }
+    rlp_list(payload)
+}
+
+/// EIP-1559 base fee of the child of a block with the given base fee, gas used and gas limit.
+pub fn next_base_fee(base_fee: u64, gas_used: u64, gas_limit: u64) -> u64 {
+    let gas_target = gas_limit / ELASTICITY_MULTIPLIER;
+    if gas_target == 0 || gas_used == gas_target {
+        return base_fee;
+    }
+    let change = |delta: u64| {
+        (base_fee as u128 * delta as u128
+            / gas_target as u128
+            / BASE_FEE_MAX_CHANGE_DENOMINATOR as u128) as u64
+    };
+    if gas_used > gas_target {
+        base_fee + change(gas_used - gas_target).max(1)
+    } else {
+        base_fee - change(gas_target - gas_used)
+    }
+}
+
+/// EIP-4844 excess blob gas of the child of a block, with the blob target of `spec`.
+pub fn excess_blob_gas(
+    parent_excess_blob_gas: u64,
+    parent_blob_gas_used: u64,
+    spec: SpecId,
+) -> u64 {
+    let target = if spec.is_enabled_in(SpecId::PRAGUE) {
+        TARGET_BLOB_GAS_PER_BLOCK_PRAGUE
+    } else {
+        TARGET_BLOB_GAS_PER_BLOCK_CANCUN
+    };
+    calc_excess_blob_gas(parent_excess_blob_gas, parent_blob_gas_used, target)
+}
+
+fn block_env(header: &BlockHeader, spec: SpecId) -> BlockEnv {
+    let mut block = BlockEnv {
+        number: header.number.saturating_to(),
+        beneficiary: header.coinbase,
+        timestamp: header.timestamp.saturating_to(),
+        gas_limit: header.gas_limit.saturating_to(),
+        basefee: header.base_fee_per_gas.unwrap_or_default().saturating_to(),
+        difficulty: header.difficulty,
+        // After the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
+        prevrandao: spec.is_enabled_in(SpecId::MERGE).then_some(header.mix_hash),
+        blob_excess_gas_and_price: None,
+    };
+    if let Some(excess_blob_gas) = header.excess_blob_gas {
+        block.set_blob_excess_gas_and_price(
+            excess_blob_gas.saturating_to(),
+            spec.is_enabled_in(SpecId::PRAGUE),
+        );
+    }
+    block
+}
+
+/// Returns the transaction environment, `None` if the sender is unknown.
+fn tx_env(transaction: &BlockTransaction) -> Option<TxEnv> {
+    let tx_type = transaction.tx_type.saturating_to::<u8>();
+    // Legacy transactions have the chain id in `v` since EIP-155.
+    let chain_id = match transaction.chain_id {
+        Some(chain_id) => Some(chain_id.saturating_to()),
+        None if tx_type == 0 && transaction.v >= U256::from(35) => {
+            Some(((transaction.v - U256::from(35)) / U256::from(2)).saturating_to())
+        }
+        None => None,
+    };
+    Some(TxEnv {
+        tx_type,
+        caller: transaction.sender?,
+        gas_limit: transaction.gas_limit.saturating_to(),
+        gas_price: transaction
+            .gas_price
+            .or(transaction.max_fee_per_gas)
+            .unwrap_or_default()
+            .saturating_to(),
+        kind: match transaction.to {
+            Some(address) => TxKind::Call(address),
+            None => TxKind::Create,
+        },
+        value: transaction.value,
+        data: transaction.data.clone(),
+        nonce: transaction.nonce.saturating_to(),
+        chain_id,
+        access_list: transaction
+            .access_list
+            .iter()
+            .flatten()
+            .map(|item| (item.address, item.storage_keys.clone()))
+            .collect(),
+        gas_priority_fee: transaction
+            .max_priority_fee_per_gas
+            .map(|fee| fee.saturating_to()),
+        blob_hashes: transaction.blob_versioned_hashes.clone(),
+        max_fee_per_blob_gas: transaction
+            .max_fee_per_blob_gas
+            .unwrap_or_default()
+            .saturating_to(),
+        authorization_list: transaction
+            .authorization_list
+            .iter()
+            .flatten()
+            .cloned()
+            .map(Into::into)
+            .collect(),
+    })
+}
+
+/// Calls a system contract and commits its changes, returns the output.
+///
+/// The call is skipped if the contract has no code. Changes to the system address and the
+/// beneficiary are dropped as the call must not touch them.
+pub fn system_call(
+    state: &mut State<EmptyDB>,
+    block: &BlockEnv,
+    cfg: &CfgEnv,
+    address: Address,
+    data: Bytes,
+) -> Result<Bytes, BlockError> {
+    let has_code = state
+        .basic(address)
+        .expect("EmptyDB is infallible")
+        .is_some_and(|account| !account.is_empty_code_hash());
+    if !has_code {
+        return Ok(Bytes::new());
+    }
+
+    let block = BlockEnv {
+        beneficiary: SYSTEM_ADDRESS,
+        basefee: 0,
+        gas_limit: u64::MAX,
+        ..block.clone()
+    };
+    let tx = TxEnv {
+        caller: SYSTEM_ADDRESS,
+        kind: TxKind::Call(address),
+        data,
+        gas_limit: SYSTEM_CALL_GAS_LIMIT,
+        gas_price: 0,
+        gas_priority_fee: None,
+        chain_id: None,
+        ..Default::default()
+    };
+    let error = |error: String| BlockError::SystemCall { address, error };
+    let ResultAndState {
+        result,
+        state: mut changes,
+    } = Context::builder()
+        .with_block(&block)
+        .with_tx(&tx)
+        .with_cfg(cfg)
+        .with_db(&mut *state)
+        .exec_previous()
+        .map_err(|e| error(e.to_string()))?;
+    changes.remove(&SYSTEM_ADDRESS);
+    state.commit(changes);
+
+    match result {
+        ExecutionResult::Success { output, .. } => Ok(output.into_data()),
+        result => Err(error(format!("{result:?}"))),
+    }
+}
+
+/// Block reward of the miner, zero since the Merge.
+pub fn block_reward(spec: SpecId) -> u128 {
+    if spec.is_enabled_in(SpecId::MERGE) {
+        0
+    } else if spec.is_enabled_in(SpecId::PETERSBURG) {
+        2 * ONE_ETHER
+    } else if spec.is_enabled_in(SpecId::BYZANTIUM) {
+        3 * ONE_ETHER
+    } else {
+        5 * ONE_ETHER
+    }
+}
+
+/// Rewards of the miner and of the ommers, given by their beneficiary and the distance of their
+/// number to the block number.
+pub fn rewards(
+    coinbase: Address,
+    block_reward: u128,
+    ommers: impl ExactSizeIterator<Item = (Address, u64)>,
+) -> Vec<(Address, u128)> {
+    let mut rewards = vec![(
+        coinbase,
+        block_reward + block_reward / 32 * ommers.len() as u128,
+    )];
+    for (ommer, delta) in ommers {
+        let reward = 8u128.saturating_sub(delta as u128) * block_reward / 8;
+        rewards.push((ommer, reward));
+    }
+    rewards
+}
+
+/// Root of the withdrawals trie.
+pub fn withdrawals_root(withdrawals: &[Withdrawal]) -> B256 {
+    ordered_trie_root(withdrawals.iter().map(withdrawal_rlp))
+}
+
+/// Balance increments of the withdrawals in wei.
+pub fn withdrawal_balances(
+    withdrawals: &[Withdrawal],
+) -> impl Iterator<Item = (Address, u128)> + '_ {
+    withdrawals.iter().map(|withdrawal| {
+        (
+            withdrawal.address,
+            withdrawal.amount.saturating_to::<u128>() * GWEI_TO_WEI,
+        )
+    })
+}
+
+pub fn withdrawal_rlp(withdrawal: &Withdrawal) -> Vec<u8> {
+    let mut payload = Vec::new();
+    withdrawal.index.encode(&mut payload);
+    withdrawal.validator_index.encode(&mut payload);
+    withdrawal.address.encode(&mut payload);
+    withdrawal.amount.encode(&mut payload);
+    rlp_list(payload)
+}
+
+pub fn rlp_list(payload: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 4);
+    alloy_rlp::Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut out);
+    out.extend_from_slice(&payload);
+    out
+}
+
+/// Returns the EIP-7685 requests of the block by type: deposits, withdrawals and consolidations.
+pub fn block_requests(
+    state: &mut State<EmptyDB>,
+    block: &BlockEnv,
+    cfg: &CfgEnv,
+    receipts: &[Receipt],
+) -> Result<Vec<(u8, Bytes)>, BlockError> {
+    Ok(vec![
+        (0, deposit_requests(receipts)?),
+        (
+            1,
+            system_call(state, block, cfg, WITHDRAWAL_REQUEST_ADDRESS, Bytes::new())?,
+        ),
+        (
+            2,
+            system_call(
+                state,
+                block,
+                cfg,
+                CONSOLIDATION_REQUEST_ADDRESS,
+                Bytes::new(),
+            )?,
+        ),
+    ])
+}
+
+/// Collects EIP-6110 deposit requests from the deposit contract logs.
+fn deposit_requests(receipts: &[Receipt]) -> Result<Bytes, BlockError> {
+    let mut requests = Vec::new();
+    let logs = receipts.iter().flat_map(|receipt| &receipt.logs);
+    for log in logs.filter(|log| log.address == DEPOSIT_CONTRACT_ADDRESS) {
+        if log.topics().first() != Some(&DepositEvent::SIGNATURE_HASH) {
+            continue;
+        }
+        let event = DepositEvent::decode_log_data(&log.data, true)
+            .map_err(|_| BlockError::InvalidDepositEvent)?;
+        let fields = [
+            (&event.pubkey, 48),
+            (&event.withdrawal_credentials, 32),
+            (&event.amount, 8),
+            (&event.signature, 96),
+            (&event.index, 8),
+        ];
+        for (field, len) in fields {
+            if field.len() != len {
+                return Err(BlockError::InvalidDepositEvent);
+            }
+            requests.extend_from_slice(field);
+        }
+    }
+    Ok(requests.into())
+}
+
+/// EIP-7685 commitment to the requests of the block, empty requests are skipped.
+pub fn requests_hash(requests: &[(u8, Bytes)]) -> B256 {
+    let mut hasher = Sha256::new();
+    for (request_type, data) in requests.iter().filter(|(_, data)| !data.is_empty()) {
+        let mut request = Sha256::new();
+        request.update([*request_type]);
+        request.update(data);
+        hasher.update(request.finalize());
+    }
+    B256::from_slice(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{alloy_primitives::B64, b256};
+
+    #[test]
+    fn empty_requests_hash() {
+        // sha256 of the empty string.
+        assert_eq!(
+            requests_hash(&[(0, Bytes::new()), (1, Bytes::new()), (2, Bytes::new())]),
+            b256!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+    }
+
+    #[test]
+    fn base_fee() {
+        let gas_limit = 10_000_000;
+        assert_eq!(
+            next_base_fee(INITIAL_BASE_FEE, gas_limit / 2, gas_limit),
+            INITIAL_BASE_FEE
+        );
+        assert_eq!(
+            next_base_fee(INITIAL_BASE_FEE, gas_limit, gas_limit),
+            1_125_000_000
+        );
+        assert_eq!(next_base_fee(INITIAL_BASE_FEE, 0, gas_limit), 875_000_000);
+        // Increases by at least one.
+        assert_eq!(next_base_fee(7, gas_limit / 2 + 1, gas_limit), 8);
+    }
+
+    /// Returns a header with the number as timestamp, headers with different `salt` differ.
+    fn header(parent_hash: B256, number: u64, salt: u8) -> BlockHeader {
+        let mut header = BlockHeader {
+            parent_hash,
+            uncle_hash: keccak256([alloy_rlp::EMPTY_LIST_CODE]),
+            coinbase: Address::with_last_byte(salt),
+            state_root: B256::ZERO,
+            transactions_trie: B256::ZERO,
+            receipt_trie: B256::ZERO,
+            bloom: Bloom::ZERO,
+            difficulty: U256::from(0x20000),
+            number: U256::from(number),
+            gas_limit: U256::from(10_000_000),
+            gas_used: U256::ZERO,
+            timestamp: U256::from(number),
+            extra_data: Bytes::new(),
+            mix_hash: B256::ZERO,
+            nonce: B64::ZERO,
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+            hash: B256::ZERO,
+        };
+        header.hash = header_hash(&header);
+        header
+    }
+
+    /// Returns a valid header of a child of `parent`.
+    fn child(parent: &BlockHeader, salt: u8) -> BlockHeader {
+        header(parent.hash, parent.number.saturating_to::<u64>() + 1, salt)
+    }
+
+    /// Returns the child of the chain parent with the ommers.
+    fn block_with_ommers(chain: &Chain, ommers: Vec<BlockHeader>) -> ExecutableBlock {
+        let mut header = child(&chain.parent, 0);
+        header.uncle_hash = keccak256(rlp_list(ommers.iter().flat_map(header_rlp).collect()));
+        header.hash = header_hash(&header);
+        ExecutableBlock {
+            header,
+            transactions: Vec::new(),
+            uncle_headers: ommers,
+            withdrawals: None,
+        }
+    }
+
+    #[test]
+    fn ommer_validation() {
+        let genesis = header(B256::ZERO, 0, 0);
+        let first = child(&genesis, 0);
+        let second = child(&first, 0);
+        let ancestors = [&genesis, &first, &second].map(|header| Ancestor {
+            header: header.clone(),
+            ommers: Vec::new(),
+        });
+        let chain = Chain {
+            network: SpecName::Berlin,
+            chain_id: 1,
+            cache: CacheState::new(false),
+            parent: second.clone(),
+            block_hashes: BTreeMap::new(),
+            ancestors: ancestors.to_vec(),
+        };
+        let validate = |ommers: Vec<BlockHeader>| {
+            chain.validate_ommers(&block_with_ommers(&chain, ommers), SpecId::BERLIN)
+        };
+        let invalid_ommer = |result: Result<(), BlockError>, index: usize| matches!(result, Err(BlockError::InvalidOmmer { index: i, .. }) if i == index);
+
+        let ommer = child(&genesis, 1);
+        assert!(validate(vec![]).is_ok());
+        assert!(validate(vec![ommer.clone(), child(&first, 1)]).is_ok());
+        assert!(matches!(
+            validate(vec![ommer.clone(), child(&first, 1), child(&genesis, 2)]),
+            Err(BlockError::InvalidHeader("too many ommers"))
+        ));
+        assert!(invalid_ommer(
+            validate(vec![ommer.clone(), ommer.clone()]),
+            1
+        ));
+        assert!(invalid_ommer(validate(vec![first.clone()]), 0));
+        // Sibling of the block, its depth is zero.
+        assert!(invalid_ommer(validate(vec![child(&second, 1)]), 0));
+        // Parent is not an ancestor.
+        assert!(invalid_ommer(validate(vec![child(&ommer, 1)]), 0));
+        // Header hash doesn't match.
+        let mut forged = ommer.clone();
+        forged.hash = B256::with_last_byte(1);
+        assert!(invalid_ommer(validate(vec![forged]), 0));
+        // Invalid header of the ommer.
+        let mut future = ommer.clone();
+        future.gas_used = future.gas_limit + U256::from(1);
+        future.hash = header_hash(&future);
+        assert!(invalid_ommer(validate(vec![future]), 0));
+
+        // Ommer included by an ancestor.
+        let mut chain = chain.clone();
+        chain.ancestors[2].ommers.push(ommer.hash);
+        let block = block_with_ommers(&chain, vec![ommer.clone()]);
+        assert!(invalid_ommer(
+            chain.validate_ommers(&block, SpecId::BERLIN),
+            0
+        ));
+
+        // Uncle hash must commit to the ommers.
+        let mut block = block_with_ommers(&chain, vec![]);
+        block.uncle_headers.push(ommer);
+        assert!(matches!(
+            chain.validate_ommers(&block, SpecId::BERLIN),
+            Err(BlockError::Mismatch {
+                field: "uncle hash",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn ommer_rewards() {
+        let rewards = rewards(
+            Address::with_last_byte(1),
+            2 * ONE_ETHER,
+            [(Address::with_last_byte(2), 1)].into_iter(),
+        );
+        assert_eq!(
+            rewards,
+            [
+                (Address::with_last_byte(1), 2 * ONE_ETHER + ONE_ETHER / 16),
+                (Address::with_last_byte(2), 2 * ONE_ETHER * 7 / 8),
+            ]
+        );
+    }
+}
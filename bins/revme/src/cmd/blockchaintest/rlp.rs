@@ -0,0 +1,246 @@
+//! Decoding of RLP encoded blocks, for blocks of the tests that only have the RLP encoding.
+use super::block::{BlockError, ExecutableBlock};
+use alloy_rlp::{Decodable, Header};
+use revm::{
+    primitives::keccak256,
+    signed_tx::{SignedTransaction, SignedTxError},
+    specification::hardfork::SpecId,
+};
+use statetest_types::{BlockHeader, SpecName, Withdrawal};
+
+/// Decodes a block and recovers the senders of its transactions.
+///
+/// A sender that can't be recovered is left unknown, the block is rejected when executed.
+pub fn decode_block(rlp: &[u8], network: SpecName) -> Result<ExecutableBlock, BlockError> {
+    let mut buf = rlp;
+    let mut payload = list_payload(&mut buf)?;
+    finish(buf)?;
+
+    let header = decode_header(&mut payload)?;
+    let spec = network.spec_at(
+        header.number.saturating_to(),
+        header.timestamp.saturating_to(),
+    );
+
+    let mut items = list_payload(&mut payload)?;
+    let mut transactions = Vec::new();
+    while !items.is_empty() {
+        let index = transactions.len();
+        let tx = SignedTransaction::decode_list_item(&mut items).map_err(|error| match error {
+            SignedTxError::Rlp(error) => BlockError::Rlp(error),
+            error => BlockError::Transaction {
+                index,
+                error: error.to_string(),
+            },
+        })?;
+        // EIP-2: `s` must be in the lower half of the curve order since Homestead.
+        let sender = if spec.is_enabled_in(SpecId::HOMESTEAD) {
+            tx.recover_sender()
+        } else {
+            tx.recover_sender_unchecked()
+        };
+        transactions.push(sender.ok().map(|sender| tx.to_tx_env(sender)));
+    }
+
+    let mut items = list_payload(&mut payload)?;
+    let mut uncle_headers = Vec::new();
+    while !items.is_empty() {
+        uncle_headers.push(decode_header(&mut items)?);
+    }
+
+    // Withdrawals are appended to the body since Shanghai.
+    let withdrawals = if payload.is_empty() {
+        None
+    } else {
+        let mut items = list_payload(&mut payload)?;
+        let mut withdrawals = Vec::new();
+        while !items.is_empty() {
+            withdrawals.push(decode_withdrawal(&mut items)?);
+        }
+        Some(withdrawals)
+    };
+    finish(payload)?;
+
+    Ok(ExecutableBlock {
+        header,
+        transactions,
+        uncle_headers,
+        withdrawals,
+    })
+}
+
+/// Decodes a header, the hash is the keccak hash of its encoding.
+fn decode_header(buf: &mut &[u8]) -> alloy_rlp::Result<BlockHeader> {
+    let encoded = *buf;
+    let mut payload = list_payload(buf)?;
+    let encoded = &encoded[..encoded.len() - buf.len()];
+    let fields = &mut payload;
+
+    let header = BlockHeader {
+        parent_hash: Decodable::decode(fields)?,
+        uncle_hash: Decodable::decode(fields)?,
+        coinbase: Decodable::decode(fields)?,
+        state_root: Decodable::decode(fields)?,
+        transactions_trie: Decodable::decode(fields)?,
+        receipt_trie: Decodable::decode(fields)?,
+        bloom: Decodable::decode(fields)?,
+        difficulty: Decodable::decode(fields)?,
+        number: Decodable::decode(fields)?,
+        gas_limit: Decodable::decode(fields)?,
+        gas_used: Decodable::decode(fields)?,
+        timestamp: Decodable::decode(fields)?,
+        extra_data: Decodable::decode(fields)?,
+        mix_hash: Decodable::decode(fields)?,
+        nonce: Decodable::decode(fields)?,
+        // Fields added by forks are appended in order.
+        base_fee_per_gas: optional(fields)?,
+        withdrawals_root: optional(fields)?,
+        blob_gas_used: optional(fields)?,
+        excess_blob_gas: optional(fields)?,
+        parent_beacon_block_root: optional(fields)?,
+        requests_hash: optional(fields)?,
+        hash: keccak256(encoded),
+    };
+    finish(payload)?;
+    Ok(header)
+}
+
+fn decode_withdrawal(buf: &mut &[u8]) -> alloy_rlp::Result<Withdrawal> {
+    let mut payload = list_payload(buf)?;
+    let fields = &mut payload;
+    let withdrawal = Withdrawal {
+        index: Decodable::decode(fields)?,
+        validator_index: Decodable::decode(fields)?,
+        address: Decodable::decode(fields)?,
+        amount: Decodable::decode(fields)?,
+    };
+    finish(payload)?;
+    Ok(withdrawal)
+}
+
+/// Decodes a trailing optional field, `None` at the end of the list.
+fn optional<T: Decodable>(buf: &mut &[u8]) -> alloy_rlp::Result<Option<T>> {
+    if buf.is_empty() {
+        return Ok(None);
+    }
+    T::decode(buf).map(Some)
+}
+
+/// Decodes a list header and returns its payload, advancing `buf` past the list.
+fn list_payload<'a>(buf: &mut &'a [u8]) -> alloy_rlp::Result<&'a [u8]> {
+    let header = Header::decode(buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString);
+    }
+    if buf.len() < header.payload_length {
+        return Err(alloy_rlp::Error::InputTooShort);
+    }
+    let (payload, rest) = buf.split_at(header.payload_length);
+    *buf = rest;
+    Ok(payload)
+}
+
+fn finish(rest: &[u8]) -> alloy_rlp::Result<()> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(alloy_rlp::Error::ListLengthMismatch {
+            expected: 0,
+            got: rest.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::blockchaintest::block::{header_hash, header_rlp, rlp_list, withdrawal_rlp};
+    use revm::primitives::{
+        address,
+        alloy_primitives::{Bloom, B64},
+        hex, Address, Bytes, B256, U256,
+    };
+
+    fn header(number: u64) -> BlockHeader {
+        BlockHeader {
+            parent_hash: B256::with_last_byte(1),
+            uncle_hash: B256::with_last_byte(2),
+            coinbase: Address::with_last_byte(3),
+            state_root: B256::with_last_byte(4),
+            transactions_trie: B256::with_last_byte(5),
+            receipt_trie: B256::with_last_byte(6),
+            bloom: Bloom::ZERO,
+            difficulty: U256::ZERO,
+            number: U256::from(number),
+            gas_limit: U256::from(30_000_000),
+            gas_used: U256::from(21_000),
+            timestamp: U256::from(12),
+            extra_data: Bytes::from_static(b"revm"),
+            mix_hash: B256::ZERO,
+            nonce: B64::ZERO,
+            base_fee_per_gas: Some(U256::from(7)),
+            withdrawals_root: Some(B256::with_last_byte(7)),
+            blob_gas_used: None,
+            excess_blob_gas: None,
+            parent_beacon_block_root: None,
+            requests_hash: None,
+            hash: B256::ZERO,
+        }
+    }
+
+    #[test]
+    fn decode_block_rlp() {
+        let header = header(1);
+        let uncle = BlockHeader {
+            base_fee_per_gas: None,
+            withdrawals_root: None,
+            ..self::header(0)
+        };
+        // Example from EIP-155.
+        let tx = hex!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+        let withdrawal = Withdrawal {
+            index: U256::from(1),
+            validator_index: U256::from(2),
+            address: Address::with_last_byte(8),
+            amount: U256::from(3),
+        };
+
+        let mut payload = header_rlp(&header);
+        payload.extend_from_slice(&rlp_list(tx.to_vec()));
+        payload.extend_from_slice(&rlp_list(header_rlp(&uncle)));
+        payload.extend_from_slice(&rlp_list(withdrawal_rlp(&withdrawal)));
+        let rlp = rlp_list(payload);
+
+        let block = decode_block(&rlp, SpecName::Shanghai).unwrap();
+        assert_eq!(
+            block.header,
+            BlockHeader {
+                hash: header_hash(&header),
+                ..header.clone()
+            }
+        );
+        assert_eq!(block.uncle_headers.len(), 1);
+        assert_eq!(block.uncle_headers[0].base_fee_per_gas, None);
+        assert_eq!(block.withdrawals, Some(vec![withdrawal]));
+        let tx = block.transactions[0].as_ref().unwrap();
+        assert_eq!(
+            tx.caller,
+            address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")
+        );
+        assert_eq!(tx.nonce, 9);
+
+        // Truncated block.
+        assert!(matches!(
+            decode_block(&rlp[..rlp.len() - 1], SpecName::Shanghai),
+            Err(BlockError::Rlp(_))
+        ));
+        // Unsupported transaction type.
+        let mut payload = header_rlp(&header);
+        payload.extend_from_slice(&rlp_list(vec![0x82, 0x05, 0xc0]));
+        payload.extend_from_slice(&rlp_list(Vec::new()));
+        assert!(matches!(
+            decode_block(&rlp_list(payload), SpecName::Shanghai),
+            Err(BlockError::Transaction { index: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,439 @@
+use super::{
+    block::{header_hash, Ancestor, BlockError, Chain, ExecutableBlock, MAX_OMMER_DEPTH},
+    rlp::decode_block,
+};
+use crate::cmd::statetest::merkle_trie::state_merkle_trie_root;
+use database::CacheState;
+use indicatif::{ProgressBar, ProgressDrawTarget};
+use revm::{
+    bytecode::Bytecode,
+    primitives::{keccak256, Address, HashMap, B256},
+    specification::hardfork::SpecId,
+};
+use serde_json::json;
+use statetest_types::{AccountInfo, BlockchainTestSuite, BlockchainTestUnit, SpecName};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Path: {path}\nName: {name}\nError: {kind}")]
+pub struct TestError {
+    pub name: String,
+    pub path: String,
+    pub kind: TestErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum TestErrorKind {
+    #[error("genesis state root mismatch: got {got}, expected {expected}")]
+    GenesisStateRootMismatch { got: B256, expected: B256 },
+    #[error("genesis hash mismatch: got {got}, expected {expected}")]
+    GenesisHashMismatch { got: B256, expected: B256 },
+    #[error("block {index} rejected: {error}")]
+    BlockRejected {
+        index: usize,
+        error: Box<BlockError>,
+    },
+    #[error("block {index} imported, expected exception {exception:?}")]
+    UnexpectedValidBlock { index: usize, exception: String },
+    #[error("block {index} rejected: {error}, expected exception {exception:?}")]
+    UnexpectedRejection {
+        index: usize,
+        exception: String,
+        error: Box<BlockError>,
+    },
+    #[error("last block hash mismatch: got {got}, expected {expected}")]
+    LastBlockHashMismatch { got: B256, expected: B256 },
+    #[error("post state root mismatch: got {got}, expected {expected}")]
+    PostStateRootMismatch { got: B256, expected: B256 },
+    #[error("test has neither post state nor post state hash")]
+    MissingPostState,
+    #[error(transparent)]
+    SerdeDeserialize(#[from] serde_json::Error),
+    #[error("thread panicked")]
+    Panic,
+}
+
+/// Returns `true` if the network is not supported.
+fn skip_network(network: &SpecName) -> bool {
+    matches!(
+        network,
+        // Constantinople was immediately replaced by Petersburg.
+        SpecName::Constantinople
+            | SpecName::ByzantiumToConstantinopleAt5
+            // DAO fork irregular state change is not implemented.
+            | SpecName::HomesteadToDaoAt5
+            | SpecName::Osaka
+            | SpecName::Unknown
+    )
+}
+
+/// Category of a block rejection, the rejection must match the expected exception.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rejection {
+    /// Block can't be decoded.
+    Rlp,
+    /// Transaction of the block is invalid.
+    Transaction,
+    /// Header or body of the block is invalid.
+    Block,
+}
+
+impl Rejection {
+    fn of_error(error: &BlockError) -> Self {
+        match error {
+            BlockError::Rlp(_) => Self::Rlp,
+            BlockError::MissingSender { .. }
+            | BlockError::BlockGasLimitExceeded { .. }
+            | BlockError::BlobGasLimitExceeded { .. }
+            | BlockError::Transaction { .. } => Self::Transaction,
+            BlockError::InvalidHeader(_)
+            | BlockError::InvalidOmmer { .. }
+            | BlockError::Mismatch { .. }
+            | BlockError::SystemCall { .. }
+            | BlockError::InvalidDepositEvent => Self::Block,
+        }
+    }
+
+    /// Category of an exception, as `TransactionException.INTRINSIC_GAS_TOO_LOW`,
+    /// `BlockException.RLP_STRUCTURES_ENCODING` or `TR_NoFunds`.
+    fn of_exception(exception: &str) -> Self {
+        if exception.contains("RLP") {
+            Self::Rlp
+        } else if exception.starts_with("TransactionException.") || exception.starts_with("TR_") {
+            Self::Transaction
+        } else {
+            Self::Block
+        }
+    }
+}
+
+/// Returns `true` if the error matches one of the `|` separated expected exceptions.
+fn is_expected_rejection(error: &BlockError, exception: &str) -> bool {
+    let rejection = Rejection::of_error(error);
+    exception
+        .split('|')
+        .any(|exception| Rejection::of_exception(exception.trim()) == rejection)
+}
+
+fn cache_state(accounts: &HashMap<Address, AccountInfo>) -> CacheState {
+    let mut cache_state = CacheState::new(false);
+    for (address, info) in accounts {
+        let code_hash = keccak256(&info.code);
+        let bytecode = Bytecode::new_raw_checked(info.code.clone())
+            .unwrap_or(Bytecode::new_legacy(info.code.clone()));
+        let acc_info = revm::state::AccountInfo {
+            balance: info.balance,
+            code_hash,
+            code: Some(bytecode),
+            nonce: info.nonce,
+        };
+        cache_state.insert_account_with_storage(*address, acc_info, info.storage.clone());
+    }
+    cache_state
+}
+
+/// Imports the blocks of the test and checks the resulting chain.
+fn execute_test(unit: &BlockchainTestUnit) -> Result<(), TestErrorKind> {
+    let genesis = &unit.genesis_block_header;
+    let spec = unit.network.spec_at(0, genesis.timestamp.saturating_to());
+    let mut cache = cache_state(&unit.pre);
+    cache.set_state_clear_flag(spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+
+    let state_root = state_merkle_trie_root(cache.trie_account());
+    if state_root != genesis.state_root {
+        return Err(TestErrorKind::GenesisStateRootMismatch {
+            got: state_root,
+            expected: genesis.state_root,
+        });
+    }
+    let hash = header_hash(genesis);
+    if hash != genesis.hash {
+        return Err(TestErrorKind::GenesisHashMismatch {
+            got: hash,
+            expected: genesis.hash,
+        });
+    }
+
+    let mut chain = Chain {
+        network: unit.network,
+        chain_id: unit.chain_id(),
+        cache,
+        parent: genesis.clone(),
+        block_hashes: BTreeMap::from([(genesis.number.saturating_to(), genesis.hash)]),
+        ancestors: vec![Ancestor {
+            header: genesis.clone(),
+            ommers: Vec::new(),
+        }],
+    };
+    for (index, block) in unit.blocks.iter().enumerate() {
+        // Blocks that only have the RLP encoding are decoded from it.
+        let executable = match block.decoded().and_then(ExecutableBlock::from_decoded) {
+            Some(executable) => Ok(executable),
+            None => decode_block(&block.rlp, unit.network),
+        };
+        let result = executable.and_then(|executable| {
+            let cache = chain.execute_block(&executable)?;
+            Ok((executable, cache))
+        });
+        match (result, &block.expect_exception) {
+            (Ok((executable, cache)), None) => {
+                let header = executable.header;
+                chain
+                    .block_hashes
+                    .insert(header.number.saturating_to(), header.hash);
+                chain.cache = cache;
+                chain.parent = header.clone();
+                if chain.ancestors.len() > MAX_OMMER_DEPTH as usize {
+                    chain.ancestors.remove(0);
+                }
+                chain.ancestors.push(Ancestor {
+                    header,
+                    ommers: executable
+                        .uncle_headers
+                        .iter()
+                        .map(|ommer| ommer.hash)
+                        .collect(),
+                });
+            }
+            // Invalid block is discarded.
+            (Err(error), Some(exception)) if is_expected_rejection(&error, exception) => {}
+            (Err(error), Some(exception)) => {
+                return Err(TestErrorKind::UnexpectedRejection {
+                    index,
+                    exception: exception.clone(),
+                    error: Box::new(error),
+                })
+            }
+            (Ok(_), Some(exception)) => {
+                return Err(TestErrorKind::UnexpectedValidBlock {
+                    index,
+                    exception: exception.clone(),
+                })
+            }
+            (Err(error), None) => {
+                return Err(TestErrorKind::BlockRejected {
+                    index,
+                    error: Box::new(error),
+                })
+            }
+        }
+    }
+
+    if chain.parent.hash != unit.last_block_hash {
+        return Err(TestErrorKind::LastBlockHashMismatch {
+            got: chain.parent.hash,
+            expected: unit.last_block_hash,
+        });
+    }
+    let expected = match (&unit.post_state, unit.post_state_hash) {
+        (Some(post_state), _) => state_merkle_trie_root(cache_state(post_state).trie_account()),
+        (None, Some(hash)) => hash,
+        (None, None) => return Err(TestErrorKind::MissingPostState),
+    };
+    let state_root = state_merkle_trie_root(chain.cache.trie_account());
+    if state_root != expected {
+        return Err(TestErrorKind::PostStateRootMismatch {
+            got: state_root,
+            expected,
+        });
+    }
+    Ok(())
+}
+
+pub fn execute_test_suite(
+    path: &Path,
+    elapsed: &Arc<Mutex<Duration>>,
+    print_json_outcome: bool,
+) -> Result<(), TestError> {
+    let s = std::fs::read_to_string(path).unwrap();
+    let path = path.to_string_lossy().into_owned();
+    let suite: BlockchainTestSuite = serde_json::from_str(&s).map_err(|e| TestError {
+        name: "Unknown".to_string(),
+        path: path.clone(),
+        kind: e.into(),
+    })?;
+
+    for (name, unit) in suite.0 {
+        if skip_network(&unit.network) {
+            continue;
+        }
+
+        let timer = Instant::now();
+        let result = execute_test(&unit);
+        *elapsed.lock().unwrap() += timer.elapsed();
+
+        if print_json_outcome {
+            let json = json!({
+                "test": name,
+                "fork": format!("{:?}", unit.network),
+                "blocks": unit.blocks.len(),
+                "pass": result.is_ok(),
+                "errorMsg": result.as_ref().err().map(ToString::to_string).unwrap_or_default(),
+            });
+            eprintln!("{json}");
+        }
+        result.map_err(|kind| TestError {
+            name,
+            path: path.clone(),
+            kind,
+        })?;
+    }
+    Ok(())
+}
+
+pub fn run(
+    test_files: Vec<PathBuf>,
+    mut single_thread: bool,
+    print_outcome: bool,
+    keep_going: bool,
+) -> Result<(), TestError> {
+    // `print_outcome` implies single_thread
+    if print_outcome {
+        single_thread = true;
+    }
+    let n_files = test_files.len();
+
+    let n_errors = Arc::new(AtomicUsize::new(0));
+    let console_bar = Arc::new(ProgressBar::with_draw_target(
+        Some(n_files as u64),
+        ProgressDrawTarget::stdout(),
+    ));
+    let queue = Arc::new(Mutex::new((0usize, test_files)));
+    let elapsed = Arc::new(Mutex::new(Duration::ZERO));
+
+    let num_threads = match (single_thread, std::thread::available_parallelism()) {
+        (true, _) | (false, Err(_)) => 1,
+        (false, Ok(n)) => n.get(),
+    };
+    let num_threads = num_threads.min(n_files);
+    let mut handles = Vec::with_capacity(num_threads);
+    for i in 0..num_threads {
+        let queue = queue.clone();
+        let n_errors = n_errors.clone();
+        let console_bar = console_bar.clone();
+        let elapsed = elapsed.clone();
+
+        let thread = std::thread::Builder::new().name(format!("runner-{i}"));
+
+        let f = move || loop {
+            if !keep_going && n_errors.load(Ordering::SeqCst) > 0 {
+                return Ok(());
+            }
+
+            let test_path = {
+                let (current_idx, queue) = &mut *queue.lock().unwrap();
+                let Some(test_path) = queue.get(*current_idx).cloned() else {
+                    return Ok(());
+                };
+                *current_idx += 1;
+                test_path
+            };
+
+            let result = execute_test_suite(&test_path, &elapsed, print_outcome);
+
+            // Increment after the test is done.
+            console_bar.inc(1);
+
+            if let Err(err) = result {
+                n_errors.fetch_add(1, Ordering::SeqCst);
+                if !keep_going {
+                    return Err(err);
+                }
+                println!("\n{err}");
+            }
+        };
+        handles.push(thread.spawn(f).unwrap());
+    }
+
+    // join all threads before returning an error
+    let mut thread_errors = Vec::new();
+    for (i, handle) in handles.into_iter().enumerate() {
+        match handle.join() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => thread_errors.push(e),
+            Err(_) => thread_errors.push(TestError {
+                name: format!("thread {i} panicked"),
+                path: "".to_string(),
+                kind: TestErrorKind::Panic,
+            }),
+        }
+    }
+    console_bar.finish();
+
+    println!(
+        "Finished execution. Total CPU time: {:.6}s",
+        elapsed.lock().unwrap().as_secs_f64()
+    );
+
+    let n_errors = n_errors.load(Ordering::SeqCst);
+    if n_errors == 0 && thread_errors.is_empty() {
+        println!("All tests passed!");
+        return Ok(());
+    }
+    println!("Encountered {n_errors} errors out of {n_files} total tests");
+    if thread_errors.is_empty() {
+        std::process::exit(1);
+    }
+    Err(thread_errors.swap_remove(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_rejection() {
+        let rlp = BlockError::Rlp(alloy_rlp::Error::InputTooShort);
+        let transaction = BlockError::Transaction {
+            index: 0,
+            error: "lack of funds".to_string(),
+        };
+        let header = BlockError::InvalidHeader("gas limit");
+
+        assert!(is_expected_rejection(
+            &rlp,
+            "BlockException.RLP_STRUCTURES_ENCODING"
+        ));
+        assert!(!is_expected_rejection(
+            &rlp,
+            "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS"
+        ));
+        assert!(is_expected_rejection(
+            &transaction,
+            "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS"
+        ));
+        assert!(is_expected_rejection(&transaction, "TR_NoFunds"));
+        assert!(!is_expected_rejection(
+            &transaction,
+            "BlockException.INCORRECT_BLOCK_FORMAT"
+        ));
+        assert!(is_expected_rejection(
+            &header,
+            "TransactionException.GAS_ALLOWANCE_EXCEEDED|BlockException.INVALID_GASLIMIT"
+        ));
+        assert!(!is_expected_rejection(&header, "TR_GasLimitReached"));
+
+        let ommer = BlockError::InvalidOmmer {
+            index: 0,
+            error: Box::new(BlockError::InvalidHeader("ommer is an ancestor")),
+        };
+        assert!(is_expected_rejection(&ommer, "UncleIsAncestor"));
+        assert!(is_expected_rejection(
+            &ommer,
+            "BlockException.TOO_MANY_UNCLES"
+        ));
+        assert!(!is_expected_rejection(
+            &ommer,
+            "TransactionException.INTRINSIC_GAS_TOO_LOW"
+        ));
+    }
+}
@@ -1,6 +1,11 @@
 use clap::Parser;
 use revm::{
-    bytecode::eof::{self, validate_eof_inner, CodeType, Eof, EofError},
+    bytecode::{
+        disassembler::Disassembly,
+        eof::{self, validate_eof_inner, CodeType, Eof, EofError},
+        legacy::ControlFlowGraph,
+        selectors::recover_selectors,
+    },
     primitives::{hex, Bytes},
     specification::constants::MAX_INITCODE_SIZE,
 };
@@ -15,6 +20,15 @@ pub struct Cmd {
     /// Is EOF code in RUNTIME mode.
     #[arg(long)]
     eof_runtime: bool,
+    /// Print the control-flow graph of legacy bytecode in DOT format.
+    #[arg(long)]
+    cfg: bool,
+    /// Print the function selectors recovered from the dispatcher.
+    #[arg(long)]
+    selectors: bool,
+    /// Print JSON instead of text or the DOT control-flow graph.
+    #[arg(long)]
+    json: bool,
     /// Bytecode in hex format string.
     ///
     /// - If bytes start with 0xFE it will be interpreted as a EOF.
@@ -36,6 +50,41 @@ fn trim_decode(input: &str) -> Option<Bytes> {
 }
 
 impl Cmd {
+    /// Prints the control-flow graph of legacy bytecode.
+    fn print_cfg(&self, bytes: &Bytes) {
+        if bytes.starts_with(&eof::EOF_MAGIC_BYTES) {
+            eprintln!("Control-flow graph is only supported for legacy bytecode");
+            return;
+        }
+        let cfg = ControlFlowGraph::new(bytes);
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&cfg).unwrap());
+        } else {
+            print!("{}", cfg.to_dot(bytes));
+        }
+    }
+
+    /// Prints the disassembly or the recovered selectors as text or JSON.
+    fn print_disassembly(&self, disassembly: &Disassembly) {
+        if self.selectors {
+            let entries = recover_selectors(disassembly);
+            if self.json {
+                println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+            } else {
+                for entry in entries {
+                    println!(
+                        "{} section {} pc 0x{:04x}",
+                        entry.selector, entry.code_section, entry.pc
+                    );
+                }
+            }
+        } else if self.json {
+            println!("{}", serde_json::to_string_pretty(disassembly).unwrap());
+        } else {
+            print!("{disassembly}");
+        }
+    }
+
     /// Runs statetest command.
     pub fn run(&self) {
         let container_kind = if self.eof_initcode {
@@ -51,17 +100,25 @@ impl Cmd {
                 return;
             };
 
+            if self.cfg {
+                self.print_cfg(&bytes);
+                return;
+            }
+
             if bytes[0] == 0xEF {
                 match Eof::decode(bytes) {
                     Ok(eof) => {
-                        println!("Decoding: {:#?}", eof);
+                        match Disassembly::eof(&eof) {
+                            Ok(disassembly) => self.print_disassembly(&disassembly),
+                            Err(e) => eprintln!("Decoding Error: {:#?}", e),
+                        }
                         let res = validate_eof_inner(&eof, container_kind);
                         println!("Validation: {:#?}", res);
                     }
                     Err(e) => eprintln!("Decoding Error: {:#?}", e),
                 }
             } else {
-                eof::printer::print(&bytes)
+                self.print_disassembly(&Disassembly::legacy(bytes))
             }
             return;
         }
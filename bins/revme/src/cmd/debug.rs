@@ -0,0 +1,304 @@
+mod command;
+mod debugger;
+
+use crate::cmd::statetest::{
+    parse_fork, set_spec, set_test_tx, unit_env, unit_prestate, TestErrorKind, TestFilter,
+};
+use clap::Parser;
+use command::Breakpoint;
+use database::{CacheState, State};
+use debugger::Debugger;
+use inspector::exec::InspectEvm;
+use regex::Regex;
+use revm::{
+    bytecode::{Bytecode, BytecodeDecodeError},
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::result::{ExecutionResult, HaltReason},
+    primitives::{hex, Address, Bytes, TxKind},
+    specification::hardfork::SpecId,
+    state::AccountInfo,
+    Context,
+};
+use statetest_types::{SpecName, TestSuite};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid bytecode")]
+    InvalidBytecode,
+    #[error("Invalid input")]
+    InvalidInput,
+    #[error("Invalid statetest: {0}")]
+    InvalidStatetest(serde_json::Error),
+    #[error("No test matches the filters")]
+    NoMatchingTest,
+    #[error(transparent)]
+    Test(#[from] TestErrorKind),
+    #[error("EVM Error: {0}")]
+    Evm(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    BytecodeDecodeError(#[from] BytecodeDecodeError),
+}
+
+/// `debug` subcommand
+///
+/// Executes a transaction and stops before its first instruction, `help` at the prompt lists the
+/// commands. The transaction runs raw bytecode, deployed at the target address, or a test of a
+/// statetest file.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Hex-encoded EVM bytecode to be executed
+    #[arg(required_unless_present_any = ["path", "statetest"])]
+    bytecode: Option<String>,
+    /// Path to a file containing the hex-encoded EVM bytecode to be executed
+    #[arg(long, alias = "codefile", conflicts_with = "bytecode")]
+    path: Option<PathBuf>,
+    /// Hex-encoded input/calldata bytes
+    #[arg(long, default_value = "")]
+    input: String,
+    /// Run the bytecode as initcode of a contract creation
+    #[arg(long)]
+    create: bool,
+    /// Fork of the execution, named as in the tests
+    #[arg(long, default_value = "Prague", value_parser = parse_fork)]
+    spec: SpecName,
+    /// Address of the caller
+    #[arg(long, default_value = "0x0000000000000000000000000000000000000001")]
+    caller: Address,
+    /// Address the bytecode is deployed at and called
+    #[arg(long, default_value = "0x0000000000000000000000000000000000000000")]
+    target: Address,
+    /// Gas limit of the transaction
+    #[arg(long, default_value_t = 30_000_000)]
+    gas: u64,
+    /// Statetest file to debug a test of, instead of the bytecode
+    ///
+    /// The first test selected by `--name`, `--fork`, `--data`, `--gas-index` and `--value` is
+    /// debugged.
+    #[arg(long, conflicts_with_all = ["bytecode", "path"])]
+    statetest: Option<PathBuf>,
+    /// Select the test with a name matching the regex
+    #[arg(long, requires = "statetest")]
+    name: Option<Regex>,
+    /// Select the test of the fork
+    #[arg(long, requires = "statetest", value_parser = parse_fork)]
+    fork: Option<SpecName>,
+    /// Select the test with the data index
+    #[arg(short = 'd', long, requires = "statetest")]
+    data: Option<usize>,
+    /// Select the test with the gas index
+    #[arg(short = 'g', long = "gas-index", requires = "statetest")]
+    gas_index: Option<usize>,
+    /// Select the test with the value index
+    #[arg(short = 'v', long, requires = "statetest")]
+    value: Option<usize>,
+    /// Breakpoints set before the execution, as in `--break "op SSTORE"`
+    #[arg(short = 'b', long = "break")]
+    breakpoints: Vec<Breakpoint>,
+}
+
+/// Transaction to debug with the accounts it runs on.
+struct Execution {
+    prestate: CacheState,
+    cfg: CfgEnv,
+    block: BlockEnv,
+    tx: TxEnv,
+}
+
+impl Execution {
+    /// Executes the transaction on a copy of the prestate.
+    fn run<R: BufRead, W: Write>(
+        &self,
+        debugger: &mut Debugger<R, W>,
+    ) -> Result<ExecutionResult<HaltReason>, Error> {
+        let mut cache = self.prestate.clone();
+        cache.set_state_clear_flag(self.cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+        let mut state = State::builder()
+            .with_cached_prestate(cache)
+            .with_bundle_update()
+            .build();
+        let mut ctx = Context::builder()
+            .with_block(&self.block)
+            .with_tx(&self.tx)
+            .with_cfg(&self.cfg)
+            .with_db(&mut state);
+        ctx.inspect_previous(debugger)
+            .map(|out| out.result)
+            .map_err(|e| Error::Evm(e.to_string()))
+    }
+}
+
+impl Cmd {
+    /// Runs `debug` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let execution = match &self.statetest {
+            Some(path) => self.statetest_execution(path)?,
+            None => self.bytecode_execution()?,
+        };
+
+        let mut debugger = Debugger::new(io::stdin().lock(), io::stdout());
+        for breakpoint in &self.breakpoints {
+            debugger.add_breakpoint(*breakpoint)?;
+        }
+        if !self.breakpoints.is_empty() {
+            debugger.continue_to_breakpoint();
+        }
+        debug(&execution, &mut debugger)
+    }
+
+    fn bytecode_execution(&self) -> Result<Execution, Error> {
+        let bytecode = match &self.path {
+            Some(path) => fs::read_to_string(path)?,
+            None => self.bytecode.clone().unwrap_or_default(),
+        };
+        let bytecode: Bytes = hex::decode(bytecode.trim())
+            .map_err(|_| Error::InvalidBytecode)?
+            .into();
+        let input: Bytes = hex::decode(self.input.trim())
+            .map_err(|_| Error::InvalidInput)?
+            .into();
+
+        let mut prestate = CacheState::new(false);
+        let (kind, data) = if self.create {
+            (TxKind::Create, bytecode)
+        } else {
+            let code = Bytecode::new_raw_checked(bytecode)?;
+            let info = AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            };
+            prestate.insert_account(self.target, info);
+            (TxKind::Call(self.target), input)
+        };
+
+        let mut cfg = CfgEnv::default();
+        cfg.spec = self.spec.to_spec_id();
+        let mut block = BlockEnv::default();
+        if cfg.spec.is_enabled_in(SpecId::MERGE) {
+            block.prevrandao = Some(Default::default());
+        }
+        let tx = TxEnv {
+            caller: self.caller,
+            kind,
+            data,
+            gas_limit: self.gas,
+            ..Default::default()
+        };
+        Ok(Execution {
+            prestate,
+            cfg,
+            block,
+            tx,
+        })
+    }
+
+    fn statetest_execution(&self, path: &Path) -> Result<Execution, Error> {
+        let suite: TestSuite =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(Error::InvalidStatetest)?;
+        let filter = TestFilter {
+            forks: self.fork.into_iter().collect(),
+            name: self.name.clone(),
+            data: self.data,
+            gas: self.gas_index,
+            value: self.value,
+        };
+
+        for (name, unit) in &suite.0 {
+            if !filter.matches_name(name) {
+                continue;
+            }
+            for (spec_name, tests) in &unit.post {
+                if *spec_name == SpecName::Constantinople || !filter.matches_fork(spec_name) {
+                    continue;
+                }
+                for test in tests {
+                    if !filter.matches_indexes(&test.indexes) {
+                        continue;
+                    }
+                    let (mut cfg, mut block, mut tx) = unit_env(unit)?;
+                    set_spec(unit, *spec_name, &mut cfg, &mut block);
+                    if !set_test_tx(unit, test, &mut tx) {
+                        continue;
+                    }
+                    let indexes = &test.indexes;
+                    println!(
+                        "Debugging {name} {spec_name:?} data {} gas {} value {}",
+                        indexes.data, indexes.gas, indexes.value
+                    );
+                    return Ok(Execution {
+                        prestate: unit_prestate(unit),
+                        cfg,
+                        block,
+                        tx,
+                    });
+                }
+            }
+        }
+        Err(Error::NoMatchingTest)
+    }
+}
+
+/// Executes the transaction until the debugger quits, again each time it goes back.
+fn debug<R: BufRead, W: Write>(
+    execution: &Execution,
+    debugger: &mut Debugger<R, W>,
+) -> Result<(), Error> {
+    loop {
+        let result = execution.run(debugger)?;
+        let restart = match debugger.take_restart() {
+            Some(step) => Some(step),
+            None => debugger.finished(&result)?,
+        };
+        let Some(step) = restart else {
+            return Ok(());
+        };
+        debugger.restart(step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripted_session() {
+        // PUSH1 0x2a PUSH1 0x01 SSTORE PUSH1 0x07 PUSH1 0x02 TSTORE STOP
+        let cmd = Cmd::parse_from(["debug", "602a600155600760025d00"]);
+        let execution = cmd.bytecode_execution().unwrap();
+
+        let script = "b op TSTORE\nc\nsl\nts\nst\nn\nts\nback 3\nbt\nq\nback\n\n";
+        let mut output = Vec::new();
+        let mut debugger = Debugger::new(script.as_bytes(), &mut output);
+        debug(&execution, &mut debugger).unwrap();
+        drop(debugger);
+        let output = String::from_utf8(output).unwrap();
+
+        let target = Address::ZERO;
+        for expected in [
+            format!("#0 depth 1 {target} pc 0 PUSH1 0x2a gas"),
+            "Stopped at breakpoint 0, opcode TSTORE\n".to_string(),
+            format!("#5 depth 1 {target} pc 9 TSTORE"),
+            "(debug) 0x1: 0x2a\n".to_string(),
+            "(debug) Transient storage is empty\n".to_string(),
+            "   0: 0x2\n   1: 0x7\n".to_string(),
+            "(debug) 0x2: 0x7\n".to_string(),
+            format!("#3 depth 1 {target} pc 5 PUSH1 0x07"),
+            format!("(debug) #1 TX {target} pc 5 PUSH1\n"),
+            "Success (Stop)".to_string(),
+            "Execution finished after 7 steps".to_string(),
+            format!("#6 depth 1 {target} pc 10 STOP"),
+        ] {
+            assert!(
+                output.contains(&expected),
+                "missing {expected:?} in\n{output}"
+            );
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use revm::{
+    bytecode::opcode::OpCode,
+    primitives::{Address, U256},
+};
+use std::{fmt, str::FromStr};
+
+/// Condition that stops the execution before an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Instruction at the program counter, in any frame.
+    Pc(usize),
+    /// Every execution of the opcode.
+    Opcode(u8),
+    /// First instruction of a frame executing at the address.
+    Address(Address),
+    /// First instruction of a frame at the call depth.
+    Depth(usize),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pc(pc) => write!(f, "pc {pc}"),
+            Self::Opcode(op) => write!(f, "opcode {}", OpCode::name_by_op(*op)),
+            Self::Address(address) => write!(f, "address {address}"),
+            Self::Depth(depth) => write!(f, "depth {depth}"),
+        }
+    }
+}
+
+/// Command of the debugger prompt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Executes the instruction, stopping in called frames.
+    StepInto,
+    /// Executes the instruction, calls run until they return.
+    StepOver,
+    /// Runs until the current frame returns.
+    StepOut,
+    /// Runs until a breakpoint.
+    Continue,
+    Break(Breakpoint),
+    Breakpoints,
+    Delete(usize),
+    Stack,
+    Memory {
+        offset: usize,
+        len: Option<usize>,
+    },
+    Storage(Option<U256>),
+    TransientStorage(Option<U256>),
+    ReturnData,
+    Calls,
+    Where,
+    /// Goes back the number of steps by running the execution again.
+    Back(usize),
+    /// Runs to the step, again from the start if it is before the current one.
+    Goto(usize),
+    Help,
+    Quit,
+}
+
+pub const HELP: &str = "\
+s, step              execute the instruction, stepping into calls
+n, next              execute the instruction, stepping over calls
+o, out               run until the current call returns
+c, continue          run until a breakpoint
+b, break <kind> <v>  stop at `pc <n>`, `op <name>`, `addr <address>` or `depth <n>`
+bl, breakpoints      list the breakpoints
+d, delete <index>    delete a breakpoint
+st, stack            print the stack, top first
+m, memory [off] [n]  print the memory
+sl, storage [slot]   print the storage of the current address
+ts, tstorage [slot]  print the transient storage of the current address
+r, returndata        print the return data of the last call
+bt, calls            print the call stack
+w, where             print the current instruction
+back [n]             go back n steps, 1 by default
+g, goto <step>       run to the step
+q, quit              run to the end without stopping
+an empty line repeats the last step command";
+
+/// Parses `<kind> <value>`, as in `pc 10`, `op SSTORE`, `addr 0x..` or `depth 2`.
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let (Some(kind), Some(value), None) = (words.next(), words.next(), words.next()) else {
+            return Err(format!(
+                "invalid breakpoint `{s}`, expected `<kind> <value>`"
+            ));
+        };
+        let breakpoint = match kind {
+            "pc" => Self::Pc(parse_number(value)?),
+            "op" | "opcode" => Self::Opcode(parse_opcode(value)?),
+            "addr" | "address" => Self::Address(
+                value
+                    .parse()
+                    .map_err(|_| format!("invalid address `{value}`"))?,
+            ),
+            "depth" => Self::Depth(parse_number(value)?),
+            kind => return Err(format!("unknown breakpoint kind `{kind}`")),
+        };
+        Ok(breakpoint)
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("empty command".to_string());
+        };
+        let args: Vec<&str> = words.collect();
+        let arg = |index: usize| -> Result<&str, String> {
+            args.get(index)
+                .copied()
+                .ok_or_else(|| format!("`{name}` is missing an argument"))
+        };
+
+        let command = match name {
+            "s" | "step" => Self::StepInto,
+            "n" | "next" => Self::StepOver,
+            "o" | "out" => Self::StepOut,
+            "c" | "continue" => Self::Continue,
+            "b" | "break" => Self::Break(args.join(" ").parse()?),
+            "bl" | "breakpoints" => Self::Breakpoints,
+            "d" | "delete" => Self::Delete(parse_number(arg(0)?)?),
+            "st" | "stack" => Self::Stack,
+            "m" | "memory" => Self::Memory {
+                offset: args.first().map_or(Ok(0), |s| parse_number(s))?,
+                len: args.get(1).map(|s| parse_number(s)).transpose()?,
+            },
+            "sl" | "storage" => Self::Storage(args.first().map(|s| parse_u256(s)).transpose()?),
+            "ts" | "tstorage" => {
+                Self::TransientStorage(args.first().map(|s| parse_u256(s)).transpose()?)
+            }
+            "r" | "returndata" => Self::ReturnData,
+            "bt" | "calls" => Self::Calls,
+            "w" | "where" => Self::Where,
+            "back" => Self::Back(args.first().map_or(Ok(1), |s| parse_number(s))?),
+            "g" | "goto" => Self::Goto(parse_number(arg(0)?)?),
+            "h" | "help" => Self::Help,
+            "q" | "quit" => Self::Quit,
+            name => {
+                return Err(format!(
+                    "unknown command `{name}`, `help` lists the commands"
+                ))
+            }
+        };
+        Ok(command)
+    }
+}
+
+/// Parses a decimal or `0x` prefixed hex number.
+fn parse_number(s: &str) -> Result<usize, String> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| format!("invalid number `{s}`"))
+}
+
+fn parse_u256(s: &str) -> Result<U256, String> {
+    U256::from_str(s).map_err(|_| format!("invalid number `{s}`"))
+}
+
+/// Parses an opcode name or number.
+fn parse_opcode(s: &str) -> Result<u8, String> {
+    if let Some(op) = OpCode::parse(&s.to_uppercase()) {
+        return Ok(op.get());
+    }
+    parse_number(s)
+        .ok()
+        .and_then(|op| u8::try_from(op).ok())
+        .ok_or_else(|| format!("unknown opcode `{s}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!("n".parse(), Ok(Command::StepOver));
+        assert_eq!(
+            "break op sstore".parse(),
+            Ok(Command::Break(Breakpoint::Opcode(0x55)))
+        );
+        assert_eq!("b pc 0x10".parse(), Ok(Command::Break(Breakpoint::Pc(16))));
+        assert_eq!(
+            "m 32".parse(),
+            Ok(Command::Memory {
+                offset: 32,
+                len: None
+            })
+        );
+        assert_eq!("sl 0x1".parse(), Ok(Command::Storage(Some(U256::from(1)))));
+        assert_eq!("back".parse(), Ok(Command::Back(1)));
+        assert!("b pc".parse::<Command>().is_err());
+        assert!("b op NOPE".parse::<Command>().is_err());
+    }
+}
@@ -0,0 +1,478 @@
+use super::command::{Breakpoint, Command, HELP};
+use inspector::journal::{JournalExt, JournalExtGetter};
+use revm::{
+    bytecode::opcode::OpCode,
+    context_interface::{
+        result::{ExecutionResult, HaltReason},
+        Journal, JournalGetter,
+    },
+    database_interface::Database,
+    interpreter::{
+        interpreter::EthInterpreter,
+        interpreter_types::{
+            InputsTrait, Jumps, LegacyBytecode, LoopControl, MemoryTrait, ReturnData, RuntimeFlag,
+        },
+        Interpreter,
+    },
+    primitives::{hex, Address, U256},
+};
+use std::io::{self, BufRead, Write};
+
+/// How the execution continues until the next stop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    /// Stops before the next instruction.
+    StepInto,
+    /// Stops at the next instruction at the depth or above it.
+    StepOver(usize),
+    /// Stops at the next instruction above the depth.
+    StepOut(usize),
+    /// Stops at a breakpoint.
+    Continue,
+    /// Stops at the step, breakpoints are ignored.
+    RunTo(usize),
+    /// Runs to the end without stopping.
+    Finish,
+}
+
+/// Frame of the call stack.
+#[derive(Clone, Debug)]
+struct Frame {
+    /// Instruction that created the frame, `TX` for the frame of the transaction.
+    kind: &'static str,
+    address: Address,
+    /// Program counter of the last instruction of the frame.
+    pc: usize,
+    opcode: u8,
+}
+
+/// Interactive debugger [`Inspector`](inspector::Inspector).
+///
+/// Stops before instructions and reads commands from `input` until one of them resumes the
+/// execution. Going back to a previous step can't be done in place, the debugger then finishes the
+/// execution silently and [`Debugger::restart`] has to be used to execute the transaction again.
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    /// Index of the current instruction in the execution.
+    step: usize,
+    mode: Mode,
+    breakpoints: Vec<Breakpoint>,
+    frames: Vec<Frame>,
+    /// Step to go back to after the execution.
+    restart: Option<usize>,
+    /// Last step command, repeated by an empty line.
+    last: Command,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            step: 0,
+            mode: Mode::StepInto,
+            breakpoints: Vec::new(),
+            frames: Vec::new(),
+            restart: None,
+            last: Command::StepInto,
+        }
+    }
+
+    /// Returns the step to go back to, if a command asked for it.
+    pub fn take_restart(&mut self) -> Option<usize> {
+        self.restart.take()
+    }
+
+    /// Prepares the debugger to execute the transaction again and stop at the step.
+    ///
+    /// Breakpoints are kept.
+    pub fn restart(&mut self, step: usize) {
+        self.step = 0;
+        self.frames.clear();
+        self.mode = Mode::RunTo(step);
+    }
+
+    /// Runs to the first breakpoint instead of stopping at the first instruction.
+    pub fn continue_to_breakpoint(&mut self) {
+        self.mode = Mode::Continue;
+    }
+
+    /// Writes the result and reads commands after the end of the execution.
+    ///
+    /// Returns the step to go back to, `None` to quit.
+    pub fn finished(&mut self, result: &ExecutionResult<HaltReason>) -> io::Result<Option<usize>> {
+        match result {
+            ExecutionResult::Success {
+                reason,
+                gas_used,
+                output,
+                ..
+            } => writeln!(
+                self.output,
+                "Success ({reason:?}), gas used {gas_used}, output {}",
+                hex::encode_prefixed(output.data())
+            )?,
+            ExecutionResult::Revert { gas_used, output } => writeln!(
+                self.output,
+                "Revert, gas used {gas_used}, output {}",
+                hex::encode_prefixed(output)
+            )?,
+            ExecutionResult::Halt { reason, gas_used } => {
+                writeln!(self.output, "Halt ({reason:?}), gas used {gas_used}")?
+            }
+        }
+        writeln!(self.output, "Execution finished after {} steps", self.step)?;
+        loop {
+            let Some(command) = self.read_command()? else {
+                return Ok(None);
+            };
+            match command {
+                Command::Back(steps) => return Ok(Some(self.step.saturating_sub(steps))),
+                Command::Goto(step) if step < self.step => return Ok(Some(step)),
+                Command::Quit => return Ok(None),
+                Command::Help => writeln!(self.output, "{HELP}")?,
+                Command::Break(breakpoint) => self.add_breakpoint(breakpoint)?,
+                Command::Breakpoints => self.list_breakpoints()?,
+                Command::Delete(index) => self.delete_breakpoint(index)?,
+                _ => writeln!(
+                    self.output,
+                    "Execution has finished, use `back`, `goto` or `quit`"
+                )?,
+            }
+        }
+    }
+
+    /// Reads the next command, `None` at the end of the input.
+    ///
+    /// Invalid commands are reported and skipped.
+    fn read_command(&mut self) -> io::Result<Option<Command>> {
+        loop {
+            write!(self.output, "(debug) ")?;
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                writeln!(self.output)?;
+                return Ok(None);
+            }
+            if line.trim().is_empty() {
+                return Ok(Some(self.last.clone()));
+            }
+            match line.parse() {
+                Ok(command) => return Ok(Some(command)),
+                Err(error) => writeln!(self.output, "{error}")?,
+            }
+        }
+    }
+
+    fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns the breakpoint that stops the execution before the instruction.
+    fn breakpoint(&self, pc: usize, opcode: u8, frame_start: bool) -> Option<usize> {
+        let address = self.frames.last().map(|frame| frame.address);
+        self.breakpoints
+            .iter()
+            .position(|breakpoint| match *breakpoint {
+                Breakpoint::Pc(at) => at == pc,
+                Breakpoint::Opcode(op) => op == opcode,
+                Breakpoint::Address(at) => frame_start && address == Some(at),
+                Breakpoint::Depth(depth) => frame_start && depth == self.depth(),
+            })
+    }
+
+    /// Returns whether the execution stops before the instruction.
+    fn should_stop(&self, breakpoint: Option<usize>) -> bool {
+        let depth = self.depth();
+        match self.mode {
+            Mode::StepInto => true,
+            Mode::RunTo(step) => step == self.step,
+            Mode::Finish => false,
+            Mode::StepOver(at) if depth <= at => true,
+            Mode::StepOut(at) if depth < at => true,
+            Mode::StepOver(_) | Mode::StepOut(_) | Mode::Continue => breakpoint.is_some(),
+        }
+    }
+
+    /// Reads and executes commands until one of them resumes the execution.
+    fn prompt<CTX>(
+        &mut self,
+        interp: &mut Interpreter<EthInterpreter>,
+        context: &mut CTX,
+    ) -> io::Result<()>
+    where
+        CTX: JournalGetter + JournalExtGetter,
+    {
+        self.location(interp)?;
+        loop {
+            let Some(command) = self.read_command()? else {
+                self.mode = Mode::Finish;
+                return Ok(());
+            };
+            let depth = self.depth();
+            let mode = match command {
+                Command::StepInto => Mode::StepInto,
+                Command::StepOver => Mode::StepOver(depth),
+                Command::StepOut => Mode::StepOut(depth),
+                Command::Continue => Mode::Continue,
+                Command::Quit => Mode::Finish,
+                Command::Back(steps) => {
+                    self.restart = Some(self.step.saturating_sub(steps));
+                    Mode::Finish
+                }
+                Command::Goto(step) if step > self.step => Mode::RunTo(step),
+                Command::Goto(step) if step < self.step => {
+                    self.restart = Some(step);
+                    Mode::Finish
+                }
+                command => {
+                    self.inspect(command, interp, context)?;
+                    continue;
+                }
+            };
+            if matches!(
+                command,
+                Command::StepInto | Command::StepOver | Command::StepOut | Command::Continue
+            ) {
+                self.last = command;
+            }
+            self.mode = mode;
+            return Ok(());
+        }
+    }
+
+    /// Executes a command that doesn't resume the execution.
+    fn inspect<CTX>(
+        &mut self,
+        command: Command,
+        interp: &mut Interpreter<EthInterpreter>,
+        context: &mut CTX,
+    ) -> io::Result<()>
+    where
+        CTX: JournalGetter + JournalExtGetter,
+    {
+        let address = interp.input.target_address();
+        match command {
+            Command::Break(breakpoint) => self.add_breakpoint(breakpoint)?,
+            Command::Breakpoints => self.list_breakpoints()?,
+            Command::Delete(index) => self.delete_breakpoint(index)?,
+            Command::Stack => {
+                let stack = interp.stack.data();
+                if stack.is_empty() {
+                    writeln!(self.output, "Stack is empty")?;
+                }
+                for (index, item) in stack.iter().rev().enumerate() {
+                    writeln!(self.output, "{index:>4}: {item:#x}")?;
+                }
+            }
+            Command::Memory { offset, len } => {
+                let size = interp.memory.size();
+                let end = len.map_or(size, |len| offset.saturating_add(len).min(size));
+                writeln!(self.output, "Memory size {size}")?;
+                if offset < end {
+                    let memory = interp.memory.slice(offset..end);
+                    for (index, word) in memory.chunks(32).enumerate() {
+                        let offset = offset + index * 32;
+                        writeln!(self.output, "{offset:#06x}: {}", hex::encode(word))?;
+                    }
+                }
+            }
+            Command::Storage(slot) => {
+                let loaded = context
+                    .journal_ext()
+                    .evm_state()
+                    .get(&address)
+                    .map(|account| &account.storage);
+                match slot {
+                    Some(slot) => {
+                        let value = match loaded.and_then(|storage| storage.get(&slot)) {
+                            Some(value) => Ok(value.present_value),
+                            None => context.journal().db().storage(address, slot),
+                        };
+                        match value {
+                            Ok(value) => writeln!(self.output, "{slot:#x}: {value:#x}")?,
+                            Err(_) => writeln!(self.output, "Failed to read slot {slot:#x}")?,
+                        }
+                    }
+                    None => {
+                        let mut slots: Vec<_> = loaded
+                            .into_iter()
+                            .flatten()
+                            .map(|(slot, value)| (*slot, value.present_value))
+                            .collect();
+                        slots.sort_unstable();
+                        if slots.is_empty() {
+                            writeln!(self.output, "No storage slots loaded")?;
+                        }
+                        for (slot, value) in slots {
+                            writeln!(self.output, "{slot:#x}: {value:#x}")?;
+                        }
+                    }
+                }
+            }
+            Command::TransientStorage(slot) => {
+                let Some(transient) = context.journal_ext().transient_storage() else {
+                    return writeln!(self.output, "Transient storage is not available");
+                };
+                let mut slots: Vec<(U256, U256)> = transient
+                    .iter()
+                    .filter(|((at, key), _)| *at == address && slot.is_none_or(|slot| slot == *key))
+                    .map(|((_, key), value)| (*key, *value))
+                    .collect();
+                slots.sort_unstable();
+                if slots.is_empty() {
+                    match slot {
+                        Some(slot) => writeln!(self.output, "{slot:#x}: 0x0")?,
+                        None => writeln!(self.output, "Transient storage is empty")?,
+                    }
+                }
+                for (slot, value) in slots {
+                    writeln!(self.output, "{slot:#x}: {value:#x}")?;
+                }
+            }
+            Command::ReturnData => {
+                let data = interp.return_data.buffer();
+                writeln!(
+                    self.output,
+                    "Return data ({} bytes): {}",
+                    data.len(),
+                    hex::encode_prefixed(data)
+                )?;
+            }
+            Command::Calls => {
+                for (index, frame) in self.frames.iter().enumerate().rev() {
+                    writeln!(
+                        self.output,
+                        "#{} {} {} pc {} {}",
+                        index + 1,
+                        frame.kind,
+                        frame.address,
+                        frame.pc,
+                        OpCode::name_by_op(frame.opcode)
+                    )?;
+                }
+            }
+            Command::Where => self.location(interp)?,
+            Command::Goto(_) => writeln!(self.output, "Already at step {}", self.step)?,
+            Command::Help => writeln!(self.output, "{HELP}")?,
+            Command::StepInto
+            | Command::StepOver
+            | Command::StepOut
+            | Command::Continue
+            | Command::Back(_)
+            | Command::Quit => unreachable!("resuming commands are handled by the prompt"),
+        }
+        Ok(())
+    }
+
+    /// Writes the current instruction.
+    fn location(&mut self, interp: &mut Interpreter<EthInterpreter>) -> io::Result<()> {
+        let pc = interp.bytecode.pc();
+        let opcode = interp.bytecode.opcode();
+        write!(
+            self.output,
+            "#{} depth {} {} pc {pc} {}",
+            self.step,
+            self.depth(),
+            interp.input.target_address(),
+            OpCode::name_by_op(opcode)
+        )?;
+        let immediate = OpCode::new(opcode).map_or(0, |op| op.info().immediate_size() as usize);
+        if immediate > 0 && !interp.runtime_flag.is_eof() {
+            let code = interp.bytecode.bytecode_slice();
+            let start = (pc + 1).min(code.len());
+            let end = (start + immediate).min(code.len());
+            write!(self.output, " {}", hex::encode_prefixed(&code[start..end]))?;
+        }
+        writeln!(self.output, " gas {}", interp.control.gas().remaining())
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> io::Result<()> {
+        self.breakpoints.push(breakpoint);
+        writeln!(
+            self.output,
+            "Breakpoint {} at {breakpoint}",
+            self.breakpoints.len() - 1
+        )
+    }
+
+    fn list_breakpoints(&mut self) -> io::Result<()> {
+        if self.breakpoints.is_empty() {
+            writeln!(self.output, "No breakpoints")?;
+        }
+        for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+            writeln!(self.output, "{index}: {breakpoint}")?;
+        }
+        Ok(())
+    }
+
+    fn delete_breakpoint(&mut self, index: usize) -> io::Result<()> {
+        if index < self.breakpoints.len() {
+            let breakpoint = self.breakpoints.remove(index);
+            writeln!(self.output, "Deleted breakpoint at {breakpoint}")
+        } else {
+            writeln!(self.output, "No breakpoint {index}")
+        }
+    }
+}
+
+impl<CTX, R, W> inspector::Inspector<CTX, EthInterpreter> for Debugger<R, W>
+where
+    CTX: JournalGetter + JournalExtGetter,
+    R: BufRead,
+    W: Write,
+{
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        let pc = interp.bytecode.pc();
+        let opcode = interp.bytecode.opcode();
+        // Frames are tracked by the depth of the journal, frames deeper than the current
+        // instruction have returned.
+        let depth = context.journal().depth();
+        self.frames.truncate(depth);
+        let frame_start = self.frames.len() < depth;
+        if frame_start {
+            // The parent frame is stopped at the instruction that created this one.
+            let kind = self
+                .frames
+                .last()
+                .map_or("TX", |parent| OpCode::name_by_op(parent.opcode));
+            self.frames.push(Frame {
+                kind,
+                address: interp.input.target_address(),
+                pc,
+                opcode,
+            });
+        }
+        if let Some(frame) = self.frames.last_mut() {
+            frame.pc = pc;
+            frame.opcode = opcode;
+        }
+
+        let breakpoint = self.breakpoint(pc, opcode, frame_start);
+        if self.should_stop(breakpoint) {
+            let hit = breakpoint.filter(|_| {
+                matches!(
+                    self.mode,
+                    Mode::Continue | Mode::StepOver(_) | Mode::StepOut(_)
+                )
+            });
+            let result = match hit {
+                Some(index) => writeln!(
+                    self.output,
+                    "Stopped at breakpoint {index}, {}",
+                    self.breakpoints[index]
+                ),
+                None => Ok(()),
+            }
+            .and_then(|_| self.prompt(interp, context));
+            if result.is_err() {
+                self.mode = Mode::Finish;
+            }
+        }
+    }
+
+    fn step_end(&mut self, _interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        self.step += 1;
+    }
+}
@@ -1,12 +1,21 @@
+use crate::cmd::{statetest::parse_fork, t8n::types::Alloc};
 use clap::Parser;
-use database::BenchmarkDB;
+use database::CacheDB;
 use inspector::{exec::InspectEvm, inspectors::TracerEip3155};
 use revm::{
     bytecode::{Bytecode, BytecodeDecodeError},
-    primitives::{address, hex, Address, TxKind},
-    transact_main, Context, Database, ExecuteEvm,
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::result::{ExecutionResult, HaltReason, Output},
+    database_interface::{DatabaseRef, EmptyDB},
+    handler::validation::validate_initial_tx_gas,
+    primitives::{hex, keccak256, Address, Bytes, TxKind, B256, U256},
+    specification::{gas_schedule::GasSchedule, hardfork::SpecId},
+    state::{AccountInfo, EvmState},
+    Context, ExecuteEvm,
 };
-use std::io::Error as IoError;
+use serde_json::{json, Map, Value};
+use statetest_types::SpecName;
+use std::io::{BufWriter, Error as IoError, Write};
 use std::path::PathBuf;
 use std::time::Duration;
 use std::{borrow::Cow, fs};
@@ -19,8 +28,12 @@ pub enum Errors {
     InvalidBytecode,
     #[error("Invalid input")]
     InvalidInput,
-    #[error("EVM Error")]
-    EVMError,
+    #[error("Invalid prestate: {0}")]
+    InvalidPrestate(serde_json::Error),
+    #[error("Bytecode is required to create a contract")]
+    MissingBytecode,
+    #[error("EVM Error: {0}")]
+    EVMError(String),
     #[error(transparent)]
     Io(#[from] IoError),
     #[error(transparent)]
@@ -29,16 +42,17 @@ pub enum Errors {
 
 /// Evm runner command allows running arbitrary evm bytecode
 ///
-/// Bytecode can be provided from cli or from file with `--path` option.
+/// Bytecode can be provided from cli or from file with `--path` option. It is deployed at the
+/// target address, on top of the accounts of the prestate.
 #[derive(Parser, Debug)]
 pub struct Cmd {
     /// Hex-encoded EVM bytecode to be executed
-    #[arg(required_unless_present = "path")]
+    #[arg(required_unless_present_any = ["path", "prestate"])]
     bytecode: Option<String>,
     /// Path to a file containing the hex-encoded EVM bytecode to be executed
     ///
     /// Overrides the positional `bytecode` argument.
-    #[arg(long)]
+    #[arg(long, alias = "codefile")]
     path: Option<PathBuf>,
     /// Whether to run in benchmarking mode
     #[arg(long)]
@@ -52,42 +66,133 @@ pub struct Cmd {
     /// Whether to print the trace
     #[arg(long)]
     trace: bool,
+    /// Write the EIP-3155 trace to the file instead of stdout
+    #[arg(long = "trace.file")]
+    trace_file: Option<PathBuf>,
+    /// Include memory in the trace
+    #[arg(long = "trace.memory")]
+    trace_memory: bool,
+    /// Print the result, gas, logs and state diff as JSON
+    #[arg(long)]
+    json: bool,
+    /// Fork of the execution, named as in the tests
+    #[arg(long, default_value = "Prague", value_parser = parse_fork)]
+    spec: SpecName,
+    /// JSON file with the accounts of the prestate, in the `alloc.json` format of `t8n`
+    #[arg(long, alias = "alloc")]
+    prestate: Option<PathBuf>,
+    /// Address of the caller
+    #[arg(long, default_value = "0x0000000000000000000000000000000000000001")]
+    caller: Address,
+    /// Address the bytecode is deployed at and called
+    #[arg(long, default_value = "0x0000000000000000000000000000000000000000")]
+    target: Address,
+    /// Run the bytecode as initcode of a contract creation
+    #[arg(long)]
+    create: bool,
+    /// Gas limit of the transaction
+    #[arg(long, default_value_t = 30_000_000)]
+    gas: u64,
+    /// Gas price of the transaction
+    #[arg(long = "gas-price", default_value_t = 0)]
+    gas_price: u128,
+    /// Value sent with the transaction, the caller needs the balance in the prestate
+    #[arg(long, default_value = "0")]
+    value: U256,
+    /// Number of the block
+    #[arg(long = "block.number")]
+    block_number: Option<u64>,
+    /// Timestamp of the block
+    #[arg(long = "block.timestamp")]
+    block_timestamp: Option<u64>,
+    /// Beneficiary of the block
+    #[arg(long = "block.coinbase")]
+    block_coinbase: Option<Address>,
+    /// Gas limit of the block
+    #[arg(long = "block.gaslimit")]
+    block_gas_limit: Option<u64>,
+    /// Base fee of the block
+    #[arg(long = "block.basefee")]
+    block_basefee: Option<u64>,
+    /// Difficulty of the block, before the merge
+    #[arg(long = "block.difficulty")]
+    block_difficulty: Option<U256>,
+    /// Randomness of the block, after the merge
+    #[arg(long = "block.prevrandao")]
+    block_prevrandao: Option<B256>,
 }
 
 impl Cmd {
-    /// Runs evm runner command.
-    pub fn run(&self) -> Result<(), Errors> {
-        const CALLER: Address = address!("0000000000000000000000000000000000000001");
-
-        let bytecode_str: Cow<'_, str> = if let Some(path) = &self.path {
+    /// Returns the database and the environment of the transaction.
+    fn env(&self) -> Result<(CacheDB<EmptyDB>, BlockEnv, TxEnv, CfgEnv), Errors> {
+        let bytecode_str: Option<Cow<'_, str>> = if let Some(path) = &self.path {
             // Check if path exists.
             if !path.exists() {
                 return Err(Errors::PathNotExists);
             }
-            fs::read_to_string(path)?.into()
-        } else if let Some(bytecode) = &self.bytecode {
-            bytecode.as_str().into()
+            Some(fs::read_to_string(path)?.into())
         } else {
-            unreachable!()
+            self.bytecode.as_deref().map(Into::into)
         };
-
-        let bytecode = hex::decode(bytecode_str.trim()).map_err(|_| Errors::InvalidBytecode)?;
-        let input = hex::decode(self.input.trim())
+        let bytecode: Option<Bytes> = bytecode_str
+            .map(|s| hex::decode(s.trim()).map_err(|_| Errors::InvalidBytecode))
+            .transpose()?
+            .map(Into::into);
+        let input: Bytes = hex::decode(self.input.trim())
             .map_err(|_| Errors::InvalidInput)?
             .into();
 
-        let mut db = BenchmarkDB::new_bytecode(Bytecode::new_raw_checked(bytecode.into())?);
+        let mut db = CacheDB::new(EmptyDB::default());
+        if let Some(path) = &self.prestate {
+            let alloc: Alloc = serde_json::from_str(&fs::read_to_string(path)?)
+                .map_err(Errors::InvalidPrestate)?;
+            insert_alloc(&mut db, alloc);
+        }
+
+        let (kind, data) = if self.create {
+            let code = bytecode.ok_or(Errors::MissingBytecode)?;
+            (TxKind::Create, code)
+        } else {
+            if let Some(code) = bytecode {
+                let mut info = db.basic_ref(self.target).unwrap().unwrap_or_default();
+                let code = Bytecode::new_raw_checked(code)?;
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+                db.insert_account_info(self.target, info);
+            }
+            (TxKind::Call(self.target), input)
+        };
+        let nonce = db
+            .basic_ref(self.caller)
+            .unwrap()
+            .map_or(0, |account| account.nonce);
 
-        let nonce = db.basic(CALLER).unwrap().map_or(0, |account| account.nonce);
+        let spec = self.spec.to_spec_id();
+        let mut cfg = CfgEnv::default();
+        cfg.spec = spec;
+        let tx = TxEnv {
+            caller: self.caller,
+            kind,
+            data,
+            nonce,
+            value: self.value,
+            gas_limit: self.gas,
+            gas_price: self.gas_price,
+            ..Default::default()
+        };
+        let block = self.block_env(spec);
+        Ok((db, block, tx, cfg))
+    }
 
-        // BenchmarkDB is dummy state that implements Database trait.
-        // The bytecode is deployed at zero address.
-        let mut ctx = Context::builder().with_db(db).modify_tx_chained(|tx| {
-            tx.caller = CALLER;
-            tx.kind = TxKind::Call(Address::ZERO);
-            tx.data = input;
-            tx.nonce = nonce;
-        });
+    /// Runs evm runner command.
+    pub fn run(&self) -> Result<(), Errors> {
+        let spec = self.spec.to_spec_id();
+        let (db, block, tx, cfg) = self.env()?;
+        let mut ctx = Context::builder()
+            .with_block(block)
+            .with_tx(tx)
+            .with_cfg(cfg)
+            .with_db(db);
 
         if self.bench {
             // Microbenchmark
@@ -100,15 +205,50 @@ impl Cmd {
             return Ok(());
         }
 
-        let out = if self.trace {
-            let inspector = TracerEip3155::new(Box::new(std::io::stdout()));
+        let out = if self.trace || self.trace_file.is_some() {
+            let writer: Box<dyn Write> = match &self.trace_file {
+                Some(path) => Box::new(BufWriter::new(fs::File::create(path)?)),
+                None => Box::new(std::io::stdout()),
+            };
+            let mut inspector = TracerEip3155::new(writer);
+            if self.trace_memory {
+                inspector = inspector.with_memory();
+            }
             ctx.inspect_previous(inspector)
-                .map_err(|_| Errors::EVMError)?
         } else {
-            let out = transact_main(&mut ctx).map_err(|_| Errors::EVMError)?;
+            ctx.exec_previous()
+        }
+        .map_err(|e| Errors::EVMError(e.to_string()))?;
+
+        if self.json {
+            let gas = validate_initial_tx_gas(&ctx.tx, spec, GasSchedule::for_spec(spec))
+                .unwrap_or_default();
+            let refunded = match &out.result {
+                ExecutionResult::Success { gas_refunded, .. } => *gas_refunded,
+                _ => 0,
+            };
+            let json = json!({
+                "result": result_json(&out.result),
+                "gas": {
+                    "limit": self.gas,
+                    "intrinsic": gas.initial_gas,
+                    "floor": gas.floor_gas,
+                    "execution": (out.result.gas_used() + refunded)
+                        .saturating_sub(gas.initial_gas),
+                    "refunded": refunded,
+                    "used": out.result.gas_used(),
+                },
+                "logs": out.result.logs().iter().map(|log| json!({
+                    "address": log.address,
+                    "topics": log.topics(),
+                    "data": log.data.data,
+                })).collect::<Vec<_>>(),
+                "stateDiff": state_diff(&ctx.journaled_state.database, &out.state),
+            });
+            println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        } else {
             println!("Result: {:#?}", out.result);
-            out
-        };
+        }
 
         if self.state {
             println!("State: {:#?}", out.state);
@@ -116,4 +256,199 @@ impl Cmd {
 
         Ok(())
     }
+
+    /// Executes the transaction and writes its EIP-3155 trace to `output`, nothing is printed.
+    pub fn trace(&self, output: Box<dyn Write>) -> Result<(), Errors> {
+        let (db, block, tx, cfg) = self.env()?;
+        let mut ctx = Context::builder()
+            .with_block(block)
+            .with_tx(tx)
+            .with_cfg(cfg)
+            .with_db(db);
+        let mut inspector = TracerEip3155::new(output);
+        if self.trace_memory {
+            inspector = inspector.with_memory();
+        }
+        ctx.inspect_previous(inspector)
+            .map_err(|e| Errors::EVMError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn block_env(&self, spec: SpecId) -> BlockEnv {
+        let mut block = BlockEnv::default();
+        if let Some(number) = self.block_number {
+            block.number = number;
+        }
+        if let Some(timestamp) = self.block_timestamp {
+            block.timestamp = timestamp;
+        }
+        if let Some(coinbase) = self.block_coinbase {
+            block.beneficiary = coinbase;
+        }
+        if let Some(gas_limit) = self.block_gas_limit {
+            block.gas_limit = gas_limit;
+        }
+        if let Some(basefee) = self.block_basefee {
+            block.basefee = basefee;
+        }
+        if let Some(difficulty) = self.block_difficulty {
+            block.difficulty = difficulty;
+        }
+        if let Some(prevrandao) = self.block_prevrandao {
+            block.prevrandao = Some(prevrandao);
+        }
+        if spec.is_enabled_in(SpecId::MERGE) && block.prevrandao.is_none() {
+            block.prevrandao = Some(B256::ZERO);
+        }
+        block
+    }
+}
+
+pub fn insert_alloc(db: &mut CacheDB<EmptyDB>, alloc: Alloc) {
+    for (address, account) in alloc {
+        let code = Bytecode::new_raw_checked(account.code.clone())
+            .unwrap_or(Bytecode::new_legacy(account.code.clone()));
+        let info = AccountInfo {
+            balance: account.balance,
+            nonce: account.nonce.saturating_to(),
+            code_hash: keccak256(&account.code),
+            code: Some(code),
+        };
+        db.insert_account_info(address, info);
+        for (slot, value) in account.storage {
+            db.insert_account_storage(address, slot, value).unwrap();
+        }
+    }
+}
+
+fn result_json(result: &ExecutionResult<HaltReason>) -> Value {
+    let (status, reason) = match result {
+        ExecutionResult::Success { reason, .. } => ("success", format!("{reason:?}")),
+        ExecutionResult::Revert { .. } => ("revert", "Revert".to_string()),
+        ExecutionResult::Halt { reason, .. } => ("halt", format!("{reason:?}")),
+    };
+    let mut json = json!({
+        "status": status,
+        "reason": reason,
+        "output": result.output().cloned().unwrap_or_default(),
+    });
+    if let ExecutionResult::Success {
+        output: Output::Create(_, Some(address)),
+        ..
+    } = result
+    {
+        json["contractAddress"] = json!(address);
+    }
+    json
+}
+
+/// Returns the changes of the accounts, with the value before and after the execution.
+fn state_diff(db: &CacheDB<EmptyDB>, state: &EvmState) -> Value {
+    let mut addresses: Vec<_> = state.keys().collect();
+    addresses.sort();
+
+    let mut diff = Map::new();
+    for address in addresses {
+        let account = &state[address];
+        let pre = db.basic_ref(*address).unwrap().unwrap_or_default();
+        let post = if account.is_selfdestructed() {
+            AccountInfo::default()
+        } else {
+            account.info.clone()
+        };
+
+        let mut changes = Map::new();
+        if pre.balance != post.balance {
+            changes.insert(
+                "balance".into(),
+                json!({ "from": pre.balance, "to": post.balance }),
+            );
+        }
+        if pre.nonce != post.nonce {
+            changes.insert(
+                "nonce".into(),
+                json!({ "from": pre.nonce, "to": post.nonce }),
+            );
+        }
+        if pre.code_hash != post.code_hash {
+            let code = |info: &AccountInfo| {
+                info.code
+                    .as_ref()
+                    .map(Bytecode::original_bytes)
+                    .unwrap_or_default()
+            };
+            changes.insert(
+                "code".into(),
+                json!({ "from": code(&pre), "to": code(&post) }),
+            );
+        }
+        let mut slots: Vec<_> = account
+            .storage
+            .iter()
+            .filter(|(_, slot)| slot.is_changed())
+            .collect();
+        slots.sort_by_key(|(key, _)| *key);
+        let storage: Map<_, _> = slots
+            .into_iter()
+            .map(|(key, slot)| {
+                (
+                    format!("{key:#x}"),
+                    json!({ "from": slot.original_value, "to": slot.present_value }),
+                )
+            })
+            .collect();
+        if !storage.is_empty() {
+            changes.insert("storage".into(), Value::Object(storage));
+        }
+        if !changes.is_empty() {
+            diff.insert(address.to_string(), Value::Object(changes));
+        }
+    }
+    Value::Object(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{context_interface::result::SuccessReason, primitives::address};
+
+    #[test]
+    fn sstore_state_diff() {
+        let target = address!("00000000000000000000000000000000000000aa");
+        let mut db = CacheDB::new(EmptyDB::default());
+        // SSTORE(1, 2)
+        let code = Bytecode::new_raw(hex!("6002600155").into());
+        db.insert_account_info(
+            target,
+            AccountInfo {
+                code_hash: code.hash_slow(),
+                code: Some(code),
+                ..Default::default()
+            },
+        );
+        let tx = TxEnv {
+            kind: TxKind::Call(target),
+            ..Default::default()
+        };
+        let mut ctx = Context::builder().with_tx(tx).with_db(db);
+        let out = ctx.exec_previous().unwrap();
+        assert!(matches!(
+            out.result,
+            ExecutionResult::Success {
+                reason: SuccessReason::Stop,
+                ..
+            }
+        ));
+
+        let diff = state_diff(&ctx.journaled_state.database, &out.state);
+        assert_eq!(
+            diff[target.to_string()]["storage"]["0x1"],
+            json!({ "from": U256::ZERO, "to": U256::from(2) })
+        );
+        assert_eq!(
+            diff[Address::ZERO.to_string()]["nonce"],
+            json!({ "from": 0, "to": 1 })
+        );
+        assert_eq!(result_json(&out.result)["status"], "success");
+    }
 }
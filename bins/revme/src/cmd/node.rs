@@ -0,0 +1,353 @@
+mod chain;
+mod genesis;
+mod rpc;
+
+use crate::cmd::statetest::parse_fork;
+use chain::Chain;
+use clap::Parser;
+use genesis::{ForkFixture, Genesis, Head};
+use revm::primitives::B256;
+use serde::de::DeserializeOwned;
+use statetest_types::SpecName;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tiny_http::{Header, Method, Response, Server};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Failed to parse {path}: {source}")]
+    Json {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Failed to start the server: {0}")]
+    Server(String),
+}
+
+/// `node` subcommand
+///
+/// Runs a development node that serves JSON-RPC over HTTP. The chain starts from a genesis file or
+/// from a fork fixture, a block and the accounts recorded from another chain, and its blocks are
+/// executed with revm.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// Genesis file in the format of geth
+    #[arg(long, required_unless_present = "fork", conflicts_with = "fork")]
+    genesis: Option<PathBuf>,
+    /// Fork fixture with the block and the accounts to start from
+    #[arg(long)]
+    fork: Option<PathBuf>,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// Port to listen on
+    #[arg(long, default_value_t = 8545)]
+    port: u16,
+    /// Mine the pending transactions every interval of seconds instead of as soon as they are sent
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    block_time: Option<u64>,
+    /// Fork of the execution, named as in the tests
+    #[arg(long, default_value = "Prague", value_parser = parse_fork)]
+    spec: SpecName,
+    /// Chain id, instead of the one of the genesis file or fixture
+    #[arg(long)]
+    chain_id: Option<u64>,
+}
+
+impl Cmd {
+    /// Runs `node` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let chain = Arc::new(Mutex::new(self.chain()?));
+        let address = format!("{}:{}", self.host, self.port);
+        let server = Server::http(&address).map_err(|e| Error::Server(e.to_string()))?;
+        {
+            let chain = chain.lock().unwrap();
+            let block = chain.latest();
+            println!(
+                "Listening on http://{address}, chain id {} at block {}",
+                chain.chain_id, block.env.number
+            );
+        }
+
+        if let Some(seconds) = self.block_time {
+            let chain = chain.clone();
+            thread::spawn(move || loop {
+                thread::sleep(Duration::from_secs(seconds));
+                // Blocks are only mined when there are transactions, as in geth's dev mode.
+                let mut chain = chain.lock().unwrap();
+                if chain.has_pending() {
+                    report_rejected(&chain.mine());
+                }
+            });
+        }
+
+        let content_type =
+            Header::from_bytes("Content-Type", "application/json").expect("valid header");
+        for mut request in server.incoming_requests() {
+            if *request.method() != Method::Post {
+                let _ = request.respond(Response::empty(405));
+                continue;
+            }
+            let mut body = String::new();
+            if request.as_reader().read_to_string(&mut body).is_err() {
+                let _ = request.respond(Response::empty(400));
+                continue;
+            }
+            let response = rpc::handle(&mut chain.lock().unwrap(), &body);
+            let body = response.map(|r| r.to_string()).unwrap_or_default();
+            let _ = request.respond(Response::from_string(body).with_header(content_type.clone()));
+        }
+        Ok(())
+    }
+
+    fn chain(&self) -> Result<Chain, Error> {
+        let (mut head, alloc): (Head, _) = match (&self.genesis, &self.fork) {
+            (Some(path), _) => read_json::<Genesis>(path)?.into_head(),
+            (None, Some(path)) => read_json::<ForkFixture>(path)?.into_head(),
+            (None, None) => unreachable!("clap requires a genesis or a fork"),
+        };
+        if let Some(chain_id) = self.chain_id {
+            head.chain_id = chain_id;
+        }
+        let instant = self.block_time.is_none();
+        Ok(Chain::new(head, alloc, self.spec.to_spec_id(), instant))
+    }
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let json = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    serde_json::from_str(&json).map_err(|source| Error::Json {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn report_rejected(rejected: &[(B256, String)]) {
+    for (hash, reason) in rejected {
+        eprintln!("Dropped transaction {hash}: {reason}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::t8n::transaction::sign;
+    use revm::{
+        primitives::{address, b256, Bytes, TxKind, U256},
+        signed_tx::SignedTransaction,
+    };
+    use serde_json::{json, Value};
+
+    const SECRET_KEY: B256 =
+        b256!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+
+    fn request(chain: &mut Chain, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = rpc::handle(chain, &request.to_string()).unwrap();
+        assert_eq!(response["error"], Value::Null, "{method}: {response}");
+        response["result"].clone()
+    }
+
+    #[test]
+    fn transfer_snapshot_and_trace() {
+        let sender = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let recipient = address!("1000000000000000000000000000000000000001");
+        let genesis: Genesis = serde_json::from_value(json!({
+            "config": { "chainId": 1 },
+            "gasLimit": "0x1c9c380",
+            "alloc": {
+                sender.to_string(): { "balance": "0xde0b6b3a7640000" },
+                // PUSH1 0x2a PUSH1 0x00 SSTORE STOP
+                recipient.to_string(): { "balance": "0x0", "code": "0x602a60005500" },
+            },
+        }))
+        .unwrap();
+        let (head, alloc) = genesis.into_head();
+        let mut chain = Chain::new(head, alloc, SpecName::Prague.to_spec_id(), true);
+
+        let call = json!({ "from": sender, "to": recipient, "value": "0x1" });
+        assert_eq!(request(&mut chain, "eth_call", json!([call])), json!("0x"));
+        let gas = request(&mut chain, "eth_estimateGas", json!([call]));
+        let transfer =
+            json!({ "from": sender, "to": address!("2000000000000000000000000000000000000002") });
+        assert_eq!(
+            request(&mut chain, "eth_estimateGas", json!([transfer])),
+            json!("0x5208")
+        );
+
+        let snapshot = request(&mut chain, "evm_snapshot", json!([]));
+        let mut tx = SignedTransaction {
+            tx_type: 2,
+            chain_id: Some(1),
+            gas_price: 2_000_000_000,
+            max_priority_fee_per_gas: 1,
+            gas_limit: u64::from_str_radix(&gas.as_str().unwrap()[2..], 16).unwrap(),
+            to: TxKind::Call(recipient),
+            value: U256::from(1),
+            ..Default::default()
+        };
+        sign(&mut tx, &SECRET_KEY).unwrap();
+        let raw = Bytes::from(tx.encoded());
+        let bundle = request(&mut chain, "eth_callBundle", json!([{ "txs": [raw] }]));
+        assert_eq!(bundle["results"][0]["txHash"], json!(tx.hash()));
+        assert_eq!(bundle["totalGasUsed"], json!(tx.gas_limit));
+        assert_eq!(
+            bundle["stateDiff"][recipient.to_string()]["balance"]["to"],
+            json!("0x1")
+        );
+        assert_eq!(
+            request(&mut chain, "eth_blockNumber", json!([])),
+            json!("0x0")
+        );
+
+        let hash = request(&mut chain, "eth_sendRawTransaction", json!([raw]));
+
+        let receipt = request(&mut chain, "eth_getTransactionReceipt", json!([hash]));
+        assert_eq!(receipt["status"], json!("0x1"));
+        assert_eq!(receipt["blockNumber"], json!("0x1"));
+        assert_eq!(receipt["gasUsed"], gas);
+        assert_eq!(
+            request(&mut chain, "eth_getBalance", json!([recipient, "latest"])),
+            json!("0x1")
+        );
+        assert_eq!(
+            request(
+                &mut chain,
+                "eth_getStorageAt",
+                json!([recipient, "0x0", "latest"])
+            ),
+            json!(B256::with_last_byte(0x2a))
+        );
+        assert_eq!(
+            request(&mut chain, "eth_getBalance", json!([recipient, "0x0"])),
+            json!("0x0")
+        );
+        assert_eq!(
+            request(
+                &mut chain,
+                "eth_getStorageAt",
+                json!([recipient, "0x0", "0x0"])
+            ),
+            json!(B256::ZERO)
+        );
+
+        let trace = request(&mut chain, "debug_traceTransaction", json!([hash]));
+        assert_eq!(trace["failed"], json!(false));
+        let ops: Vec<&str> = trace["structLogs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|log| log["op"].as_str().unwrap())
+            .collect();
+        assert_eq!(ops, ["PUSH1", "PUSH1", "SSTORE", "STOP"]);
+
+        // The same transaction is rejected once its nonce is used.
+        let response = rpc::handle(
+            &mut chain,
+            &json!({ "jsonrpc": "2.0", "id": 2, "method": "eth_sendRawTransaction", "params": [raw] })
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], json!(-32000));
+
+        assert_eq!(
+            request(&mut chain, "evm_revert", json!([snapshot])),
+            json!(true)
+        );
+        assert_eq!(
+            request(&mut chain, "eth_blockNumber", json!([])),
+            json!("0x0")
+        );
+        assert_eq!(
+            request(&mut chain, "eth_getTransactionReceipt", json!([hash])),
+            Value::Null
+        );
+        assert_eq!(
+            request(&mut chain, "eth_getBalance", json!([recipient, "latest"])),
+            json!("0x0")
+        );
+        assert_eq!(
+            request(
+                &mut chain,
+                "eth_getStorageAt",
+                json!([recipient, "0x0", "latest"])
+            ),
+            json!(B256::ZERO)
+        );
+    }
+
+    #[test]
+    fn destroyed_storage_history() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let genesis: Genesis = serde_json::from_value(json!({
+            "config": { "chainId": 1 },
+            "gasLimit": "0x1c9c380",
+            "alloc": {
+                address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b").to_string():
+                    { "balance": "0xde0b6b3a7640000" },
+                // CALLER SELFDESTRUCT
+                contract.to_string(): {
+                    "balance": "0x0",
+                    "code": "0x33ff",
+                    "storage": { "0x01": "0x2a" },
+                },
+            },
+        }))
+        .unwrap();
+        let (head, alloc) = genesis.into_head();
+        // The storage of an account is only wiped by SELFDESTRUCT before Cancun.
+        let mut chain = Chain::new(head, alloc, SpecName::Shanghai.to_spec_id(), true);
+
+        let snapshot = request(&mut chain, "evm_snapshot", json!([]));
+        let mut tx = SignedTransaction {
+            tx_type: 2,
+            chain_id: Some(1),
+            gas_price: 2_000_000_000,
+            max_priority_fee_per_gas: 1,
+            gas_limit: 100_000,
+            to: TxKind::Call(contract),
+            ..Default::default()
+        };
+        sign(&mut tx, &SECRET_KEY).unwrap();
+        request(
+            &mut chain,
+            "eth_sendRawTransaction",
+            json!([Bytes::from(tx.encoded())]),
+        );
+
+        let storage = |chain: &mut Chain, block: &str| {
+            request(chain, "eth_getStorageAt", json!([contract, "0x1", block]))
+        };
+        let stored = json!(B256::with_last_byte(0x2a));
+        assert_eq!(storage(&mut chain, "latest"), json!(B256::ZERO));
+        assert_eq!(storage(&mut chain, "0x0"), stored);
+        assert_eq!(
+            request(&mut chain, "eth_getCode", json!([contract, "0x0"])),
+            json!("0x33ff")
+        );
+
+        request(&mut chain, "evm_revert", json!([snapshot]));
+        assert_eq!(storage(&mut chain, "latest"), stored);
+        assert_eq!(
+            request(&mut chain, "eth_getCode", json!([contract, "latest"])),
+            json!("0x33ff")
+        );
+    }
+
+    #[test]
+    fn rejects_zero_block_time() {
+        let args = ["node", "--genesis", "genesis.json", "--block-time"];
+        assert!(Cmd::try_parse_from(args.iter().chain(&["0"])).is_err());
+        assert!(Cmd::try_parse_from(args.iter().chain(&["1"])).is_ok());
+    }
+}
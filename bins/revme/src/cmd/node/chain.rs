@@ -0,0 +1,706 @@
+use super::genesis::Head;
+use crate::cmd::{
+    blockchaintest::block::next_base_fee,
+    evmrunner::insert_alloc,
+    t8n::types::Alloc,
+    tracediff::trace::{parse_trace, TraceStep},
+};
+use builder::{
+    simulate_bundle, BundleError, BundleSimulation, BundleTransaction, Receipt, ReceiptOutcome,
+};
+use database::{
+    states::{bundle_state::BundleRetention, reverts::AccountInfoRevert},
+    AccountRevert, AccountState, BundleState, CacheDB, DbAccount, OriginalValuesKnown,
+    RevertToSlot, State,
+};
+use inspector::{exec::InspectEvm, inspectors::TracerEip3155};
+use revm::{
+    context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
+    context_interface::result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+    database_interface::{DatabaseRef, EmptyDB, WrapDatabaseRef},
+    primitives::{keccak256, Address, Bytes, TxKind, B256, U256},
+    signed_tx::{SignedTransaction, SignedTxError},
+    specification::hardfork::SpecId,
+    state::{AccountInfo, Bytecode},
+    Context, ExecuteCommitEvm, ExecuteEvm,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::Infallible,
+    io::{self, Write},
+    mem,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ChainError {
+    #[error("invalid transaction: {0}")]
+    Decode(#[from] SignedTxError),
+    #[error("invalid chain id: have {got}, want {expected}")]
+    ChainId { got: u64, expected: u64 },
+    #[error("{0}")]
+    Invalid(#[from] InvalidTransaction),
+    #[error("execution reverted")]
+    Reverted(Bytes),
+    #[error("execution halted: {0:?}")]
+    Halted(HaltReason),
+    #[error("unknown block {0}")]
+    UnknownBlock(u64),
+    #[error("transaction {0} not found")]
+    UnknownTransaction(B256),
+    #[error("failed to parse the trace: {0}")]
+    Trace(String),
+    #[error(transparent)]
+    Bundle(#[from] BundleError),
+}
+
+impl From<EVMError<Infallible, InvalidTransaction>> for ChainError {
+    fn from(error: EVMError<Infallible, InvalidTransaction>) -> Self {
+        match error {
+            EVMError::Transaction(error) => Self::Invalid(error),
+            EVMError::Header(_) | EVMError::Custom(_) | EVMError::Precompile(_) => {
+                unreachable!("blocks of the node are valid: {error}")
+            }
+            EVMError::Database(error) => match error {},
+        }
+    }
+}
+
+/// Block of the chain as seen by [`Chain::state_at`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockId {
+    Latest,
+    /// Latest block with the pending transactions counted in the nonces.
+    Pending,
+    /// Block the node started from.
+    Earliest,
+    Number(u64),
+}
+
+/// Transaction included in a block.
+#[derive(Clone, Debug)]
+pub struct MinedTransaction {
+    pub tx: SignedTransaction,
+    pub hash: B256,
+    pub sender: Address,
+    pub receipt: Receipt,
+    pub gas_used: u64,
+    pub effective_gas_price: u128,
+    pub contract_address: Option<Address>,
+    /// Index in the block of the first log of the transaction.
+    pub first_log_index: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub env: BlockEnv,
+    /// Hash of the block, it is not the hash of the header as the node doesn't build headers.
+    pub hash: B256,
+    pub gas_used: u64,
+    pub transactions: Vec<MinedTransaction>,
+    /// Changes that restore the state before the block, kept with [`BundleRetention::Reverts`].
+    reverts: Vec<(Address, AccountRevert)>,
+}
+
+/// State of the chain, [`Chain::revert`] restores it by undoing the blocks mined since a snapshot.
+#[derive(Debug)]
+struct ChainState {
+    db: CacheDB<EmptyDB>,
+    /// Blocks since the head the node started from, which is the first one.
+    blocks: Vec<Block>,
+    /// Block number and index of the mined transactions.
+    transactions: HashMap<B256, (u64, usize)>,
+    pending: Vec<(SignedTransaction, Address)>,
+}
+
+impl ChainState {
+    /// Applies the changes of a mined block to the database and returns the reverts of the block.
+    ///
+    /// The bundle only knows the slots that were loaded, so the slots of a wiped storage that were
+    /// not are added to the reverts from the database.
+    fn commit(&mut self, mut bundle: BundleState) -> Vec<(Address, AccountRevert)> {
+        let mut reverts = bundle.take_all_reverts().pop().unwrap_or_default();
+        let changes = bundle.to_plain_state(OriginalValuesKnown::Yes);
+        for wiped in changes
+            .storage
+            .iter()
+            .filter(|storage| storage.wipe_storage)
+        {
+            let Some(account) = self.db.accounts.get(&wiped.address) else {
+                continue;
+            };
+            let index = match reverts
+                .iter()
+                .position(|(address, _)| *address == wiped.address)
+            {
+                Some(index) => index,
+                None => {
+                    let revert = AccountRevert {
+                        wipe_storage: true,
+                        ..Default::default()
+                    };
+                    reverts.push((wiped.address, revert));
+                    reverts.len() - 1
+                }
+            };
+            let revert = &mut reverts[index].1;
+            for (slot, value) in &account.storage {
+                let previous = revert
+                    .storage
+                    .entry(*slot)
+                    .or_insert(RevertToSlot::Destroyed);
+                if *previous == RevertToSlot::Destroyed {
+                    *previous = RevertToSlot::Some(*value);
+                }
+            }
+        }
+
+        for (hash, code) in changes.contracts {
+            self.db.contracts.insert(hash, code);
+        }
+        for (address, info) in changes.accounts {
+            match info {
+                Some(info) => {
+                    let account = self.db.accounts.entry(address).or_default();
+                    account.info = info;
+                    account.account_state = AccountState::None;
+                }
+                None => {
+                    self.db
+                        .accounts
+                        .insert(address, DbAccount::new_not_existing());
+                }
+            }
+        }
+        for changed in changes.storage {
+            let account = self.db.accounts.entry(changed.address).or_default();
+            if changed.wipe_storage {
+                account.storage.clear();
+            }
+            account.storage.extend(changed.storage);
+        }
+        reverts
+    }
+
+    /// Restores the state before the block that has the reverts.
+    fn revert(&mut self, reverts: &[(Address, AccountRevert)]) {
+        for (address, revert) in reverts {
+            let account = self.db.accounts.entry(*address).or_default();
+            match &revert.account {
+                AccountInfoRevert::DoNothing => {}
+                AccountInfoRevert::DeleteIt => *account = DbAccount::new_not_existing(),
+                AccountInfoRevert::RevertTo(info) => {
+                    account.info = info.clone();
+                    account.account_state = AccountState::None;
+                }
+            }
+            if revert.wipe_storage {
+                account.storage.clear();
+            }
+            for (slot, previous) in &revert.storage {
+                account.storage.insert(*slot, previous.to_previous_value());
+            }
+        }
+    }
+}
+
+/// State after an older block, read by undoing the changes of the later blocks on the database.
+#[derive(Clone, Copy, Debug)]
+struct History<'a> {
+    db: &'a CacheDB<EmptyDB>,
+    /// Blocks mined after the block, from the oldest.
+    later: &'a [Block],
+}
+
+impl History<'_> {
+    /// Returns the revert of the account by the first later block that changed it.
+    fn reverts(&self, address: Address) -> impl Iterator<Item = &AccountRevert> {
+        self.later.iter().filter_map(move |block| {
+            block
+                .reverts
+                .iter()
+                .find(|(changed, _)| *changed == address)
+                .map(|(_, revert)| revert)
+        })
+    }
+}
+
+impl DatabaseRef for History<'_> {
+    type Error = Infallible;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        for revert in self.reverts(address) {
+            match &revert.account {
+                AccountInfoRevert::DoNothing => {}
+                AccountInfoRevert::DeleteIt => return Ok(None),
+                AccountInfoRevert::RevertTo(info) => return Ok(Some(info.clone())),
+            }
+        }
+        self.db.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        for revert in self.reverts(address) {
+            if let Some(previous) = revert.storage.get(&index) {
+                return Ok(previous.to_previous_value());
+            }
+            // The reverts of a wiped storage have all of its slots.
+            if revert.wipe_storage {
+                return Ok(U256::ZERO);
+            }
+        }
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+/// Point [`Chain::revert`] goes back to.
+#[derive(Debug)]
+struct Snapshot {
+    /// Number of blocks when the snapshot was taken.
+    blocks: usize,
+    pending: Vec<(SignedTransaction, Address)>,
+}
+
+/// Chain of the development node.
+///
+/// Transactions are executed on a single [`CacheDB`] with the latest state. Each block keeps the
+/// reverts of its changes, the state of older blocks is read by undoing them to run calls on it
+/// and to trace their transactions again.
+#[derive(Debug)]
+pub struct Chain {
+    pub chain_id: u64,
+    spec: SpecId,
+    /// Whether transactions are mined as soon as they are sent.
+    instant: bool,
+    state: ChainState,
+    snapshots: Vec<Snapshot>,
+}
+
+impl Chain {
+    pub fn new(head: Head, alloc: Alloc, spec: SpecId, instant: bool) -> Self {
+        let mut db = CacheDB::new(EmptyDB::default());
+        insert_alloc(&mut db, alloc);
+        for (number, hash) in &head.block_hashes {
+            db.block_hashes.insert(U256::from(*number), *hash);
+        }
+
+        let mut env = BlockEnv {
+            number: head.number,
+            beneficiary: head.coinbase,
+            timestamp: head.timestamp,
+            gas_limit: head.gas_limit,
+            basefee: head.base_fee,
+            difficulty: head.difficulty,
+            prevrandao: spec.is_enabled_in(SpecId::MERGE).then_some(head.prevrandao),
+            ..Default::default()
+        };
+        env.set_blob_excess_gas_and_price(0, spec.is_enabled_in(SpecId::PRAGUE));
+        let block = Block {
+            env,
+            hash: head.hash,
+            gas_used: 0,
+            transactions: Vec::new(),
+            reverts: Vec::new(),
+        };
+        Self {
+            chain_id: head.chain_id,
+            spec,
+            instant,
+            state: ChainState {
+                db,
+                blocks: vec![block],
+                transactions: HashMap::new(),
+                pending: Vec::new(),
+            },
+            snapshots: Vec::new(),
+        }
+    }
+
+    pub fn latest(&self) -> &Block {
+        self.state.blocks.last().expect("chain has the head block")
+    }
+
+    /// Returns the block with the number.
+    pub fn block(&self, number: u64) -> Result<&Block, ChainError> {
+        let first = self.state.blocks[0].env.number;
+        number
+            .checked_sub(first)
+            .and_then(|index| self.state.blocks.get(index as usize))
+            .ok_or(ChainError::UnknownBlock(number))
+    }
+
+    /// Returns the mined transaction with its block.
+    pub fn transaction(&self, hash: &B256) -> Option<(&Block, &MinedTransaction)> {
+        let (number, index) = self.state.transactions.get(hash)?;
+        let block = self.block(*number).ok()?;
+        Some((block, &block.transactions[*index]))
+    }
+
+    /// Returns whether there are transactions waiting to be mined.
+    pub fn has_pending(&self) -> bool {
+        !self.state.pending.is_empty()
+    }
+
+    /// Returns the state after the block at the index.
+    fn history(&self, index: usize) -> History<'_> {
+        History {
+            db: &self.state.db,
+            later: &self.state.blocks[index + 1..],
+        }
+    }
+
+    /// Returns the state after the block and the block.
+    fn state_at(&self, id: BlockId) -> Result<(History<'_>, &Block), ChainError> {
+        let index = match id {
+            BlockId::Latest | BlockId::Pending => self.state.blocks.len() - 1,
+            BlockId::Earliest => 0,
+            BlockId::Number(number) => {
+                let block = self.block(number)?;
+                (block.env.number - self.state.blocks[0].env.number) as usize
+            }
+        };
+        Ok((self.history(index), &self.state.blocks[index]))
+    }
+
+    pub fn account(&self, address: Address, id: BlockId) -> Result<AccountInfo, ChainError> {
+        let (db, _) = self.state_at(id)?;
+        let mut info = match db.basic_ref(address) {
+            Ok(info) => info.unwrap_or_default(),
+            Err(error) => match error {},
+        };
+        if id == BlockId::Pending {
+            let pending = self.state.pending.iter();
+            info.nonce += pending.filter(|(_, sender)| *sender == address).count() as u64;
+        }
+        Ok(info)
+    }
+
+    pub fn code(&self, address: Address, id: BlockId) -> Result<Bytes, ChainError> {
+        let info = self.account(address, id)?;
+        let (db, _) = self.state_at(id)?;
+        let code = match db.code_by_hash_ref(info.code_hash) {
+            Ok(code) => code,
+            Err(error) => match error {},
+        };
+        Ok(code.original_bytes())
+    }
+
+    pub fn storage(&self, address: Address, slot: U256, id: BlockId) -> Result<U256, ChainError> {
+        let (db, _) = self.state_at(id)?;
+        match db.storage_ref(address, slot) {
+            Ok(value) => Ok(value),
+            Err(error) => match error {},
+        }
+    }
+
+    fn cfg(&self) -> CfgEnv {
+        let mut cfg = CfgEnv::default();
+        cfg.chain_id = self.chain_id;
+        cfg.spec = self.spec;
+        cfg
+    }
+
+    /// Executes the call on the state after the block without changing it.
+    ///
+    /// As in geth, the nonce, the base fee and EIP-3607 are not checked.
+    pub fn call(&self, tx: &TxEnv, id: BlockId) -> Result<ExecutionResult<HaltReason>, ChainError> {
+        let (db, block) = self.state_at(id)?;
+        let mut cfg = self.cfg();
+        cfg.disable_nonce_check = true;
+        cfg.disable_base_fee = true;
+        cfg.disable_eip3607 = true;
+        let mut ctx = Context::builder()
+            .with_block(&block.env)
+            .with_tx(tx)
+            .with_cfg(&cfg)
+            .with_db(WrapDatabaseRef(db));
+        Ok(ctx.exec_previous()?.result)
+    }
+
+    /// Returns the lowest gas limit with which the call succeeds, at most its gas limit.
+    pub fn estimate_gas(&self, tx: &TxEnv, id: BlockId) -> Result<u64, ChainError> {
+        let mut tx = tx.clone();
+        let gas_used = match self.call(&tx, id)? {
+            ExecutionResult::Success { gas_used, .. } => gas_used,
+            ExecutionResult::Revert { output, .. } => return Err(ChainError::Reverted(output)),
+            ExecutionResult::Halt { reason, .. } => return Err(ChainError::Halted(reason)),
+        };
+        // Refunds make the gas used lower than the gas needed, the search starts from it.
+        let mut low = gas_used.saturating_sub(1);
+        let mut high = tx.gas_limit;
+        while low + 1 < high {
+            tx.gas_limit = low + (high - low) / 2;
+            match self.call(&tx, id) {
+                Ok(result) if result.is_success() => high = tx.gas_limit,
+                _ => low = tx.gas_limit,
+            }
+        }
+        Ok(high)
+    }
+
+    /// Adds the EIP-2718 encoded transaction to the pending transactions.
+    ///
+    /// Transactions are mined in a new block when the mining is instant, and are rejected if they
+    /// are invalid. Otherwise they are only checked when the next block is mined.
+    pub fn send_raw_transaction(&mut self, bytes: &[u8]) -> Result<B256, ChainError> {
+        let tx = SignedTransaction::decode(bytes)?;
+        if let Some(chain_id) = tx.chain_id.filter(|id| *id != self.chain_id) {
+            return Err(ChainError::ChainId {
+                got: chain_id,
+                expected: self.chain_id,
+            });
+        }
+        let sender = tx.recover_sender()?;
+        let hash = tx.hash();
+        if self.instant {
+            let block = self.next_block_env();
+            let tx_env = tx.to_tx_env(sender);
+            let cfg = self.cfg();
+            let mut ctx = Context::builder()
+                .with_block(&block)
+                .with_tx(&tx_env)
+                .with_cfg(&cfg)
+                .with_db(WrapDatabaseRef(&self.state.db));
+            ctx.exec_previous()?;
+            self.state.pending.push((tx, sender));
+            self.mine();
+        } else {
+            self.state.pending.push((tx, sender));
+        }
+        Ok(hash)
+    }
+
+    fn next_block_env(&self) -> BlockEnv {
+        self.child_env(self.latest())
+    }
+
+    /// Returns the environment of a block on top of the parent.
+    fn child_env(&self, parent: &Block) -> BlockEnv {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let mut env = BlockEnv {
+            number: parent.env.number + 1,
+            timestamp: now.max(parent.env.timestamp + 1),
+            basefee: if self.spec.is_enabled_in(SpecId::LONDON) {
+                next_base_fee(parent.env.basefee, parent.gas_used, parent.env.gas_limit)
+            } else {
+                0
+            },
+            ..parent.env.clone()
+        };
+        env.set_blob_excess_gas_and_price(0, self.spec.is_enabled_in(SpecId::PRAGUE));
+        env
+    }
+
+    /// Simulates the bundle in a block on top of the block, without changing the chain.
+    pub fn call_bundle(
+        &self,
+        txs: &[BundleTransaction],
+        id: BlockId,
+        timestamp: Option<u64>,
+    ) -> Result<(BlockEnv, BundleSimulation), ChainError> {
+        let (db, parent) = self.state_at(id)?;
+        let mut block = self.child_env(parent);
+        if let Some(timestamp) = timestamp {
+            block.timestamp = timestamp;
+        }
+        let simulation = simulate_bundle(db, txs, &self.cfg(), &block)?;
+        Ok((block, simulation))
+    }
+
+    /// Mines a block with the pending transactions that fit in it.
+    ///
+    /// Returns the hashes of the invalid transactions that were dropped, with the reason.
+    pub fn mine(&mut self) -> Vec<(B256, String)> {
+        let env = self.next_block_env();
+        let cfg = self.cfg();
+        let parent_hash = self.latest().hash;
+        let pending = mem::take(&mut self.state.pending);
+        let mut state = State::builder()
+            .with_database_ref(&self.state.db)
+            .with_bundle_update()
+            .build();
+
+        let mut transactions = Vec::new();
+        let mut rejected = Vec::new();
+        let mut deferred = Vec::new();
+        let mut gas_used = 0u64;
+        let mut log_index = 0;
+        for (tx, sender) in pending {
+            if gas_used.saturating_add(tx.gas_limit) > env.gas_limit {
+                deferred.push((tx, sender));
+                continue;
+            }
+            let tx_env = tx.to_tx_env(sender);
+            let mut ctx = Context::builder()
+                .with_block(&env)
+                .with_tx(&tx_env)
+                .with_cfg(&cfg)
+                .with_db(&mut state);
+            let result = match ctx.exec_commit_previous() {
+                Ok(result) => result,
+                Err(error) => {
+                    rejected.push((tx.hash(), error.to_string()));
+                    continue;
+                }
+            };
+            gas_used += result.gas_used();
+
+            let contract_address = match tx.to {
+                TxKind::Create if result.is_success() => Some(sender.create(tx.nonce)),
+                _ => None,
+            };
+            let effective_gas_price = if tx.tx_type >= 2 {
+                tx.gas_price
+                    .min(env.basefee as u128 + tx.max_priority_fee_per_gas)
+            } else {
+                tx.gas_price
+            };
+            let tx_gas_used = result.gas_used();
+            let outcome = ReceiptOutcome::Status(result.is_success());
+            let receipt = Receipt::new(tx.tx_type, outcome, gas_used, result.into_logs());
+            let first_log_index = log_index;
+            log_index += receipt.logs.len();
+            transactions.push(MinedTransaction {
+                hash: tx.hash(),
+                tx,
+                sender,
+                receipt,
+                gas_used: tx_gas_used,
+                effective_gas_price,
+                contract_address,
+                first_log_index,
+            });
+        }
+        state.merge_transitions(BundleRetention::Reverts);
+        let bundle = state.take_bundle();
+        drop(state);
+        let reverts = self.state.commit(bundle);
+        self.state.pending = deferred;
+
+        let mut preimage = parent_hash.to_vec();
+        preimage.extend_from_slice(&env.number.to_be_bytes());
+        preimage.extend_from_slice(&env.timestamp.to_be_bytes());
+        for mined in &transactions {
+            preimage.extend_from_slice(mined.hash.as_slice());
+        }
+        let hash = keccak256(preimage);
+        self.state
+            .db
+            .block_hashes
+            .insert(U256::from(env.number), hash);
+        for (index, mined) in transactions.iter().enumerate() {
+            self.state
+                .transactions
+                .insert(mined.hash, (env.number, index));
+        }
+        self.state.blocks.push(Block {
+            env,
+            hash,
+            gas_used,
+            transactions,
+            reverts,
+        });
+        rejected
+    }
+
+    /// Executes the transaction again on the state of its block and returns its EIP-3155 steps.
+    pub fn trace(
+        &self,
+        hash: &B256,
+        memory: bool,
+    ) -> Result<(ExecutionResult<HaltReason>, Vec<TraceStep>), ChainError> {
+        let (block, mined) = self
+            .transaction(hash)
+            .ok_or(ChainError::UnknownTransaction(*hash))?;
+        let cfg = self.cfg();
+        // The state before the block is the state after its parent.
+        let index = (block.env.number - self.state.blocks[0].env.number) as usize;
+        let mut db = CacheDB::new(self.history(index - 1));
+        for previous in block.transactions.iter().take_while(|tx| tx.hash != *hash) {
+            let tx_env = previous.tx.to_tx_env(previous.sender);
+            let mut ctx = Context::builder()
+                .with_block(&block.env)
+                .with_tx(&tx_env)
+                .with_cfg(&cfg)
+                .with_db(&mut db);
+            ctx.exec_commit_previous()?;
+        }
+
+        let output = SharedBuffer::default();
+        let mut tracer = TracerEip3155::new(Box::new(output.clone())).without_summary();
+        if memory {
+            tracer = tracer.with_memory();
+        }
+        let tx_env = mined.tx.to_tx_env(mined.sender);
+        let mut ctx = Context::builder()
+            .with_block(&block.env)
+            .with_tx(&tx_env)
+            .with_cfg(&cfg)
+            .with_db(&mut db);
+        let result = ctx.inspect_previous(tracer)?.result;
+        let trace = String::from_utf8_lossy(&output.0.borrow()).into_owned();
+        let steps = parse_trace(&trace)
+            .map_err(|(line, error)| ChainError::Trace(format!("line {line}: {error}")))?;
+        Ok((result, steps))
+    }
+
+    /// Saves the state and returns the id to revert to it.
+    pub fn snapshot(&mut self) -> usize {
+        self.snapshots.push(Snapshot {
+            blocks: self.state.blocks.len(),
+            pending: self.state.pending.clone(),
+        });
+        self.snapshots.len() - 1
+    }
+
+    /// Restores the state of the snapshot, the snapshot and later ones are removed.
+    ///
+    /// Returns `false` if there is no snapshot with the id.
+    pub fn revert(&mut self, id: usize) -> bool {
+        if id >= self.snapshots.len() {
+            return false;
+        }
+        self.snapshots.truncate(id + 1);
+        let snapshot = self.snapshots.pop().expect("snapshot exists");
+        while self.state.blocks.len() > snapshot.blocks {
+            let block = self.state.blocks.pop().expect("chain has the block");
+            self.state.revert(&block.reverts);
+            self.state
+                .db
+                .block_hashes
+                .remove(&U256::from(block.env.number));
+            for mined in &block.transactions {
+                self.state.transactions.remove(&mined.hash);
+            }
+        }
+        self.state.pending = snapshot.pending;
+        true
+    }
+}
+
+/// Buffer the tracer writes to.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use crate::cmd::t8n::types::Alloc;
+use revm::primitives::{Address, B256, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Block the chain of the node starts after.
+#[derive(Clone, Debug)]
+pub struct Head {
+    pub chain_id: u64,
+    pub number: u64,
+    pub hash: B256,
+    pub timestamp: u64,
+    pub gas_limit: u64,
+    pub base_fee: u64,
+    pub coinbase: Address,
+    pub difficulty: U256,
+    pub prevrandao: B256,
+    /// Hashes of the previous blocks, available to `BLOCKHASH`.
+    pub block_hashes: BTreeMap<u64, B256>,
+}
+
+/// Genesis file in the format of geth, the fork configuration is not read.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Genesis {
+    #[serde(default)]
+    pub config: GenesisConfig,
+    pub alloc: Alloc,
+    #[serde(default)]
+    pub number: U256,
+    #[serde(default)]
+    pub timestamp: U256,
+    pub gas_limit: U256,
+    pub base_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub coinbase: Address,
+    #[serde(default)]
+    pub difficulty: U256,
+    #[serde(default)]
+    pub mix_hash: B256,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenesisConfig {
+    pub chain_id: Option<u64>,
+}
+
+/// Chain id of the node when the genesis file doesn't have one.
+pub const DEFAULT_CHAIN_ID: u64 = 1337;
+
+/// Base fee of the genesis block when the genesis file doesn't have one, as in EIP-1559.
+const INITIAL_BASE_FEE: u64 = 1_000_000_000;
+
+impl Genesis {
+    /// Returns the genesis block and the accounts.
+    ///
+    /// The hash of the genesis block is not the hash of its header, the node doesn't compute
+    /// header hashes.
+    pub fn into_head(self) -> (Head, Alloc) {
+        let head = Head {
+            chain_id: self.config.chain_id.unwrap_or(DEFAULT_CHAIN_ID),
+            number: self.number.saturating_to(),
+            hash: B256::ZERO,
+            timestamp: self.timestamp.saturating_to(),
+            gas_limit: self.gas_limit.saturating_to(),
+            base_fee: self
+                .base_fee_per_gas
+                .map_or(INITIAL_BASE_FEE, |base_fee| base_fee.saturating_to()),
+            coinbase: self.coinbase,
+            difficulty: self.difficulty,
+            prevrandao: self.mix_hash,
+            block_hashes: BTreeMap::new(),
+        };
+        (head, self.alloc)
+    }
+}
+
+/// State of a chain recorded at a block, with the accounts used by the transactions that run on
+/// top of it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkFixture {
+    pub chain_id: u64,
+    pub block: ForkBlock,
+    pub accounts: Alloc,
+    /// Hashes of previous blocks by number, numbers are decimal or hex strings.
+    #[serde(default)]
+    pub block_hashes: BTreeMap<String, B256>,
+}
+
+/// Header fields of the block the fixture was recorded at.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkBlock {
+    pub number: U256,
+    pub hash: B256,
+    pub timestamp: U256,
+    pub gas_limit: U256,
+    #[serde(default)]
+    pub base_fee_per_gas: U256,
+    #[serde(alias = "miner")]
+    pub coinbase: Address,
+    #[serde(default)]
+    pub difficulty: U256,
+    #[serde(default, alias = "mixHash")]
+    pub prev_randao: B256,
+}
+
+impl ForkFixture {
+    /// Returns the recorded block and the accounts.
+    pub fn into_head(self) -> (Head, Alloc) {
+        let number = self.block.number.saturating_to();
+        let mut block_hashes: BTreeMap<u64, B256> = self
+            .block_hashes
+            .iter()
+            .filter_map(|(number, hash)| {
+                let number = match number.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => number.parse(),
+                };
+                Some((number.ok()?, *hash))
+            })
+            .collect();
+        block_hashes.insert(number, self.block.hash);
+        let head = Head {
+            chain_id: self.chain_id,
+            number,
+            hash: self.block.hash,
+            timestamp: self.block.timestamp.saturating_to(),
+            gas_limit: self.block.gas_limit.saturating_to(),
+            base_fee: self.block.base_fee_per_gas.saturating_to(),
+            coinbase: self.block.coinbase,
+            difficulty: self.block.difficulty,
+            prevrandao: self.block.prev_randao,
+            block_hashes,
+        };
+        (head, self.accounts)
+    }
+}
@@ -0,0 +1,497 @@
+use super::chain::{Block, BlockId, Chain, ChainError, MinedTransaction};
+use builder::{BundleSimulation, BundleTransaction, ReceiptOutcome};
+use revm::{
+    bytecode::opcode::OpCode,
+    context::tx::TxEnv,
+    context_interface::result::ExecutionResult,
+    primitives::{keccak256, Address, Bytes, TxKind, B256, U256},
+    signed_tx::SignedTransaction,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::fmt::LowerHex;
+
+/// Version returned by `web3_clientVersion`.
+const CLIENT_VERSION: &str = concat!("revme/", env!("CARGO_PKG_VERSION"));
+
+/// Error of a JSON-RPC request, codes are the ones of geth.
+#[derive(Debug)]
+pub struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(-32602, message)
+    }
+
+    fn to_json(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+impl From<ChainError> for RpcError {
+    fn from(error: ChainError) -> Self {
+        match error {
+            ChainError::Reverted(ref output) => Self {
+                code: 3,
+                message: error.to_string(),
+                data: Some(json!(output)),
+            },
+            error => Self::new(-32000, error.to_string()),
+        }
+    }
+}
+
+/// Handles a JSON-RPC request or batch of requests and returns the response.
+///
+/// Returns `None` if all requests are notifications.
+pub fn handle(chain: &mut Chain, body: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(error) => {
+            return Some(response(
+                Value::Null,
+                Err(RpcError::new(-32700, error.to_string())),
+            ))
+        }
+    };
+    match request {
+        Value::Array(requests) if requests.is_empty() => Some(response(
+            Value::Null,
+            Err(RpcError::new(-32600, "empty batch")),
+        )),
+        Value::Array(requests) => {
+            let responses: Vec<Value> = requests
+                .into_iter()
+                .filter_map(|request| handle_request(chain, request))
+                .collect();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        request => handle_request(chain, request),
+    }
+}
+
+fn handle_request(chain: &mut Chain, request: Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        let error = RpcError::new(-32600, "invalid request");
+        return Some(response(id.unwrap_or_default(), Err(error)));
+    };
+    let params = match request.get("params") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(params)) => params.clone(),
+        Some(_) => {
+            let error = RpcError::invalid_params("params must be an array");
+            return id.map(|id| response(id, Err(error)));
+        }
+    };
+    let result = call(chain, method, &params);
+    id.map(|id| response(id, result))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error.to_json() }),
+    }
+}
+
+fn call(chain: &mut Chain, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+    let result = match method {
+        "web3_clientVersion" => json!(CLIENT_VERSION),
+        "net_version" => json!(chain.chain_id.to_string()),
+        "eth_chainId" => quantity(chain.chain_id),
+        "eth_blockNumber" => quantity(chain.latest().env.number),
+        "eth_gasPrice" => quantity(chain.latest().env.basefee),
+        "eth_getBalance" => {
+            let account = chain.account(param(params, 0)?, block_id(params, 1)?)?;
+            quantity(account.balance)
+        }
+        "eth_getTransactionCount" => {
+            let account = chain.account(param(params, 0)?, block_id(params, 1)?)?;
+            quantity(account.nonce)
+        }
+        "eth_getCode" => json!(chain.code(param(params, 0)?, block_id(params, 1)?)?),
+        "eth_getStorageAt" => {
+            let slot: U256 = param(params, 1)?;
+            let value = chain.storage(param(params, 0)?, slot, block_id(params, 2)?)?;
+            json!(B256::from(value))
+        }
+        "eth_call" => {
+            let tx = call_tx(chain, param(params, 0)?);
+            match chain.call(&tx, block_id(params, 1)?)? {
+                ExecutionResult::Success { output, .. } => json!(output.into_data()),
+                ExecutionResult::Revert { output, .. } => {
+                    return Err(ChainError::Reverted(output).into())
+                }
+                ExecutionResult::Halt { reason, .. } => {
+                    return Err(ChainError::Halted(reason).into())
+                }
+            }
+        }
+        "eth_estimateGas" => {
+            let tx = call_tx(chain, param(params, 0)?);
+            quantity(chain.estimate_gas(&tx, block_id(params, 1)?)?)
+        }
+        "eth_sendRawTransaction" => {
+            let bytes: Bytes = param(params, 0)?;
+            json!(chain.send_raw_transaction(&bytes)?)
+        }
+        "eth_getTransactionReceipt" => {
+            let hash: B256 = param(params, 0)?;
+            match chain.transaction(&hash) {
+                Some((block, mined)) => receipt_json(block, mined),
+                None => Value::Null,
+            }
+        }
+        "debug_traceTransaction" => {
+            let hash: B256 = param(params, 0)?;
+            let options: TraceOptions = optional_param(params, 1)?.unwrap_or_default();
+            trace_json(chain, &hash, &options)?
+        }
+        "eth_callBundle" => call_bundle(chain, param(params, 0)?)?,
+        "evm_mine" => {
+            chain.mine();
+            json!("0x0")
+        }
+        "evm_snapshot" => quantity(chain.snapshot() as u64),
+        "evm_revert" => {
+            let id: U256 = param(params, 0)?;
+            json!(chain.revert(id.saturating_to()))
+        }
+        method => {
+            return Err(RpcError::new(
+                -32601,
+                format!("the method {method} does not exist/is not available"),
+            ))
+        }
+    };
+    Ok(result)
+}
+
+fn quantity(value: impl LowerHex) -> Value {
+    json!(format!("{value:#x}"))
+}
+
+fn param<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<T, RpcError> {
+    optional_param(params, index)?.ok_or_else(|| {
+        RpcError::invalid_params(format!("missing value for required argument {index}"))
+    })
+}
+
+fn optional_param<T: DeserializeOwned>(
+    params: &[Value],
+    index: usize,
+) -> Result<Option<T>, RpcError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|error| {
+                RpcError::invalid_params(format!("invalid argument {index}: {error}"))
+            }),
+    }
+}
+
+/// Parses a block tag or number, the latest block by default.
+fn block_id(params: &[Value], index: usize) -> Result<BlockId, RpcError> {
+    optional_param::<String>(params, index)?.map_or(Ok(BlockId::Latest), |tag| parse_block_id(&tag))
+}
+
+fn parse_block_id(tag: &str) -> Result<BlockId, RpcError> {
+    let id = match tag {
+        "latest" | "safe" | "finalized" => BlockId::Latest,
+        "pending" => BlockId::Pending,
+        "earliest" => BlockId::Earliest,
+        number => {
+            let number = number
+                .strip_prefix("0x")
+                .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| RpcError::invalid_params(format!("invalid block `{tag}`")))?;
+            BlockId::Number(number)
+        }
+    };
+    Ok(id)
+}
+
+/// Transaction of `eth_call` and `eth_estimateGas`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallRequest {
+    from: Option<Address>,
+    to: Option<Address>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    value: Option<U256>,
+    #[serde(alias = "data")]
+    input: Option<Bytes>,
+    nonce: Option<U256>,
+}
+
+/// Returns the environment of the call, the gas limit is the one of the block by default and the
+/// gas price is zero.
+fn call_tx(chain: &Chain, request: CallRequest) -> TxEnv {
+    let gas_limit = chain.latest().env.gas_limit;
+    let mut tx = TxEnv {
+        caller: request.from.unwrap_or_default(),
+        kind: request.to.map_or(TxKind::Create, TxKind::Call),
+        gas_limit: request
+            .gas
+            .map_or(gas_limit, |gas| gas.saturating_to::<u64>().min(gas_limit)),
+        value: request.value.unwrap_or_default(),
+        data: request.input.unwrap_or_default(),
+        nonce: request.nonce.unwrap_or_default().saturating_to(),
+        chain_id: Some(chain.chain_id),
+        ..Default::default()
+    };
+    if let Some(max_fee) = request.max_fee_per_gas {
+        tx.tx_type = 2;
+        tx.gas_price = max_fee.saturating_to();
+        tx.gas_priority_fee = Some(
+            request
+                .max_priority_fee_per_gas
+                .unwrap_or_default()
+                .saturating_to(),
+        );
+    } else {
+        tx.gas_price = request.gas_price.unwrap_or_default().saturating_to();
+    }
+    tx
+}
+
+fn receipt_json(block: &Block, mined: &MinedTransaction) -> Value {
+    let index = block
+        .transactions
+        .iter()
+        .position(|tx| tx.hash == mined.hash)
+        .unwrap_or_default() as u64;
+    let logs: Vec<Value> = mined
+        .receipt
+        .logs
+        .iter()
+        .enumerate()
+        .map(|(log_index, log)| {
+            json!({
+                "address": log.address,
+                "topics": log.data.topics(),
+                "data": log.data.data,
+                "blockHash": block.hash,
+                "blockNumber": quantity(block.env.number),
+                "transactionHash": mined.hash,
+                "transactionIndex": quantity(index),
+                "logIndex": quantity((mined.first_log_index + log_index) as u64),
+                "removed": false,
+            })
+        })
+        .collect();
+    json!({
+        "transactionHash": mined.hash,
+        "transactionIndex": quantity(index),
+        "blockHash": block.hash,
+        "blockNumber": quantity(block.env.number),
+        "from": mined.sender,
+        "to": mined.tx.to.to().copied(),
+        "cumulativeGasUsed": quantity(mined.receipt.cumulative_gas_used),
+        "gasUsed": quantity(mined.gas_used),
+        "effectiveGasPrice": quantity(mined.effective_gas_price),
+        "contractAddress": mined.contract_address,
+        "logs": logs,
+        "logsBloom": mined.receipt.bloom,
+        "status": quantity(matches!(mined.receipt.outcome, ReceiptOutcome::Status(true)) as u64),
+        "type": quantity(mined.tx.tx_type as u64),
+    })
+}
+
+/// Options of the struct logger of `debug_traceTransaction`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceOptions {
+    #[serde(default)]
+    disable_stack: bool,
+    #[serde(default)]
+    enable_memory: bool,
+}
+
+/// Returns the trace in the format of the struct logger of geth.
+fn trace_json(chain: &Chain, hash: &B256, options: &TraceOptions) -> Result<Value, RpcError> {
+    let (result, steps) = chain.trace(hash, options.enable_memory)?;
+    let struct_logs: Vec<Value> = steps
+        .iter()
+        .map(|step| {
+            let mut log = json!({
+                "pc": step.pc,
+                "op": OpCode::name_by_op(step.op),
+                "gas": step.gas,
+                "gasCost": step.gas_cost,
+                "depth": step.depth,
+            });
+            if !options.disable_stack {
+                log["stack"] = json!(step
+                    .stack
+                    .iter()
+                    .map(|value| format!("{value:#x}"))
+                    .collect::<Vec<_>>());
+            }
+            if let Some(memory) = &step.memory {
+                log["memory"] = json!(memory
+                    .chunks(32)
+                    .map(|word| B256::right_padding_from(word).to_string()[2..].to_string())
+                    .collect::<Vec<_>>());
+            }
+            if let Some(error) = &step.error {
+                log["error"] = json!(error);
+            }
+            log
+        })
+        .collect();
+    let return_value = result.output().cloned().unwrap_or_default();
+    Ok(json!({
+        "gas": result.gas_used(),
+        "failed": !result.is_success(),
+        "returnValue": return_value.to_string()[2..],
+        "structLogs": struct_logs,
+    }))
+}
+
+/// Bundle of `eth_callBundle`, as in the Flashbots API.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallBundleRequest {
+    txs: Vec<Bytes>,
+    /// Block the bundle is simulated on top of, the latest one by default.
+    state_block_number: Option<String>,
+    timestamp: Option<u64>,
+    /// Transactions that are dropped from the bundle if they revert.
+    #[serde(default)]
+    reverting_tx_hashes: Vec<B256>,
+}
+
+/// Simulates the bundle, the amounts of wei are decimal strings as in the Flashbots API.
+fn call_bundle(chain: &Chain, request: CallBundleRequest) -> Result<Value, RpcError> {
+    let txs = request
+        .txs
+        .iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            let tx = SignedTransaction::decode(bytes).map_err(|error| {
+                RpcError::invalid_params(format!("transaction {index}: {error}"))
+            })?;
+            let can_revert = request.reverting_tx_hashes.contains(&tx.hash());
+            Ok(BundleTransaction { tx, can_revert })
+        })
+        .collect::<Result<Vec<_>, RpcError>>()?;
+    let id = match &request.state_block_number {
+        Some(tag) => parse_block_id(tag)?,
+        None => BlockId::Latest,
+    };
+    let (block, simulation) = chain.call_bundle(&txs, id, request.timestamp)?;
+    let BundleSimulation {
+        results,
+        total_gas_used,
+        gas_fees,
+        coinbase_diff,
+        eth_sent_to_coinbase,
+        bundle_gas_price,
+        first_revert,
+        state_diff,
+    } = simulation;
+
+    let bundle_hash = keccak256(
+        results
+            .iter()
+            .flat_map(|result| result.hash.0)
+            .collect::<Vec<u8>>(),
+    );
+    let results: Vec<Value> = results
+        .into_iter()
+        .zip(&txs)
+        .map(|(result, bundle_tx)| {
+            let mut json = json!({
+                "txHash": result.hash,
+                "fromAddress": result.sender,
+                "toAddress": bundle_tx.tx.to.to().copied(),
+                "gasUsed": result.gas_used,
+                "gasPrice": result.gas_price.to_string(),
+                "gasFees": result.gas_fees.to_string(),
+                "coinbaseDiff": result.coinbase_diff.to_string(),
+                "ethSentToCoinbase": result.eth_sent_to_coinbase.to_string(),
+                "rolledBack": result.rolled_back,
+            });
+            match result.result {
+                Ok(ExecutionResult::Success { output, .. }) => {
+                    json["value"] = json!(output.into_data())
+                }
+                Ok(ExecutionResult::Revert { output, .. }) => json["revert"] = json!(output),
+                Ok(ExecutionResult::Halt { reason, .. }) => {
+                    json["error"] = json!(format!("{reason:?}"))
+                }
+                Err(error) => json["error"] = json!(error.to_string()),
+            }
+            json
+        })
+        .collect();
+    let state_diff: serde_json::Map<String, Value> = state_diff
+        .into_iter()
+        .map(|(address, diff)| {
+            let before = diff.before.unwrap_or_default();
+            let after = diff.after.unwrap_or_default();
+            let mut json = json!({});
+            if before.balance != after.balance {
+                json["balance"] =
+                    json!({ "from": quantity(before.balance), "to": quantity(after.balance) });
+            }
+            if before.nonce != after.nonce {
+                json["nonce"] =
+                    json!({ "from": quantity(before.nonce), "to": quantity(after.nonce) });
+            }
+            if before.code_hash != after.code_hash {
+                json["codeHash"] = json!({ "from": before.code_hash, "to": after.code_hash });
+            }
+            if !diff.storage.is_empty() {
+                let storage: serde_json::Map<String, Value> = diff
+                    .storage
+                    .into_iter()
+                    .map(|(slot, (from, to))| {
+                        let slot = B256::from(slot).to_string();
+                        (
+                            slot,
+                            json!({ "from": B256::from(from), "to": B256::from(to) }),
+                        )
+                    })
+                    .collect();
+                json["storage"] = Value::Object(storage);
+            }
+            (address.to_string(), json)
+        })
+        .collect();
+    let state_block_number = block.number - 1;
+    Ok(json!({
+        "bundleHash": bundle_hash,
+        "bundleGasPrice": bundle_gas_price.to_string(),
+        "coinbaseDiff": coinbase_diff.to_string(),
+        "ethSentToCoinbase": eth_sent_to_coinbase.to_string(),
+        "gasFees": gas_fees.to_string(),
+        "totalGasUsed": total_gas_used,
+        "stateBlockNumber": state_block_number,
+        "firstRevert": first_revert.map(|index| txs[index].tx.hash()),
+        "results": results,
+        "stateDiff": state_diff,
+    }))
+}
@@ -1,11 +1,19 @@
+pub mod diff;
 pub mod merkle_trie;
+pub mod report;
 mod runner;
 pub mod utils;
 
-pub use runner::TestError as Error;
+pub use runner::{
+    find_all_json_tests, set_spec, set_test_tx, unit_env, unit_prestate, TestError as Error,
+    TestErrorKind, TestFilter,
+};
 
 use clap::Parser;
-use runner::{find_all_json_tests, run, TestError};
+use regex::Regex;
+use runner::{run, Reports, TestError};
+use serde_json::Value;
+use statetest_types::SpecName;
 use std::path::PathBuf;
 
 /// `statetest` subcommand
@@ -36,22 +44,81 @@ pub struct Cmd {
     /// Keep going after a test failure
     #[clap(long, alias = "no-fail-fast")]
     keep_going: bool,
+    /// Run every test again with decoded dispatch, with and without block precharging, and
+    /// compare it with the dispatch loop
+    ///
+    /// Not used together with `--json`.
+    #[clap(long)]
+    diff_dispatch: bool,
+    /// Run only the given forks, named as in the tests
+    #[clap(long = "fork", value_delimiter = ',', value_parser = parse_fork)]
+    forks: Vec<SpecName>,
+    /// Run only the tests with a name matching the regex
+    #[clap(long)]
+    name: Option<Regex>,
+    /// Run only the tests with the data index
+    #[clap(short = 'd', long)]
+    data: Option<usize>,
+    /// Run only the tests with the gas index
+    #[clap(short = 'g', long)]
+    gas: Option<usize>,
+    /// Run only the tests with the value index
+    #[clap(short = 'v', long)]
+    value: Option<usize>,
+    /// Write a JUnit XML report of the executed tests to the file
+    #[clap(long)]
+    junit: Option<PathBuf>,
+    /// Write a JSON summary of the executed tests to the file
+    #[clap(long)]
+    summary: Option<PathBuf>,
+}
+
+pub fn parse_fork(name: &str) -> Result<SpecName, String> {
+    serde_json::from_value(Value::String(name.to_string()))
+        .ok()
+        .filter(|fork| *fork != SpecName::Unknown)
+        .ok_or_else(|| format!("unknown fork {name}"))
 }
 
 impl Cmd {
+    /// Paths of the test files and folders.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the filter selecting the tests to run.
+    pub fn filter(&self) -> TestFilter {
+        TestFilter {
+            forks: self.forks.clone(),
+            name: self.name.clone(),
+            data: self.data,
+            gas: self.gas,
+            value: self.value,
+        }
+    }
+
     /// Runs `statetest` command.
     pub fn run(&self) -> Result<(), TestError> {
+        // Tests of all paths are run together so that the reports cover all of them.
+        let mut test_files = Vec::new();
         for path in &self.paths {
             println!("\nRunning tests in {}...", path.display());
-            let test_files = find_all_json_tests(path);
-            run(
-                test_files,
-                self.single_thread,
-                self.json,
-                self.json_outcome,
-                self.keep_going,
-            )?
+            test_files.extend(find_all_json_tests(path));
         }
-        Ok(())
+        let filter = self.filter();
+        let reports = Reports {
+            junit: self.junit.clone(),
+            summary: self.summary.clone(),
+        };
+        run(
+            test_files,
+            self.single_thread,
+            self.json,
+            self.json_outcome,
+            self.keep_going,
+            self.diff_dispatch,
+            filter,
+            &reports,
+        )
     }
 }
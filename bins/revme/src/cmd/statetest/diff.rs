@@ -0,0 +1,199 @@
+use database::CacheState;
+use revm::primitives::{keccak256, Address, HashMap, B256, U256};
+use statetest_types::AccountInfo;
+use std::{collections::BTreeSet, fmt};
+
+/// Difference of an account between the expected post state and the state after execution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccountDiff {
+    Missing(Address),
+    Unexpected(Address),
+    Balance {
+        address: Address,
+        got: U256,
+        expected: U256,
+    },
+    Nonce {
+        address: Address,
+        got: u64,
+        expected: u64,
+    },
+    CodeHash {
+        address: Address,
+        got: B256,
+        expected: B256,
+    },
+    /// Missing slots have a zero value.
+    Storage {
+        address: Address,
+        slot: U256,
+        got: U256,
+        expected: U256,
+    },
+}
+
+impl fmt::Display for AccountDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(address) => write!(f, "{address}: account is missing"),
+            Self::Unexpected(address) => write!(f, "{address}: account is not expected"),
+            Self::Balance {
+                address,
+                got,
+                expected,
+            } => write!(f, "{address}: balance {got}, expected {expected}"),
+            Self::Nonce {
+                address,
+                got,
+                expected,
+            } => write!(f, "{address}: nonce {got}, expected {expected}"),
+            Self::CodeHash {
+                address,
+                got,
+                expected,
+            } => write!(f, "{address}: code hash {got}, expected {expected}"),
+            Self::Storage {
+                address,
+                slot,
+                got,
+                expected,
+            } => write!(
+                f,
+                "{address}: storage {slot:#x}: {got:#x}, expected {expected:#x}"
+            ),
+        }
+    }
+}
+
+/// Differences between the expected post state and the state after execution, ordered by
+/// address.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateDiff(pub Vec<AccountDiff>);
+
+impl StateDiff {
+    /// Compares the accounts of the cache with the expected ones.
+    pub fn new(expected: &HashMap<Address, AccountInfo>, cache: &CacheState) -> Self {
+        let got: HashMap<_, _> = cache.trie_account().into_iter().collect();
+        let addresses: BTreeSet<_> = expected.keys().chain(got.keys()).collect();
+
+        let mut diffs = Vec::new();
+        for address in addresses {
+            let address = *address;
+            let (expected, got) = match (expected.get(&address), got.get(&address)) {
+                (Some(expected), Some(got)) => (expected, got),
+                (Some(_), None) => {
+                    diffs.push(AccountDiff::Missing(address));
+                    continue;
+                }
+                (None, _) => {
+                    diffs.push(AccountDiff::Unexpected(address));
+                    continue;
+                }
+            };
+
+            if got.info.balance != expected.balance {
+                diffs.push(AccountDiff::Balance {
+                    address,
+                    got: got.info.balance,
+                    expected: expected.balance,
+                });
+            }
+            if got.info.nonce != expected.nonce {
+                diffs.push(AccountDiff::Nonce {
+                    address,
+                    got: got.info.nonce,
+                    expected: expected.nonce,
+                });
+            }
+            let code_hash = keccak256(&expected.code);
+            if got.info.code_hash != code_hash {
+                diffs.push(AccountDiff::CodeHash {
+                    address,
+                    got: got.info.code_hash,
+                    expected: code_hash,
+                });
+            }
+
+            let slots: BTreeSet<_> = expected.storage.keys().chain(got.storage.keys()).collect();
+            for slot in slots {
+                let got = got.storage.get(slot).copied().unwrap_or_default();
+                let expected = expected.storage.get(slot).copied().unwrap_or_default();
+                if got != expected {
+                    diffs.push(AccountDiff::Storage {
+                        address,
+                        slot: *slot,
+                        got,
+                        expected,
+                    });
+                }
+            }
+        }
+        Self(diffs)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Writes every difference on its own indented line, nothing if there are none.
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diff in &self.0 {
+            write!(f, "\n  {diff}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::{primitives::Bytes, state};
+
+    #[test]
+    fn state_diff() {
+        let a = Address::with_last_byte(1);
+        let b = Address::with_last_byte(2);
+        let c = Address::with_last_byte(3);
+
+        let mut cache = CacheState::new(true);
+        let info = state::AccountInfo {
+            balance: U256::from(10),
+            nonce: 1,
+            ..Default::default()
+        };
+        let storage = [(U256::from(1), U256::from(5)), (U256::from(2), U256::ZERO)];
+        cache.insert_account_with_storage(a, info.clone(), storage.into_iter().collect());
+        cache.insert_account(c, info);
+
+        let account = AccountInfo {
+            balance: U256::from(10),
+            code: Bytes::new(),
+            nonce: 2,
+            storage: [(U256::from(1), U256::from(6))].into_iter().collect(),
+        };
+        let expected = [(a, account.clone()), (b, account)].into_iter().collect();
+
+        let diff = StateDiff::new(&expected, &cache);
+        assert_eq!(
+            diff.0,
+            vec![
+                AccountDiff::Nonce {
+                    address: a,
+                    got: 1,
+                    expected: 2
+                },
+                AccountDiff::Storage {
+                    address: a,
+                    slot: U256::from(1),
+                    got: U256::from(5),
+                    expected: U256::from(6),
+                },
+                AccountDiff::Missing(b),
+                AccountDiff::Unexpected(c),
+            ]
+        );
+        assert!(diff.to_string().contains("storage 0x1: 0x5, expected 0x6"));
+    }
+}
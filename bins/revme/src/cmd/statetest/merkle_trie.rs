@@ -3,7 +3,7 @@ use database::PlainAccount;
 use hash_db::Hasher;
 use plain_hasher::PlainHasher;
 use revm::primitives::{keccak256, Address, Log, B256, U256};
-use triehash::sec_trie_root;
+use triehash::{ordered_trie_root as ordered_root, sec_trie_root};
 
 pub fn log_rlp_hash(logs: &[Log]) -> B256 {
     let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
@@ -56,6 +56,17 @@ where
     sec_trie_root::<KeccakHasher, _, _, _>(input)
 }
 
+/// Root of the trie keyed by the RLP encoded index of the items, used for the transactions,
+/// receipts and withdrawals of a block.
+#[inline]
+pub fn ordered_trie_root<I, B>(input: I) -> B256
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    ordered_root::<KeccakHasher, _>(input)
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct KeccakHasher;
 
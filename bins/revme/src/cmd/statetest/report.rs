@@ -0,0 +1,211 @@
+use serde_json::{json, Map, Value};
+use statetest_types::SpecName;
+use std::{collections::BTreeMap, fmt::Write, fs, io, path::Path, time::Duration};
+
+/// Outcome of a test, recorded for the reports.
+#[derive(Clone, Debug)]
+pub struct TestOutcome {
+    pub path: String,
+    pub name: String,
+    pub fork: SpecName,
+    pub data: usize,
+    pub gas: usize,
+    pub value: usize,
+    /// Execution time of the transaction.
+    pub time: Duration,
+    /// Error message if the test failed.
+    pub error: Option<String>,
+}
+
+impl TestOutcome {
+    /// Name of the test with its fork and indexes.
+    fn id(&self) -> String {
+        format!(
+            "{}[{:?}-d{}-g{}-v{}]",
+            self.name, self.fork, self.data, self.gas, self.value
+        )
+    }
+}
+
+/// Sorts the outcomes, tests run by different threads are recorded in any order.
+fn sorted(outcomes: &[TestOutcome]) -> Vec<&TestOutcome> {
+    let mut outcomes: Vec<_> = outcomes.iter().collect();
+    outcomes.sort_by(|a, b| {
+        (&a.path, &a.name, a.fork, a.data, a.gas, a.value)
+            .cmp(&(&b.path, &b.name, b.fork, b.data, b.gas, b.value))
+    });
+    outcomes
+}
+
+/// Returns the JUnit XML report, with a test suite per file.
+pub fn junit_xml(outcomes: &[TestOutcome]) -> String {
+    let mut files: BTreeMap<&str, Vec<&TestOutcome>> = BTreeMap::new();
+    for outcome in sorted(outcomes) {
+        files.entry(&outcome.path).or_default().push(outcome);
+    }
+    let failures = outcomes.iter().filter(|o| o.error.is_some()).count();
+    let time = |outcomes: &[&TestOutcome]| {
+        outcomes
+            .iter()
+            .map(|o| o.time)
+            .sum::<Duration>()
+            .as_secs_f64()
+    };
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let all: Vec<_> = outcomes.iter().collect();
+    writeln!(
+        xml,
+        "<testsuites name=\"statetest\" tests=\"{}\" failures=\"{failures}\" time=\"{:.6}\">",
+        outcomes.len(),
+        time(&all)
+    )
+    .unwrap();
+    for (path, outcomes) in files {
+        let failures = outcomes.iter().filter(|o| o.error.is_some()).count();
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" time=\"{:.6}\">",
+            escape_xml(path),
+            outcomes.len(),
+            time(&outcomes)
+        )
+        .unwrap();
+        for outcome in outcomes {
+            write!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.6}\"",
+                escape_xml(path),
+                escape_xml(&outcome.id()),
+                outcome.time.as_secs_f64()
+            )
+            .unwrap();
+            match &outcome.error {
+                None => xml.push_str("/>\n"),
+                Some(error) => {
+                    let message = error.lines().next().unwrap_or_default();
+                    writeln!(
+                        xml,
+                        ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>",
+                        escape_xml(message),
+                        escape_xml(error)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Returns the summary report with the number of passed and failed tests, in total and per fork,
+/// and the failed tests.
+pub fn summary_json(outcomes: &[TestOutcome], elapsed: Duration) -> Value {
+    let mut forks: BTreeMap<SpecName, (usize, usize)> = BTreeMap::new();
+    let mut failures = Vec::new();
+    for outcome in sorted(outcomes) {
+        let (passed, failed) = forks.entry(outcome.fork).or_default();
+        match &outcome.error {
+            None => *passed += 1,
+            Some(error) => {
+                *failed += 1;
+                failures.push(json!({
+                    "path": outcome.path,
+                    "test": outcome.name,
+                    "fork": format!("{:?}", outcome.fork),
+                    "d": outcome.data,
+                    "g": outcome.gas,
+                    "v": outcome.value,
+                    "error": error,
+                }));
+            }
+        }
+    }
+    let forks: Map<String, Value> = forks
+        .into_iter()
+        .map(|(fork, (passed, failed))| {
+            (
+                format!("{fork:?}"),
+                json!({ "passed": passed, "failed": failed }),
+            )
+        })
+        .collect();
+    json!({
+        "total": outcomes.len(),
+        "passed": outcomes.len() - failures.len(),
+        "failed": failures.len(),
+        "cpuTime": elapsed.as_secs_f64(),
+        "forks": forks,
+        "failures": failures,
+    })
+}
+
+pub fn write_junit(path: &Path, outcomes: &[TestOutcome]) -> io::Result<()> {
+    fs::write(path, junit_xml(outcomes))
+}
+
+pub fn write_summary(path: &Path, outcomes: &[TestOutcome], elapsed: Duration) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(&summary_json(outcomes, elapsed))?;
+    fs::write(path, json)
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(name: &str, fork: SpecName, error: Option<&str>) -> TestOutcome {
+        TestOutcome {
+            path: "tests/a.json".to_string(),
+            name: name.to_string(),
+            fork,
+            data: 0,
+            gas: 1,
+            value: 0,
+            time: Duration::from_millis(2),
+            error: error.map(ToString::to_string),
+        }
+    }
+
+    #[test]
+    fn reports() {
+        let outcomes = [
+            outcome("b", SpecName::Prague, None),
+            outcome(
+                "a<1>",
+                SpecName::Cancun,
+                Some("state root mismatch\n  diff"),
+            ),
+            outcome("a<1>", SpecName::Prague, None),
+        ];
+
+        let xml = junit_xml(&outcomes);
+        assert!(xml.contains(r#"<testsuites name="statetest" tests="3" failures="1""#));
+        assert!(xml.contains(r#"name="a&lt;1&gt;[Cancun-d0-g1-v0]" time="0.002000">"#));
+        assert!(xml.contains(r#"<failure message="state root mismatch">"#));
+        assert!(xml.find("a&lt;1&gt;[Prague").unwrap() < xml.find("b[Prague").unwrap());
+
+        let summary = summary_json(&outcomes, Duration::ZERO);
+        assert_eq!(summary["total"], 3);
+        assert_eq!(summary["failed"], 1);
+        assert_eq!(summary["forks"]["Prague"]["passed"], 2);
+        assert_eq!(summary["forks"]["Cancun"]["failed"], 1);
+        assert_eq!(summary["failures"][0]["test"], "a<1>");
+    }
+}
@@ -1,10 +1,13 @@
 use super::{
+    diff::StateDiff,
     merkle_trie::{log_rlp_hash, state_merkle_trie_root},
+    report::{write_junit, write_summary, TestOutcome},
     utils::recover_address,
 };
-use database::State;
+use database::{CacheState, State};
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use inspector::{exec::InspectCommitEvm, inspectors::TracerEip3155};
+use regex::Regex;
 use revm::{
     bytecode::Bytecode,
     context::{block::BlockEnv, cfg::CfgEnv, tx::TxEnv},
@@ -19,12 +22,12 @@ use revm::{
     Context, ExecuteCommitEvm,
 };
 use serde_json::json;
-use statetest_types::{SpecName, Test, TestSuite};
+use statetest_types::{SpecName, Test, TestSuite, TestUnit, TxPartIndices};
 
 use std::{
     convert::Infallible,
     fmt::Debug,
-    io::stderr,
+    io::{self, stderr},
     path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
@@ -49,6 +52,9 @@ pub enum TestErrorKind {
     LogsRootMismatch { got: B256, expected: B256 },
     #[error("state root mismatch: got {got}, expected {expected}")]
     StateRootMismatch { got: B256, expected: B256 },
+    /// State root mismatch of a test with the expected accounts.
+    #[error("state root mismatch: got {got}, the accounts differ from the post state:{diff}")]
+    PostStateMismatch { got: B256, diff: Box<StateDiff> },
     #[error("unknown private key: {0:?}")]
     UnknownPrivateKey(B256),
     #[error("unexpected exception: got {got_exception:?}, expected {expected_exception:?}")]
@@ -61,12 +67,54 @@ pub enum TestErrorKind {
         expected_output: Option<Bytes>,
         got_output: Option<Bytes>,
     },
+    #[error(
+        "decoded dispatch mismatch (block precharge: {block_precharge}): \
+         got {decoded_result}, expected {loop_result}"
+    )]
+    DispatchMismatch {
+        block_precharge: bool,
+        loop_result: String,
+        decoded_result: String,
+    },
     #[error(transparent)]
     SerdeDeserialize(#[from] serde_json::Error),
+    #[error("failed to write report: {0}")]
+    Report(#[from] io::Error),
     #[error("thread panicked")]
     Panic,
 }
 
+/// Selects the tests to run, by default all of them.
+#[derive(Debug, Default)]
+pub struct TestFilter {
+    /// Forks to run, all of them if empty.
+    pub forks: Vec<SpecName>,
+    /// Regex that the test name has to match.
+    pub name: Option<Regex>,
+    /// Data index of the transaction.
+    pub data: Option<usize>,
+    /// Gas index of the transaction.
+    pub gas: Option<usize>,
+    /// Value index of the transaction.
+    pub value: Option<usize>,
+}
+
+impl TestFilter {
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.name.as_ref().is_none_or(|regex| regex.is_match(name))
+    }
+
+    pub fn matches_fork(&self, fork: &SpecName) -> bool {
+        self.forks.is_empty() || self.forks.contains(fork)
+    }
+
+    pub fn matches_indexes(&self, indexes: &TxPartIndices) -> bool {
+        self.data.is_none_or(|data| data == indexes.data)
+            && self.gas.is_none_or(|gas| gas == indexes.gas)
+            && self.value.is_none_or(|value| value == indexes.value)
+    }
+}
+
 pub fn find_all_json_tests(path: &Path) -> Vec<PathBuf> {
     if path.is_file() {
         vec![path.to_path_buf()]
@@ -220,9 +268,17 @@ fn check_evm_execution(
     }
 
     if state_root != test.hash {
-        let kind = TestErrorKind::StateRootMismatch {
-            got: state_root,
-            expected: test.hash,
+        let diff = StateDiff::new(&test.state, &db.cache);
+        let kind = if test.state.is_empty() || diff.is_empty() {
+            TestErrorKind::StateRootMismatch {
+                got: state_root,
+                expected: test.hash,
+            }
+        } else {
+            TestErrorKind::PostStateMismatch {
+                got: state_root,
+                diff: Box::new(diff),
+            }
         };
         print_json_output(Some(kind.to_string()));
         return Err(kind);
@@ -233,11 +289,247 @@ fn check_evm_execution(
     Ok(())
 }
 
+/// Runs the test again with decoded dispatch, with and without block precharging, and checks
+/// that it passes with the same execution result as `loop_result`.
+#[allow(clippy::too_many_arguments)]
+fn check_decoded_dispatch(
+    test: &Test,
+    expected_output: Option<&Bytes>,
+    test_name: &str,
+    cache_state: &CacheState,
+    block: &BlockEnv,
+    tx: &TxEnv,
+    cfg: &CfgEnv,
+    loop_result: &Result<ExecutionResult<HaltReason>, EVMError<Infallible, InvalidTransaction>>,
+) -> Result<(), TestErrorKind> {
+    [false, true].into_iter().try_for_each(|block_precharge| {
+        let mut cfg = cfg.clone();
+        cfg.decoded_dispatch = true;
+        cfg.block_precharge = block_precharge;
+        check_dispatch(
+            test,
+            expected_output,
+            test_name,
+            cache_state,
+            block,
+            tx,
+            &cfg,
+            loop_result,
+        )
+    })
+}
+
+/// Runs the test again with `cfg` and checks that it passes with the same execution result
+/// as `loop_result`.
+#[allow(clippy::too_many_arguments)]
+fn check_dispatch(
+    test: &Test,
+    expected_output: Option<&Bytes>,
+    test_name: &str,
+    cache_state: &CacheState,
+    block: &BlockEnv,
+    tx: &TxEnv,
+    cfg: &CfgEnv,
+    loop_result: &Result<ExecutionResult<HaltReason>, EVMError<Infallible, InvalidTransaction>>,
+) -> Result<(), TestErrorKind> {
+    let mut cache = cache_state.clone();
+    cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+    let mut state = database::State::builder()
+        .with_cached_prestate(cache)
+        .with_bundle_update()
+        .build();
+    let mut ctx = Context::builder()
+        .with_block(block)
+        .with_tx(tx)
+        .with_cfg(cfg)
+        .with_db(&mut state);
+
+    let res = ctx.exec_commit_previous();
+    if res != *loop_result {
+        return Err(TestErrorKind::DispatchMismatch {
+            block_precharge: cfg.block_precharge,
+            loop_result: format!("{loop_result:?}"),
+            decoded_result: format!("{res:?}"),
+        });
+    }
+    check_evm_execution(
+        test,
+        expected_output,
+        test_name,
+        &res,
+        ctx.journaled_state.database,
+        cfg.spec,
+        false,
+    )
+}
+
+/// Returns the accounts before the execution of the test unit.
+pub fn unit_prestate(unit: &TestUnit) -> CacheState {
+    let mut cache_state = database::CacheState::new(false);
+    for (address, info) in &unit.pre {
+        let code_hash = keccak256(&info.code);
+        let bytecode = Bytecode::new_raw_checked(info.code.clone())
+            .unwrap_or(Bytecode::new_legacy(info.code.clone()));
+        let acc_info = revm::state::AccountInfo {
+            balance: info.balance,
+            code_hash,
+            code: Some(bytecode),
+            nonce: info.nonce,
+        };
+        cache_state.insert_account_with_storage(*address, acc_info, info.storage.clone());
+    }
+    cache_state
+}
+
+/// Returns the environment of the test unit.
+///
+/// The fork is set by [`set_spec`] and the transaction fields selected by the test indexes by
+/// [`set_test_tx`].
+pub fn unit_env(unit: &TestUnit) -> Result<(CfgEnv, BlockEnv, TxEnv), TestErrorKind> {
+    let mut cfg = CfgEnv::default();
+    let mut block = BlockEnv::default();
+    let mut tx = TxEnv::default();
+    // For mainnet
+    cfg.chain_id = 1;
+
+    // Block env
+    block.number = unit.env.current_number.try_into().unwrap_or(u64::MAX);
+    block.beneficiary = unit.env.current_coinbase;
+    block.timestamp = unit.env.current_timestamp.try_into().unwrap_or(u64::MAX);
+    block.gas_limit = unit.env.current_gas_limit.try_into().unwrap_or(u64::MAX);
+    block.basefee = unit
+        .env
+        .current_base_fee
+        .unwrap_or_default()
+        .try_into()
+        .unwrap_or(u64::MAX);
+    block.difficulty = unit.env.current_difficulty;
+    // After the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
+    block.prevrandao = unit.env.current_random;
+
+    // Tx env
+    tx.caller = if let Some(address) = unit.transaction.sender {
+        address
+    } else {
+        recover_address(unit.transaction.secret_key.as_slice()).ok_or(
+            TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key),
+        )?
+    };
+    tx.gas_price = unit
+        .transaction
+        .gas_price
+        .or(unit.transaction.max_fee_per_gas)
+        .unwrap_or_default()
+        .try_into()
+        .unwrap_or(u128::MAX);
+    tx.gas_priority_fee = unit
+        .transaction
+        .max_priority_fee_per_gas
+        .map(|b| u128::try_from(b).expect("max priority fee less than u128::MAX"));
+    // EIP-4844
+    tx.blob_hashes = unit.transaction.blob_versioned_hashes.clone();
+    tx.max_fee_per_blob_gas = unit
+        .transaction
+        .max_fee_per_blob_gas
+        .map(|b| u128::try_from(b).expect("max fee less than u128::MAX"))
+        .unwrap_or(u128::MAX);
+
+    tx.kind = match unit.transaction.to {
+        Some(add) => TxKind::Call(add),
+        None => TxKind::Create,
+    };
+    Ok((cfg, block, tx))
+}
+
+/// Sets the fork of the configuration and the block fields that depend on it.
+pub fn set_spec(unit: &TestUnit, spec_name: SpecName, cfg: &mut CfgEnv, block: &mut BlockEnv) {
+    cfg.spec = spec_name.to_spec_id();
+
+    // EIP-4844
+    if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
+        block.set_blob_excess_gas_and_price(
+            current_excess_blob_gas.to(),
+            cfg.spec.is_enabled_in(SpecId::PRAGUE),
+        );
+    } else if let (Some(parent_blob_gas_used), Some(parent_excess_blob_gas)) = (
+        unit.env.parent_blob_gas_used,
+        unit.env.parent_excess_blob_gas,
+    ) {
+        block.set_blob_excess_gas_and_price(
+            calc_excess_blob_gas(
+                parent_blob_gas_used.to(),
+                parent_excess_blob_gas.to(),
+                unit.env
+                    .parent_target_blobs_per_block
+                    .map(|i| i.to())
+                    .unwrap_or(TARGET_BLOB_GAS_PER_BLOCK_CANCUN),
+            ),
+            cfg.spec.is_enabled_in(SpecId::PRAGUE),
+        );
+    }
+
+    if cfg.spec.is_enabled_in(SpecId::MERGE) && block.prevrandao.is_none() {
+        // If spec is merge and prevrandao is not set, set it to default
+        block.prevrandao = Some(B256::default());
+    }
+}
+
+/// Sets the transaction fields selected by the indexes of the test.
+///
+/// Returns `false` if the transaction type of the test is invalid.
+pub fn set_test_tx(unit: &TestUnit, test: &Test, tx: &mut TxEnv) -> bool {
+    let Some(tx_type) = unit.transaction.tx_type(test.indexes.data) else {
+        return false;
+    };
+
+    tx.tx_type = tx_type as u8;
+
+    tx.gas_limit = unit.transaction.gas_limit[test.indexes.gas].saturating_to();
+
+    tx.data = unit
+        .transaction
+        .data
+        .get(test.indexes.data)
+        .unwrap()
+        .clone();
+
+    tx.nonce = u64::try_from(unit.transaction.nonce).unwrap();
+    tx.value = unit.transaction.value[test.indexes.value];
+
+    tx.access_list = unit
+        .transaction
+        .access_lists
+        .get(test.indexes.data)
+        .and_then(Option::as_deref)
+        .map(|access_list| {
+            access_list
+                .iter()
+                .map(|item| (item.address, item.storage_keys.clone()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    tx.authorization_list = unit
+        .transaction
+        .authorization_list
+        .clone()
+        .map(|auth_list| auth_list.into_iter().map(Into::into).collect::<Vec<_>>())
+        .unwrap_or_default();
+    true
+}
+
+/// Executes the tests of the file selected by the filter.
+///
+/// Outcomes of the executed tests are pushed to `outcomes` if given.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_test_suite(
     path: &Path,
     elapsed: &Arc<Mutex<Duration>>,
     trace: bool,
     print_json_outcome: bool,
+    diff_dispatch: bool,
+    filter: &TestFilter,
+    outcomes: Option<&Mutex<Vec<TestOutcome>>>,
 ) -> Result<(), TestError> {
     if skip_test(path) {
         return Ok(());
@@ -252,158 +544,56 @@ pub fn execute_test_suite(
     })?;
 
     for (name, unit) in suite.0 {
-        // Create database and insert cache
-        let mut cache_state = database::CacheState::new(false);
-        for (address, info) in unit.pre {
-            let code_hash = keccak256(&info.code);
-            let bytecode = Bytecode::new_raw_checked(info.code.clone())
-                .unwrap_or(Bytecode::new_legacy(info.code));
-            let acc_info = revm::state::AccountInfo {
-                balance: info.balance,
-                code_hash,
-                code: Some(bytecode),
-                nonce: info.nonce,
-            };
-            cache_state.insert_account_with_storage(address, acc_info, info.storage);
+        if !filter.matches_name(&name) {
+            continue;
         }
 
-        let mut cfg = CfgEnv::default();
-        let mut block = BlockEnv::default();
-        let mut tx = TxEnv::default();
-        // For mainnet
-        cfg.chain_id = 1;
-
-        // Block env
-        block.number = unit.env.current_number.try_into().unwrap_or(u64::MAX);
-        block.beneficiary = unit.env.current_coinbase;
-        block.timestamp = unit.env.current_timestamp.try_into().unwrap_or(u64::MAX);
-        block.gas_limit = unit.env.current_gas_limit.try_into().unwrap_or(u64::MAX);
-        block.basefee = unit
-            .env
-            .current_base_fee
-            .unwrap_or_default()
-            .try_into()
-            .unwrap_or(u64::MAX);
-        block.difficulty = unit.env.current_difficulty;
-        // After the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
-        block.prevrandao = unit.env.current_random;
-
-        // Tx env
-        tx.caller = if let Some(address) = unit.transaction.sender {
-            address
-        } else {
-            recover_address(unit.transaction.secret_key.as_slice()).ok_or_else(|| TestError {
-                name: name.clone(),
-                path: path.clone(),
-                kind: TestErrorKind::UnknownPrivateKey(unit.transaction.secret_key),
-            })?
-        };
-        tx.gas_price = unit
-            .transaction
-            .gas_price
-            .or(unit.transaction.max_fee_per_gas)
-            .unwrap_or_default()
-            .try_into()
-            .unwrap_or(u128::MAX);
-        tx.gas_priority_fee = unit
-            .transaction
-            .max_priority_fee_per_gas
-            .map(|b| u128::try_from(b).expect("max priority fee less than u128::MAX"));
-        // EIP-4844
-        tx.blob_hashes = unit.transaction.blob_versioned_hashes.clone();
-        tx.max_fee_per_blob_gas = unit
-            .transaction
-            .max_fee_per_blob_gas
-            .map(|b| u128::try_from(b).expect("max fee less than u128::MAX"))
-            .unwrap_or(u128::MAX);
+        let cache_state = unit_prestate(&unit);
+        let (mut cfg, mut block, mut tx) = unit_env(&unit).map_err(|kind| TestError {
+            name: name.clone(),
+            path: path.clone(),
+            kind,
+        })?;
 
         // Post and execution
-        for (spec_name, tests) in unit.post {
+        for (spec_name, tests) in &unit.post {
+            let spec_name = *spec_name;
             // Constantinople was immediately extended by Petersburg.
             // There isn't any production Constantinople transaction
             // so we don't support it and skip right to Petersburg.
-            if spec_name == SpecName::Constantinople {
+            if spec_name == SpecName::Constantinople || !filter.matches_fork(&spec_name) {
                 continue;
             }
 
-            cfg.spec = spec_name.to_spec_id();
+            set_spec(&unit, spec_name, &mut cfg, &mut block);
 
-            // EIP-4844
-            if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
-                block.set_blob_excess_gas_and_price(
-                    current_excess_blob_gas.to(),
-                    cfg.spec.is_enabled_in(SpecId::PRAGUE),
-                );
-            } else if let (Some(parent_blob_gas_used), Some(parent_excess_blob_gas)) = (
-                unit.env.parent_blob_gas_used,
-                unit.env.parent_excess_blob_gas,
-            ) {
-                block.set_blob_excess_gas_and_price(
-                    calc_excess_blob_gas(
-                        parent_blob_gas_used.to(),
-                        parent_excess_blob_gas.to(),
-                        unit.env
-                            .parent_target_blobs_per_block
-                            .map(|i| i.to())
-                            .unwrap_or(TARGET_BLOB_GAS_PER_BLOCK_CANCUN),
-                    ),
-                    cfg.spec.is_enabled_in(SpecId::PRAGUE),
-                );
-            }
-
-            if cfg.spec.is_enabled_in(SpecId::MERGE) && block.prevrandao.is_none() {
-                // If spec is merge and prevrandao is not set, set it to default
-                block.prevrandao = Some(B256::default());
-            }
+            for (index, test) in tests.iter().enumerate() {
+                if !filter.matches_indexes(&test.indexes) {
+                    continue;
+                }
 
-            for (index, test) in tests.into_iter().enumerate() {
-                let Some(tx_type) = unit.transaction.tx_type(test.indexes.data) else {
+                if !set_test_tx(&unit, test, &mut tx) {
                     if test.expect_exception.is_some() {
                         continue;
                     } else {
                         panic!("Invalid transaction type without expected exception");
                     }
-                };
+                }
 
-                tx.tx_type = tx_type as u8;
-
-                tx.gas_limit = unit.transaction.gas_limit[test.indexes.gas].saturating_to();
-
-                tx.data = unit
-                    .transaction
-                    .data
-                    .get(test.indexes.data)
-                    .unwrap()
-                    .clone();
-
-                tx.nonce = u64::try_from(unit.transaction.nonce).unwrap();
-                tx.value = unit.transaction.value[test.indexes.value];
-
-                tx.access_list = unit
-                    .transaction
-                    .access_lists
-                    .get(test.indexes.data)
-                    .and_then(Option::as_deref)
-                    .map(|access_list| {
-                        access_list
-                            .iter()
-                            .map(|item| (item.address, item.storage_keys.clone()))
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap_or_default();
-
-                tx.authorization_list = unit
-                    .transaction
-                    .authorization_list
-                    .clone()
-                    .map(|auth_list| auth_list.into_iter().map(Into::into).collect::<Vec<_>>())
-                    .unwrap_or_default();
-
-                let to = match unit.transaction.to {
-                    Some(add) => TxKind::Call(add),
-                    None => TxKind::Create,
+                let record = |output: &Result<(), TestErrorKind>, time| {
+                    if let Some(outcomes) = outcomes {
+                        outcomes.lock().unwrap().push(TestOutcome {
+                            path: path.clone(),
+                            name: name.clone(),
+                            fork: spec_name,
+                            data: test.indexes.data,
+                            gas: test.indexes.gas,
+                            value: test.indexes.value,
+                            time,
+                            error: output.as_ref().err().map(ToString::to_string),
+                        });
+                    }
                 };
-                tx.kind = to;
 
                 let mut cache = cache_state.clone();
                 cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
@@ -429,13 +619,14 @@ pub fn execute_test_suite(
                     let res = ctx.inspect_commit_previous(
                         TracerEip3155::new(Box::new(stderr())).without_summary(),
                     );
-                    *elapsed.lock().unwrap() += timer.elapsed();
+                    let time = timer.elapsed();
+                    *elapsed.lock().unwrap() += time;
 
                     let spec = cfg.spec();
                     let db = &mut ctx.journaled_state.database;
                     // Dump state and traces if test failed
                     let output = check_evm_execution(
-                        &test,
+                        test,
                         unit.out.as_ref(),
                         &name,
                         &res,
@@ -443,6 +634,7 @@ pub fn execute_test_suite(
                         spec,
                         print_json_outcome,
                     );
+                    record(&output, time);
                     let Err(e) = output else {
                         continue;
                     };
@@ -450,20 +642,37 @@ pub fn execute_test_suite(
                 } else {
                     let timer = Instant::now();
                     let res = ctx.exec_commit_previous();
-                    *elapsed.lock().unwrap() += timer.elapsed();
+                    let time = timer.elapsed();
+                    *elapsed.lock().unwrap() += time;
 
                     let spec = cfg.spec();
                     let db = ctx.journaled_state.database;
                     // Dump state and traces if test failed
                     let output = check_evm_execution(
-                        &test,
+                        test,
                         unit.out.as_ref(),
                         &name,
                         &res,
                         db,
                         spec,
                         print_json_outcome,
-                    );
+                    )
+                    .and_then(|()| {
+                        if !diff_dispatch {
+                            return Ok(());
+                        }
+                        check_decoded_dispatch(
+                            test,
+                            unit.out.as_ref(),
+                            &name,
+                            &cache_state,
+                            &block,
+                            &tx,
+                            &cfg,
+                            &res,
+                        )
+                    });
+                    record(&output, time);
                     let Err(e) = output else {
                         continue;
                     };
@@ -522,12 +731,25 @@ pub fn execute_test_suite(
     Ok(())
 }
 
+/// Paths of the reports written after the tests are run.
+#[derive(Debug, Default)]
+pub struct Reports {
+    /// JUnit XML report.
+    pub junit: Option<PathBuf>,
+    /// JSON summary report.
+    pub summary: Option<PathBuf>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     test_files: Vec<PathBuf>,
     mut single_thread: bool,
     trace: bool,
     mut print_outcome: bool,
     keep_going: bool,
+    diff_dispatch: bool,
+    filter: TestFilter,
+    reports: &Reports,
 ) -> Result<(), TestError> {
     // Trace implies print_outcome
     if trace {
@@ -546,6 +768,9 @@ pub fn run(
     ));
     let queue = Arc::new(Mutex::new((0usize, test_files)));
     let elapsed = Arc::new(Mutex::new(std::time::Duration::ZERO));
+    let filter = Arc::new(filter);
+    let outcomes = (reports.junit.is_some() || reports.summary.is_some())
+        .then(|| Arc::new(Mutex::new(Vec::new())));
 
     let num_threads = match (single_thread, std::thread::available_parallelism()) {
         (true, _) | (false, Err(_)) => 1,
@@ -558,6 +783,8 @@ pub fn run(
         let n_errors = n_errors.clone();
         let console_bar = console_bar.clone();
         let elapsed = elapsed.clone();
+        let filter = filter.clone();
+        let outcomes = outcomes.clone();
 
         let thread = std::thread::Builder::new().name(format!("runner-{i}"));
 
@@ -576,7 +803,15 @@ pub fn run(
                 (prev_idx, test_path)
             };
 
-            let result = execute_test_suite(&test_path, &elapsed, trace, print_outcome);
+            let result = execute_test_suite(
+                &test_path,
+                &elapsed,
+                trace,
+                print_outcome,
+                diff_dispatch,
+                &filter,
+                outcomes.as_deref(),
+            );
 
             // Increment after the test is done.
             console_bar.inc(1);
@@ -606,11 +841,29 @@ pub fn run(
     }
     console_bar.finish();
 
+    let elapsed = *elapsed.lock().unwrap();
     println!(
         "Finished execution. Total CPU time: {:.6}s",
-        elapsed.lock().unwrap().as_secs_f64()
+        elapsed.as_secs_f64()
     );
 
+    if let Some(outcomes) = outcomes {
+        let outcomes = outcomes.lock().unwrap();
+        let report_error = |path: &Path, error| TestError {
+            name: "report".to_string(),
+            path: path.display().to_string(),
+            kind: TestErrorKind::Report(error),
+        };
+        if let Some(path) = &reports.junit {
+            write_junit(path, &outcomes).map_err(|e| report_error(path, e))?;
+            println!("JUnit report written to {}", path.display());
+        }
+        if let Some(path) = &reports.summary {
+            write_summary(path, &outcomes, elapsed).map_err(|e| report_error(path, e))?;
+            println!("Summary report written to {}", path.display());
+        }
+    }
+
     let n_errors = n_errors.load(Ordering::SeqCst);
     let n_thread_errors = thread_errors.len();
     if n_errors == 0 && n_thread_errors == 0 {
@@ -632,3 +885,30 @@ pub fn run(
         Err(thread_errors.swap_remove(0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoded_dispatch_matches_loop_on_fixtures() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../tests");
+        let test_files = ["eof_suite/eest/state_tests", "pectra_devnet5/state_tests"]
+            .into_iter()
+            .flat_map(|path| find_all_json_tests(&root.join(path)))
+            .collect::<Vec<_>>();
+        assert!(!test_files.is_empty());
+
+        run(
+            test_files,
+            false,
+            false,
+            false,
+            false,
+            true,
+            TestFilter::default(),
+            &Reports::default(),
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+pub mod transaction;
+mod transition;
+pub mod types;
+
+use crate::cmd::blockchaintest::block::BlockError;
+use clap::Parser;
+use revm::{
+    primitives::Bytes,
+    signed_tx::{decode_transactions, encode_transactions, SignedTransaction},
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use statetest_types::SpecName;
+use std::{
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
+use thiserror::Error;
+use transaction::{TransactionError, TransactionJson};
+use transition::{transition, TransitionConfig};
+use types::{Alloc, Env};
+
+/// Name of an input or output that is read from stdin or written to stdout or stderr.
+const STDIN: &str = "stdin";
+const STDOUT: &str = "stdout";
+const STDERR: &str = "stderr";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{name}: {source}")]
+    Json {
+        name: String,
+        source: serde_json::Error,
+    },
+    #[error("transactions: {0}")]
+    Transactions(#[from] TransactionError),
+    #[error("unsupported fork {0}")]
+    UnsupportedFork(String),
+    #[error("{0} missing in stdin")]
+    MissingInput(&'static str),
+    #[error("{0} is required in env")]
+    MissingEnv(&'static str),
+    #[error(transparent)]
+    Block(#[from] BlockError),
+}
+
+/// `t8n` subcommand
+///
+/// Applies transactions on top of a state and writes the resulting state and receipts, with the
+/// interface of geth's `evm t8n`.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// File with the prestate, or `stdin`
+    #[arg(long = "input.alloc", default_value = "alloc.json")]
+    input_alloc: String,
+    /// File with the block environment, or `stdin`
+    #[arg(long = "input.env", default_value = "env.json")]
+    input_env: String,
+    /// File with the transactions, or `stdin`
+    ///
+    /// Files with the `.rlp` extension contain the hex encoded RLP list of the transactions,
+    /// other files a JSON list of transactions.
+    #[arg(long = "input.txs", default_value = "txs.json")]
+    input_txs: String,
+    /// Directory of the output files and traces
+    #[arg(long = "output.basedir", default_value = ".")]
+    output_basedir: PathBuf,
+    /// File of the result, or `stdout` or `stderr`
+    #[arg(long = "output.result", default_value = "result.json")]
+    output_result: String,
+    /// File of the poststate, or `stdout` or `stderr`
+    #[arg(long = "output.alloc", default_value = "alloc.json")]
+    output_alloc: String,
+    /// File of the RLP of the included transactions, or `stdout` or `stderr`
+    #[arg(long = "output.body")]
+    output_body: Option<String>,
+    /// Fork of the block, named as in the tests
+    #[arg(long = "state.fork", default_value = "Prague")]
+    fork: String,
+    /// Block reward in wei, `-1` disables it
+    ///
+    /// Defaults to the reward of the fork.
+    #[arg(long = "state.reward", allow_negative_numbers = true)]
+    reward: Option<i64>,
+    /// Chain id of the transactions
+    #[arg(long = "state.chainid", default_value_t = 1)]
+    chain_id: u64,
+    /// Write EIP-3155 traces of the transactions to `trace-<index>-<hash>.jsonl` in the base
+    /// directory
+    #[arg(long)]
+    trace: bool,
+    /// Include memory in the traces
+    #[arg(long = "trace.memory")]
+    trace_memory: bool,
+}
+
+/// Inputs read from stdin.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StdinInput {
+    alloc: Option<Alloc>,
+    env: Option<Env>,
+    txs: Option<Vec<TransactionJson>>,
+    txs_rlp: Option<Bytes>,
+}
+
+impl Cmd {
+    /// Runs `t8n` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let network = serde_json::from_value::<SpecName>(Value::String(self.fork.clone()))
+            .ok()
+            .filter(|network| {
+                !matches!(
+                    network,
+                    SpecName::Constantinople
+                        | SpecName::ByzantiumToConstantinopleAt5
+                        | SpecName::HomesteadToDaoAt5
+                        | SpecName::Unknown
+                )
+            })
+            .ok_or_else(|| Error::UnsupportedFork(self.fork.clone()))?;
+
+        let mut stdin = if [&self.input_alloc, &self.input_env, &self.input_txs]
+            .iter()
+            .any(|input| *input == STDIN)
+        {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|source| Error::Io {
+                    path: STDIN.into(),
+                    source,
+                })?;
+            parse_json(STDIN, &input)?
+        } else {
+            StdinInput::default()
+        };
+
+        let alloc: Alloc = match self.input_alloc.as_str() {
+            STDIN => stdin.alloc.take().ok_or(Error::MissingInput("alloc"))?,
+            path => parse_json(path, &read_file(path)?)?,
+        };
+        let env: Env = match self.input_env.as_str() {
+            STDIN => stdin.env.take().ok_or(Error::MissingInput("env"))?,
+            path => parse_json(path, &read_file(path)?)?,
+        };
+        let txs = match self.input_txs.as_str() {
+            STDIN => match (stdin.txs_rlp.take(), stdin.txs.take()) {
+                (Some(rlp), _) => decode_rlp(&rlp)?,
+                (None, Some(txs)) => self.sign(txs),
+                (None, None) => Vec::new(),
+            },
+            path if path.ends_with(".rlp") => {
+                let rlp: Bytes = parse_json(path, &read_file(path)?)?;
+                decode_rlp(&rlp)?
+            }
+            path => self.sign(parse_json(path, &read_file(path)?)?),
+        };
+
+        fs::create_dir_all(&self.output_basedir).map_err(|source| Error::Io {
+            path: self.output_basedir.clone(),
+            source,
+        })?;
+        let config = TransitionConfig {
+            network,
+            chain_id: self.chain_id,
+            reward: self.reward,
+            trace_dir: self.trace.then(|| self.output_basedir.clone()),
+            trace_memory: self.trace_memory,
+        };
+        let output = transition(alloc, &env, txs, &config)?;
+
+        let mut stdout = Map::new();
+        let mut stderr = Map::new();
+        let mut outputs = vec![
+            (&self.output_alloc, "alloc", to_value(&output.alloc)),
+            (&self.output_result, "result", to_value(&output.result)),
+        ];
+        if let Some(name) = &self.output_body {
+            let body = Bytes::from(encode_transactions(&output.included));
+            outputs.push((name, "body", to_value(&body)));
+        }
+        for (name, key, value) in outputs {
+            match name.as_str() {
+                STDOUT => {
+                    stdout.insert(key.to_string(), value);
+                }
+                STDERR => {
+                    stderr.insert(key.to_string(), value);
+                }
+                file => {
+                    let path = self.output_basedir.join(file);
+                    let json = serde_json::to_string_pretty(&value).expect("value is serializable");
+                    fs::write(&path, json).map_err(|source| Error::Io { path, source })?;
+                }
+            }
+        }
+        if !stdout.is_empty() {
+            println!("{}", serde_json::to_string_pretty(&stdout).unwrap());
+        }
+        if !stderr.is_empty() {
+            eprintln!("{}", serde_json::to_string_pretty(&stderr).unwrap());
+        }
+        Ok(())
+    }
+
+    /// Converts JSON transactions, signing the ones with a secret key.
+    fn sign(&self, txs: Vec<TransactionJson>) -> Vec<Result<SignedTransaction, TransactionError>> {
+        txs.into_iter()
+            .map(|tx| tx.into_signed(self.chain_id))
+            .collect()
+    }
+}
+
+fn read_file(path: &str) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.into(),
+        source,
+    })
+}
+
+fn parse_json<T: for<'de> Deserialize<'de>>(name: &str, json: &str) -> Result<T, Error> {
+    serde_json::from_str(json).map_err(|source| Error::Json {
+        name: name.to_string(),
+        source,
+    })
+}
+
+fn decode_rlp(rlp: &[u8]) -> Result<Vec<Result<SignedTransaction, TransactionError>>, Error> {
+    let transactions = decode_transactions(rlp).map_err(TransactionError::from)?;
+    Ok(transactions.into_iter().map(Ok).collect())
+}
+
+fn to_value(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).expect("value is serializable")
+}
@@ -0,0 +1,219 @@
+use k256::ecdsa::SigningKey;
+use revm::{
+    primitives::{Address, Bytes, TxKind, B256, U256},
+    signed_tx::{AccessListItem, Signature, SignedAuthorization, SignedTransaction, SignedTxError},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TransactionError {
+    #[error(transparent)]
+    Decode(#[from] SignedTxError),
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+}
+
+/// Signs the transaction with the secret key.
+pub fn sign(tx: &mut SignedTransaction, secret_key: &B256) -> Result<(), TransactionError> {
+    let key = SigningKey::from_slice(secret_key.as_slice())
+        .map_err(|_| TransactionError::InvalidSecretKey)?;
+    let (signature, recovery_id) = key
+        .sign_prehash_recoverable(tx.signature_hash().as_slice())
+        .map_err(|_| SignedTxError::InvalidSignature)?;
+    let y_parity = recovery_id.is_y_odd() as u64;
+    tx.signature = Signature {
+        v: match (tx.tx_type, tx.chain_id) {
+            (0, Some(chain_id)) => 35 + 2 * chain_id + y_parity,
+            (0, None) => 27 + y_parity,
+            _ => y_parity,
+        },
+        r: U256::from_be_slice(&signature.r().to_bytes()),
+        s: U256::from_be_slice(&signature.s().to_bytes()),
+    };
+    Ok(())
+}
+
+/// Transaction in the JSON format of `txs.json`.
+///
+/// Transactions with a `secretKey` and no signature are signed with the key.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionJson {
+    #[serde(rename = "type", default)]
+    pub tx_type: U256,
+    pub chain_id: Option<U256>,
+    #[serde(default)]
+    pub nonce: U256,
+    pub gas_price: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(alias = "gasLimit")]
+    pub gas: U256,
+    pub to: Option<Address>,
+    #[serde(default)]
+    pub value: U256,
+    #[serde(alias = "data", default)]
+    pub input: Bytes,
+    #[serde(default)]
+    pub access_list: Vec<AccessListItemJson>,
+    pub max_fee_per_blob_gas: Option<U256>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+    #[serde(default)]
+    pub authorization_list: Vec<AuthorizationJson>,
+    #[serde(default)]
+    pub v: U256,
+    #[serde(default)]
+    pub r: U256,
+    #[serde(default)]
+    pub s: U256,
+    pub secret_key: Option<B256>,
+    /// Whether a legacy transaction has EIP-155 replay protection, defaults to `true`.
+    pub protected: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListItemJson {
+    pub address: Address,
+    #[serde(default)]
+    pub storage_keys: Vec<B256>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizationJson {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: U256,
+    #[serde(alias = "yParity")]
+    pub v: U256,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl TransactionJson {
+    /// Converts to a signed transaction, signing it if needed.
+    pub fn into_signed(self, default_chain_id: u64) -> Result<SignedTransaction, TransactionError> {
+        let tx_type = self.tx_type.saturating_to::<u8>();
+        let is_protected = self.protected.unwrap_or(true);
+        let chain_id = match self.chain_id {
+            Some(chain_id) => Some(chain_id.saturating_to()),
+            // Legacy `v` has the chain id since EIP-155.
+            None if tx_type == 0 && self.v >= U256::from(35) => {
+                Some(((self.v - U256::from(35)) / U256::from(2)).saturating_to())
+            }
+            None if tx_type == 0 && (!is_protected || self.v != U256::ZERO) => None,
+            None => Some(default_chain_id),
+        };
+        let mut tx = SignedTransaction {
+            tx_type,
+            chain_id,
+            nonce: self.nonce.saturating_to(),
+            gas_price: self
+                .gas_price
+                .or(self.max_fee_per_gas)
+                .unwrap_or_default()
+                .saturating_to(),
+            max_priority_fee_per_gas: self
+                .max_priority_fee_per_gas
+                .unwrap_or_default()
+                .saturating_to(),
+            gas_limit: self.gas.saturating_to(),
+            to: self.to.map_or(TxKind::Create, TxKind::Call),
+            value: self.value,
+            input: self.input,
+            access_list: self
+                .access_list
+                .into_iter()
+                .map(|item| AccessListItem {
+                    address: item.address,
+                    storage_keys: item.storage_keys,
+                })
+                .collect(),
+            max_fee_per_blob_gas: self
+                .max_fee_per_blob_gas
+                .unwrap_or_default()
+                .saturating_to(),
+            blob_versioned_hashes: self.blob_versioned_hashes,
+            authorization_list: self
+                .authorization_list
+                .into_iter()
+                .map(|auth| SignedAuthorization {
+                    chain_id: auth.chain_id,
+                    address: auth.address,
+                    nonce: auth.nonce.saturating_to(),
+                    signature: Signature {
+                        v: auth.v.saturating_to(),
+                        r: auth.r,
+                        s: auth.s,
+                    },
+                })
+                .collect(),
+            signature: Signature {
+                v: self.v.saturating_to(),
+                r: self.r,
+                s: self.s,
+            },
+        };
+        if let Some(secret_key) = self.secret_key {
+            if tx.signature.r.is_zero() && tx.signature.s.is_zero() {
+                sign(&mut tx, &secret_key)?;
+            }
+        }
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::{address, b256};
+
+    #[test]
+    fn sign_and_recover() {
+        let key = b256!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+        let sender = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        for tx_type in 0..=4 {
+            let mut tx = SignedTransaction {
+                tx_type,
+                chain_id: Some(1),
+                gas_limit: 21_000,
+                to: TxKind::Call(sender),
+                access_list: vec![AccessListItem {
+                    address: sender,
+                    storage_keys: vec![B256::ZERO],
+                }],
+                blob_versioned_hashes: vec![B256::ZERO],
+                authorization_list: vec![SignedAuthorization::default()],
+                ..Default::default()
+            };
+            sign(&mut tx, &key).unwrap();
+            assert_eq!(tx.recover_sender().unwrap(), sender);
+
+            let decoded = SignedTransaction::decode(&tx.encoded()).unwrap();
+            let expected = match tx_type {
+                0..=2 => SignedTransaction {
+                    blob_versioned_hashes: vec![],
+                    authorization_list: vec![],
+                    access_list: if tx_type == 0 {
+                        vec![]
+                    } else {
+                        tx.access_list.clone()
+                    },
+                    ..tx.clone()
+                },
+                3 => SignedTransaction {
+                    authorization_list: vec![],
+                    ..tx.clone()
+                },
+                _ => SignedTransaction {
+                    blob_versioned_hashes: vec![],
+                    ..tx.clone()
+                },
+            };
+            assert_eq!(decoded, expected);
+        }
+    }
+}
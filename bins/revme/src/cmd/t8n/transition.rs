@@ -0,0 +1,399 @@
+use super::{
+    transaction::TransactionError,
+    types::{Alloc, AllocAccount, Env, ExecutionResult, Receipt, ReceiptLog, RejectedTransaction},
+    Error,
+};
+use crate::cmd::{
+    blockchaintest::block::{
+        block_requests, block_reward, excess_blob_gas, next_base_fee, requests_hash, rewards,
+        system_call, withdrawal_balances, withdrawals_root, BEACON_ROOTS_ADDRESS,
+        HISTORY_STORAGE_ADDRESS,
+    },
+    statetest::merkle_trie::{log_rlp_hash, ordered_trie_root, state_merkle_trie_root},
+};
+use builder::{max_blob_gas, ReceiptOutcome};
+use database::{CacheState, State};
+use inspector::{exec::InspectCommitEvm, inspectors::TracerEip3155};
+use revm::{
+    bytecode::Bytecode,
+    context::{block::BlockEnv, cfg::CfgEnv},
+    primitives::{alloy_primitives::Bloom, keccak256, Bytes, TxKind, B256, U256},
+    signed_tx::SignedTransaction,
+    specification::{eip4844::GAS_PER_BLOB, hardfork::SpecId},
+    Context, ExecuteCommitEvm,
+};
+use statetest_types::SpecName;
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+/// Options of the state transition.
+#[derive(Clone, Debug)]
+pub struct TransitionConfig {
+    pub network: SpecName,
+    pub chain_id: u64,
+    /// Block reward in wei, `None` for the reward of the fork and negative to disable it.
+    pub reward: Option<i64>,
+    /// Directory of the EIP-3155 traces, `None` to disable tracing.
+    pub trace_dir: Option<PathBuf>,
+    pub trace_memory: bool,
+}
+
+/// Outcome of [`transition`].
+#[derive(Debug)]
+pub struct TransitionOutput {
+    pub alloc: Alloc,
+    pub result: ExecutionResult,
+    /// Transactions included in the block.
+    pub included: Vec<SignedTransaction>,
+}
+
+/// Applies the transactions on top of `alloc` as a block described by `env`.
+///
+/// Invalid transactions are reported as rejected and don't change the state.
+pub fn transition(
+    alloc: Alloc,
+    env: &Env,
+    txs: Vec<Result<SignedTransaction, TransactionError>>,
+    config: &TransitionConfig,
+) -> Result<TransitionOutput, Error> {
+    let number = env.current_number.saturating_to();
+    let spec = config
+        .network
+        .spec_at(number, env.current_timestamp.saturating_to());
+
+    let mut cfg = CfgEnv::default();
+    cfg.chain_id = config.chain_id;
+    cfg.spec = spec;
+    let block_env = block_env(env, spec)?;
+
+    let mut cache = cache_state(alloc);
+    cache.set_state_clear_flag(spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+    let block_hashes = env.block_hashes();
+    let mut state = State::builder()
+        .with_cached_prestate(cache)
+        .with_block_hashes(block_hashes.clone())
+        .build();
+
+    // Pre-block system calls.
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        let root = env
+            .parent_beacon_block_root
+            .ok_or(Error::MissingEnv("parentBeaconBlockRoot"))?;
+        system_call(
+            &mut state,
+            &block_env,
+            &cfg,
+            BEACON_ROOTS_ADDRESS,
+            root.into(),
+        )?;
+    }
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        let parent_hash = number
+            .checked_sub(1)
+            .and_then(|parent| block_hashes.get(&parent))
+            .copied()
+            .unwrap_or_default();
+        system_call(
+            &mut state,
+            &block_env,
+            &cfg,
+            HISTORY_STORAGE_ADDRESS,
+            parent_hash.into(),
+        )?;
+    }
+
+    // Transactions.
+    let mut included = Vec::new();
+    let mut receipts = Vec::new();
+    let mut receipts_json = Vec::new();
+    let mut rejected = Vec::new();
+    let mut gas_used = 0u64;
+    let mut blob_gas_used = 0u64;
+    let mut log_index = 0;
+    let max_blob_gas = max_blob_gas(spec);
+    for (index, tx) in txs.into_iter().enumerate() {
+        let mut reject = |error: String| rejected.push(RejectedTransaction { index, error });
+        let (tx, caller) = match tx.and_then(|tx| Ok((tx.recover_sender()?, tx))) {
+            Ok((caller, tx)) => (tx, caller),
+            Err(error) => {
+                reject(error.to_string());
+                continue;
+            }
+        };
+        if gas_used.saturating_add(tx.gas_limit) > block_env.gas_limit {
+            reject(format!(
+                "gas limit reached: have {}, want {}",
+                block_env.gas_limit - gas_used,
+                tx.gas_limit
+            ));
+            continue;
+        }
+        let tx_blob_gas = tx.blob_versioned_hashes.len() as u64 * GAS_PER_BLOB;
+        if blob_gas_used + tx_blob_gas > max_blob_gas {
+            reject(format!(
+                "blob gas limit reached: have {}, want {tx_blob_gas}",
+                max_blob_gas - blob_gas_used
+            ));
+            continue;
+        }
+
+        let tx_env = tx.to_tx_env(caller);
+        let hash = tx.hash();
+        let ctx = Context::builder()
+            .with_block(&block_env)
+            .with_tx(&tx_env)
+            .with_cfg(&cfg)
+            .with_db(&mut state);
+        let result = match &config.trace_dir {
+            Some(dir) => {
+                let path = dir.join(format!("trace-{index}-{hash}.jsonl"));
+                let file = File::create(&path).map_err(|source| Error::Io { path, source })?;
+                let mut tracer = TracerEip3155::new(Box::new(BufWriter::new(file)));
+                if config.trace_memory {
+                    tracer = tracer.with_memory();
+                }
+                let mut ctx = ctx;
+                ctx.inspect_commit_previous(tracer)
+            }
+            None => {
+                let mut ctx = ctx;
+                ctx.exec_commit_previous()
+            }
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                reject(error.to_string());
+                continue;
+            }
+        };
+        gas_used += result.gas_used();
+        blob_gas_used += tx_blob_gas;
+
+        // Receipts before Byzantium have the intermediate state root instead of the status.
+        let outcome = if spec.is_enabled_in(SpecId::BYZANTIUM) {
+            ReceiptOutcome::Status(result.is_success())
+        } else {
+            ReceiptOutcome::StateRoot(state_merkle_trie_root(state.cache.trie_account()))
+        };
+        let tx_gas_used = result.gas_used();
+        let receipt = builder::Receipt::new(tx.tx_type, outcome, gas_used, result.into_logs());
+
+        let tx_index = included.len();
+        let logs = receipt
+            .logs
+            .iter()
+            .map(|log| {
+                log_index += 1;
+                ReceiptLog::new(log, number, hash, tx_index, log_index - 1)
+            })
+            .collect::<Vec<_>>();
+        let effective_gas_price = if tx.tx_type >= 2 {
+            tx.gas_price
+                .min(block_env.basefee as u128 + tx.max_priority_fee_per_gas)
+        } else {
+            tx.gas_price
+        };
+        receipts_json.push(Receipt {
+            tx_type: U256::from(tx.tx_type),
+            root: match receipt.outcome {
+                ReceiptOutcome::StateRoot(root) => root.into(),
+                ReceiptOutcome::Status(_) => Bytes::new(),
+            },
+            status: U256::from(!matches!(receipt.outcome, ReceiptOutcome::Status(false))),
+            cumulative_gas_used: U256::from(gas_used),
+            logs_bloom: receipt.bloom,
+            logs: (!logs.is_empty()).then_some(logs),
+            transaction_hash: hash,
+            contract_address: match tx.to {
+                TxKind::Create => caller.create(tx.nonce),
+                TxKind::Call(_) => Default::default(),
+            },
+            gas_used: U256::from(tx_gas_used),
+            effective_gas_price: U256::from(effective_gas_price),
+            block_hash: B256::ZERO,
+            transaction_index: U256::from(tx_index),
+        });
+        receipts.push(receipt);
+        included.push(tx);
+    }
+
+    // Block and ommer rewards, withdrawals after Shanghai.
+    let block_reward = match config.reward {
+        None => Some(block_reward(spec)),
+        Some(reward) => u128::try_from(reward).ok(),
+    };
+    if let Some(block_reward) = block_reward {
+        let ommers = env
+            .ommers
+            .iter()
+            .map(|ommer| (ommer.address, ommer.delta.saturating_to()));
+        state
+            .increment_balances(rewards(block_env.beneficiary, block_reward, ommers))
+            .expect("EmptyDB is infallible");
+    }
+    let withdrawals_root = if spec.is_enabled_in(SpecId::SHANGHAI) {
+        let withdrawals = env
+            .withdrawals
+            .as_deref()
+            .ok_or(Error::MissingEnv("withdrawals"))?;
+        state
+            .increment_balances(withdrawal_balances(withdrawals))
+            .expect("EmptyDB is infallible");
+        Some(withdrawals_root(withdrawals))
+    } else {
+        None
+    };
+
+    // Post-block requests.
+    let requests = if spec.is_enabled_in(SpecId::PRAGUE) {
+        Some(block_requests(&mut state, &block_env, &cfg, &receipts)?)
+    } else {
+        None
+    };
+
+    let logs = receipts
+        .iter()
+        .flat_map(|receipt| receipt.logs.iter().cloned())
+        .collect::<Vec<_>>();
+    let is_cancun = spec.is_enabled_in(SpecId::CANCUN);
+    let result = ExecutionResult {
+        state_root: state_merkle_trie_root(state.cache.trie_account()),
+        tx_root: ordered_trie_root(included.iter().map(SignedTransaction::encoded)),
+        receipts_root: ordered_trie_root(receipts.iter().map(builder::Receipt::encoded)),
+        logs_hash: log_rlp_hash(&logs),
+        logs_bloom: receipts
+            .iter()
+            .fold(Bloom::ZERO, |bloom, receipt| bloom | receipt.bloom),
+        receipts: receipts_json,
+        rejected,
+        current_difficulty: (!spec.is_enabled_in(SpecId::MERGE)).then_some(block_env.difficulty),
+        gas_used: U256::from(gas_used),
+        current_base_fee: spec
+            .is_enabled_in(SpecId::LONDON)
+            .then_some(U256::from(block_env.basefee)),
+        withdrawals_root,
+        current_excess_blob_gas: block_env
+            .blob_excess_gas_and_price
+            .filter(|_| is_cancun)
+            .map(|blob| U256::from(blob.excess_blob_gas)),
+        blob_gas_used: is_cancun.then_some(U256::from(blob_gas_used)),
+        requests_hash: requests.as_deref().map(requests_hash),
+        requests: requests.map(|requests| {
+            requests
+                .into_iter()
+                .filter(|(_, data)| !data.is_empty())
+                .map(|(request_type, data)| [&[request_type], &data[..]].concat().into())
+                .collect()
+        }),
+    };
+    Ok(TransitionOutput {
+        alloc: alloc_from_cache(&state.cache),
+        result,
+        included,
+    })
+}
+
+/// Builds the block environment, deriving missing fields from the parent fields.
+fn block_env(env: &Env, spec: SpecId) -> Result<BlockEnv, Error> {
+    let is_merge = spec.is_enabled_in(SpecId::MERGE);
+    let basefee = match env.current_base_fee {
+        Some(base_fee) => base_fee.saturating_to(),
+        None if spec.is_enabled_in(SpecId::LONDON) => {
+            match (
+                env.parent_base_fee,
+                env.parent_gas_used,
+                env.parent_gas_limit,
+            ) {
+                (Some(base_fee), Some(gas_used), Some(gas_limit)) => next_base_fee(
+                    base_fee.saturating_to(),
+                    gas_used.saturating_to(),
+                    gas_limit.saturating_to(),
+                ),
+                _ => return Err(Error::MissingEnv("currentBaseFee")),
+            }
+        }
+        None => 0,
+    };
+    let mut block = BlockEnv {
+        number: env.current_number.saturating_to(),
+        beneficiary: env.current_coinbase,
+        timestamp: env.current_timestamp.saturating_to(),
+        gas_limit: env.current_gas_limit.saturating_to(),
+        basefee,
+        difficulty: if is_merge {
+            U256::ZERO
+        } else {
+            env.current_difficulty
+                .ok_or(Error::MissingEnv("currentDifficulty"))?
+        },
+        // After the Merge prevrandao replaces the difficulty.
+        prevrandao: if is_merge {
+            Some(
+                env.current_random
+                    .ok_or(Error::MissingEnv("currentRandom"))?,
+            )
+        } else {
+            None
+        },
+        blob_excess_gas_and_price: None,
+    };
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        let excess = match (
+            env.current_excess_blob_gas,
+            env.parent_excess_blob_gas,
+            env.parent_blob_gas_used,
+        ) {
+            (Some(excess), _, _) => excess.saturating_to(),
+            (None, Some(excess), Some(used)) => {
+                excess_blob_gas(excess.saturating_to(), used.saturating_to(), spec)
+            }
+            _ => 0,
+        };
+        block.set_blob_excess_gas_and_price(excess, spec.is_enabled_in(SpecId::PRAGUE));
+    }
+    Ok(block)
+}
+
+fn cache_state(alloc: Alloc) -> CacheState {
+    let mut cache = CacheState::new(false);
+    for (address, account) in alloc {
+        let code_hash = keccak256(&account.code);
+        let bytecode = Bytecode::new_raw_checked(account.code.clone())
+            .unwrap_or(Bytecode::new_legacy(account.code));
+        let info = revm::state::AccountInfo {
+            balance: account.balance,
+            code_hash,
+            code: Some(bytecode),
+            nonce: account.nonce.saturating_to(),
+        };
+        cache.insert_account_with_storage(address, info, account.storage.into_iter().collect());
+    }
+    cache
+}
+
+/// Returns the accounts of the state, zero storage slots are skipped.
+fn alloc_from_cache(cache: &CacheState) -> Alloc {
+    cache
+        .trie_account()
+        .into_iter()
+        .map(|(address, account)| {
+            let info = &account.info;
+            let account = AllocAccount {
+                balance: info.balance,
+                code: info
+                    .code
+                    .as_ref()
+                    .map(Bytecode::original_bytes)
+                    .unwrap_or_default(),
+                nonce: U256::from(info.nonce),
+                storage: account
+                    .storage
+                    .iter()
+                    .filter(|(_, value)| !value.is_zero())
+                    .map(|(key, value)| (*key, *value))
+                    .collect(),
+            };
+            (address, account)
+        })
+        .collect()
+}
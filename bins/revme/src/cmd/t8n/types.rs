@@ -0,0 +1,190 @@
+use revm::primitives::{alloy_primitives::Bloom, Address, Bytes, Log, B256, U256};
+use serde::{Deserialize, Serialize};
+use statetest_types::Withdrawal;
+use std::collections::BTreeMap;
+
+/// Account of `alloc.json`, missing fields are zero.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocAccount {
+    #[serde(default)]
+    pub balance: U256,
+    #[serde(default, skip_serializing_if = "<[u8]>::is_empty")]
+    pub code: Bytes,
+    #[serde(default, skip_serializing_if = "U256::is_zero")]
+    pub nonce: U256,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// State of all accounts, the format of `alloc.json`.
+pub type Alloc = BTreeMap<Address, AllocAccount>;
+
+/// Block environment of `env.json`.
+///
+/// Fields of the current block that are not given are derived from the parent fields.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Env {
+    pub current_coinbase: Address,
+    pub current_difficulty: Option<U256>,
+    pub current_random: Option<B256>,
+    pub current_gas_limit: U256,
+    pub current_number: U256,
+    pub current_timestamp: U256,
+    pub current_base_fee: Option<U256>,
+    pub current_excess_blob_gas: Option<U256>,
+    pub parent_base_fee: Option<U256>,
+    pub parent_gas_used: Option<U256>,
+    pub parent_gas_limit: Option<U256>,
+    pub parent_excess_blob_gas: Option<U256>,
+    pub parent_blob_gas_used: Option<U256>,
+    pub parent_beacon_block_root: Option<B256>,
+    /// Hashes of previous blocks by number, numbers are decimal or hex strings.
+    #[serde(default)]
+    pub block_hashes: BTreeMap<String, B256>,
+    #[serde(default)]
+    pub ommers: Vec<Ommer>,
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+impl Env {
+    /// Returns the block hashes by number, entries with an invalid number are skipped.
+    pub fn block_hashes(&self) -> BTreeMap<u64, B256> {
+        self.block_hashes
+            .iter()
+            .filter_map(|(number, hash)| {
+                let number = match number.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => number.parse(),
+                };
+                Some((number.ok()?, *hash))
+            })
+            .collect()
+    }
+}
+
+/// Ommer of the block, `delta` is the distance of its number to the block number.
+#[derive(Debug, Deserialize)]
+pub struct Ommer {
+    pub delta: U256,
+    pub address: Address,
+}
+
+/// Outcome of the state transition, the format of `result.json`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionResult {
+    pub state_root: B256,
+    pub tx_root: B256,
+    pub receipts_root: B256,
+    pub logs_hash: B256,
+    pub logs_bloom: Bloom,
+    pub receipts: Vec<Receipt>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rejected: Vec<RejectedTransaction>,
+    pub current_difficulty: Option<U256>,
+    pub gas_used: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_base_fee: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub withdrawals_root: Option<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_excess_blob_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_hash: Option<B256>,
+    /// Requests prefixed by their type, empty requests are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests: Option<Vec<Bytes>>,
+}
+
+/// Receipt of an included transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    #[serde(rename = "type")]
+    pub tx_type: U256,
+    /// Intermediate state root before Byzantium, empty after.
+    pub root: Bytes,
+    pub status: U256,
+    pub cumulative_gas_used: U256,
+    pub logs_bloom: Bloom,
+    pub logs: Option<Vec<ReceiptLog>>,
+    pub transaction_hash: B256,
+    pub contract_address: Address,
+    pub gas_used: U256,
+    pub effective_gas_price: U256,
+    pub block_hash: B256,
+    pub transaction_index: U256,
+}
+
+/// Log of a receipt.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_number: U256,
+    pub transaction_hash: B256,
+    pub transaction_index: U256,
+    pub block_hash: B256,
+    pub log_index: U256,
+    pub removed: bool,
+}
+
+impl ReceiptLog {
+    pub fn new(
+        log: &Log,
+        block_number: u64,
+        tx_hash: B256,
+        tx_index: usize,
+        log_index: usize,
+    ) -> Self {
+        Self {
+            address: log.address,
+            topics: log.topics().to_vec(),
+            data: log.data.data.clone(),
+            block_number: U256::from(block_number),
+            transaction_hash: tx_hash,
+            transaction_index: U256::from(tx_index),
+            block_hash: B256::ZERO,
+            log_index: U256::from(log_index),
+            removed: false,
+        }
+    }
+}
+
+/// Transaction that could not be included, with the reason.
+#[derive(Debug, Serialize)]
+pub struct RejectedTransaction {
+    pub index: usize,
+    pub error: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_block_hashes() {
+        let env: Env = serde_json::from_str(
+            r#"{
+                "currentCoinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                "currentGasLimit": "0x1000000",
+                "currentNumber": "0x2",
+                "currentTimestamp": "0x3e8",
+                "blockHashes": {
+                    "0": "0x1111111111111111111111111111111111111111111111111111111111111111",
+                    "0x1": "0x2222222222222222222222222222222222222222222222222222222222222222"
+                }
+            }"#,
+        )
+        .unwrap();
+        let hashes = env.block_hashes();
+        assert_eq!(hashes.len(), 2);
+        assert_eq!(hashes[&0], B256::repeat_byte(0x11));
+        assert_eq!(hashes[&1], B256::repeat_byte(0x22));
+    }
+}
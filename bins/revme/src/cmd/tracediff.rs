@@ -0,0 +1,275 @@
+mod diff;
+pub mod trace;
+
+use crate::cmd::{
+    evmrunner,
+    statetest::{
+        self, find_all_json_tests, set_spec, set_test_tx, unit_env, unit_prestate, TestErrorKind,
+    },
+};
+use clap::Parser;
+use database::State;
+use diff::{first_divergence, report, Field};
+use inspector::{exec::InspectCommitEvm, inspectors::TracerEip3155};
+use revm::{specification::hardfork::SpecId, Context};
+use statetest_types::{SpecName, TestSuite};
+use std::{
+    cell::RefCell,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    rc::Rc,
+};
+use thiserror::Error;
+use trace::{parse_trace, FieldError, TraceStep};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{name}:{line}: {source}")]
+    Trace {
+        name: String,
+        line: usize,
+        source: FieldError,
+    },
+    #[error("invalid `--run` arguments: {0}")]
+    RunArgs(#[from] clap::Error),
+    #[error("`--run` supports `statetest` and `evm`, got {0:?}")]
+    UnsupportedRun(String),
+    #[error("{path}: {source}")]
+    Statetest {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("`--run` selects {0} tests, the statetest has to select a single test")]
+    TestSelection(usize),
+    #[error(transparent)]
+    Test(#[from] TestErrorKind),
+    #[error(transparent)]
+    Evm(#[from] evmrunner::Errors),
+    #[error("second trace or `--run` is required")]
+    MissingTrace,
+    #[error("traces diverge at step {0}")]
+    Diverged(usize),
+}
+
+/// `tracediff` subcommand
+///
+/// Compares two EIP-3155 traces and reports their first divergence.
+#[derive(Parser, Debug)]
+pub struct Cmd {
+    /// File with the first trace, one JSON step per line
+    ///
+    /// Lines that are not steps are skipped.
+    left: PathBuf,
+    /// File with the second trace
+    right: Option<PathBuf>,
+    /// Produce the second trace by running revme with the arguments, e.g.
+    /// `--run statetest test.json --fork Cancun -d 0`
+    ///
+    /// `statetest` and `evm` are run in-process with tracing enabled, the statetest has to select
+    /// a single test.
+    #[arg(
+        long,
+        num_args = 1..,
+        allow_hyphen_values = true,
+        conflicts_with = "right"
+    )]
+    run: Vec<String>,
+    /// Fields that are not compared
+    ///
+    /// Most clients don't write the return data so it is ignored by default.
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "return-data")]
+    ignore: Vec<Field>,
+    /// Number of steps printed before the divergence
+    #[arg(long, default_value_t = 5)]
+    context: usize,
+}
+
+impl Cmd {
+    /// Runs `tracediff` command.
+    pub fn run(&self) -> Result<(), Error> {
+        let left_name = self.left.display().to_string();
+        let left = parse(&left_name, &read(&self.left)?)?;
+
+        let (right_name, right) = match (&self.right, self.run.is_empty()) {
+            (Some(path), _) => {
+                let name = path.display().to_string();
+                let trace = parse(&name, &read(path)?)?;
+                (name, trace)
+            }
+            (None, false) => {
+                let name = format!("revme {}", self.run.join(" "));
+                let trace = parse(&name, &self.run_revme()?)?;
+                (name, trace)
+            }
+            (None, true) => return Err(Error::MissingTrace),
+        };
+
+        let Some(divergence) = first_divergence(&left, &right, &self.ignore) else {
+            println!("Traces are equal ({} steps)", left.len());
+            return Ok(());
+        };
+        print!(
+            "{}",
+            report(
+                (&left_name, &right_name),
+                &left,
+                &right,
+                &divergence,
+                self.context
+            )
+        );
+        Err(Error::Diverged(divergence.step))
+    }
+
+    /// Runs revme with the `--run` arguments and returns the trace.
+    ///
+    /// Tests may fail, the result of the execution is not checked.
+    fn run_revme(&self) -> Result<String, Error> {
+        let output = SharedBuffer::default();
+        match self.run.first().map(String::as_str) {
+            Some("statetest") => {
+                let cmd = statetest::Cmd::try_parse_from(&self.run)?;
+                trace_statetest(&cmd, Box::new(output.clone()))?;
+            }
+            Some("evm") => {
+                let cmd = evmrunner::Cmd::try_parse_from(&self.run)?;
+                cmd.trace(Box::new(output.clone()))?;
+            }
+            _ => return Err(Error::UnsupportedRun(self.run.join(" "))),
+        }
+        let trace = String::from_utf8_lossy(&output.0.borrow()).into_owned();
+        Ok(trace)
+    }
+}
+
+/// Writer of the trace, read after the tracer is dropped.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Traces the test selected by the statetest arguments, fails unless exactly one is selected.
+fn trace_statetest(cmd: &statetest::Cmd, output: Box<dyn Write>) -> Result<(), Error> {
+    let filter = cmd.filter();
+    let mut selected = None;
+    let mut count = 0;
+    for path in cmd
+        .paths()
+        .iter()
+        .flat_map(|path| find_all_json_tests(path))
+    {
+        let suite: TestSuite = serde_json::from_str(&read(&path)?)
+            .map_err(|source| Error::Statetest { path, source })?;
+        for (name, unit) in suite.0 {
+            if !filter.matches_name(&name) {
+                continue;
+            }
+            // Tests with an invalid transaction type are not run.
+            let tests: Vec<(SpecName, usize)> = unit
+                .post
+                .iter()
+                .filter(|(spec_name, _)| {
+                    **spec_name != SpecName::Constantinople && filter.matches_fork(spec_name)
+                })
+                .flat_map(|(spec_name, tests)| {
+                    tests.iter().enumerate().filter_map(|(index, test)| {
+                        let selected = filter.matches_indexes(&test.indexes)
+                            && unit.transaction.tx_type(test.indexes.data).is_some();
+                        selected.then_some((*spec_name, index))
+                    })
+                })
+                .collect();
+            count += tests.len();
+            if let (None, Some(&test)) = (&selected, tests.first()) {
+                selected = Some((unit, test));
+            }
+        }
+    }
+    let (Some((unit, (spec_name, index))), 1) = (selected, count) else {
+        return Err(Error::TestSelection(count));
+    };
+
+    let test = &unit.post[&spec_name][index];
+    let (mut cfg, mut block, mut tx) = unit_env(&unit)?;
+    set_spec(&unit, spec_name, &mut cfg, &mut block);
+    set_test_tx(&unit, test, &mut tx);
+
+    let mut cache = unit_prestate(&unit);
+    cache.set_state_clear_flag(cfg.spec.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+    let mut state = State::builder()
+        .with_cached_prestate(cache)
+        .with_bundle_update()
+        .build();
+    let mut ctx = Context::builder()
+        .with_block(&block)
+        .with_tx(&tx)
+        .with_cfg(&cfg)
+        .with_db(&mut state);
+    let _ = ctx.inspect_commit_previous(TracerEip3155::new(output).without_summary());
+    Ok(())
+}
+
+fn read(path: &PathBuf) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.clone(),
+        source,
+    })
+}
+
+fn parse(name: &str, trace: &str) -> Result<Vec<TraceStep>, Error> {
+    parse_trace(trace).map_err(|(line, source)| Error::Trace {
+        name: name.to_string(),
+        line,
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_in_process() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/pectra_devnet5/state_tests/homestead/yul/yul_example/yul.json"
+        );
+        let cmd =
+            |run: &[&str]| Cmd::parse_from(["tracediff", "left.jsonl", "--run"].iter().chain(run));
+
+        let trace = cmd(&["statetest", path, "--fork", "Cancun"])
+            .run_revme()
+            .unwrap();
+        let steps = parse("run", &trace).unwrap();
+        assert!(!steps.is_empty());
+        // The same test in every fork is selected.
+        assert!(matches!(
+            cmd(&["statetest", path]).run_revme(),
+            Err(Error::TestSelection(10))
+        ));
+        assert!(matches!(
+            cmd(&["statetest", path, "--fork", "Osaka"]).run_revme(),
+            Err(Error::TestSelection(0))
+        ));
+
+        // PUSH1 0x01 PUSH1 0x02 ADD STOP
+        let trace = cmd(&["evm", "600160020100"]).run_revme().unwrap();
+        assert_eq!(parse("run", &trace).unwrap().len(), 4);
+        assert!(matches!(
+            cmd(&["t8n"]).run_revme(),
+            Err(Error::UnsupportedRun(_))
+        ));
+    }
+}
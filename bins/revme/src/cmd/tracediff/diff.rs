@@ -0,0 +1,237 @@
+use super::trace::TraceStep;
+use clap::ValueEnum;
+use revm::primitives::hex;
+use std::fmt::{self, Write};
+
+/// Field of a step that is compared when the steps execute the same instruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Field {
+    Gas,
+    GasCost,
+    Refund,
+    Stack,
+    MemSize,
+    Memory,
+    ReturnData,
+}
+
+/// Reason of a divergence between two traces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// Steps have a different depth, pc or opcode.
+    Execution,
+    /// Field of the steps differs, with a description of the difference.
+    Field(Field, String),
+    /// Left trace ends before the right one.
+    LeftEnded,
+    /// Right trace ends before the left one.
+    RightEnded,
+}
+
+impl fmt::Display for DivergenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Execution => f.write_str("different instruction"),
+            Self::Field(_, description) => f.write_str(description),
+            Self::LeftEnded => f.write_str("left trace ends"),
+            Self::RightEnded => f.write_str("right trace ends"),
+        }
+    }
+}
+
+/// First divergence of two traces, at the index of the step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    pub step: usize,
+    pub kind: DivergenceKind,
+}
+
+/// Returns the first divergence of the traces, `None` if they are equal.
+///
+/// Steps are aligned by their index, the traces diverge at the first step with a different
+/// depth, pc or opcode, or with a difference in a field that is not ignored.
+pub fn first_divergence(
+    left: &[TraceStep],
+    right: &[TraceStep],
+    ignore: &[Field],
+) -> Option<Divergence> {
+    for (step, (l, r)) in left.iter().zip(right).enumerate() {
+        let kind = if (l.depth, l.pc, l.op) != (r.depth, r.pc, r.op) {
+            DivergenceKind::Execution
+        } else if let Some((field, description)) = compare_fields(l, r, ignore) {
+            DivergenceKind::Field(field, description)
+        } else {
+            continue;
+        };
+        return Some(Divergence { step, kind });
+    }
+    let step = left.len().min(right.len());
+    let kind = match left.len().cmp(&right.len()) {
+        std::cmp::Ordering::Less => DivergenceKind::LeftEnded,
+        std::cmp::Ordering::Greater => DivergenceKind::RightEnded,
+        std::cmp::Ordering::Equal => return None,
+    };
+    Some(Divergence { step, kind })
+}
+
+fn compare_fields(l: &TraceStep, r: &TraceStep, ignore: &[Field]) -> Option<(Field, String)> {
+    let compared = |field| !ignore.contains(&field);
+    let numbers = [
+        (Field::Gas, "gas", l.gas, r.gas),
+        (Field::GasCost, "gas cost", l.gas_cost, r.gas_cost),
+        (Field::Refund, "refund", l.refund, r.refund),
+    ];
+    for (field, name, l, r) in numbers {
+        if compared(field) && l != r {
+            return Some((field, format!("{name} {l} vs {r}")));
+        }
+    }
+
+    if compared(Field::Stack) {
+        if l.stack.len() != r.stack.len() {
+            return Some((
+                Field::Stack,
+                format!("stack size {} vs {}", l.stack.len(), r.stack.len()),
+            ));
+        }
+        // Stack is written from the bottom, items are numbered from the top.
+        let items = l.stack.iter().rev().zip(r.stack.iter().rev());
+        if let Some((index, (l, r))) = items.enumerate().find(|(_, (l, r))| l != r) {
+            return Some((
+                Field::Stack,
+                format!("stack item {index} from the top {l:#x} vs {r:#x}"),
+            ));
+        }
+    }
+    if compared(Field::MemSize) && l.mem_size != r.mem_size {
+        return Some((
+            Field::MemSize,
+            format!("memory size {} vs {}", l.mem_size, r.mem_size),
+        ));
+    }
+    let optional = [
+        (Field::Memory, "memory", &l.memory, &r.memory),
+        (
+            Field::ReturnData,
+            "return data",
+            &l.return_data,
+            &r.return_data,
+        ),
+    ];
+    for (field, name, l, r) in optional {
+        // Not every client writes memory and return data.
+        let (Some(l), Some(r)) = (l, r) else {
+            continue;
+        };
+        if !compared(field) || l == r {
+            continue;
+        }
+        let offset = l
+            .iter()
+            .zip(r.iter())
+            .position(|(l, r)| l != r)
+            .unwrap_or(l.len().min(r.len()));
+        return Some((
+            field,
+            format!(
+                "{name} differs at offset {offset} (size {} vs {})",
+                l.len(),
+                r.len()
+            ),
+        ));
+    }
+    None
+}
+
+/// Returns the report of the divergence with `context` steps before it.
+pub fn report(
+    names: (&str, &str),
+    left: &[TraceStep],
+    right: &[TraceStep],
+    divergence: &Divergence,
+    context: usize,
+) -> String {
+    let step = divergence.step;
+    let mut out = String::new();
+    writeln!(out, "Traces diverge at step {step}: {}", divergence.kind).unwrap();
+
+    let start = step.saturating_sub(context);
+    if start < step {
+        writeln!(out, "\nPrevious steps:").unwrap();
+        for (index, step) in left.iter().enumerate().take(step).skip(start) {
+            writeln!(out, "  #{index} {step}").unwrap();
+        }
+    }
+
+    for (name, trace) in [names.0, names.1].into_iter().zip([left, right]) {
+        writeln!(out, "\n{name}:").unwrap();
+        let Some(step) = trace.get(step) else {
+            writeln!(out, "  trace ends after {} steps", trace.len()).unwrap();
+            continue;
+        };
+        writeln!(out, "  #{} {step}", divergence.step).unwrap();
+        if let Some(error) = &step.error {
+            writeln!(out, "  error: {error}").unwrap();
+        }
+        writeln!(out, "  stack (top first):").unwrap();
+        for (index, item) in step.stack.iter().rev().enumerate() {
+            writeln!(out, "    {index}: {item:#x}").unwrap();
+        }
+        writeln!(out, "  memory size: {}", step.mem_size).unwrap();
+        if let Some(memory) = &step.memory {
+            for (index, word) in memory.chunks(32).enumerate() {
+                writeln!(out, "    {:#06x}: {}", index * 32, hex::encode(word)).unwrap();
+            }
+        }
+        if let Some(return_data) = step.return_data.as_ref().filter(|d| !d.is_empty()) {
+            writeln!(out, "  return data: {return_data}").unwrap();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::U256;
+
+    fn step(pc: u64, gas: u64, stack: &[u64]) -> TraceStep {
+        TraceStep {
+            pc,
+            op: 0x01,
+            gas,
+            depth: 1,
+            stack: stack.iter().copied().map(U256::from).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn divergence() {
+        let left = [step(0, 10, &[1, 2]), step(1, 7, &[3]), step(2, 4, &[])];
+        let mut right = left.to_vec();
+        assert_eq!(first_divergence(&left, &right, &[]), None);
+
+        right[1].stack = vec![U256::from(4)];
+        let divergence = first_divergence(&left, &right, &[]).unwrap();
+        assert_eq!(divergence.step, 1);
+        assert_eq!(
+            divergence.kind.to_string(),
+            "stack item 0 from the top 0x3 vs 0x4"
+        );
+        assert!(first_divergence(&left, &right, &[Field::Stack]).is_none());
+
+        right[1].pc = 5;
+        let divergence = first_divergence(&left, &right, &[]).unwrap();
+        assert_eq!(divergence.kind, DivergenceKind::Execution);
+        let report = report(("a", "b"), &left, &right, &divergence, 3);
+        assert!(report.contains("Previous steps:\n  #0 depth 1 pc 0 ADD gas 10"));
+
+        right.truncate(1);
+        let divergence = first_divergence(&left, &right, &[]).unwrap();
+        assert_eq!(
+            (divergence.step, divergence.kind),
+            (1, DivergenceKind::RightEnded)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use revm::{
+    bytecode::opcode::OpCode,
+    primitives::{hex, Bytes, U256},
+};
+use serde_json::{Map, Value};
+use std::{fmt, str::FromStr};
+
+/// Step of an EIP-3155 trace.
+///
+/// Clients write numbers either as JSON numbers, hex or decimal strings, all of them are accepted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraceStep {
+    pub pc: u64,
+    pub op: u8,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub stack: Vec<U256>,
+    pub depth: u64,
+    pub refund: u64,
+    pub mem_size: u64,
+    pub return_data: Option<Bytes>,
+    pub memory: Option<Bytes>,
+    pub error: Option<String>,
+}
+
+/// Invalid field of a trace step.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid field `{field}`: {value}")]
+pub struct FieldError {
+    field: &'static str,
+    value: Value,
+}
+
+impl TraceStep {
+    /// Parses a line of the trace.
+    ///
+    /// Returns `None` for lines that are not steps, such as the summary or other output.
+    pub fn parse_line(line: &str) -> Option<Result<Self, FieldError>> {
+        let Ok(Value::Object(object)) = serde_json::from_str(line.trim()) else {
+            return None;
+        };
+        if !object.contains_key("pc") || !object.contains_key("op") {
+            return None;
+        }
+        Some(Self::from_object(&object))
+    }
+
+    fn from_object(object: &Map<String, Value>) -> Result<Self, FieldError> {
+        let number = |field: &'static str, required: bool| -> Result<u64, FieldError> {
+            match object.get(field) {
+                None | Some(Value::Null) if !required => Ok(0),
+                value => {
+                    let value = value.cloned().unwrap_or_default();
+                    parse_number(&value).ok_or(FieldError { field, value })
+                }
+            }
+        };
+        let bytes = |field: &'static str| -> Result<Option<Bytes>, FieldError> {
+            match object.get(field) {
+                None | Some(Value::Null) => Ok(None),
+                Some(value) => parse_bytes(value).map(Some).ok_or(FieldError {
+                    field,
+                    value: value.clone(),
+                }),
+            }
+        };
+
+        let stack = match object.get("stack") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(value) => value
+                .as_array()
+                .and_then(|items| {
+                    items
+                        .iter()
+                        .map(|item| U256::from_str(item.as_str()?).ok())
+                        .collect()
+                })
+                .ok_or(FieldError {
+                    field: "stack",
+                    value: value.clone(),
+                })?,
+        };
+        let op = number("op", true)?;
+        Ok(Self {
+            pc: number("pc", true)?,
+            op: u8::try_from(op).map_err(|_| FieldError {
+                field: "op",
+                value: op.into(),
+            })?,
+            gas: number("gas", false)?,
+            gas_cost: number("gasCost", false)?,
+            stack,
+            depth: number("depth", false)?,
+            refund: number("refund", false)?,
+            mem_size: number("memSize", false)?,
+            return_data: bytes("returnData")?,
+            memory: bytes("memory")?,
+            error: object
+                .get("error")
+                .and_then(Value::as_str)
+                .filter(|error| !error.is_empty())
+                .map(ToString::to_string),
+        })
+    }
+
+    /// Name of the opcode.
+    pub fn op_name(&self) -> &'static str {
+        OpCode::name_by_op(self.op)
+    }
+}
+
+/// Writes the step on one line, without the stack and memory.
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "depth {} pc {} {} gas {} cost {} refund {}",
+            self.depth,
+            self.pc,
+            self.op_name(),
+            self.gas,
+            self.gas_cost,
+            self.refund
+        )
+    }
+}
+
+/// Parses the steps of an EIP-3155 trace, lines that are not steps are skipped.
+///
+/// The error has the line number of the invalid step.
+pub fn parse_trace(trace: &str) -> Result<Vec<TraceStep>, (usize, FieldError)> {
+    trace
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| Some(TraceStep::parse_line(line)?.map_err(|e| (index + 1, e))))
+        .collect()
+}
+
+fn parse_number(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => s.parse().ok(),
+        },
+        _ => None,
+    }
+}
+
+/// Parses a hex string, or a list of hex words as older geth versions write the memory.
+fn parse_bytes(value: &Value) -> Option<Bytes> {
+    match value {
+        Value::String(s) => hex::decode(s).ok().map(Into::into),
+        Value::Array(words) => {
+            let mut bytes = Vec::new();
+            for word in words {
+                bytes.extend(hex::decode(word.as_str()?).ok()?);
+            }
+            Some(bytes.into())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_steps() {
+        let trace = r#"
+{"pc":0,"op":96,"gas":"0x5f5e100","gasCost":"0x3","stack":[],"depth":1,"returnData":"0x","refund":"0x0","memSize":"0","opName":"PUSH1"}
+not a step
+{"pc":2,"op":1,"gas":99999997,"gasCost":3,"memory":["0x00000000000000000000000000000000000000000000000000000000000000ff"],"memSize":32,"stack":["0x1","0x2"],"depth":1,"refund":0}
+{"stateRoot":"0x00","output":"","gasUsed":"0x6","pass":true}
+"#;
+        let steps = parse_trace(trace).unwrap();
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].op_name(), "PUSH1");
+        assert_eq!(steps[0].gas, 100_000_000);
+        assert_eq!(steps[1].gas, 99_999_997);
+        assert_eq!(steps[1].stack, vec![U256::from(1), U256::from(2)]);
+        assert_eq!(steps[1].memory.as_ref().unwrap()[31], 0xff);
+        assert_eq!(steps[1].mem_size, 32);
+
+        let (line, _) = parse_trace(r#"{"pc":0,"op":"x"}"#).unwrap_err();
+        assert_eq!(line, 1);
+    }
+}
@@ -0,0 +1,267 @@
+use revm::{
+    context::{Cfg, CfgEnv},
+    context_interface::result::InvalidTransaction,
+    handler::validation::{validate_eip4844_tx, validate_initial_tx_gas, validate_priority_fee_tx},
+    primitives::{Address, TxKind, B256, U256},
+    signed_tx::{SignedTransaction, SignedTxError},
+    specification::{constants::MAX_INITCODE_SIZE, gas_schedule::GasSchedule, hardfork::SpecId},
+};
+use serde_json::json;
+use statetest_types::{SpecName, TransactionTestResult, TransactionTestSuite};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error("Path: {path}\nName: {name}\nError: {kind}")]
+pub struct TestError {
+    pub name: String,
+    pub path: String,
+    pub kind: TestErrorKind,
+}
+
+#[derive(Debug, Error)]
+pub enum TestErrorKind {
+    #[error("{fork:?}: transaction is valid, expected exception {exception}")]
+    UnexpectedValid { fork: SpecName, exception: String },
+    #[error("{fork:?}: transaction is invalid: {error}")]
+    UnexpectedInvalid { fork: SpecName, error: TxError },
+    #[error("{fork:?}: {field} mismatch: got {got}, expected {expected}")]
+    Mismatch {
+        fork: SpecName,
+        field: &'static str,
+        got: String,
+        expected: String,
+    },
+    #[error(transparent)]
+    SerdeDeserialize(#[from] serde_json::Error),
+}
+
+/// Reason for a transaction being invalid in a fork.
+#[derive(Debug, Error)]
+pub enum TxError {
+    #[error(transparent)]
+    Decode(#[from] SignedTxError),
+    #[error(transparent)]
+    Invalid(#[from] InvalidTransaction),
+    #[error("set code transaction can't create a contract")]
+    SetCodeCreate,
+}
+
+/// Valid transaction with its sender and intrinsic gas.
+#[derive(Debug)]
+pub struct ValidTransaction {
+    pub hash: B256,
+    pub sender: Address,
+    pub intrinsic_gas: u64,
+}
+
+/// Returns `true` if the fork is not supported.
+fn skip_fork(fork: &SpecName) -> bool {
+    matches!(
+        fork,
+        // Constantinople was immediately replaced by Petersburg.
+        SpecName::Constantinople | SpecName::ByzantiumToConstantinopleAt5 | SpecName::Unknown
+    )
+}
+
+/// Decodes the transaction and checks that it can be included in a block of the `spec` fork.
+///
+/// Only the checks that don't depend on the state or the block are done.
+pub fn validate_transaction(bytes: &[u8], spec: SpecId) -> Result<ValidTransaction, TxError> {
+    let tx = SignedTransaction::decode(bytes)?;
+    let enabled_in = match tx.tx_type {
+        1 => Some((SpecId::BERLIN, InvalidTransaction::Eip2930NotSupported)),
+        2 => Some((SpecId::LONDON, InvalidTransaction::Eip1559NotSupported)),
+        3 => Some((SpecId::CANCUN, InvalidTransaction::Eip4844NotSupported)),
+        4 => Some((SpecId::PRAGUE, InvalidTransaction::Eip7702NotSupported)),
+        _ => None,
+    };
+    if let Some((fork, error)) = enabled_in {
+        if !spec.is_enabled_in(fork) {
+            return Err(error.into());
+        }
+    }
+    // EIP-155: Simple replay attack protection
+    if tx.tx_type == 0 && tx.chain_id.is_some() && !spec.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
+        return Err(InvalidTransaction::InvalidChainId.into());
+    }
+    // EIP-2: `s` must be in the lower half of the curve order since Homestead.
+    let sender = if spec.is_enabled_in(SpecId::HOMESTEAD) {
+        tx.recover_sender()?
+    } else {
+        tx.recover_sender_unchecked()?
+    };
+    // EIP-2681: Limit account nonce to 2^64-1
+    if tx.nonce == u64::MAX {
+        return Err(InvalidTransaction::NonceOverflowInTransaction.into());
+    }
+
+    if tx.tx_type >= 2 {
+        validate_priority_fee_tx(tx.gas_price, tx.max_priority_fee_per_gas, None)?;
+    }
+    let is_create = tx.to == TxKind::Create;
+    match tx.tx_type {
+        3 => {
+            if is_create {
+                return Err(InvalidTransaction::BlobCreateTransaction.into());
+            }
+            // The blob gas price depends on the block and is not checked.
+            let max_blobs = CfgEnv::default().blob_max_count(spec);
+            validate_eip4844_tx(
+                &tx.blob_versioned_hashes,
+                tx.max_fee_per_blob_gas,
+                0,
+                max_blobs,
+            )?;
+        }
+        4 => {
+            if is_create {
+                return Err(TxError::SetCodeCreate);
+            }
+            if tx.authorization_list.is_empty() {
+                return Err(InvalidTransaction::EmptyAuthorizationList.into());
+            }
+        }
+        _ => {}
+    }
+    // EIP-3860: Limit and meter initcode
+    if is_create && spec.is_enabled_in(SpecId::SHANGHAI) && tx.input.len() > MAX_INITCODE_SIZE {
+        return Err(InvalidTransaction::CreateInitCodeSizeLimit.into());
+    }
+
+    let tx_env = tx.to_tx_env(sender);
+    let gas = validate_initial_tx_gas(&tx_env, spec, GasSchedule::for_spec(spec))?;
+    Ok(ValidTransaction {
+        hash: tx.hash(),
+        sender,
+        intrinsic_gas: gas.initial_gas,
+    })
+}
+
+/// Checks the outcome of the transaction in a fork against the expected one.
+fn check_result(
+    bytes: &[u8],
+    fork: SpecName,
+    expected: &TransactionTestResult,
+) -> Result<(), TestErrorKind> {
+    let result = validate_transaction(bytes, fork.to_spec_id());
+    let valid = match (result, &expected.exception) {
+        (Ok(_), Some(exception)) => {
+            return Err(TestErrorKind::UnexpectedValid {
+                fork,
+                exception: exception.clone(),
+            })
+        }
+        (Err(_), Some(_)) => return Ok(()),
+        (Err(error), None) => return Err(TestErrorKind::UnexpectedInvalid { fork, error }),
+        (Ok(valid), None) => valid,
+    };
+
+    let mismatch = |field, got: String, expected: String| TestErrorKind::Mismatch {
+        fork,
+        field,
+        got,
+        expected,
+    };
+    if let Some(hash) = expected.hash {
+        if valid.hash != hash {
+            return Err(mismatch("hash", valid.hash.to_string(), hash.to_string()));
+        }
+    }
+    if let Some(sender) = expected.sender {
+        if valid.sender != sender {
+            return Err(mismatch(
+                "sender",
+                valid.sender.to_string(),
+                sender.to_string(),
+            ));
+        }
+    }
+    if let Some(intrinsic_gas) = expected.intrinsic_gas {
+        if intrinsic_gas != U256::from(valid.intrinsic_gas) {
+            return Err(mismatch(
+                "intrinsic gas",
+                valid.intrinsic_gas.to_string(),
+                intrinsic_gas.to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn execute_test_suite(path: &Path, print_json_outcome: bool) -> Result<(), TestError> {
+    let s = std::fs::read_to_string(path).unwrap();
+    let path = path.to_string_lossy().into_owned();
+    let suite: TransactionTestSuite = serde_json::from_str(&s).map_err(|e| TestError {
+        name: "Unknown".to_string(),
+        path: path.clone(),
+        kind: e.into(),
+    })?;
+
+    for (name, unit) in suite.0 {
+        for (fork, expected) in &unit.result {
+            if skip_fork(fork) {
+                continue;
+            }
+            let result = check_result(&unit.txbytes, *fork, expected);
+            if print_json_outcome {
+                let json = json!({
+                    "test": name,
+                    "fork": format!("{fork:?}"),
+                    "pass": result.is_ok(),
+                    "errorMsg": result.as_ref().err().map(ToString::to_string).unwrap_or_default(),
+                });
+                eprintln!("{json}");
+            }
+            result.map_err(|kind| TestError {
+                name: name.clone(),
+                path: path.clone(),
+                kind,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+pub fn run(
+    test_files: Vec<PathBuf>,
+    print_outcome: bool,
+    keep_going: bool,
+) -> Result<(), TestError> {
+    let n_files = test_files.len();
+    let mut n_errors = 0;
+    for path in test_files {
+        if let Err(err) = execute_test_suite(&path, print_outcome) {
+            n_errors += 1;
+            if !keep_going {
+                return Err(err);
+            }
+            println!("\n{err}");
+        }
+    }
+
+    if n_errors == 0 {
+        println!("All tests passed!");
+        return Ok(());
+    }
+    println!("Encountered {n_errors} errors out of {n_files} total tests");
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::primitives::hex;
+
+    #[test]
+    fn eip155_fork_validity() {
+        // Example from EIP-155.
+        let raw = hex!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+        assert!(matches!(
+            validate_transaction(&raw, SpecId::TANGERINE),
+            Err(TxError::Invalid(InvalidTransaction::InvalidChainId))
+        ));
+        let valid = validate_transaction(&raw, SpecId::SPURIOUS_DRAGON).unwrap();
+        assert_eq!(valid.intrinsic_gas, 21_000);
+    }
+}
@@ -0,0 +1,401 @@
+//! Block building from candidate transactions.
+use crate::{max_blob_gas, priority_fee, Receipt, ReceiptOutcome};
+use database::{states::bundle_state::BundleRetention, BundleState, State};
+use revm::{
+    context::{block::BlockEnv, cfg::CfgEnv},
+    context_interface::result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+    primitives::{Address, B256, U256},
+    signed_tx::{SignedTransaction, SignedTxError},
+    specification::eip4844::GAS_PER_BLOB,
+    Context, Database, DatabaseCommit, ExecuteEvm,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    fmt,
+};
+
+/// What the builder does with transactions that revert or halt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RevertPolicy {
+    /// Include them, they pay for the gas they used as in any block.
+    #[default]
+    Include,
+    /// Skip them, their state changes and fees are discarded.
+    Skip,
+}
+
+/// Options of [`build_block`].
+#[derive(Clone, Debug)]
+pub struct BuilderConfig {
+    pub cfg: CfgEnv,
+    pub block: BlockEnv,
+    pub revert_policy: RevertPolicy,
+}
+
+/// Why a candidate transaction was not included.
+#[derive(Debug)]
+pub enum SkipReason {
+    /// The sender can't be recovered from the signature.
+    Signature(SignedTxError),
+    /// The transaction doesn't fit in the gas left in the block.
+    GasLimit { available: u64, required: u64 },
+    /// The blobs of the transaction don't fit in the blob gas left in the block.
+    BlobGasLimit { available: u64, required: u64 },
+    /// The transaction is invalid on the state of the block.
+    Invalid(InvalidTransaction),
+    /// The transaction reverted or halted and the policy is [`RevertPolicy::Skip`].
+    Reverted(ExecutionResult<HaltReason>),
+    /// A transaction of the sender with a lower nonce was skipped.
+    NonceGap,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signature(error) => write!(f, "{error}"),
+            Self::GasLimit {
+                available,
+                required,
+            } => write!(f, "gas limit reached: have {available}, want {required}"),
+            Self::BlobGasLimit {
+                available,
+                required,
+            } => write!(
+                f,
+                "blob gas limit reached: have {available}, want {required}"
+            ),
+            Self::Invalid(error) => write!(f, "{error}"),
+            Self::Reverted(ExecutionResult::Halt { reason, .. }) => {
+                write!(f, "execution halted: {reason:?}")
+            }
+            Self::Reverted(_) => write!(f, "execution reverted"),
+            Self::NonceGap => write!(f, "a previous transaction of the sender was skipped"),
+        }
+    }
+}
+
+/// Transaction included in the block.
+#[derive(Debug)]
+pub struct IncludedTransaction {
+    pub tx: SignedTransaction,
+    pub hash: B256,
+    pub sender: Address,
+    pub result: ExecutionResult<HaltReason>,
+    /// Fee paid to the beneficiary, the base fee is burnt.
+    pub fee: U256,
+}
+
+/// Candidate transaction that was not included.
+#[derive(Debug)]
+pub struct SkippedTransaction {
+    pub hash: B256,
+    /// Sender of the transaction, `None` if it can't be recovered.
+    pub sender: Option<Address>,
+    pub reason: SkipReason,
+}
+
+/// Outcome of [`build_block`].
+#[derive(Debug)]
+pub struct BuiltBlock {
+    /// Changes of the block, with the reverts to undo them.
+    pub bundle: BundleState,
+    /// Transactions in the order of the block.
+    pub transactions: Vec<IncludedTransaction>,
+    pub receipts: Vec<Receipt>,
+    pub skipped: Vec<SkippedTransaction>,
+    pub gas_used: u64,
+    pub blob_gas_used: u64,
+    /// Fees paid to the beneficiary by the transactions.
+    pub total_fees: U256,
+}
+
+/// Transactions of a sender, by nonce.
+struct SenderQueue {
+    sender: Address,
+    txs: VecDeque<SignedTransaction>,
+}
+
+/// Builds a block from the candidate transactions on top of the state.
+///
+/// The transaction with the highest priority fee goes first, among the transactions that are next
+/// in nonce order for their sender; ties go to the sender seen first, and transactions that can't
+/// pay the base fee go last. Transactions that don't fit
+/// in the gas or blob gas left, or that are invalid, are skipped with the following transactions
+/// of their sender, except for those with a nonce that is too low. Reverted transactions are
+/// included or skipped according to the [`RevertPolicy`].
+///
+/// Receipts have the status of the transaction, they are not valid before Byzantium. The state
+/// must be built with bundle updates for the bundle to have the changes of the block.
+pub fn build_block<DB: Database>(
+    state: &mut State<DB>,
+    candidates: Vec<SignedTransaction>,
+    config: &BuilderConfig,
+) -> Result<BuiltBlock, EVMError<DB::Error, InvalidTransaction>> {
+    let block = &config.block;
+    let mut skipped = Vec::new();
+
+    // Group the candidates by sender, in nonce order.
+    let mut queues: Vec<SenderQueue> = Vec::new();
+    let mut sender_index = HashMap::new();
+    for tx in candidates {
+        let sender = match tx.recover_sender() {
+            Ok(sender) => sender,
+            Err(error) => {
+                skipped.push(SkippedTransaction {
+                    hash: tx.hash(),
+                    sender: None,
+                    reason: SkipReason::Signature(error),
+                });
+                continue;
+            }
+        };
+        let index = *sender_index.entry(sender).or_insert_with(|| {
+            queues.push(SenderQueue {
+                sender,
+                txs: VecDeque::new(),
+            });
+            queues.len() - 1
+        });
+        queues[index].txs.push_back(tx);
+    }
+    for queue in &mut queues {
+        queue.txs.make_contiguous().sort_by_key(|tx| tx.nonce);
+    }
+
+    let mut heap = BinaryHeap::new();
+    for (index, queue) in queues.iter().enumerate() {
+        if let Some(tx) = queue.txs.front() {
+            heap.push((priority_fee(tx, block.basefee), Reverse(index)));
+        }
+    }
+
+    let mut transactions = Vec::new();
+    let mut receipts = Vec::new();
+    let mut gas_used = 0u64;
+    let mut blob_gas_used = 0u64;
+    let mut total_fees = U256::ZERO;
+    let max_blob_gas = max_blob_gas(config.cfg.spec);
+    while let Some((_, Reverse(index))) = heap.pop() {
+        let queue = &mut queues[index];
+        let sender = queue.sender;
+        let tx = queue
+            .txs
+            .pop_front()
+            .expect("queues in the heap are not empty");
+        let tx_blob_gas = blob_gas(&tx);
+
+        let reason = if gas_used.saturating_add(tx.gas_limit) > block.gas_limit {
+            SkipReason::GasLimit {
+                available: block.gas_limit - gas_used,
+                required: tx.gas_limit,
+            }
+        } else if blob_gas_used + tx_blob_gas > max_blob_gas {
+            SkipReason::BlobGasLimit {
+                available: max_blob_gas - blob_gas_used,
+                required: tx_blob_gas,
+            }
+        } else {
+            let tx_env = tx.to_tx_env(sender);
+            let mut ctx = Context::builder()
+                .with_block(block)
+                .with_tx(&tx_env)
+                .with_cfg(&config.cfg)
+                .with_db(&mut *state);
+            match ctx.exec_previous() {
+                Ok(output)
+                    if output.result.is_success()
+                        || config.revert_policy == RevertPolicy::Include =>
+                {
+                    state.commit(output.state);
+                    let result = output.result;
+                    gas_used += result.gas_used();
+                    blob_gas_used += tx_blob_gas;
+                    let fee = U256::from(result.gas_used())
+                        * U256::from(priority_fee(&tx, block.basefee).unwrap_or_default());
+                    total_fees += fee;
+
+                    let outcome = ReceiptOutcome::Status(result.is_success());
+                    let logs = result.logs().to_vec();
+                    receipts.push(Receipt::new(tx.tx_type, outcome, gas_used, logs));
+                    transactions.push(IncludedTransaction {
+                        hash: tx.hash(),
+                        tx,
+                        sender,
+                        result,
+                        fee,
+                    });
+                    if let Some(next) = queue.txs.front() {
+                        heap.push((priority_fee(next, block.basefee), Reverse(index)));
+                    }
+                    continue;
+                }
+                Ok(output) => SkipReason::Reverted(output.result),
+                Err(EVMError::Transaction(error)) => SkipReason::Invalid(error),
+                Err(error) => return Err(error),
+            }
+        };
+
+        // A transaction with the same nonce was included, the next one may be valid.
+        let nonce_too_low = matches!(
+            reason,
+            SkipReason::Invalid(InvalidTransaction::NonceTooLow { .. })
+        );
+        skipped.push(SkippedTransaction {
+            hash: tx.hash(),
+            sender: Some(sender),
+            reason,
+        });
+        if nonce_too_low {
+            if let Some(next) = queue.txs.front() {
+                heap.push((priority_fee(next, block.basefee), Reverse(index)));
+            }
+        } else {
+            skipped.extend(queue.txs.drain(..).map(|tx| SkippedTransaction {
+                hash: tx.hash(),
+                sender: Some(sender),
+                reason: SkipReason::NonceGap,
+            }));
+        }
+    }
+
+    state.merge_transitions(BundleRetention::Reverts);
+    Ok(BuiltBlock {
+        bundle: state.take_bundle(),
+        transactions,
+        receipts,
+        skipped,
+        gas_used,
+        blob_gas_used,
+        total_fees,
+    })
+}
+
+fn blob_gas(tx: &SignedTransaction) -> u64 {
+    tx.blob_versioned_hashes.len() as u64 * GAS_PER_BLOB
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sign;
+    use database::CacheState;
+    use revm::{
+        bytecode::Bytecode,
+        primitives::{address, b256, TxKind},
+        specification::hardfork::SpecId,
+        state::AccountInfo,
+    };
+
+    const KEY_A: B256 = b256!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+    const KEY_B: B256 = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+    const GWEI: u128 = 1_000_000_000;
+
+    fn tx(key: &B256, nonce: u64, tip: u128, to: Address) -> SignedTransaction {
+        let mut tx = SignedTransaction {
+            tx_type: 2,
+            chain_id: Some(1),
+            nonce,
+            gas_price: 100 * GWEI,
+            max_priority_fee_per_gas: tip * GWEI,
+            gas_limit: 50_000,
+            to: TxKind::Call(to),
+            ..Default::default()
+        };
+        sign(&mut tx, key);
+        tx
+    }
+
+    #[test]
+    fn priority_and_nonce_order() {
+        let recipient = address!("1000000000000000000000000000000000000001");
+        // PUSH1 0 PUSH1 0 REVERT
+        let reverter = address!("2000000000000000000000000000000000000002");
+        let a = tx(&KEY_A, 0, 1, recipient).recover_sender().unwrap();
+        let b = tx(&KEY_B, 0, 1, recipient).recover_sender().unwrap();
+
+        let mut cache = CacheState::new(true);
+        for sender in [a, b] {
+            let info = AccountInfo {
+                balance: U256::from(10u128.pow(18)),
+                ..Default::default()
+            };
+            cache.insert_account(sender, info);
+        }
+        let code = Bytecode::new_legacy([0x60, 0x00, 0x60, 0x00, 0xfd].into());
+        let info = AccountInfo {
+            code_hash: code.hash_slow(),
+            code: Some(code),
+            ..Default::default()
+        };
+        cache.insert_account(reverter, info);
+        let mut state = State::builder()
+            .with_cached_prestate(cache)
+            .with_bundle_update()
+            .build();
+
+        let mut cfg = CfgEnv::default();
+        cfg.spec = SpecId::PRAGUE;
+        let config = BuilderConfig {
+            cfg,
+            block: BlockEnv {
+                basefee: GWEI as u64,
+                gas_limit: 100_000,
+                ..Default::default()
+            },
+            revert_policy: RevertPolicy::Skip,
+        };
+        let candidates = vec![
+            tx(&KEY_A, 1, 5, recipient),
+            tx(&KEY_A, 0, 1, recipient),
+            tx(&KEY_B, 0, 3, recipient),
+            tx(&KEY_B, 1, 10, reverter),
+            tx(&KEY_B, 2, 10, recipient),
+            tx(&KEY_A, 2, 1, recipient),
+        ];
+        let hashes: Vec<B256> = candidates.iter().map(SignedTransaction::hash).collect();
+        let built = build_block(&mut state, candidates, &config).unwrap();
+
+        // B0 pays the highest tip of the first transactions, A1 can only follow A0.
+        let included: Vec<B256> = built.transactions.iter().map(|tx| tx.hash).collect();
+        assert_eq!(included, [hashes[2], hashes[1], hashes[0]]);
+        assert_eq!(built.gas_used, 3 * 21_000);
+        assert_eq!(built.receipts[2].cumulative_gas_used, 3 * 21_000);
+        assert_eq!(built.total_fees, U256::from(21_000 * 9 * GWEI));
+
+        let skipped: Vec<(B256, String)> = built
+            .skipped
+            .iter()
+            .map(|tx| (tx.hash, tx.reason.to_string()))
+            .collect();
+        assert_eq!(
+            skipped,
+            [
+                (hashes[3], "execution reverted".to_string()),
+                (
+                    hashes[4],
+                    "a previous transaction of the sender was skipped".to_string()
+                ),
+                (
+                    hashes[5],
+                    "gas limit reached: have 37000, want 50000".to_string()
+                ),
+            ]
+        );
+
+        let account = built.bundle.account(&a).unwrap();
+        assert_eq!(account.info.as_ref().unwrap().nonce, 2);
+        assert_eq!(
+            built
+                .bundle
+                .account(&b)
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .nonce,
+            1
+        );
+        assert!(built.bundle.account(&reverter).is_none());
+    }
+}
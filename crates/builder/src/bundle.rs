@@ -0,0 +1,408 @@
+//! Simulation of transaction bundles.
+use crate::priority_fee;
+use database::CacheDB;
+use revm::{
+    context::{block::BlockEnv, cfg::CfgEnv},
+    context_interface::result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction},
+    primitives::{Address, B256, I256, U256},
+    signed_tx::{SignedTransaction, SignedTxError},
+    state::AccountInfo,
+    Context, Database, DatabaseCommit, DatabaseRef, ExecuteEvm,
+};
+use std::{collections::BTreeMap, fmt};
+
+/// Error simulating a bundle.
+#[derive(Debug)]
+pub enum BundleError {
+    /// The sender of the transaction can't be recovered.
+    Signature { index: usize, source: SignedTxError },
+    /// The transaction is invalid and is not allowed to revert.
+    Invalid {
+        index: usize,
+        source: InvalidTransaction,
+    },
+    /// Error of the database.
+    Evm(String),
+}
+
+impl std::error::Error for BundleError {}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Signature { index, source } => write!(f, "transaction {index}: {source}"),
+            Self::Invalid { index, source } => write!(f, "transaction {index}: {source}"),
+            Self::Evm(error) => write!(f, "EVM Error: {error}"),
+        }
+    }
+}
+
+/// Transaction of a bundle.
+#[derive(Clone, Debug)]
+pub struct BundleTransaction {
+    pub tx: SignedTransaction,
+    /// Whether the transaction is dropped from the bundle if it reverts or is invalid.
+    pub can_revert: bool,
+}
+
+/// Outcome of a transaction of the bundle.
+#[derive(Debug)]
+pub struct BundleTxResult {
+    pub hash: B256,
+    pub sender: Address,
+    /// Execution result, or the reason a transaction that can revert is invalid.
+    pub result: Result<ExecutionResult<HaltReason>, InvalidTransaction>,
+    /// Whether the transaction was dropped, its changes are not in the state of the next ones.
+    pub rolled_back: bool,
+    pub gas_used: u64,
+    /// Priority fee per gas paid to the beneficiary.
+    pub gas_price: u128,
+    /// Priority fees paid to the beneficiary.
+    pub gas_fees: U256,
+    /// Change of the balance of the beneficiary, negative if the transaction paid from it.
+    pub coinbase_diff: I256,
+    /// Value sent to the beneficiary by the transaction besides the fees, zero if the balance of
+    /// the beneficiary grew by less than the fees.
+    pub eth_sent_to_coinbase: U256,
+}
+
+/// Change of an account by the bundle.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AccountDiff {
+    /// Account before the bundle, `None` if it didn't exist.
+    pub before: Option<AccountInfo>,
+    /// Account after the bundle, `None` if it doesn't exist.
+    pub after: Option<AccountInfo>,
+    /// Changed storage slots, with their values before and after the bundle.
+    pub storage: BTreeMap<U256, (U256, U256)>,
+}
+
+/// Outcome of [`simulate_bundle`].
+#[derive(Debug)]
+pub struct BundleSimulation {
+    pub results: Vec<BundleTxResult>,
+    /// Gas used by the transactions that were not rolled back.
+    pub total_gas_used: u64,
+    pub gas_fees: U256,
+    /// Change of the balance of the beneficiary, negative if the bundle paid from it.
+    pub coinbase_diff: I256,
+    /// Value sent to the beneficiary besides the fees, zero if its balance grew by less than the
+    /// fees.
+    pub eth_sent_to_coinbase: U256,
+    /// Payment to the beneficiary per gas used, which orders bundles in a block, zero if the
+    /// balance of the beneficiary decreased.
+    pub bundle_gas_price: U256,
+    /// Index of the first transaction that reverted without being allowed to, builders don't
+    /// include such bundles.
+    pub first_revert: Option<usize>,
+    /// Accounts changed by the bundle.
+    pub state_diff: BTreeMap<Address, AccountDiff>,
+}
+
+/// Executes the transactions of the bundle in order on a [`CacheDB`] over the state, which is not
+/// changed.
+///
+/// Transactions that can revert are rolled back if they revert, halt or are invalid, the other
+/// transactions must be valid. The payments to the beneficiary are its balance changes, so they
+/// include the value transferred to it.
+pub fn simulate_bundle<DB: DatabaseRef>(
+    db: DB,
+    txs: &[BundleTransaction],
+    cfg: &CfgEnv,
+    block: &BlockEnv,
+) -> Result<BundleSimulation, BundleError> {
+    let mut cache = CacheDB::new(db);
+    let coinbase = block.beneficiary;
+
+    let mut results = Vec::with_capacity(txs.len());
+    let mut first_revert = None;
+    for (index, bundle_tx) in txs.iter().enumerate() {
+        let tx = &bundle_tx.tx;
+        let sender = tx
+            .recover_sender()
+            .map_err(|source| BundleError::Signature { index, source })?;
+        let tx_env = tx.to_tx_env(sender);
+        let balance_before = coinbase_balance(&mut cache, coinbase)?;
+
+        let mut ctx = Context::builder()
+            .with_block(block)
+            .with_tx(&tx_env)
+            .with_cfg(cfg)
+            .with_db(&mut cache);
+        let output = match ctx.exec_previous() {
+            Ok(output) => output,
+            Err(EVMError::Transaction(source)) if bundle_tx.can_revert => {
+                results.push(BundleTxResult::rolled_back(tx, sender, Err(source)));
+                continue;
+            }
+            Err(EVMError::Transaction(source)) => {
+                return Err(BundleError::Invalid { index, source })
+            }
+            Err(error) => return Err(BundleError::Evm(error.to_string())),
+        };
+        if !output.result.is_success() {
+            if bundle_tx.can_revert {
+                results.push(BundleTxResult::rolled_back(tx, sender, Ok(output.result)));
+                continue;
+            }
+            first_revert.get_or_insert(index);
+        }
+
+        cache.commit(output.state);
+        let result = output.result;
+        let gas_price = priority_fee(tx, block.basefee).unwrap_or_default();
+        let gas_fees = U256::from(result.gas_used()) * U256::from(gas_price);
+        let coinbase_diff = balance_diff(balance_before, coinbase_balance(&mut cache, coinbase)?);
+        results.push(BundleTxResult {
+            hash: tx.hash(),
+            sender,
+            gas_used: result.gas_used(),
+            result: Ok(result),
+            rolled_back: false,
+            gas_price,
+            gas_fees,
+            coinbase_diff,
+            eth_sent_to_coinbase: eth_sent(coinbase_diff, gas_fees),
+        });
+    }
+
+    let included = results.iter().filter(|result| !result.rolled_back);
+    let total_gas_used = included.clone().map(|result| result.gas_used).sum::<u64>();
+    let gas_fees = included.clone().map(|result| result.gas_fees).sum::<U256>();
+    let coinbase_diff = included.fold(I256::ZERO, |diff, result| diff + result.coinbase_diff);
+    let bundle_gas_price = U256::try_from(coinbase_diff)
+        .unwrap_or_default()
+        .checked_div(U256::from(total_gas_used))
+        .unwrap_or_default();
+    let state_diff = state_diff(&cache).map_err(|e| BundleError::Evm(e.to_string()))?;
+    Ok(BundleSimulation {
+        results,
+        total_gas_used,
+        gas_fees,
+        coinbase_diff,
+        eth_sent_to_coinbase: eth_sent(coinbase_diff, gas_fees),
+        bundle_gas_price,
+        first_revert,
+        state_diff,
+    })
+}
+
+impl BundleTxResult {
+    fn rolled_back(
+        tx: &SignedTransaction,
+        sender: Address,
+        result: Result<ExecutionResult<HaltReason>, InvalidTransaction>,
+    ) -> Self {
+        Self {
+            hash: tx.hash(),
+            sender,
+            gas_used: result.as_ref().map_or(0, ExecutionResult::gas_used),
+            result,
+            rolled_back: true,
+            gas_price: 0,
+            gas_fees: U256::ZERO,
+            coinbase_diff: I256::ZERO,
+            eth_sent_to_coinbase: U256::ZERO,
+        }
+    }
+}
+
+fn coinbase_balance<DB: DatabaseRef>(
+    cache: &mut CacheDB<DB>,
+    coinbase: Address,
+) -> Result<U256, BundleError> {
+    cache
+        .basic(coinbase)
+        .map(|info| info.map(|info| info.balance).unwrap_or_default())
+        .map_err(|e| BundleError::Evm(e.to_string()))
+}
+
+/// Signed change from `before` to `after`, balances are below `2^255`.
+fn balance_diff(before: U256, after: U256) -> I256 {
+    I256::from_raw(after.wrapping_sub(before))
+}
+
+/// Part of the balance change that is not the fees, zero if it is smaller than the fees.
+fn eth_sent(coinbase_diff: I256, gas_fees: U256) -> U256 {
+    U256::try_from(coinbase_diff - I256::from_raw(gas_fees)).unwrap_or_default()
+}
+
+/// Compares the accounts of the cache with the ones of the state under it.
+///
+/// Slots of an account whose storage was cleared are only compared if the bundle wrote them.
+fn state_diff<DB: DatabaseRef>(
+    cache: &CacheDB<DB>,
+) -> Result<BTreeMap<Address, AccountDiff>, DB::Error> {
+    let mut diff = BTreeMap::new();
+    for (address, account) in &cache.accounts {
+        let before = cache.db.basic_ref(*address)?;
+        let after = account.info();
+        let mut storage = BTreeMap::new();
+        for (slot, value) in &account.storage {
+            let original = cache.db.storage_ref(*address, *slot)?;
+            if original != *value {
+                storage.insert(*slot, (original, *value));
+            }
+        }
+        let same = |a: &AccountInfo, b: &AccountInfo| {
+            a.balance == b.balance && a.nonce == b.nonce && a.code_hash == b.code_hash
+        };
+        let changed = match (&before, &after) {
+            (Some(before), Some(after)) => !same(before, after),
+            (None, None) => false,
+            (Some(info), None) | (None, Some(info)) => !info.is_empty(),
+        };
+        if changed || !storage.is_empty() {
+            diff.insert(
+                *address,
+                AccountDiff {
+                    before,
+                    after,
+                    storage,
+                },
+            );
+        }
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sign;
+    use revm::{
+        bytecode::Bytecode,
+        database_interface::EmptyDB,
+        primitives::{address, b256, TxKind},
+        specification::hardfork::SpecId,
+    };
+
+    const KEY_A: B256 = b256!("45a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8");
+    const KEY_B: B256 = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+    const GWEI: u128 = 1_000_000_000;
+
+    fn bundle_tx(
+        key: &B256,
+        nonce: u64,
+        to: Address,
+        value: u64,
+        can_revert: bool,
+    ) -> BundleTransaction {
+        let mut tx = SignedTransaction {
+            tx_type: 2,
+            chain_id: Some(1),
+            nonce,
+            gas_price: 100 * GWEI,
+            max_priority_fee_per_gas: 2 * GWEI,
+            gas_limit: 50_000,
+            to: TxKind::Call(to),
+            value: U256::from(value),
+            ..Default::default()
+        };
+        sign(&mut tx, key);
+        BundleTransaction { tx, can_revert }
+    }
+
+    #[test]
+    fn rollback_and_coinbase_payments() {
+        let coinbase = address!("c000000000000000000000000000000000000000");
+        let recipient = address!("1000000000000000000000000000000000000001");
+        // PUSH1 0 PUSH1 0 REVERT
+        let reverter = address!("2000000000000000000000000000000000000002");
+        let txs = vec![
+            bundle_tx(&KEY_A, 0, coinbase, 1000, false),
+            bundle_tx(&KEY_B, 0, reverter, 0, true),
+            bundle_tx(&KEY_B, 0, recipient, 5, false),
+            bundle_tx(&KEY_A, 1, reverter, 0, false),
+        ];
+        let a = txs[0].tx.recover_sender().unwrap();
+        let b = txs[1].tx.recover_sender().unwrap();
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        for sender in [a, b] {
+            let info = AccountInfo {
+                balance: U256::from(10u128.pow(18)),
+                ..Default::default()
+            };
+            db.insert_account_info(sender, info);
+        }
+        let code = Bytecode::new_legacy([0x60, 0x00, 0x60, 0x00, 0xfd].into());
+        let info = AccountInfo {
+            code_hash: code.hash_slow(),
+            code: Some(code),
+            ..Default::default()
+        };
+        db.insert_account_info(reverter, info);
+
+        let mut cfg = CfgEnv::default();
+        cfg.spec = SpecId::PRAGUE;
+        let block = BlockEnv {
+            beneficiary: coinbase,
+            basefee: GWEI as u64,
+            ..Default::default()
+        };
+        let simulation = simulate_bundle(&db, &txs, &cfg, &block).unwrap();
+
+        let rolled_back: Vec<bool> = simulation.results.iter().map(|r| r.rolled_back).collect();
+        assert_eq!(rolled_back, [false, true, false, false]);
+        assert_eq!(simulation.first_revert, Some(3));
+
+        let fees = U256::from(21_000 * 2 * GWEI);
+        let transfer = &simulation.results[0];
+        assert_eq!(transfer.gas_fees, fees);
+        assert_eq!(
+            transfer.coinbase_diff,
+            I256::from_raw(fees + U256::from(1000))
+        );
+        assert_eq!(transfer.eth_sent_to_coinbase, U256::from(1000));
+
+        let gas_used = 21_000 * 2 + simulation.results[3].gas_used;
+        assert_eq!(simulation.total_gas_used, gas_used);
+        assert_eq!(simulation.eth_sent_to_coinbase, U256::from(1000));
+        assert_eq!(
+            simulation.bundle_gas_price,
+            simulation.coinbase_diff.into_raw() / U256::from(gas_used)
+        );
+
+        let diff = &simulation.state_diff;
+        assert_eq!(
+            diff[&recipient].after.as_ref().unwrap().balance,
+            U256::from(5)
+        );
+        assert_eq!(diff[&b].before.as_ref().unwrap().nonce, 0);
+        assert_eq!(diff[&b].after.as_ref().unwrap().nonce, 1);
+        assert_eq!(diff[&a].after.as_ref().unwrap().nonce, 2);
+        assert!(!diff.contains_key(&reverter));
+        // The state under the bundle is not changed.
+        assert!(!db.accounts.contains_key(&recipient));
+    }
+
+    #[test]
+    fn coinbase_pays() {
+        let recipient = address!("1000000000000000000000000000000000000001");
+        let txs = vec![bundle_tx(&KEY_A, 0, recipient, 5, false)];
+        let coinbase = txs[0].tx.recover_sender().unwrap();
+        let mut db = CacheDB::new(EmptyDB::default());
+        let info = AccountInfo {
+            balance: U256::from(10u128.pow(18)),
+            ..Default::default()
+        };
+        db.insert_account_info(coinbase, info);
+
+        let mut cfg = CfgEnv::default();
+        cfg.spec = SpecId::PRAGUE;
+        let block = BlockEnv {
+            beneficiary: coinbase,
+            basefee: GWEI as u64,
+            ..Default::default()
+        };
+        let simulation = simulate_bundle(&db, &txs, &cfg, &block).unwrap();
+
+        // The beneficiary gets the priority fee back, it pays the base fee and the value.
+        let paid = U256::from(21_000 * GWEI + 5);
+        assert_eq!(simulation.results[0].coinbase_diff, -I256::from_raw(paid));
+        assert_eq!(simulation.coinbase_diff, -I256::from_raw(paid));
+        assert_eq!(simulation.eth_sent_to_coinbase, U256::ZERO);
+        assert_eq!(simulation.bundle_gas_price, U256::ZERO);
+    }
+}
@@ -0,0 +1,101 @@
+//! # revm-builder
+//!
+//! Block building and bundle simulation on top of revm.
+//!
+//! [`build_block`] selects and orders candidate transactions into a block by priority fee, and
+//! [`simulate_bundle`] executes a bundle of transactions without changing the state, as
+//! `eth_callBundle` of block builders.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+pub mod block;
+pub mod bundle;
+pub mod receipt;
+
+pub use block::{
+    build_block, BuilderConfig, BuiltBlock, IncludedTransaction, RevertPolicy, SkipReason,
+    SkippedTransaction,
+};
+pub use bundle::{
+    simulate_bundle, AccountDiff, BundleError, BundleSimulation, BundleTransaction, BundleTxResult,
+};
+pub use receipt::{Receipt, ReceiptOutcome};
+
+use revm::{
+    signed_tx::SignedTransaction,
+    specification::{
+        eip4844::{MAX_BLOB_GAS_PER_BLOCK_CANCUN, MAX_BLOB_GAS_PER_BLOCK_PRAGUE},
+        hardfork::SpecId,
+    },
+};
+
+/// Priority fee per gas paid by the transaction at the base fee, `None` if it can't pay the base
+/// fee.
+pub fn priority_fee(tx: &SignedTransaction, base_fee: u64) -> Option<u128> {
+    let max_fee = tx.gas_price.checked_sub(base_fee as u128)?;
+    if tx.tx_type >= 2 {
+        Some(max_fee.min(tx.max_priority_fee_per_gas))
+    } else {
+        Some(max_fee)
+    }
+}
+
+/// Maximum blob gas used by a block.
+pub fn max_blob_gas(spec: SpecId) -> u64 {
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        MAX_BLOB_GAS_PER_BLOCK_PRAGUE
+    } else {
+        MAX_BLOB_GAS_PER_BLOCK_CANCUN
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use k256::ecdsa::SigningKey;
+    use revm::{
+        primitives::{B256, U256},
+        signed_tx::{Signature, SignedTransaction},
+    };
+
+    /// Signs the transaction with the secret key.
+    pub(crate) fn sign(tx: &mut SignedTransaction, secret_key: &B256) {
+        let key = SigningKey::from_slice(secret_key.as_slice()).unwrap();
+        let (signature, recovery_id) = key
+            .sign_prehash_recoverable(tx.signature_hash().as_slice())
+            .unwrap();
+        let y_parity = recovery_id.is_y_odd() as u64;
+        tx.signature = Signature {
+            v: match (tx.tx_type, tx.chain_id) {
+                (0, Some(chain_id)) => 35 + 2 * chain_id + y_parity,
+                (0, None) => 27 + y_parity,
+                _ => y_parity,
+            },
+            r: U256::from_be_slice(&signature.r().to_bytes()),
+            s: U256::from_be_slice(&signature.s().to_bytes()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priority_fee_at_base_fee() {
+        let legacy = SignedTransaction {
+            gas_price: 10,
+            ..Default::default()
+        };
+        assert_eq!(priority_fee(&legacy, 7), Some(3));
+        assert_eq!(priority_fee(&legacy, 11), None);
+
+        let eip1559 = SignedTransaction {
+            tx_type: 2,
+            gas_price: 10,
+            max_priority_fee_per_gas: 2,
+            ..Default::default()
+        };
+        assert_eq!(priority_fee(&eip1559, 7), Some(2));
+        assert_eq!(priority_fee(&eip1559, 9), Some(1));
+        assert_eq!(priority_fee(&eip1559, 11), None);
+    }
+}
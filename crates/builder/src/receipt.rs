@@ -0,0 +1,66 @@
+//! Transaction receipts.
+use alloy_rlp::Encodable;
+use revm::primitives::{alloy_primitives::Bloom, Log, B256};
+
+/// Status of a receipt.
+#[derive(Clone, Copy, Debug)]
+pub enum ReceiptOutcome {
+    /// State root after the transaction, before Byzantium.
+    StateRoot(B256),
+    /// Whether the transaction succeeded, since Byzantium.
+    Status(bool),
+}
+
+#[derive(Clone, Debug)]
+pub struct Receipt {
+    pub tx_type: u8,
+    pub outcome: ReceiptOutcome,
+    pub cumulative_gas_used: u64,
+    pub bloom: Bloom,
+    pub logs: Vec<Log>,
+}
+
+impl Receipt {
+    pub fn new(
+        tx_type: u8,
+        outcome: ReceiptOutcome,
+        cumulative_gas_used: u64,
+        logs: Vec<Log>,
+    ) -> Self {
+        let mut bloom = Bloom::ZERO;
+        for log in &logs {
+            bloom.accrue_log(log);
+        }
+        Self {
+            tx_type,
+            outcome,
+            cumulative_gas_used,
+            bloom,
+            logs,
+        }
+    }
+
+    /// Encoding of the receipt in the receipts trie, typed receipts are prefixed by the type.
+    pub fn encoded(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        match self.outcome {
+            ReceiptOutcome::StateRoot(root) => root.encode(&mut payload),
+            ReceiptOutcome::Status(status) => status.encode(&mut payload),
+        }
+        self.cumulative_gas_used.encode(&mut payload);
+        self.bloom.encode(&mut payload);
+        alloy_rlp::encode_list(&self.logs, &mut payload);
+
+        let mut out = Vec::with_capacity(payload.len() + 5);
+        if self.tx_type != 0 {
+            out.push(self.tx_type);
+        }
+        alloy_rlp::Header {
+            list: true,
+            payload_length: payload.len(),
+        }
+        .encode(&mut out);
+        out.extend_from_slice(&payload);
+        out
+    }
+}
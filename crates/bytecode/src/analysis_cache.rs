@@ -0,0 +1,337 @@
+//! Cache of analyzed bytecode shared between databases, journals and threads.
+use crate::{Bytecode, BytecodeDecodeError};
+use core::{
+    any::{Any, TypeId},
+    convert::Infallible,
+};
+use primitives::{Bytes, HashMap, B256};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError},
+    vec::Vec,
+};
+
+/// Number of bytecodes held by the [global](AnalysisCache::global) cache.
+pub const DEFAULT_ANALYSIS_CACHE_CAPACITY: usize = 4096;
+
+/// Lazily initialized global cache.
+static GLOBAL_ANALYSIS_CACHE: OnceLock<Arc<AnalysisCache>> = OnceLock::new();
+
+/// Thread-safe cache of analyzed [`Bytecode`] keyed by code hash.
+///
+/// Holds legacy bytecode together with its jump table and decoded EOF containers, so that
+/// code loaded many times is analyzed only once. Values derived from the bytecode by other
+/// crates, like pre-decoded instruction streams, are [cached next to it](Self::get_or_derive).
+/// When full, the least recently used entry is evicted.
+///
+/// The cache trusts that the code hash matches the code it is given. Empty code is never
+/// cached, as databases return it for code hashes they don't know.
+#[derive(Debug)]
+pub struct AnalysisCache {
+    /// Maximum number of cached bytecodes.
+    capacity: usize,
+    /// Cached entries.
+    inner: Mutex<LruMap>,
+}
+
+/// Caches are equal only if they are the same instance.
+impl PartialEq for AnalysisCache {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+impl Eq for AnalysisCache {}
+
+impl Default for AnalysisCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_ANALYSIS_CACHE_CAPACITY)
+    }
+}
+
+impl AnalysisCache {
+    /// Creates a new cache that holds at most `capacity` bytecodes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(LruMap::default()),
+        }
+    }
+
+    /// Returns the process wide cache with [`DEFAULT_ANALYSIS_CACHE_CAPACITY`].
+    pub fn global() -> &'static Arc<Self> {
+        GLOBAL_ANALYSIS_CACHE.get_or_init(Default::default)
+    }
+
+    /// Returns the maximum number of cached bytecodes.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of code hashes with cached bytecode or derived values.
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached bytecodes.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+    }
+
+    /// Returns the cached bytecode and marks it as recently used.
+    pub fn get(&self, code_hash: &B256) -> Option<Bytecode> {
+        self.lock().get(code_hash)
+    }
+
+    /// Caches the bytecode, evicting the least recently used one if the cache is full.
+    pub fn insert(&self, code_hash: B256, bytecode: Bytecode) {
+        if self.capacity == 0 || bytecode.is_empty() {
+            return;
+        }
+        self.lock().insert(code_hash, bytecode, self.capacity);
+    }
+
+    /// Returns the cached bytecode or caches the one returned by `f`.
+    ///
+    /// The lock is not held while `f` runs, so concurrent misses of the same code hash may
+    /// both call it.
+    pub fn get_or_insert_with<E>(
+        &self,
+        code_hash: B256,
+        f: impl FnOnce() -> Result<Bytecode, E>,
+    ) -> Result<Bytecode, E> {
+        if let Some(bytecode) = self.get(&code_hash) {
+            return Ok(bytecode);
+        }
+        let bytecode = f()?;
+        self.insert(code_hash, bytecode.clone());
+        Ok(bytecode)
+    }
+
+    /// Returns the cached bytecode or caches `bytecode`.
+    ///
+    /// Used for code that is loaded together with an account, so that all users of the
+    /// cache share one analysis of it.
+    pub fn share(&self, code_hash: B256, bytecode: Bytecode) -> Bytecode {
+        match self.get_or_insert_with(code_hash, || Ok::<_, Infallible>(bytecode)) {
+            Ok(bytecode) => bytecode,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns the value of type `T` derived from the bytecode with `code_hash`, or derives it
+    /// with `f` and caches it next to the bytecode.
+    ///
+    /// Values are cached by code hash and type even if the bytecode itself is not cached, and
+    /// are evicted together with it. The lock is not held while `f` runs, so concurrent misses
+    /// may both call it.
+    pub fn get_or_derive<T: Any + Send + Sync>(
+        &self,
+        code_hash: B256,
+        f: impl FnOnce() -> T,
+    ) -> Arc<T> {
+        if let Some(value) = self.lock().derived(&code_hash) {
+            return value;
+        }
+        let value = Arc::new(f());
+        if self.capacity != 0 {
+            self.lock()
+                .insert_derived(code_hash, value.clone(), self.capacity);
+        }
+        value
+    }
+
+    /// Returns the cached bytecode or analyzes `bytes` with [`Bytecode::new_raw_checked`]
+    /// and caches the result.
+    pub fn analyze(&self, code_hash: B256, bytes: Bytes) -> Result<Bytecode, BytecodeDecodeError> {
+        self.get_or_insert_with(code_hash, || Bytecode::new_raw_checked(bytes))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruMap> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Value derived from cached bytecode, with the type id of the value.
+type Derived = (TypeId, Arc<dyn Any + Send + Sync>);
+
+/// Cached bytecode and values derived from it.
+#[derive(Debug)]
+struct CachedCode {
+    /// `None` if only derived values are cached.
+    bytecode: Option<Bytecode>,
+    /// Tick of the last use.
+    last_used: u64,
+    /// Values derived from the bytecode.
+    derived: Vec<Derived>,
+}
+
+/// Map that tracks the order in which entries were used.
+#[derive(Debug, Default)]
+struct LruMap {
+    /// Cached bytecodes.
+    entries: HashMap<B256, CachedCode>,
+    /// Code hashes ordered by the tick of their last use.
+    order: BTreeMap<u64, B256>,
+    /// Incremented on every use.
+    tick: u64,
+}
+
+impl LruMap {
+    /// Returns the entry and marks it as recently used.
+    fn touch(&mut self, code_hash: &B256) -> Option<&mut CachedCode> {
+        let entry = self.entries.get_mut(code_hash)?;
+        self.order.remove(&entry.last_used);
+        self.tick += 1;
+        entry.last_used = self.tick;
+        self.order.insert(self.tick, *code_hash);
+        Some(entry)
+    }
+
+    fn get(&mut self, code_hash: &B256) -> Option<Bytecode> {
+        self.touch(code_hash)?.bytecode.clone()
+    }
+
+    fn derived<T: Any + Send + Sync>(&mut self, code_hash: &B256) -> Option<Arc<T>> {
+        let (_, value) = self
+            .touch(code_hash)?
+            .derived
+            .iter()
+            .find(|(type_id, _)| *type_id == TypeId::of::<T>())?;
+        value.clone().downcast().ok()
+    }
+
+    /// Returns the entry of `code_hash` and marks it as recently used, inserting an empty one
+    /// if there is none.
+    fn entry(&mut self, code_hash: B256, capacity: usize) -> &mut CachedCode {
+        if let Some(entry) = self.entries.get(&code_hash) {
+            self.order.remove(&entry.last_used);
+        } else if self.entries.len() >= capacity {
+            if let Some((_, evicted)) = self.order.pop_first() {
+                self.entries.remove(&evicted);
+            }
+        }
+        self.tick += 1;
+        self.order.insert(self.tick, code_hash);
+        let entry = self.entries.entry(code_hash).or_insert_with(|| CachedCode {
+            bytecode: None,
+            last_used: 0,
+            derived: Vec::new(),
+        });
+        entry.last_used = self.tick;
+        entry
+    }
+
+    /// Inserts the bytecode, derived values of replaced bytecode are kept.
+    fn insert(&mut self, code_hash: B256, bytecode: Bytecode, capacity: usize) {
+        self.entry(code_hash, capacity).bytecode = Some(bytecode);
+    }
+
+    fn insert_derived<T: Any + Send + Sync>(
+        &mut self,
+        code_hash: B256,
+        value: Arc<T>,
+        capacity: usize,
+    ) {
+        let entry = self.entry(code_hash, capacity);
+        if !entry
+            .derived
+            .iter()
+            .any(|(type_id, _)| *type_id == TypeId::of::<T>())
+        {
+            entry.derived.push((TypeId::of::<T>(), value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{JUMPDEST, STOP};
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = AnalysisCache::new(2);
+        let (a, b, c) = (
+            B256::with_last_byte(1),
+            B256::with_last_byte(2),
+            B256::with_last_byte(3),
+        );
+        cache.insert(a, Bytecode::new_legacy([JUMPDEST].into()));
+        cache.insert(b, Bytecode::new_legacy([STOP].into()));
+        // Makes `b` the least recently used.
+        assert!(cache.get(&a).is_some());
+        cache.insert(c, Bytecode::new_legacy([JUMPDEST, STOP].into()));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&a).is_some());
+        assert!(cache.get(&b).is_none());
+        assert!(cache.get(&c).is_some());
+    }
+
+    #[test]
+    fn analyzes_once() {
+        let cache = AnalysisCache::default();
+        let code_hash = B256::with_last_byte(1);
+        let bytecode = cache
+            .analyze(code_hash, Bytes::from_static(&[JUMPDEST, STOP]))
+            .unwrap();
+        assert!(bytecode.legacy_jump_table().unwrap().is_valid(0));
+
+        let cached = cache
+            .get_or_insert_with::<Infallible>(code_hash, || unreachable!("bytecode is cached"))
+            .unwrap();
+        assert_eq!(cached, bytecode);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let cache = AnalysisCache::new(0);
+        cache.insert(B256::ZERO, Bytecode::new_legacy([STOP].into()));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn derived_values_are_evicted_with_bytecode() {
+        let cache = AnalysisCache::new(1);
+        let (a, b) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let code = Bytecode::new_legacy([JUMPDEST, STOP].into());
+
+        // Derived values are cached without the bytecode.
+        assert_eq!(*cache.get_or_derive(a, || 1u32), 1);
+        assert_eq!(cache.get(&a), None);
+        assert_eq!(*cache.get_or_derive(a, || 2u32), 1);
+        // Values of other types are cached separately.
+        assert_eq!(*cache.get_or_derive(a, || 3u64), 3);
+
+        // Inserting the bytecode keeps the derived values.
+        cache.insert(a, code.clone());
+        assert_eq!(cache.get(&a), Some(code.clone()));
+        assert_eq!(*cache.get_or_derive(a, || 4u32), 1);
+
+        cache.insert(b, code);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(*cache.get_or_derive(a, || 5u32), 5);
+    }
+
+    #[test]
+    fn empty_code_is_not_cached() {
+        let cache = AnalysisCache::default();
+        let code_hash = B256::with_last_byte(1);
+        assert!(cache.share(code_hash, Bytecode::new()).is_empty());
+        assert!(cache.is_empty());
+
+        let code = Bytecode::new_legacy([JUMPDEST, STOP].into());
+        assert_eq!(cache.share(code_hash, code.clone()), code);
+        // The cached analysis is returned for the same code hash.
+        assert_eq!(cache.share(code_hash, Bytecode::new()), code);
+    }
+}
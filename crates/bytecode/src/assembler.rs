@@ -0,0 +1,589 @@
+//! Assembler of EVM mnemonics.
+//!
+//! The source is read line by line. Comments start with `;` or `//`. A line can start with a
+//! label definition `name:` and can contain one instruction or directive:
+//!
+//! ```text
+//! start:
+//!     PUSH 0x0100        ; PUSH with the smallest width that fits, at least PUSH1
+//!     PUSH2 0x01         ; explicit width, the value is left padded
+//!     PUSH end           ; offset of a label, PUSH2 unless the width is explicit
+//!     JUMP
+//! end:
+//!     JUMPDEST
+//!     STOP
+//!     .data 0xdeadbeef   ; raw bytes
+//! ```
+//!
+//! `.bytes` also appends raw bytes to the code, in both modes.
+//!
+//! Mnemonics are case insensitive, labels are case sensitive. Numbers are decimal or `0x`
+//! prefixed hex.
+//!
+//! In EOF mode, see [`assemble_eof`], the `.code` directive starts a code section, optionally
+//! followed by its name and its `inputs=`, `outputs=` and `max_stack=` types. Labels are local to
+//! their code section and `RJUMP`, `RJUMPI` and `RJUMPV` take labels. `CALLF` and `JUMPF` take a
+//! section name or index. `.data` appends to the data section and `.container` adds a
+//! subcontainer:
+//!
+//! ```text
+//! .code main max_stack=1
+//!     PUSH0
+//!     RJUMPI done
+//!     CALLF helper
+//! done:
+//!     STOP
+//! .code helper inputs=0 outputs=0 max_stack=0
+//!     RETF
+//! .data 0x1234
+//! ```
+use crate::{
+    eof::{Eof, EofBody, TypesSection},
+    opcode::{self, OpCode},
+};
+use core::fmt;
+use primitives::{hex, Bytes, U256};
+use std::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Assembler error with the line where it happened.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AsmError {
+    /// Line number, starting from 1.
+    pub line: usize,
+    /// Kind of the error.
+    pub kind: AsmErrorKind,
+}
+
+/// Kind of an [`AsmError`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AsmErrorKind {
+    /// Mnemonic is not a known opcode.
+    UnknownOpcode(String),
+    /// Directive is not known.
+    UnknownDirective(String),
+    /// Number or hex string can't be parsed.
+    InvalidNumber(String),
+    /// Opcode expects an immediate.
+    MissingImmediate,
+    /// Opcode or directive got more operands than it takes.
+    UnexpectedOperand(String),
+    /// Value doesn't fit into the immediate.
+    ImmediateTooLarge,
+    /// Label is defined twice.
+    DuplicateLabel(String),
+    /// Label is not defined.
+    UnknownLabel(String),
+    /// Code section is not defined.
+    UnknownSection(String),
+    /// Relative jump doesn't fit into 16 bits.
+    JumpOutOfRange,
+    /// Directive is only valid in EOF mode.
+    EofOnly(String),
+}
+
+impl fmt::Display for AsmErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOpcode(s) => write!(f, "unknown opcode `{s}`"),
+            Self::UnknownDirective(s) => write!(f, "unknown directive `{s}`"),
+            Self::InvalidNumber(s) => write!(f, "invalid number `{s}`"),
+            Self::MissingImmediate => f.write_str("missing immediate"),
+            Self::UnexpectedOperand(s) => write!(f, "unexpected operand `{s}`"),
+            Self::ImmediateTooLarge => f.write_str("immediate too large"),
+            Self::DuplicateLabel(s) => write!(f, "duplicate label `{s}`"),
+            Self::UnknownLabel(s) => write!(f, "unknown label `{s}`"),
+            Self::UnknownSection(s) => write!(f, "unknown code section `{s}`"),
+            Self::JumpOutOfRange => f.write_str("relative jump out of range"),
+            Self::EofOnly(s) => write!(f, "`{s}` is only valid in EOF mode"),
+        }
+    }
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl core::error::Error for AsmError {}
+
+/// Assembles legacy bytecode.
+pub fn assemble(source: &str) -> Result<Bytes, AsmError> {
+    let mut asm = Assembler::new(false);
+    asm.parse(source)?;
+    let mut section = asm.sections.pop().unwrap_or_default();
+    section.resolve(&[])?;
+    Ok(section.code.into())
+}
+
+/// Assembles an EOF container.
+///
+/// Code sections default to no inputs, non-returning outputs and a max stack of zero.
+pub fn assemble_eof(source: &str) -> Result<Eof, AsmError> {
+    let mut asm = Assembler::new(true);
+    asm.parse(source)?;
+    if asm.sections.is_empty() {
+        asm.sections.push(Section::default());
+    }
+
+    let names: Vec<_> = asm.sections.iter().map(|s| s.name.clone()).collect();
+    let mut body = EofBody {
+        container_section: asm.containers,
+        data_section: asm.data.into(),
+        is_data_filled: true,
+        ..Default::default()
+    };
+    let mut code = Vec::new();
+    for mut section in asm.sections {
+        section.resolve(&names)?;
+        code.extend_from_slice(&section.code);
+        body.types_section.push(section.types);
+        body.code_section.push(code.len());
+    }
+    body.code = code.into();
+    Ok(body.into_eof())
+}
+
+/// Immediate that is written once all labels are known.
+#[derive(Debug)]
+struct Fixup {
+    line: usize,
+    /// Offset of the immediate.
+    at: usize,
+    kind: FixupKind,
+}
+
+#[derive(Debug)]
+enum FixupKind {
+    /// Big endian offset of a label.
+    Absolute { label: String, width: usize },
+    /// Signed 16 bit offset of a label from `base`.
+    Relative { label: String, base: usize },
+    /// 16 bit index of a code section.
+    Section(String),
+}
+
+#[derive(Debug)]
+struct Section {
+    name: Option<String>,
+    types: TypesSection,
+    code: Vec<u8>,
+    labels: BTreeMap<String, usize>,
+    fixups: Vec<Fixup>,
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        Self {
+            name: None,
+            types: TypesSection::new(0, 0x80, 0),
+            code: Vec::new(),
+            labels: BTreeMap::new(),
+            fixups: Vec::new(),
+        }
+    }
+}
+
+impl Section {
+    /// Writes the fixups, `sections` are the names of the EOF code sections.
+    fn resolve(&mut self, sections: &[Option<String>]) -> Result<(), AsmError> {
+        for fixup in &self.fixups {
+            let error = |kind| AsmError {
+                line: fixup.line,
+                kind,
+            };
+            let label = |label: &String| {
+                self.labels
+                    .get(label)
+                    .copied()
+                    .ok_or_else(|| error(AsmErrorKind::UnknownLabel(label.clone())))
+            };
+            match &fixup.kind {
+                FixupKind::Absolute { label: name, width } => {
+                    let value = U256::from(label(name)?);
+                    let bytes = encode_immediate(value, *width).map_err(error)?;
+                    self.code[fixup.at..fixup.at + width].copy_from_slice(&bytes);
+                }
+                FixupKind::Relative { label: name, base } => {
+                    let offset = i16::try_from(label(name)? as isize - *base as isize)
+                        .map_err(|_| error(AsmErrorKind::JumpOutOfRange))?;
+                    self.code[fixup.at..fixup.at + 2].copy_from_slice(&offset.to_be_bytes());
+                }
+                FixupKind::Section(name) => {
+                    let index = sections
+                        .iter()
+                        .position(|s| s.as_ref() == Some(name))
+                        .ok_or_else(|| error(AsmErrorKind::UnknownSection(name.clone())))?;
+                    self.code[fixup.at..fixup.at + 2]
+                        .copy_from_slice(&(index as u16).to_be_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Assembler {
+    eof: bool,
+    /// Line being parsed.
+    line: usize,
+    sections: Vec<Section>,
+    containers: Vec<Bytes>,
+    data: Vec<u8>,
+}
+
+impl Assembler {
+    fn new(eof: bool) -> Self {
+        Self {
+            eof,
+            line: 0,
+            sections: Vec::new(),
+            containers: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    fn section(&mut self) -> &mut Section {
+        if self.sections.is_empty() {
+            self.sections.push(Section::default());
+        }
+        self.sections.last_mut().unwrap()
+    }
+
+    fn parse(&mut self, source: &str) -> Result<(), AsmError> {
+        for (index, line) in source.lines().enumerate() {
+            self.line = index + 1;
+            self.parse_line(line).map_err(|kind| AsmError {
+                line: self.line,
+                kind,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), AsmErrorKind> {
+        let line = line.split(';').next().unwrap_or_default();
+        let line = line.split("//").next().unwrap_or_default();
+        let line = line.replace(',', " ");
+        let mut tokens = line.split_whitespace().peekable();
+
+        if let Some(label) = tokens.peek().and_then(|t| t.strip_suffix(':')) {
+            let label = label.to_string();
+            tokens.next();
+            let section = self.section();
+            let offset = section.code.len();
+            if section.labels.insert(label.clone(), offset).is_some() {
+                return Err(AsmErrorKind::DuplicateLabel(label));
+            }
+        }
+
+        let Some(mnemonic) = tokens.next() else {
+            return Ok(());
+        };
+        let operands: Vec<&str> = tokens.collect();
+        if mnemonic.starts_with('.') {
+            self.directive(mnemonic, &operands)
+        } else {
+            self.instruction(mnemonic, &operands)
+        }
+    }
+
+    fn directive(&mut self, directive: &str, operands: &[&str]) -> Result<(), AsmErrorKind> {
+        match directive {
+            ".data" | ".bytes" => {
+                let bytes = operands
+                    .iter()
+                    .map(|s| parse_hex(s))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
+                if self.eof && directive == ".data" {
+                    self.data.extend_from_slice(&bytes);
+                } else {
+                    self.section().code.extend_from_slice(&bytes);
+                }
+            }
+            ".code" if self.eof => {
+                let mut section = Section::default();
+                for operand in operands {
+                    let Some((key, value)) = operand.split_once('=') else {
+                        section.name = Some(operand.to_string());
+                        continue;
+                    };
+                    let value = parse_number(value)?;
+                    match key {
+                        "inputs" => {
+                            section.types.inputs = value
+                                .try_into()
+                                .map_err(|_| AsmErrorKind::ImmediateTooLarge)?
+                        }
+                        "outputs" => {
+                            section.types.outputs = value
+                                .try_into()
+                                .map_err(|_| AsmErrorKind::ImmediateTooLarge)?
+                        }
+                        "max_stack" => {
+                            section.types.max_stack_size = value
+                                .try_into()
+                                .map_err(|_| AsmErrorKind::ImmediateTooLarge)?
+                        }
+                        _ => return Err(AsmErrorKind::UnexpectedOperand(operand.to_string())),
+                    }
+                }
+                self.sections.push(section);
+            }
+            ".container" if self.eof => {
+                let bytes = operands
+                    .iter()
+                    .map(|s| parse_hex(s))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
+                self.containers.push(bytes.into());
+            }
+            ".code" | ".container" => return Err(AsmErrorKind::EofOnly(directive.to_string())),
+            _ => return Err(AsmErrorKind::UnknownDirective(directive.to_string())),
+        }
+        Ok(())
+    }
+
+    fn instruction(&mut self, mnemonic: &str, operands: &[&str]) -> Result<(), AsmErrorKind> {
+        let upper = mnemonic.to_ascii_uppercase();
+        let line = self.line;
+        let section = self.section();
+        let fixup = |at, kind| Fixup { line, at, kind };
+
+        // `PUSH` with the width picked from the operand.
+        if upper == "PUSH" {
+            let [operand] = single_operand(operands)?;
+            if is_label(operand) {
+                section.code.push(opcode::PUSH2);
+                section.fixups.push(fixup(
+                    section.code.len(),
+                    FixupKind::Absolute {
+                        label: operand.to_string(),
+                        width: 2,
+                    },
+                ));
+                section.code.extend_from_slice(&[0; 2]);
+            } else {
+                let value = parse_number(operand)?;
+                let width = value.byte_len().max(1);
+                section.code.push(opcode::PUSH0 + width as u8);
+                section
+                    .code
+                    .extend_from_slice(&encode_immediate(value, width)?);
+            }
+            return Ok(());
+        }
+
+        let op = OpCode::parse(&upper).ok_or(AsmErrorKind::UnknownOpcode(upper.clone()))?;
+        section.code.push(op.get());
+        let immediate_size = op.info().immediate_size() as usize;
+        if immediate_size == 0 {
+            if let Some(operand) = operands.first() {
+                return Err(AsmErrorKind::UnexpectedOperand(operand.to_string()));
+            }
+            return Ok(());
+        }
+
+        match op.get() {
+            opcode::RJUMPV => {
+                if operands.is_empty() {
+                    return Err(AsmErrorKind::MissingImmediate);
+                }
+                let max_index = u8::try_from(operands.len() - 1)
+                    .map_err(|_| AsmErrorKind::ImmediateTooLarge)?;
+                section.code.push(max_index);
+                let table = section.code.len();
+                let base = table + operands.len() * 2;
+                for (i, operand) in operands.iter().enumerate() {
+                    section.fixups.push(fixup(
+                        table + i * 2,
+                        FixupKind::Relative {
+                            label: operand.to_string(),
+                            base,
+                        },
+                    ));
+                }
+                section.code.resize(base, 0);
+                return Ok(());
+            }
+            opcode::RJUMP | opcode::RJUMPI => {
+                let [operand] = single_operand(operands)?;
+                if is_label(operand) {
+                    let at = section.code.len();
+                    section.fixups.push(fixup(
+                        at,
+                        FixupKind::Relative {
+                            label: operand.to_string(),
+                            base: at + 2,
+                        },
+                    ));
+                    section.code.extend_from_slice(&[0; 2]);
+                } else {
+                    let offset: i16 = operand
+                        .parse()
+                        .map_err(|_| AsmErrorKind::InvalidNumber(operand.to_string()))?;
+                    section.code.extend_from_slice(&offset.to_be_bytes());
+                }
+                return Ok(());
+            }
+            opcode::CALLF | opcode::JUMPF => {
+                let [operand] = single_operand(operands)?;
+                if is_label(operand) {
+                    section.fixups.push(fixup(
+                        section.code.len(),
+                        FixupKind::Section(operand.to_string()),
+                    ));
+                    section.code.extend_from_slice(&[0; 2]);
+                    return Ok(());
+                }
+            }
+            _ => {}
+        }
+
+        let [operand] = single_operand(operands)?;
+        if is_label(operand) {
+            section.fixups.push(fixup(
+                section.code.len(),
+                FixupKind::Absolute {
+                    label: operand.to_string(),
+                    width: immediate_size,
+                },
+            ));
+            section.code.resize(section.code.len() + immediate_size, 0);
+        } else {
+            let value = parse_number(operand)?;
+            section
+                .code
+                .extend_from_slice(&encode_immediate(value, immediate_size)?);
+        }
+        Ok(())
+    }
+}
+
+fn single_operand<'a>(operands: &[&'a str]) -> Result<[&'a str; 1], AsmErrorKind> {
+    match operands {
+        [] => Err(AsmErrorKind::MissingImmediate),
+        [operand] => Ok([operand]),
+        [_, extra, ..] => Err(AsmErrorKind::UnexpectedOperand(extra.to_string())),
+    }
+}
+
+/// Numbers start with a digit or a sign, everything else is a label.
+fn is_label(operand: &str) -> bool {
+    !operand.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+')
+}
+
+fn parse_number(s: &str) -> Result<U256, AsmErrorKind> {
+    let parsed = match s.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16),
+        None => U256::from_str_radix(s, 10),
+    };
+    parsed.map_err(|_| AsmErrorKind::InvalidNumber(s.to_string()))
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>, AsmErrorKind> {
+    hex::decode(s).map_err(|_| AsmErrorKind::InvalidNumber(s.to_string()))
+}
+
+/// Encodes `value` as `width` big endian bytes.
+fn encode_immediate(value: U256, width: usize) -> Result<Vec<u8>, AsmErrorKind> {
+    if value.byte_len() > width {
+        return Err(AsmErrorKind::ImmediateTooLarge);
+    }
+    let bytes = value.to_be_bytes::<32>();
+    Ok(bytes[32 - width..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::bytes;
+
+    #[test]
+    fn assembles_legacy() {
+        let code = assemble(
+            "
+            start:  PUSH 0x0100     ; auto width
+                    push2 1
+                    PUSH end
+                    JUMP
+            data:   .data 0xdead
+                    .bytes 0xbe
+            end:    JUMPDEST
+                    PUSH1 data
+                    PUSH start
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            code,
+            bytes!("610100 610001 61000d 56 dead be 5b 600a 610000")
+        );
+    }
+
+    #[test]
+    fn legacy_errors() {
+        let error = |source| assemble(source).unwrap_err();
+        assert_eq!(
+            error("STOP\nFOO"),
+            AsmError {
+                line: 2,
+                kind: AsmErrorKind::UnknownOpcode("FOO".into())
+            }
+        );
+        assert_eq!(error("PUSH1 0x0100").kind, AsmErrorKind::ImmediateTooLarge);
+        assert_eq!(error("PUSH1").kind, AsmErrorKind::MissingImmediate);
+        assert_eq!(
+            error("ADD 1").kind,
+            AsmErrorKind::UnexpectedOperand("1".into())
+        );
+        assert_eq!(
+            error("a:\na: STOP").kind,
+            AsmErrorKind::DuplicateLabel("a".into())
+        );
+        assert_eq!(
+            error("STOP\n\nPUSH missing"),
+            AsmError {
+                line: 3,
+                kind: AsmErrorKind::UnknownLabel("missing".into())
+            }
+        );
+        assert_eq!(error(".code").kind, AsmErrorKind::EofOnly(".code".into()));
+    }
+
+    #[test]
+    fn assembles_eof() {
+        let eof = assemble_eof(
+            "
+            .code main max_stack=1
+                    PUSH0
+                    RJUMPI done
+                    CALLF helper
+                    PUSH0
+                    RJUMPV done, done
+            done:   STOP
+            .code helper outputs=0
+                    RETF
+            .data 0x1234
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            eof.body.types_section,
+            [TypesSection::new(0, 0x80, 1), TypesSection::new(0, 0, 0)]
+        );
+        assert_eq!(
+            eof.body.code(0).unwrap(),
+            bytes!("5f e1000a e30001 5f e201 0000 0000 00")
+        );
+        assert_eq!(eof.body.code(1).unwrap(), bytes!("e4"));
+        assert_eq!(eof.body.data_section, bytes!("1234"));
+        assert_eq!(Eof::decode(eof.raw.clone()).unwrap(), eof);
+        crate::validate_eof_inner(&eof, Some(crate::CodeType::ReturnOrStop)).unwrap();
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     eip7702::{Eip7702Bytecode, EIP7702_MAGIC_BYTES},
+    metadata::Metadata,
     BytecodeDecodeError, Eof, JumpTable, LegacyAnalyzedBytecode, LegacyRawBytecode,
     EOF_MAGIC_BYTES,
 };
@@ -42,6 +43,15 @@ impl Bytecode {
         }
     }
 
+    /// Returns the Solidity or Vyper metadata trailer of legacy bytecode.
+    #[inline]
+    pub fn legacy_metadata(&self) -> Option<Metadata> {
+        match &self {
+            Self::LegacyAnalyzed(analyzed) => Metadata::decode(analyzed.original_byte_slice()),
+            _ => None,
+        }
+    }
+
     /// Calculates hash of the bytecode.
     pub fn hash_slow(&self) -> B256 {
         if self.is_empty() {
@@ -0,0 +1,462 @@
+//! Disassembler of legacy and EOF bytecode.
+//!
+//! [`Disassembly`] holds the decoded instructions of every code section and subcontainer. Its
+//! [`Display`](fmt::Display) output is accepted by the [`assembler`](crate::assembler) and
+//! assembles back into the same bytes.
+use crate::{
+    eof::{Eof, EofDecodeError, TypesSection},
+    legacy::{jump_target, JumpTarget},
+    opcode::{self, custom::CustomOpCodes, OpCode},
+    EOF_MAGIC_BYTES,
+};
+use core::fmt;
+use primitives::{hex, Bytes};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Part of the code an instruction refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", content = "index", rename_all = "snake_case")
+)]
+pub enum Target {
+    /// Offset in the same code section.
+    Pc(usize),
+    /// Index of an EOF code section.
+    CodeSection(usize),
+    /// Index of an EOF subcontainer.
+    Container(usize),
+}
+
+/// Decoded instruction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Instruction {
+    /// Offset in the code section.
+    pub pc: usize,
+    /// Opcode byte.
+    pub opcode: u8,
+    /// Opcode name, `Unknown` for unknown opcodes.
+    pub name: Cow<'static, str>,
+    /// Immediate bytes.
+    pub immediate: Bytes,
+    /// Whether the code ends before the end of the immediate.
+    pub truncated: bool,
+    /// Jump targets of `JUMP`, `JUMPI` and relative jumps, called code sections and created
+    /// subcontainers.
+    ///
+    /// Legacy jumps only have a target if it is pushed right before the jump and is a valid
+    /// `JUMPDEST`.
+    pub targets: Vec<Target>,
+}
+
+impl Instruction {
+    /// Returns the size of the instruction including its immediate.
+    #[inline]
+    pub fn len(&self) -> usize {
+        1 + self.immediate.len()
+    }
+
+    /// Returns `false`, an instruction always has an opcode.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+}
+
+/// Disassembled code section.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeSection {
+    /// Types of the section, `None` for legacy code.
+    pub types: Option<TypesSection>,
+    /// Instructions ordered by their offset.
+    pub instructions: Vec<Instruction>,
+}
+
+/// Disassembled legacy code or EOF container.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Disassembly {
+    /// Disassembled bytes.
+    pub code: Bytes,
+    /// Whether the code is an EOF container.
+    pub is_eof: bool,
+    /// Code sections, legacy code has a single section.
+    pub code_sections: Vec<CodeSection>,
+    /// Disassembled EOF subcontainers.
+    pub containers: Vec<Disassembly>,
+    /// EOF data section.
+    pub data: Bytes,
+}
+
+impl Disassembly {
+    /// Disassembles an EOF container if the code starts with the EOF magic, legacy code
+    /// otherwise.
+    pub fn new(code: Bytes) -> Result<Self, EofDecodeError> {
+        Self::new_with_custom(code, &CustomOpCodes::new())
+    }
+
+    /// Disassembles the same as [`Self::new`], decoding `custom` opcodes in legacy code.
+    pub fn new_with_custom(code: Bytes, custom: &CustomOpCodes) -> Result<Self, EofDecodeError> {
+        if code.starts_with(&EOF_MAGIC_BYTES) {
+            Self::eof(&Eof::decode(code)?)
+        } else {
+            Ok(Self::legacy_with_custom(code, custom))
+        }
+    }
+
+    /// Disassembles legacy code.
+    pub fn legacy(code: Bytes) -> Self {
+        Self::legacy_with_custom(code, &CustomOpCodes::new())
+    }
+
+    /// Disassembles legacy code, decoding the names and immediates of `custom` opcodes.
+    ///
+    /// The assembler doesn't know custom opcodes, they are written as `.bytes` followed by
+    /// their name in the comment.
+    pub fn legacy_with_custom(code: Bytes, custom: &CustomOpCodes) -> Self {
+        let instructions = decode_legacy(&code, custom);
+        Self {
+            code,
+            is_eof: false,
+            code_sections: Vec::from([CodeSection {
+                types: None,
+                instructions,
+            }]),
+            containers: Vec::new(),
+            data: Bytes::new(),
+        }
+    }
+
+    /// Disassembles an EOF container and its subcontainers.
+    ///
+    /// Code is not validated, invalid instructions are decoded the same as valid ones.
+    pub fn eof(eof: &Eof) -> Result<Self, EofDecodeError> {
+        let code_sections = eof
+            .body
+            .types_section
+            .iter()
+            .enumerate()
+            .map(|(index, types)| CodeSection {
+                types: Some(*types),
+                instructions: decode_eof(&eof.body.code(index).unwrap_or_default()),
+            })
+            .collect();
+        let containers = eof
+            .body
+            .container_section
+            .iter()
+            .map(|raw| Self::eof(&Eof::decode(raw.clone())?))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            code: eof.raw.clone(),
+            is_eof: true,
+            code_sections,
+            containers,
+            data: eof.body.data_section.clone(),
+        })
+    }
+
+    /// Writes the instructions of a code section.
+    fn fmt_section(&self, f: &mut fmt::Formatter<'_>, section: &CodeSection) -> fmt::Result {
+        let starts: BTreeSet<usize> = section.instructions.iter().map(|i| i.pc).collect();
+        let labels: BTreeSet<usize> = section
+            .instructions
+            .iter()
+            .flat_map(|i| &i.targets)
+            .filter_map(|target| match target {
+                Target::Pc(pc) if starts.contains(pc) => Some(*pc),
+                _ => None,
+            })
+            .collect();
+
+        let mut line = String::new();
+        for instruction in &section.instructions {
+            if labels.contains(&instruction.pc) {
+                writeln!(f, "label_{:04x}:", instruction.pc)?;
+            }
+            line.clear();
+            if self.fmt_instruction(&mut line, instruction, &labels) {
+                writeln!(f, "    {line:<40}; {:04x}", instruction.pc)?;
+                continue;
+            }
+            let mut bytes = Vec::from([instruction.opcode]);
+            bytes.extend_from_slice(&instruction.immediate);
+            line.clear();
+            line.push_str(".bytes ");
+            line.push_str(&hex::encode_prefixed(bytes));
+            if OpCode::new(instruction.opcode).is_none() && instruction.name != UNKNOWN {
+                writeln!(
+                    f,
+                    "    {line:<40}; {:04x} {}",
+                    instruction.pc, instruction.name
+                )?;
+            } else {
+                writeln!(f, "    {line:<40}; {:04x}", instruction.pc)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the instruction in assembler syntax, returns `false` if the assembler would
+    /// encode it differently.
+    fn fmt_instruction(
+        &self,
+        line: &mut String,
+        instruction: &Instruction,
+        labels: &BTreeSet<usize>,
+    ) -> bool {
+        use fmt::Write;
+
+        let Some(op) = OpCode::new(instruction.opcode) else {
+            return false;
+        };
+        let is_rjumpv = self.is_eof && instruction.opcode == opcode::RJUMPV;
+        if instruction.truncated
+            || !is_rjumpv && op.info().immediate_size() as usize != instruction.immediate.len()
+        {
+            return false;
+        }
+        line.push_str(op.as_str());
+
+        if !self.is_eof || instruction.immediate.is_empty() {
+            if !instruction.immediate.is_empty() {
+                let _ = write!(line, " {}", hex::encode_prefixed(&instruction.immediate));
+            }
+            return true;
+        }
+        match instruction.opcode {
+            opcode::RJUMP | opcode::RJUMPI | opcode::RJUMPV => {
+                for (i, target) in instruction.targets.iter().enumerate() {
+                    let Target::Pc(pc) = target else {
+                        return false;
+                    };
+                    if !labels.contains(pc) {
+                        return false;
+                    }
+                    let separator = if i == 0 { " " } else { ", " };
+                    let _ = write!(line, "{separator}label_{pc:04x}");
+                }
+                instruction.targets.len()
+                    == if instruction.opcode == opcode::RJUMPV {
+                        (instruction.immediate.len() - 1) / 2
+                    } else {
+                        1
+                    }
+            }
+            opcode::CALLF | opcode::JUMPF => match instruction.targets.first() {
+                Some(Target::CodeSection(index)) if *index < self.code_sections.len() => {
+                    let _ = write!(line, " section_{index}");
+                    true
+                }
+                _ => false,
+            },
+            _ => {
+                let _ = write!(line, " {}", hex::encode_prefixed(&instruction.immediate));
+                true
+            }
+        }
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_eof {
+            return self.fmt_section(f, &self.code_sections[0]);
+        }
+
+        for (index, section) in self.code_sections.iter().enumerate() {
+            let types = section.types.unwrap_or_default();
+            writeln!(
+                f,
+                ".code section_{index} inputs={} outputs={} max_stack={}",
+                types.inputs, types.outputs, types.max_stack_size
+            )?;
+            self.fmt_section(f, section)?;
+        }
+        for (index, container) in self.containers.iter().enumerate() {
+            writeln!(f, ".container {}", hex::encode_prefixed(&container.code))?;
+            writeln!(f, "; container {index}")?;
+            for line in container.to_string().lines() {
+                writeln!(f, ";   {line}")?;
+            }
+        }
+        if !self.data.is_empty() {
+            writeln!(f, ".data {}", hex::encode_prefixed(&self.data))?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of unknown opcodes.
+const UNKNOWN: &str = "Unknown";
+
+/// Decodes the instruction at `pc` with an immediate of `size` bytes.
+fn decode_instruction(code: &[u8], pc: usize, size: usize, name: &'static str) -> Instruction {
+    let opcode = code[pc];
+    let end = (pc + 1 + size).min(code.len());
+    Instruction {
+        pc,
+        opcode,
+        name: Cow::Borrowed(name),
+        immediate: Bytes::copy_from_slice(&code[pc + 1..end]),
+        truncated: end < pc + 1 + size,
+        targets: Vec::new(),
+    }
+}
+
+fn decode_legacy(code: &[u8], custom: &CustomOpCodes) -> Vec<Instruction> {
+    let immediate_sizes = custom.immediate_sizes();
+    let jump_table = custom.analyze_legacy(code);
+    let mut instructions: Vec<Instruction> = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let name = custom.info(opcode).map_or(UNKNOWN, |info| info.name());
+        let mut instruction =
+            decode_instruction(code, pc, immediate_sizes[opcode as usize] as usize, name);
+        if matches!(opcode, opcode::JUMP | opcode::JUMPI) {
+            let previous = instructions.last().map(|i| i.pc);
+            if let JumpTarget::Static(target) = jump_target(code, previous, &jump_table) {
+                instruction.targets.push(Target::Pc(target));
+            }
+        }
+        pc += instruction.len();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+fn decode_eof(code: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let mut size = OpCode::info_by_op(opcode).map_or(0, |info| info.immediate_size() as usize);
+        if opcode == opcode::RJUMPV {
+            size += code
+                .get(pc + 1)
+                .map_or(0, |max_index| (*max_index as usize + 1) * 2);
+        }
+        let mut instruction = decode_instruction(code, pc, size, OpCode::name_by_op(opcode));
+        let next = pc + instruction.len();
+
+        if !instruction.truncated {
+            let immediate = &instruction.immediate;
+            let relative = |offset: &[u8]| {
+                let offset = i16::from_be_bytes([offset[0], offset[1]]) as isize;
+                next.checked_add_signed(offset).map(Target::Pc)
+            };
+            let u16_at = |i: usize| u16::from_be_bytes([immediate[i], immediate[i + 1]]) as usize;
+            instruction.targets = match opcode {
+                opcode::RJUMP | opcode::RJUMPI => relative(immediate).into_iter().collect(),
+                opcode::RJUMPV => immediate[1..].chunks(2).filter_map(relative).collect(),
+                opcode::CALLF | opcode::JUMPF => Vec::from([Target::CodeSection(u16_at(0))]),
+                opcode::EOFCREATE | opcode::RETURNCONTRACT => {
+                    Vec::from([Target::Container(immediate[0] as usize)])
+                }
+                _ => Vec::new(),
+            };
+        }
+        pc = next;
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+#[cfg(all(test, feature = "parse"))]
+mod tests {
+    use super::*;
+    use crate::{
+        assembler::{assemble, assemble_eof},
+        opcode::{immediate_size, OpCodeInfo},
+    };
+    use primitives::bytes;
+
+    #[test]
+    fn disassembles_legacy() {
+        let code = bytes!("6004 56 0c 5b 6005 57 61ff");
+        let disassembly = Disassembly::new(code.clone()).unwrap();
+        let instructions = &disassembly.code_sections[0].instructions;
+        assert_eq!(
+            instructions.iter().map(|i| i.pc).collect::<Vec<_>>(),
+            [0, 2, 3, 4, 5, 7, 8]
+        );
+        assert_eq!(instructions[1].targets, [Target::Pc(4)]);
+        // 5 is not a `JUMPDEST`.
+        assert!(instructions[5].targets.is_empty());
+        assert_eq!(instructions[2].name, "Unknown");
+        assert!(instructions[6].truncated);
+
+        let text = disassembly.to_string();
+        assert!(text.contains("label_0004:\n    JUMPDEST"));
+        assert!(text.contains("    .bytes 0x0c "));
+        assert_eq!(assemble(&text).unwrap(), code);
+    }
+
+    #[test]
+    fn disassembles_custom_opcodes() {
+        let mut custom = CustomOpCodes::new();
+        custom
+            .register(0x0c, immediate_size(OpCodeInfo::new("PUSHB"), 1))
+            .unwrap();
+        // `0x5b` is the immediate of `PUSHB`, not a `JUMPDEST`.
+        let code = bytes!("0c 5b 6001 56");
+        let disassembly = Disassembly::legacy_with_custom(code.clone(), &custom);
+        let instructions = &disassembly.code_sections[0].instructions;
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0].name, "PUSHB");
+        assert_eq!(instructions[0].immediate, bytes!("5b"));
+        assert!(instructions[2].targets.is_empty());
+
+        let text = disassembly.to_string();
+        assert!(text.contains("    .bytes 0x0c5b "));
+        assert!(text.contains("; 0000 PUSHB\n"));
+        assert_eq!(assemble(&text).unwrap(), code);
+    }
+
+    #[test]
+    fn disassembles_eof() {
+        let container = assemble_eof(".code max_stack=0\nSTOP").unwrap();
+        let eof = assemble_eof(&format!(
+            "
+            .code main max_stack=1
+                    PUSH0
+                    RJUMPI done
+                    CALLF helper
+                    PUSH0
+                    RJUMPV done, done
+            done:   STOP
+                    .bytes 0xe0
+
+            .code helper outputs=0
+                    RETF
+            .container {}
+            .data 0x1234
+            ",
+            container.raw
+        ))
+        .unwrap();
+
+        let disassembly = Disassembly::eof(&eof).unwrap();
+        assert_eq!(disassembly.code_sections.len(), 2);
+        assert_eq!(disassembly.containers.len(), 1);
+        let main = &disassembly.code_sections[0].instructions;
+        assert_eq!(main[1].targets, [Target::Pc(14)]);
+        assert_eq!(main[2].targets, [Target::CodeSection(1)]);
+        assert_eq!(main[4].targets, [Target::Pc(14), Target::Pc(14)]);
+
+        let text = disassembly.to_string();
+        assert!(text.contains("    RJUMPV label_000e, label_000e "));
+        assert!(text.contains("    CALLF section_1 "));
+        assert!(text.contains("    .bytes 0xe0 "));
+        assert_eq!(assemble_eof(&text).unwrap(), eof);
+    }
+}
@@ -1,4 +1,5 @@
 mod body;
+mod builder;
 mod decode_helpers;
 mod header;
 pub mod printer;
@@ -6,6 +7,7 @@ mod types_section;
 pub mod verification;
 
 pub use body::EofBody;
+pub use builder::EofBuilder;
 pub use header::EofHeader;
 pub use types_section::TypesSection;
 pub use verification::*;
@@ -64,6 +66,11 @@ impl Eof {
         validate_eof_inner(self, Some(mode))
     }
 
+    /// Validates the container as initcode using the given limits.
+    pub fn validate_with_limits(&self, limits: EofValidationLimits) -> Result<(), EofError> {
+        validate_eof_with_limits(self, Some(CodeType::ReturnContract), limits)
+    }
+
     /// Creates a new EOF container from the given body.
     pub fn new(body: EofBody) -> Self {
         body.into_eof()
@@ -0,0 +1,209 @@
+use super::{
+    eof_code_max_stack_size, validate_eof_with_limits, AccessTracker, CodeType, Eof, EofBody,
+    EofError, EofValidationLimits, TypesSection,
+};
+use primitives::Bytes;
+use std::vec::Vec;
+
+/// Outputs of a non-returning code section.
+const NON_RETURNING: u8 = 0x80;
+
+/// Builder of [`Eof`] containers.
+///
+/// The max stack height of every code section is computed from the stack analysis of
+/// [`validate_eof_code`](super::validate_eof_code).
+///
+/// # Example
+///
+/// ```
+/// use revm_bytecode::{eof::EofBuilder, opcode::{CALLF, PUSH0, RETF, STOP}};
+///
+/// let eof = EofBuilder::new()
+///     .non_returning_code_section(0, [CALLF, 0x00, 0x01, STOP])
+///     .code_section(0, 1, [PUSH0, RETF])
+///     .build()
+///     .unwrap();
+/// assert_eq!(eof.body.types_section[1].max_stack_size, 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EofBuilder {
+    types: Vec<TypesSection>,
+    codes: Vec<Bytes>,
+    containers: Vec<Bytes>,
+    data: Vec<u8>,
+    code_type: Option<CodeType>,
+    limits: EofValidationLimits,
+}
+
+impl EofBuilder {
+    /// Returns a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a code section returning `outputs` stack items.
+    pub fn code_section(mut self, inputs: u8, outputs: u8, code: impl Into<Bytes>) -> Self {
+        self.types.push(TypesSection::new(inputs, outputs, 0));
+        self.codes.push(code.into());
+        self
+    }
+
+    /// Adds a non-returning code section.
+    ///
+    /// The first code section must be non-returning and have no inputs.
+    pub fn non_returning_code_section(self, inputs: u8, code: impl Into<Bytes>) -> Self {
+        self.code_section(inputs, NON_RETURNING, code)
+    }
+
+    /// Adds a subcontainer.
+    pub fn container(self, container: Eof) -> Self {
+        self.raw_container(container.raw)
+    }
+
+    /// Adds an encoded subcontainer.
+    pub fn raw_container(mut self, container: impl Into<Bytes>) -> Self {
+        self.containers.push(container.into());
+        self
+    }
+
+    /// Appends bytes to the data section.
+    pub fn data(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.data.extend_from_slice(data.as_ref());
+        self
+    }
+
+    /// Sets the kind of the container used by [`build`](Self::build).
+    ///
+    /// By default it is derived from the instructions, see
+    /// [`validate_eof_inner`](super::validate_eof_inner).
+    pub fn code_type(mut self, code_type: CodeType) -> Self {
+        self.code_type = Some(code_type);
+        self
+    }
+
+    /// Sets the limits used by [`build`](Self::build).
+    pub fn limits(mut self, limits: EofValidationLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Builds and validates the container.
+    ///
+    /// Errors are the same as the ones returned by
+    /// [`validate_eof_with_limits`] for the container.
+    pub fn build(self) -> Result<Eof, EofError> {
+        let code_type = self.code_type;
+        let limits = self.limits;
+        let eof = self.build_unchecked();
+        validate_eof_with_limits(&eof, code_type, limits)?;
+        Ok(eof)
+    }
+
+    /// Builds the container without validating it.
+    ///
+    /// Max stack height of a code section that fails the stack analysis is left at zero.
+    pub fn build_unchecked(self) -> Eof {
+        let mut types = self.types;
+        for (index, code) in self.codes.iter().enumerate() {
+            let mut tracker = AccessTracker::new(None, self.codes.len(), self.containers.len())
+                .with_stack_limit(self.limits.stack_limit);
+            if let Ok(Some(max_stack_size)) = eof_code_max_stack_size(
+                code,
+                self.data.len(),
+                index,
+                self.containers.len(),
+                &types,
+                &mut tracker,
+            ) {
+                types[index].max_stack_size = max_stack_size;
+            }
+        }
+
+        let mut code = Vec::new();
+        let mut code_section = Vec::with_capacity(self.codes.len());
+        for section in &self.codes {
+            code.extend_from_slice(section);
+            code_section.push(code.len());
+        }
+        EofBody {
+            types_section: types,
+            code_section,
+            code: code.into(),
+            container_section: self.containers,
+            data_section: self.data.into(),
+            is_data_filled: true,
+        }
+        .into_eof()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eof::{validate_eof_inner, EofValidationError},
+        opcode::{ADD, CALLF, DUP1, EOFCREATE, PUSH0, RETF, RETURNCONTRACT, RJUMPI, STOP},
+    };
+
+    #[test]
+    fn computes_max_stack() {
+        let eof = EofBuilder::new()
+            .non_returning_code_section(0, [PUSH0, RJUMPI, 0x00, 0x03, CALLF, 0x00, 0x01, STOP])
+            .code_section(0, 1, [PUSH0, DUP1, DUP1, ADD, ADD, RETF])
+            .data([0x12, 0x34])
+            .build()
+            .unwrap();
+        assert_eq!(
+            eof.body.types_section,
+            [TypesSection::new(0, 0x80, 1), TypesSection::new(0, 1, 3)]
+        );
+        assert_eq!(eof.body.data_section, Bytes::from_static(&[0x12, 0x34]));
+        assert_eq!(Eof::decode(eof.raw.clone()).unwrap(), eof);
+    }
+
+    #[test]
+    fn nested_containers() {
+        let runtime = EofBuilder::new()
+            .non_returning_code_section(0, [STOP])
+            .build()
+            .unwrap();
+        let initcode = EofBuilder::new()
+            .non_returning_code_section(0, [PUSH0, PUSH0, RETURNCONTRACT, 0x00])
+            .container(runtime)
+            .code_type(CodeType::ReturnContract)
+            .build()
+            .unwrap();
+        let eof = EofBuilder::new()
+            .non_returning_code_section(0, [PUSH0, PUSH0, PUSH0, PUSH0, EOFCREATE, 0x00, STOP])
+            .container(initcode.clone())
+            .build()
+            .unwrap();
+        assert_eq!(eof.body.types_section[0].max_stack_size, 4);
+        assert_eq!(eof.body.container_section, [initcode.raw]);
+    }
+
+    #[test]
+    fn reports_validation_errors() {
+        let builder = EofBuilder::new()
+            .non_returning_code_section(0, [STOP])
+            .code_section(0, 0, [RETF]);
+        let error = builder.clone().build().unwrap_err();
+        assert_eq!(
+            error,
+            EofError::Validation(EofValidationError::CodeSectionNotAccessed)
+        );
+        assert_eq!(
+            validate_eof_inner(&builder.build_unchecked(), None),
+            Err(error)
+        );
+
+        let error = EofBuilder::new()
+            .non_returning_code_section(0, [ADD, STOP])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            EofError::Validation(EofValidationError::StackUnderflow)
+        );
+    }
+}
@@ -20,11 +20,21 @@ pub fn validate_raw_eof_inner(
     raw: Bytes,
     first_code_type: Option<CodeType>,
 ) -> Result<Eof, EofError> {
-    if raw.len() > MAX_INITCODE_SIZE {
+    validate_raw_eof_with_limits(raw, first_code_type, EofValidationLimits::default())
+}
+
+/// Decodes `raw` into an [`Eof`] container and validates it against the given limits.
+#[inline]
+pub fn validate_raw_eof_with_limits(
+    raw: Bytes,
+    first_code_type: Option<CodeType>,
+    limits: EofValidationLimits,
+) -> Result<Eof, EofError> {
+    if raw.len() > limits.max_initcode_size {
         return Err(EofError::Decode(EofDecodeError::InvalidEOFSize));
     }
     let eof = Eof::decode(raw)?;
-    validate_eof_inner(&eof, first_code_type)?;
+    validate_eof_with_limits(&eof, first_code_type, limits)?;
     Ok(eof)
 }
 
@@ -41,12 +51,22 @@ pub fn validate_eof(eof: &Eof) -> Result<(), EofError> {
 
 #[inline]
 pub fn validate_eof_inner(eof: &Eof, first_code_type: Option<CodeType>) -> Result<(), EofError> {
+    validate_eof_with_limits(eof, first_code_type, EofValidationLimits::default())
+}
+
+/// Fully validates an [`Eof`] container against the given limits.
+#[inline]
+pub fn validate_eof_with_limits(
+    eof: &Eof,
+    first_code_type: Option<CodeType>,
+    limits: EofValidationLimits,
+) -> Result<(), EofError> {
     // Data needs to be filled first first container.
     if !eof.body.is_data_filled {
         return Err(EofError::Validation(EofValidationError::DataNotFilled));
     }
     if eof.body.container_section.is_empty() {
-        validate_eof_codes(eof, first_code_type)?;
+        validate_eof_codes(eof, first_code_type, limits.stack_limit)?;
         return Ok(());
     }
 
@@ -55,7 +75,7 @@ pub fn validate_eof_inner(eof: &Eof, first_code_type: Option<CodeType>) -> Resul
 
     while let Some((eof, code_type)) = stack.pop() {
         // Validate the current container.
-        let tracker_containers = validate_eof_codes(&eof, code_type)?;
+        let tracker_containers = validate_eof_codes(&eof, code_type, limits.stack_limit)?;
         // Decode subcontainers and push them to the stack.
         for (container, code_type) in eof
             .body
@@ -70,6 +90,32 @@ pub fn validate_eof_inner(eof: &Eof, first_code_type: Option<CodeType>) -> Resul
     Ok(())
 }
 
+/// Limits that are applied while validating an [`Eof`] container.
+///
+/// Chains that raise the contract size or stack limits need the validation
+/// to follow their configuration instead of the mainnet constants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EofValidationLimits {
+    /// Maximum size of the raw initcode container.
+    ///
+    /// By default it is [`MAX_INITCODE_SIZE`].
+    pub max_initcode_size: usize,
+    /// Maximum stack height that code sections are allowed to reach.
+    ///
+    /// By default it is [`STACK_LIMIT`].
+    pub stack_limit: usize,
+}
+
+impl Default for EofValidationLimits {
+    fn default() -> Self {
+        Self {
+            max_initcode_size: MAX_INITCODE_SIZE,
+            stack_limit: STACK_LIMIT,
+        }
+    }
+}
+
 /// Validates an [`Eof`] structure, without recursing into containers.
 ///
 /// Returns a list of all sub containers that are accessed.
@@ -77,6 +123,7 @@ pub fn validate_eof_inner(eof: &Eof, first_code_type: Option<CodeType>) -> Resul
 pub fn validate_eof_codes(
     eof: &Eof,
     this_code_type: Option<CodeType>,
+    stack_limit: usize,
 ) -> Result<Vec<CodeType>, EofValidationError> {
     if eof.body.code_section.len() != eof.body.types_section.len() {
         return Err(EofValidationError::InvalidTypesSection);
@@ -99,7 +146,8 @@ pub fn validate_eof_codes(
         this_code_type,
         eof.body.code_section.len(),
         eof.body.container_section.len(),
-    );
+    )
+    .with_stack_limit(stack_limit);
 
     while let Some(index) = tracker.processing_stack.pop() {
         // Assume `index` is correct.
@@ -263,6 +311,8 @@ pub struct AccessTracker {
     ///
     /// None means it is not accessed.
     pub subcontainers: Vec<Option<CodeType>>,
+    /// Maximum stack height that code sections can reach.
+    pub stack_limit: usize,
 }
 
 impl AccessTracker {
@@ -286,12 +336,19 @@ impl AccessTracker {
             codes: vec![false; codes_size],
             processing_stack: Vec::with_capacity(4),
             subcontainers: vec![None; subcontainers_size],
+            stack_limit: STACK_LIMIT,
         };
         this.codes[0] = true;
         this.processing_stack.push(0);
         this
     }
 
+    /// Sets the maximum stack height that code sections can reach.
+    pub fn with_stack_limit(mut self, stack_limit: usize) -> Self {
+        self.stack_limit = stack_limit;
+        self
+    }
+
     /// Mark code as accessed.
     ///
     /// If code was not accessed before, it will be added to the processing stack.
@@ -410,6 +467,33 @@ pub fn validate_eof_code(
     types: &[TypesSection],
     tracker: &mut AccessTracker,
 ) -> Result<(), EofValidationError> {
+    let max_stack_size = eof_code_max_stack_size(
+        code,
+        data_size,
+        this_types_index,
+        num_of_containers,
+        types,
+        tracker,
+    )?;
+    if max_stack_size != Some(types[this_types_index].max_stack_size) {
+        // Stack overflow
+        return Err(EofValidationError::MaxStackMismatch);
+    }
+    Ok(())
+}
+
+/// Validates the code the same as [`validate_eof_code`] but returns the max stack height
+/// instead of checking it against the types section.
+///
+/// Returns `None` if the height doesn't fit into the types section.
+pub fn eof_code_max_stack_size(
+    code: &[u8],
+    data_size: usize,
+    this_types_index: usize,
+    num_of_containers: usize,
+    types: &[TypesSection],
+    tracker: &mut AccessTracker,
+) -> Result<Option<u16>, EofValidationError> {
     let this_types = &types[this_types_index];
 
     #[derive(Debug, Copy, Clone)]
@@ -566,7 +650,7 @@ pub fn validate_eof_code(
                 // We decrement by `types.inputs` as they are considered as send
                 // to the called code and included in types.max_stack_size.
                 if this_instruction.biggest - stack_requirement + target_types.max_stack_size as i32
-                    > STACK_LIMIT as i32
+                    > tracker.stack_limit as i32
                 {
                     // If stack max items + called code max stack size
                     return Err(EofValidationError::StackOverflow);
@@ -584,7 +668,7 @@ pub fn validate_eof_code(
                 // And included in types.max_stack_size.
                 if this_instruction.biggest - target_types.inputs as i32
                     + target_types.max_stack_size as i32
-                    > STACK_LIMIT as i32
+                    > tracker.stack_limit as i32
                 {
                     // stack overflow
                     return Err(EofValidationError::StackOverflow);
@@ -610,7 +694,7 @@ pub fn validate_eof_code(
                     }
 
                     // If this instruction max + target_types max is more then stack limit.
-                    if this_instruction.biggest + stack_requirement > STACK_LIMIT as i32 {
+                    if this_instruction.biggest + stack_requirement > tracker.stack_limit as i32 {
                         return Err(EofValidationError::StackOverflow);
                     }
                 }
@@ -749,12 +833,7 @@ pub fn validate_eof_code(
         max_stack_requirement = core::cmp::max(opcode.biggest, max_stack_requirement);
     }
 
-    if max_stack_requirement != types[this_types_index].max_stack_size as i32 {
-        // Stack overflow
-        return Err(EofValidationError::MaxStackMismatch);
-    }
-
-    Ok(())
+    Ok(u16::try_from(max_stack_requirement).ok())
 }
 
 #[cfg(test)]
@@ -817,6 +896,31 @@ mod test {
         );
     }
 
+    #[test]
+    fn custom_limits() {
+        let raw: Bytes = hex!(
+            "ef000101000c02000300040004000204000000008000020002000100010001e30001005fe500025fe4"
+        )
+        .into();
+        let limits = EofValidationLimits {
+            stack_limit: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_raw_eof_with_limits(raw.clone(), None, limits),
+            Err(EofError::Validation(EofValidationError::StackOverflow))
+        );
+
+        let limits = EofValidationLimits {
+            max_initcode_size: raw.len() - 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_raw_eof_with_limits(raw, None, limits),
+            Err(EofError::Decode(EofDecodeError::InvalidEOFSize))
+        );
+    }
+
     #[test]
     fn size_limit() {
         let eof = validate_raw_eof_inner(
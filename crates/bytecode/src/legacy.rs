@@ -1,7 +1,16 @@
 mod analyzed;
+mod blocks;
+mod control_flow;
 mod jump_map;
 mod raw;
 
 pub use analyzed::LegacyAnalyzedBytecode;
+pub use blocks::{
+    analyze_basic_blocks, ends_basic_block, legacy_static_gas, BasicBlock, BasicBlocks,
+};
+pub(crate) use control_flow::jump_target;
+pub use control_flow::{BlockExit, CfgBlock, ControlFlowGraph, JumpTarget};
 pub use jump_map::JumpTable;
-pub use raw::{analyze_legacy, LegacyRawBytecode};
+pub use raw::{
+    analyze_legacy, analyze_legacy_with_immediates, legacy_immediate_sizes, LegacyRawBytecode,
+};
@@ -0,0 +1,233 @@
+use super::legacy_immediate_sizes;
+use crate::opcode::{self, OpCode};
+use std::vec::Vec;
+
+/// Straight-line sequence of legacy instructions.
+///
+/// A block starts at the beginning of the code, at a `JUMPDEST` or after an instruction that
+/// ends a block. Instructions that jump, halt or whose gas cost is not static, see
+/// [`legacy_static_gas`], end a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BasicBlock {
+    /// Offset of the first instruction.
+    pub start: u32,
+    /// Offset after the last instruction, including its immediate.
+    pub end: u32,
+    /// Sum of the static gas of the instructions.
+    pub static_gas: u64,
+    /// Number of stack items the block reads below the stack height at its entry.
+    pub stack_required: u32,
+    /// Maximum stack height the block reaches above the stack height at its entry.
+    pub stack_max_growth: u32,
+}
+
+impl BasicBlock {
+    /// Returns `true` if a stack of `stack_len` items can execute the whole block without
+    /// underflowing or exceeding `stack_limit`.
+    #[inline]
+    pub fn fits_stack(&self, stack_len: usize, stack_limit: usize) -> bool {
+        stack_len >= self.stack_required as usize
+            && stack_len + self.stack_max_growth as usize <= stack_limit
+    }
+}
+
+/// Basic blocks of legacy bytecode, ordered by their offset.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BasicBlocks(pub Vec<BasicBlock>);
+
+impl BasicBlocks {
+    /// Returns the blocks.
+    #[inline]
+    pub fn as_slice(&self) -> &[BasicBlock] {
+        &self.0
+    }
+
+    /// Returns the number of blocks.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no blocks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the block that starts at `pc`.
+    pub fn block_at(&self, pc: usize) -> Option<&BasicBlock> {
+        let index = self
+            .0
+            .binary_search_by_key(&pc, |block| block.start as usize)
+            .ok()?;
+        self.0.get(index)
+    }
+}
+
+/// Returns the gas charged by a legacy opcode if it is the same in all hardforks and
+/// independent of the operands, memory and state.
+///
+/// Returns `None` for other opcodes, including unknown and custom opcodes, and for `GAS`
+/// which reads the remaining gas.
+pub const fn legacy_static_gas(opcode: u8) -> Option<u64> {
+    // Gas tiers from the yellow paper.
+    const ZERO: u64 = 0;
+    const BASE: u64 = 2;
+    const VERYLOW: u64 = 3;
+    const LOW: u64 = 5;
+    const MID: u64 = 8;
+    const HIGH: u64 = 10;
+    const JUMPDEST: u64 = 1;
+    const WARM_STORAGE_READ: u64 = 100;
+
+    let gas = match opcode {
+        opcode::STOP => ZERO,
+        opcode::ADD | opcode::SUB => VERYLOW,
+        opcode::MUL | opcode::DIV | opcode::SDIV | opcode::MOD | opcode::SMOD => LOW,
+        opcode::ADDMOD | opcode::MULMOD => MID,
+        opcode::SIGNEXTEND => LOW,
+        opcode::LT..=opcode::SAR => VERYLOW,
+        opcode::ADDRESS
+        | opcode::ORIGIN
+        | opcode::CALLER
+        | opcode::CALLVALUE
+        | opcode::CALLDATASIZE
+        | opcode::CODESIZE
+        | opcode::GASPRICE
+        | opcode::RETURNDATASIZE => BASE,
+        opcode::CALLDATALOAD => VERYLOW,
+        opcode::COINBASE..=opcode::CHAINID | opcode::BASEFEE | opcode::BLOBBASEFEE => BASE,
+        opcode::SELFBALANCE => LOW,
+        opcode::BLOBHASH => VERYLOW,
+        opcode::POP | opcode::PC | opcode::MSIZE | opcode::PUSH0 => BASE,
+        opcode::JUMP => MID,
+        opcode::JUMPI => HIGH,
+        opcode::JUMPDEST => JUMPDEST,
+        opcode::TLOAD | opcode::TSTORE => WARM_STORAGE_READ,
+        opcode::PUSH1..=opcode::PUSH32 | opcode::DUP1..=opcode::SWAP16 => VERYLOW,
+        _ => return None,
+    };
+    Some(gas)
+}
+
+/// Returns `true` if the opcode is the last instruction of its [`BasicBlock`].
+#[inline]
+pub const fn ends_basic_block(opcode: u8) -> bool {
+    matches!(opcode, opcode::STOP | opcode::JUMP | opcode::JUMPI)
+        || legacy_static_gas(opcode).is_none()
+}
+
+/// Splits legacy bytecode into basic blocks.
+///
+/// Immediates are skipped the same as in [`analyze_legacy`](super::analyze_legacy).
+pub fn analyze_basic_blocks(bytecode: &[u8]) -> BasicBlocks {
+    let immediate_sizes = legacy_immediate_sizes();
+    let mut blocks = Vec::new();
+    let mut block: Option<BasicBlock> = None;
+    // Stack height relative to the entry of the current block.
+    let mut height: i64 = 0;
+
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        let opcode = bytecode[pc];
+        if opcode == opcode::JUMPDEST {
+            blocks.extend(block.take());
+        }
+        let current = block.get_or_insert_with(|| {
+            height = 0;
+            BasicBlock {
+                start: pc as u32,
+                ..Default::default()
+            }
+        });
+
+        let (inputs, outputs) = OpCode::new(opcode).map_or((0, 0), |op| op.input_output());
+        let required = inputs as i64 - height;
+        if required > current.stack_required as i64 {
+            current.stack_required = required as u32;
+        }
+        height += outputs as i64 - inputs as i64;
+        if height > current.stack_max_growth as i64 {
+            current.stack_max_growth = height as u32;
+        }
+        current.static_gas += legacy_static_gas(opcode).unwrap_or_default();
+
+        pc = (pc + 1 + immediate_sizes[opcode as usize] as usize).min(bytecode.len());
+        current.end = pc as u32;
+        if ends_basic_block(opcode) {
+            blocks.extend(block.take());
+        }
+    }
+    blocks.extend(block);
+    BasicBlocks(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{ADD, CALL, DUP2, GAS, JUMPDEST, JUMPI, POP, PUSH1, STOP};
+
+    #[test]
+    fn splits_blocks() {
+        let blocks = analyze_basic_blocks(&[
+            PUSH1, 0x01, ADD, JUMPDEST, DUP2, PUSH1, 0x00, JUMPI, POP, GAS, CALL, STOP,
+        ]);
+        assert_eq!(
+            blocks.as_slice(),
+            [
+                BasicBlock {
+                    start: 0,
+                    end: 3,
+                    static_gas: 6,
+                    stack_required: 1,
+                    stack_max_growth: 1,
+                },
+                BasicBlock {
+                    start: 3,
+                    end: 8,
+                    static_gas: 17,
+                    stack_required: 2,
+                    stack_max_growth: 2,
+                },
+                BasicBlock {
+                    start: 8,
+                    end: 10,
+                    static_gas: 2,
+                    stack_required: 1,
+                    stack_max_growth: 0,
+                },
+                BasicBlock {
+                    start: 10,
+                    end: 11,
+                    static_gas: 0,
+                    stack_required: 7,
+                    stack_max_growth: 0,
+                },
+                BasicBlock {
+                    start: 11,
+                    end: 12,
+                    static_gas: 0,
+                    stack_required: 0,
+                    stack_max_growth: 0,
+                },
+            ]
+        );
+        assert_eq!(blocks.block_at(3), blocks.as_slice().get(1));
+        assert_eq!(blocks.block_at(4), None);
+    }
+
+    #[test]
+    fn fits_stack() {
+        let block = BasicBlock {
+            stack_required: 2,
+            stack_max_growth: 3,
+            ..Default::default()
+        };
+        assert!(!block.fits_stack(1, 1024));
+        assert!(block.fits_stack(2, 1024));
+        assert!(block.fits_stack(1021, 1024));
+        assert!(!block.fits_stack(1022, 1024));
+    }
+}
@@ -0,0 +1,347 @@
+use super::{analyze_legacy, legacy_immediate_sizes, JumpTable};
+use crate::opcode::{self, OpCode};
+use core::fmt::Write;
+use primitives::{hex, U256};
+use std::{collections::VecDeque, string::String, vec, vec::Vec};
+
+/// Target of a `JUMP` or `JUMPI`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", content = "target", rename_all = "snake_case")
+)]
+pub enum JumpTarget {
+    /// Pushed right before the jump and a valid `JUMPDEST`.
+    Static(usize),
+    /// Pushed right before the jump but not a valid `JUMPDEST`, the jump halts if taken.
+    Invalid(U256),
+    /// Computed at runtime.
+    Dynamic,
+}
+
+/// How execution leaves a [`CfgBlock`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", content = "target", rename_all = "snake_case")
+)]
+pub enum BlockExit {
+    /// Continues with the next block.
+    FallThrough,
+    /// Halts with a terminating or unknown opcode, or at the end of the code.
+    Halt,
+    /// Unconditional `JUMP`.
+    Jump(JumpTarget),
+    /// Conditional `JUMPI`, continues with the next block if the condition is zero.
+    JumpI(JumpTarget),
+}
+
+/// Basic block of a [`ControlFlowGraph`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CfgBlock {
+    /// Offset of the first instruction.
+    pub start: usize,
+    /// Offset after the last instruction, including its immediate.
+    pub end: usize,
+    /// How execution leaves the block.
+    pub exit: BlockExit,
+    /// Offsets of blocks that statically follow this block.
+    pub successors: Vec<usize>,
+    /// Whether the block can be reached from the entry of the code.
+    pub reachable: bool,
+}
+
+/// Control-flow graph of legacy bytecode.
+///
+/// Blocks start at the beginning of the code, at every `JUMPDEST` and after every jump or
+/// halting instruction. Jump targets pushed right before a jump are resolved and checked
+/// with [`analyze_legacy`], the same jump analysis the interpreter uses.
+///
+/// Targets of dynamic jumps are unknown, so if a reachable block ends with one, every block
+/// that starts with a `JUMPDEST` is considered reachable.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlFlowGraph {
+    /// Blocks ordered by their offset.
+    pub blocks: Vec<CfgBlock>,
+    /// Whether any reachable block ends with a dynamic jump.
+    pub has_dynamic_jumps: bool,
+}
+
+impl ControlFlowGraph {
+    /// Builds the graph of legacy bytecode.
+    pub fn new(bytecode: &[u8]) -> Self {
+        let jump_table = analyze_legacy(bytecode);
+        let immediate_sizes = legacy_immediate_sizes();
+        let mut blocks = Vec::new();
+
+        let mut start = 0;
+        // Offset of the previous instruction in the current block.
+        let mut previous = None;
+        let mut pc = 0;
+        while pc < bytecode.len() {
+            let opcode = bytecode[pc];
+            if opcode == opcode::JUMPDEST && pc != start {
+                blocks.push(new_block(start, pc, BlockExit::FallThrough));
+                start = pc;
+            }
+            let next = pc + 1 + immediate_sizes[opcode as usize] as usize;
+
+            let exit = match opcode {
+                opcode::JUMP => Some(BlockExit::Jump(jump_target(
+                    bytecode,
+                    previous,
+                    &jump_table,
+                ))),
+                opcode::JUMPI => Some(BlockExit::JumpI(jump_target(
+                    bytecode,
+                    previous,
+                    &jump_table,
+                ))),
+                _ if OpCode::new(opcode).is_none_or(|op| op.info().is_terminating()) => {
+                    Some(BlockExit::Halt)
+                }
+                _ => None,
+            };
+            if let Some(exit) = exit {
+                blocks.push(new_block(start, next.min(bytecode.len()), exit));
+                start = next;
+                previous = None;
+            } else {
+                previous = Some(pc);
+            }
+            pc = next;
+        }
+        if start < bytecode.len() {
+            // Code implicitly stops after the last instruction.
+            blocks.push(new_block(start, bytecode.len(), BlockExit::Halt));
+        }
+
+        let mut graph = Self {
+            blocks,
+            has_dynamic_jumps: false,
+        };
+        graph.link();
+        graph.mark_reachable(bytecode);
+        graph
+    }
+
+    /// Returns the block that starts at `pc`.
+    pub fn block_at(&self, pc: usize) -> Option<&CfgBlock> {
+        let index = self.blocks.binary_search_by_key(&pc, |b| b.start).ok()?;
+        self.blocks.get(index)
+    }
+
+    /// Returns offsets of blocks that can't be reached from the entry of the code.
+    pub fn unreachable_blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().filter(|b| !b.reachable).map(|b| b.start)
+    }
+
+    /// Renders the graph in Graphviz DOT format.
+    ///
+    /// `bytecode` must be the code the graph was built from, it is used to list the
+    /// instructions of every block. Unreachable blocks are gray and dynamic jumps are
+    /// dashed edges to a `dynamic` node.
+    pub fn to_dot(&self, bytecode: &[u8]) -> String {
+        let immediate_sizes = legacy_immediate_sizes();
+        let mut dot = String::from("digraph cfg {\n    node [shape=box fontname=monospace];\n");
+        for block in &self.blocks {
+            let mut label = String::new();
+            let mut pc = block.start;
+            while pc < block.end {
+                let opcode = bytecode[pc];
+                let size = immediate_sizes[opcode as usize] as usize;
+                let _ = write!(label, "{pc:04x}: ");
+                match OpCode::new(opcode) {
+                    Some(op) => label.push_str(op.as_str()),
+                    None => {
+                        let _ = write!(label, "UNKNOWN(0x{opcode:02X})");
+                    }
+                }
+                if size != 0 {
+                    let end = (pc + 1 + size).min(bytecode.len());
+                    let _ = write!(label, " 0x{}", hex::encode(&bytecode[pc + 1..end]));
+                }
+                label.push_str("\\l");
+                pc += 1 + size;
+            }
+            let style = if block.reachable {
+                ""
+            } else {
+                " style=filled fillcolor=gray"
+            };
+            let _ = writeln!(dot, "    b{} [label=\"{label}\"{style}];", block.start);
+
+            for successor in &block.successors {
+                let _ = writeln!(dot, "    b{} -> b{successor};", block.start);
+            }
+            if let BlockExit::Jump(JumpTarget::Dynamic) | BlockExit::JumpI(JumpTarget::Dynamic) =
+                block.exit
+            {
+                let _ = writeln!(dot, "    b{} -> dynamic [style=dashed];", block.start);
+            }
+        }
+        if self.has_dynamic_jumps {
+            dot.push_str("    dynamic [shape=ellipse];\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Fills successors of all blocks.
+    fn link(&mut self) {
+        let starts: Vec<usize> = self.blocks.iter().map(|b| b.start).collect();
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            let next = starts
+                .get(index + 1)
+                .copied()
+                .filter(|&next| next == block.end);
+            block.successors = match block.exit {
+                BlockExit::FallThrough => next.into_iter().collect(),
+                BlockExit::Halt => Vec::new(),
+                BlockExit::Jump(JumpTarget::Static(target)) => vec![target],
+                BlockExit::Jump(_) => Vec::new(),
+                BlockExit::JumpI(JumpTarget::Static(target)) => {
+                    let mut successors = vec![target];
+                    successors.extend(next.filter(|&next| next != target));
+                    successors
+                }
+                BlockExit::JumpI(_) => next.into_iter().collect(),
+            };
+        }
+    }
+
+    /// Marks blocks reachable from the entry of the code.
+    fn mark_reachable(&mut self, bytecode: &[u8]) {
+        let mut queue = VecDeque::from([0]);
+        let mut jumpdests_queued = false;
+        while let Some(index) = queue.pop_front() {
+            let Some(block) = self.blocks.get_mut(index) else {
+                continue;
+            };
+            if block.reachable {
+                continue;
+            }
+            block.reachable = true;
+
+            if matches!(
+                block.exit,
+                BlockExit::Jump(JumpTarget::Dynamic) | BlockExit::JumpI(JumpTarget::Dynamic)
+            ) {
+                self.has_dynamic_jumps = true;
+            }
+            let successors = block.successors.clone();
+            for successor in successors {
+                if let Ok(index) = self.blocks.binary_search_by_key(&successor, |b| b.start) {
+                    queue.push_back(index);
+                }
+            }
+            if self.has_dynamic_jumps && !jumpdests_queued {
+                jumpdests_queued = true;
+                let jumpdests = self
+                    .blocks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| bytecode[b.start] == opcode::JUMPDEST)
+                    .map(|(index, _)| index);
+                queue.extend(jumpdests.collect::<Vec<_>>());
+            }
+        }
+    }
+}
+
+fn new_block(start: usize, end: usize, exit: BlockExit) -> CfgBlock {
+    CfgBlock {
+        start,
+        end,
+        exit,
+        successors: Vec::new(),
+        reachable: false,
+    }
+}
+
+/// Resolves the target of a jump from the instruction before it.
+pub(crate) fn jump_target(
+    bytecode: &[u8],
+    previous: Option<usize>,
+    jump_table: &JumpTable,
+) -> JumpTarget {
+    let Some(pc) = previous else {
+        return JumpTarget::Dynamic;
+    };
+    let opcode = bytecode[pc];
+    if !(opcode::PUSH0..=opcode::PUSH32).contains(&opcode) {
+        return JumpTarget::Dynamic;
+    }
+    let size = (opcode - opcode::PUSH0) as usize;
+    let mut immediate = [0; 32];
+    let end = (pc + 1 + size).min(bytecode.len());
+    immediate[..end - pc - 1].copy_from_slice(&bytecode[pc + 1..end]);
+    let target = U256::from_be_slice(&immediate[..size]);
+
+    match usize::try_from(target) {
+        Ok(target) if jump_table.is_valid(target) => JumpTarget::Static(target),
+        _ => JumpTarget::Invalid(target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{ADD, CALLDATALOAD, JUMP, JUMPDEST, JUMPI, PUSH0, PUSH1, STOP};
+
+    #[test]
+    fn resolves_static_jumps() {
+        let code = [
+            PUSH1, 0x08, JUMPI, // 0
+            PUSH1, 0x0a, JUMP, // 3
+            JUMPDEST, STOP, // 6
+            JUMPDEST, STOP, // 8
+            PUSH1, JUMPDEST, // 10
+        ];
+        let cfg = ControlFlowGraph::new(&code);
+        let starts: Vec<_> = cfg.blocks.iter().map(|b| b.start).collect();
+        assert_eq!(starts, [0, 3, 6, 8, 10]);
+
+        assert_eq!(cfg.blocks[0].exit, BlockExit::JumpI(JumpTarget::Static(8)));
+        assert_eq!(cfg.blocks[0].successors, [8, 3]);
+        // Offset 10 is `PUSH1`, the `JUMPDEST` is its immediate.
+        assert_eq!(
+            cfg.blocks[1].exit,
+            BlockExit::Jump(JumpTarget::Invalid(U256::from(10)))
+        );
+        assert!(cfg.blocks[1].successors.is_empty());
+        assert_eq!(cfg.block_at(8).unwrap().exit, BlockExit::Halt);
+
+        assert!(!cfg.has_dynamic_jumps);
+        assert_eq!(cfg.unreachable_blocks().collect::<Vec<_>>(), [6, 10]);
+
+        let dot = cfg.to_dot(&code);
+        assert!(dot.contains("b0 -> b8;"));
+        assert!(dot.contains("b0 -> b3;"));
+        assert!(dot.contains("b6 [label=\"0006: JUMPDEST\\l0007: STOP\\l\" style=filled"));
+        assert!(dot.contains("000a: PUSH1 0x5b\\l"));
+    }
+
+    #[test]
+    fn dynamic_jump_reaches_jumpdests() {
+        let code = [
+            PUSH0,
+            CALLDATALOAD,
+            JUMP, // 0
+            JUMPDEST,
+            STOP, // 3
+            JUMPDEST,
+            STOP, // 5
+            ADD,  // 7
+        ];
+        let cfg = ControlFlowGraph::new(&code);
+        assert_eq!(cfg.blocks[0].exit, BlockExit::Jump(JumpTarget::Dynamic));
+        assert!(cfg.has_dynamic_jumps);
+        assert_eq!(cfg.unreachable_blocks().collect::<Vec<_>>(), [7]);
+        assert!(cfg.to_dot(&code).contains("b0 -> dynamic [style=dashed];"));
+    }
+}
@@ -72,3 +72,34 @@ pub fn analyze_legacy(bytetecode: &[u8]) -> JumpTable {
 
     JumpTable(Arc::new(jumps))
 }
+
+/// Returns the number of immediate bytes of every opcode in legacy bytecode.
+///
+/// Only `PUSH1` to `PUSH32` have immediates.
+pub fn legacy_immediate_sizes() -> [u8; 256] {
+    let mut sizes = [0; 256];
+    for n in 0..32 {
+        sizes[(opcode::PUSH1 + n) as usize] = n + 1;
+    }
+    sizes
+}
+
+/// Analyze the bytecode to find the jumpdests, skipping immediate bytes
+/// as given by `immediate_sizes`.
+///
+/// Used when opcodes other than `PUSH1` to `PUSH32` have immediates,
+/// see [`CustomOpCodes`](crate::opcode::custom::CustomOpCodes).
+pub fn analyze_legacy_with_immediates(bytecode: &[u8], immediate_sizes: &[u8; 256]) -> JumpTable {
+    let mut jumps: BitVec<u8> = bitvec![u8, Lsb0; 0; bytecode.len()];
+
+    let mut i = 0;
+    while i < bytecode.len() {
+        let opcode = bytecode[i];
+        if opcode::JUMPDEST == opcode {
+            jumps.set(i, true);
+        }
+        i += 1 + immediate_sizes[opcode as usize] as usize;
+    }
+
+    JumpTable(Arc::new(jumps))
+}
@@ -5,21 +5,31 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc as std;
 
+#[cfg(feature = "std")]
+pub mod analysis_cache;
+#[cfg(feature = "parse")]
+pub mod assembler;
 pub mod bytecode;
 pub mod decode_errors;
+pub mod disassembler;
 pub mod eip7702;
 pub mod eof;
 pub mod legacy;
+pub mod metadata;
 pub mod opcode;
+pub mod selectors;
 pub mod utils;
 
+#[cfg(feature = "std")]
+pub use analysis_cache::AnalysisCache;
 pub use bitvec;
 pub use bytecode::Bytecode;
 pub use decode_errors::BytecodeDecodeError;
 pub use eof::{
     verification::{
-        validate_eof, validate_eof_code, validate_eof_codes, validate_eof_inner, validate_raw_eof,
-        validate_raw_eof_inner, CodeType, EofValidationError,
+        validate_eof, validate_eof_code, validate_eof_codes, validate_eof_inner,
+        validate_eof_with_limits, validate_raw_eof, validate_raw_eof_inner,
+        validate_raw_eof_with_limits, CodeType, EofValidationError, EofValidationLimits,
     },
     Eof, EOF_MAGIC, EOF_MAGIC_BYTES, EOF_MAGIC_HASH,
 };
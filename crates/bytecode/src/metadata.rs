@@ -0,0 +1,277 @@
+//! Solidity and Vyper metadata trailer.
+//!
+//! Compilers append a CBOR encoded blob to the deployed code followed by its length as two big
+//! endian bytes. Solidity encodes a map with the `ipfs`, `bzzr0` or `bzzr1` source hash and the
+//! `solc` version. Vyper encodes a map with the `vyper` version or, since 0.3.10, an array that
+//! ends with such a map and whose length includes the two length bytes.
+use primitives::{Bytes, B256};
+use std::{format, string::String, vec::Vec};
+
+/// Maximum nesting of CBOR arrays and maps.
+const MAX_DEPTH: usize = 4;
+
+/// Compiler that produced the metadata.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Compiler {
+    /// Solidity compiler with its version, for example `0.8.26`.
+    Solc(String),
+    /// Vyper compiler with its version, for example `0.3.10`.
+    Vyper(String),
+}
+
+/// Decoded metadata trailer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    /// Offset of the metadata, which is the length of the executable code.
+    pub offset: usize,
+    /// IPFS multihash of the metadata file.
+    pub ipfs: Option<Bytes>,
+    /// Swarm hash of the metadata file, before Solidity 0.5.12.
+    pub bzzr0: Option<B256>,
+    /// Swarm hash of the metadata file, since Solidity 0.5.12.
+    pub bzzr1: Option<B256>,
+    /// Whether experimental Solidity features were enabled.
+    pub experimental: bool,
+    /// Compiler and its version.
+    pub compiler: Option<Compiler>,
+}
+
+impl Metadata {
+    /// Decodes the metadata trailer at the end of the code.
+    ///
+    /// Returns `None` if the code doesn't end with metadata that has at least one known key.
+    pub fn decode(code: &[u8]) -> Option<Self> {
+        let [.., high, low] = *code else {
+            return None;
+        };
+        let len = u16::from_be_bytes([high, low]) as usize;
+        let body = &code[..code.len() - 2];
+
+        // Solidity and older Vyper don't include the length bytes, newer Vyper does.
+        [len, len.checked_sub(2)?]
+            .into_iter()
+            .filter(|len| *len <= body.len())
+            .find_map(|len| {
+                let offset = body.len() - len;
+                let (value, rest) = Value::decode(&body[offset..], 0)?;
+                if !rest.is_empty() {
+                    return None;
+                }
+                Self::from_value(value, offset)
+            })
+    }
+
+    /// Fills the fields from the decoded CBOR, returns `None` if no known key is found.
+    fn from_value(value: Value<'_>, offset: usize) -> Option<Self> {
+        // Vyper array, the map is the last element.
+        let entries = match value {
+            Value::Map(entries) => entries,
+            Value::Array(mut items) => match items.pop()? {
+                Value::Map(entries) => entries,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let mut metadata = Self {
+            offset,
+            ..Default::default()
+        };
+        let mut known = false;
+        for (key, value) in entries {
+            let Value::Text(key) = key else {
+                continue;
+            };
+            match (key, value) {
+                ("ipfs", Value::Bytes(hash)) => {
+                    metadata.ipfs = Some(Bytes::copy_from_slice(hash));
+                }
+                ("bzzr0", Value::Bytes(hash)) if hash.len() == 32 => {
+                    metadata.bzzr0 = Some(B256::from_slice(hash));
+                }
+                ("bzzr1", Value::Bytes(hash)) if hash.len() == 32 => {
+                    metadata.bzzr1 = Some(B256::from_slice(hash));
+                }
+                ("experimental", Value::Bool(experimental)) => {
+                    metadata.experimental = experimental;
+                }
+                ("solc", Value::Bytes(&[major, minor, patch])) => {
+                    metadata.compiler = Some(Compiler::Solc(format!("{major}.{minor}.{patch}")));
+                }
+                // Prerelease versions are encoded as a string.
+                ("solc", Value::Text(version)) => {
+                    metadata.compiler = Some(Compiler::Solc(version.into()));
+                }
+                ("vyper", Value::Array(version)) => {
+                    let parts: Option<Vec<_>> = version
+                        .iter()
+                        .map(|part| match part {
+                            Value::Uint(n) => Some(format!("{n}")),
+                            _ => None,
+                        })
+                        .collect();
+                    metadata.compiler = Some(Compiler::Vyper(parts?.join(".")));
+                }
+                _ => continue,
+            }
+            known = true;
+        }
+        known.then_some(metadata)
+    }
+}
+
+/// Returns the code without the metadata trailer.
+pub fn strip_metadata(code: &[u8]) -> &[u8] {
+    match Metadata::decode(code) {
+        Some(metadata) => &code[..metadata.offset],
+        None => code,
+    }
+}
+
+/// Returns `true` if the codes are equal after stripping their metadata trailers.
+pub fn eq_ignoring_metadata(a: &[u8], b: &[u8]) -> bool {
+    strip_metadata(a) == strip_metadata(b)
+}
+
+/// Subset of CBOR used by the compilers.
+#[derive(Debug, PartialEq, Eq)]
+enum Value<'a> {
+    Uint(u64),
+    Bytes(&'a [u8]),
+    Text(&'a str),
+    Array(Vec<Value<'a>>),
+    Map(Vec<(Value<'a>, Value<'a>)>),
+    Bool(bool),
+    Null,
+}
+
+impl<'a> Value<'a> {
+    /// Decodes one value, returns it and the rest of the input.
+    fn decode(input: &'a [u8], depth: usize) -> Option<(Self, &'a [u8])> {
+        let (&initial, mut input) = input.split_first()?;
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        if major == 7 {
+            let value = match info {
+                20 => Self::Bool(false),
+                21 => Self::Bool(true),
+                22 => Self::Null,
+                _ => return None,
+            };
+            return Some((value, input));
+        }
+
+        let argument = match info {
+            0..24 => info as u64,
+            24..28 => {
+                let size = 1 << (info - 24);
+                let (bytes, rest) = input.split_at_checked(size)?;
+                input = rest;
+                bytes.iter().fold(0, |n, byte| (n << 8) | *byte as u64)
+            }
+            _ => return None,
+        };
+
+        let value = match major {
+            0 => Self::Uint(argument),
+            2 | 3 => {
+                let (bytes, rest) = input.split_at_checked(usize::try_from(argument).ok()?)?;
+                input = rest;
+                if major == 2 {
+                    Self::Bytes(bytes)
+                } else {
+                    Self::Text(core::str::from_utf8(bytes).ok()?)
+                }
+            }
+            4 | 5 if depth < MAX_DEPTH => {
+                // Every item takes at least one byte.
+                if argument > input.len() as u64 {
+                    return None;
+                }
+                let mut items = Vec::with_capacity(argument as usize);
+                let mut entries = Vec::new();
+                for _ in 0..argument {
+                    let (item, rest) = Self::decode(input, depth + 1)?;
+                    input = rest;
+                    if major == 4 {
+                        items.push(item);
+                    } else {
+                        let (value, rest) = Self::decode(input, depth + 1)?;
+                        input = rest;
+                        entries.push((item, value));
+                    }
+                }
+                if major == 4 {
+                    Self::Array(items)
+                } else {
+                    Self::Map(entries)
+                }
+            }
+            _ => return None,
+        };
+        Some((value, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::hex;
+
+    const CODE: [u8; 5] = hex!("6080604052");
+
+    #[test]
+    fn solc_ipfs() {
+        let hash = [0x12; 34];
+        let mut code = CODE.to_vec();
+        code.extend_from_slice(&hex!("a2 64 69706673 5822"));
+        code.extend_from_slice(&hash);
+        code.extend_from_slice(&hex!("64 736f6c63 43 00081a 0033"));
+
+        let metadata = Metadata::decode(&code).unwrap();
+        assert_eq!(metadata.offset, CODE.len());
+        assert_eq!(metadata.ipfs, Some(Bytes::copy_from_slice(&hash)));
+        assert_eq!(metadata.compiler, Some(Compiler::Solc("0.8.26".into())));
+        assert!(!metadata.experimental);
+        assert_eq!(code.len() - metadata.offset, 53);
+        assert_eq!(strip_metadata(&code), CODE);
+
+        let mut other = CODE.to_vec();
+        other.extend_from_slice(&hex!("a1 65 627a7a7230 5820"));
+        other.extend_from_slice(&[0x34; 32]);
+        other.extend_from_slice(&hex!("0029"));
+        assert_eq!(
+            Metadata::decode(&other).unwrap().bzzr0,
+            Some(B256::repeat_byte(0x34))
+        );
+        assert!(eq_ignoring_metadata(&code, &other));
+        assert!(!eq_ignoring_metadata(&code, &other[1..]));
+    }
+
+    #[test]
+    fn vyper() {
+        // 0.3.10 array, the length includes the length bytes.
+        let mut code = CODE.to_vec();
+        code.extend_from_slice(&hex!("84 05 80 00 a1 65 7679706572 83 00 03 0a 0011"));
+        let metadata = Metadata::decode(&code).unwrap();
+        assert_eq!(metadata.offset, CODE.len());
+        assert_eq!(metadata.compiler, Some(Compiler::Vyper("0.3.10".into())));
+
+        // Older map.
+        let mut code = CODE.to_vec();
+        code.extend_from_slice(&hex!("a1 65 7679706572 83 00 03 04 000b"));
+        let metadata = Metadata::decode(&code).unwrap();
+        assert_eq!(metadata.compiler, Some(Compiler::Vyper("0.3.4".into())));
+    }
+
+    #[test]
+    fn no_metadata() {
+        assert_eq!(Metadata::decode(&CODE), None);
+        assert_eq!(Metadata::decode(&[]), None);
+        // Valid CBOR without known keys.
+        assert_eq!(Metadata::decode(&hex!("a1 61 61 01 0004")), None);
+        assert_eq!(strip_metadata(&CODE), CODE);
+    }
+}
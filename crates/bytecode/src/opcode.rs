@@ -1,5 +1,6 @@
 //! EVM opcode definitions and utilities.
 
+pub mod custom;
 #[cfg(feature = "parse")]
 pub mod parse;
 
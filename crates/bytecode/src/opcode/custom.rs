@@ -0,0 +1,172 @@
+//! Custom opcodes.
+//!
+//! [`CustomOpCodes`] holds the information of opcodes that are not builtin. It is owned by
+//! whatever executes the bytecode and passed explicitly to the analysis that needs it, so
+//! registrations don't leak between EVM instances.
+//!
+//! Custom opcodes are supported only in legacy bytecode, EOF validation accepts builtin
+//! opcodes only.
+use super::{OpCodeInfo, OPCODE_INFO};
+use crate::legacy::{analyze_legacy, analyze_legacy_with_immediates, legacy_immediate_sizes};
+use crate::JumpTable;
+use core::fmt;
+
+/// Error returned when a custom opcode can't be registered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CustomOpCodeError {
+    /// Opcode value is already used by a builtin opcode.
+    BuiltinOpcode(u8),
+    /// Opcode name is already used by another opcode.
+    NameTaken(&'static str),
+}
+
+impl fmt::Display for CustomOpCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BuiltinOpcode(opcode) => write!(f, "opcode 0x{opcode:02X} is a builtin opcode"),
+            Self::NameTaken(name) => write!(f, "opcode name {name} is already used"),
+        }
+    }
+}
+
+impl core::error::Error for CustomOpCodeError {}
+
+/// Set of custom opcodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CustomOpCodes {
+    info: [Option<OpCodeInfo>; 256],
+}
+
+impl Default for CustomOpCodes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CustomOpCodes {
+    /// Creates an empty set of custom opcodes.
+    pub const fn new() -> Self {
+        Self { info: [None; 256] }
+    }
+
+    /// Registers a custom opcode.
+    ///
+    /// Registering the same opcode again replaces its information.
+    pub fn register(&mut self, opcode: u8, info: OpCodeInfo) -> Result<(), CustomOpCodeError> {
+        if OPCODE_INFO[opcode as usize].is_some() {
+            return Err(CustomOpCodeError::BuiltinOpcode(opcode));
+        }
+        let name = info.name();
+        if self.opcode_by_name(name).is_some_and(|op| op != opcode) {
+            return Err(CustomOpCodeError::NameTaken(name));
+        }
+        self.info[opcode as usize] = Some(info);
+        Ok(())
+    }
+
+    /// Removes a custom opcode, returning its information if it was registered.
+    pub fn unregister(&mut self, opcode: u8) -> Option<OpCodeInfo> {
+        self.info[opcode as usize].take()
+    }
+
+    /// Returns `true` if no custom opcode is registered.
+    pub fn is_empty(&self) -> bool {
+        self.info.iter().all(Option::is_none)
+    }
+
+    /// Returns the information of a registered custom opcode.
+    #[inline]
+    pub fn get(&self, opcode: u8) -> Option<OpCodeInfo> {
+        self.info[opcode as usize]
+    }
+
+    /// Returns the information of a builtin or registered custom opcode.
+    #[inline]
+    pub fn info(&self, opcode: u8) -> Option<OpCodeInfo> {
+        OPCODE_INFO[opcode as usize].or(self.info[opcode as usize])
+    }
+
+    /// Returns the builtin or registered custom opcode with the given name.
+    pub fn opcode_by_name(&self, name: &str) -> Option<u8> {
+        OPCODE_INFO
+            .iter()
+            .chain(&self.info)
+            .position(|info| info.is_some_and(|info| info.name() == name))
+            .map(|position| (position % 256) as u8)
+    }
+
+    /// Returns the number of immediate bytes of every opcode in legacy bytecode,
+    /// including custom opcodes.
+    pub fn immediate_sizes(&self) -> [u8; 256] {
+        let mut sizes = legacy_immediate_sizes();
+        for (size, info) in sizes.iter_mut().zip(&self.info) {
+            if let Some(info) = info {
+                *size = info.immediate_size();
+            }
+        }
+        sizes
+    }
+
+    /// Returns `true` if any custom opcode has immediate bytes.
+    pub fn has_immediates(&self) -> bool {
+        self.info
+            .iter()
+            .flatten()
+            .any(|info| info.immediate_size() != 0)
+    }
+
+    /// Analyzes legacy bytecode to find the jumpdests, skipping immediate bytes of
+    /// custom opcodes.
+    pub fn analyze_legacy(&self, bytecode: &[u8]) -> JumpTable {
+        if self.has_immediates() {
+            analyze_legacy_with_immediates(bytecode, &self.immediate_sizes())
+        } else {
+            analyze_legacy(bytecode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::{immediate_size, stack_io, OpCode, ADD, JUMPDEST};
+
+    #[test]
+    fn register_and_unregister() {
+        // 0x0C is not used by any builtin opcode.
+        let info = stack_io(immediate_size(OpCodeInfo::new("CUSTOMOP"), 2), 1, 1);
+        let mut custom = CustomOpCodes::new();
+        assert_eq!(
+            custom.register(ADD, info),
+            Err(CustomOpCodeError::BuiltinOpcode(ADD))
+        );
+        assert_eq!(
+            custom.register(0x0C, OpCodeInfo::new("ADD")),
+            Err(CustomOpCodeError::NameTaken("ADD"))
+        );
+        custom.register(0x0C, info).unwrap();
+        assert_eq!(
+            custom.register(0x0D, info),
+            Err(CustomOpCodeError::NameTaken("CUSTOMOP"))
+        );
+
+        assert_eq!(custom.info(0x0C), Some(info));
+        assert_eq!(custom.info(ADD).map(|info| info.name()), Some("ADD"));
+        assert_eq!(custom.opcode_by_name("CUSTOMOP"), Some(0x0C));
+        assert_eq!(custom.opcode_by_name("ADD"), Some(ADD));
+        assert_eq!(custom.immediate_sizes()[0x0C], 2);
+        // Registration is not visible outside of the set.
+        assert!(OpCode::new(0x0C).is_none());
+
+        // Immediate bytes of custom opcode are not a jump destination.
+        let code = [0x0C, JUMPDEST, JUMPDEST, JUMPDEST];
+        let jump_table = custom.analyze_legacy(&code);
+        assert!(!jump_table.is_valid(1));
+        assert!(!jump_table.is_valid(2));
+        assert!(jump_table.is_valid(3));
+        assert!(analyze_legacy(&code).is_valid(1));
+
+        assert_eq!(custom.unregister(0x0C), Some(info));
+        assert!(custom.is_empty());
+    }
+}
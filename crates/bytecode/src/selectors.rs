@@ -0,0 +1,157 @@
+//! Recovery of external function selectors from a contract dispatcher.
+//!
+//! Compilers dispatch calls by comparing the selector with a `PUSH4` constant and jumping to
+//! the function when they are equal:
+//!
+//! ```text
+//! DUP1 PUSH4 0xa9059cbb EQ PUSH2 0x00b7 JUMPI   ; jump to the function if equal
+//! PUSH4 0xa9059cbb DUP2 XOR PUSH2 0x0042 JUMPI  ; skip the function if not equal
+//! DUP1 PUSH4 0xa9059cbb EQ RJUMPI 0x0009        ; EOF
+//! ```
+//!
+//! Comparisons with `EQ`, `XOR` and `SUB`, optionally followed by `ISZERO`, are recognized.
+//! Dispatchers that use jump tables, such as Vyper's hash based dispatch, are not.
+use crate::{
+    disassembler::{Disassembly, Instruction, Target},
+    opcode,
+};
+use primitives::FixedBytes;
+use std::vec::Vec;
+
+/// Maximum number of `DUP` and `SWAP` instructions between the selector and the comparison.
+const MAX_STACK_SHUFFLES: usize = 2;
+
+/// External function recovered from the dispatcher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionEntry {
+    /// Function selector.
+    pub selector: FixedBytes<4>,
+    /// Code section of the entry, zero for legacy code.
+    pub code_section: usize,
+    /// Offset of the first instruction of the function in its code section.
+    pub pc: usize,
+}
+
+/// Returns the functions dispatched by the code, in the order they are compared.
+///
+/// Only the first entry of a selector is returned.
+pub fn recover_selectors(disassembly: &Disassembly) -> Vec<FunctionEntry> {
+    let mut entries: Vec<FunctionEntry> = Vec::new();
+    for (code_section, section) in disassembly.code_sections.iter().enumerate() {
+        let instructions = &section.instructions;
+        for (index, instruction) in instructions.iter().enumerate() {
+            if instruction.opcode != opcode::PUSH4 || instruction.truncated {
+                continue;
+            }
+            let Some(pc) = dispatch_entry(&instructions[index + 1..]) else {
+                continue;
+            };
+            let selector = FixedBytes::from_slice(&instruction.immediate);
+            if entries.iter().all(|entry| entry.selector != selector) {
+                entries.push(FunctionEntry {
+                    selector,
+                    code_section,
+                    pc,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// Matches the comparison and the jump after a `PUSH4`, returns the function entry.
+fn dispatch_entry(instructions: &[Instruction]) -> Option<usize> {
+    let mut rest = instructions;
+    let mut shuffles = 0;
+    while let [first, tail @ ..] = rest {
+        if !matches!(first.opcode, opcode::DUP1..=opcode::SWAP16) {
+            break;
+        }
+        shuffles += 1;
+        if shuffles > MAX_STACK_SHUFFLES {
+            return None;
+        }
+        rest = tail;
+    }
+
+    let (compare, mut rest) = rest.split_first()?;
+    let mut jumps_if_equal = match compare.opcode {
+        opcode::EQ => true,
+        opcode::XOR | opcode::SUB => false,
+        _ => return None,
+    };
+    if let [first, tail @ ..] = rest {
+        if first.opcode == opcode::ISZERO {
+            jumps_if_equal = !jumps_if_equal;
+            rest = tail;
+        }
+    }
+
+    let jump = match rest {
+        [push, jump, ..] if (opcode::PUSH1..=opcode::PUSH32).contains(&push.opcode) => {
+            (jump.opcode == opcode::JUMPI).then_some(jump)?
+        }
+        [jump, ..] if jump.opcode == opcode::RJUMPI => jump,
+        _ => return None,
+    };
+    if !jumps_if_equal {
+        return Some(jump.pc + jump.len());
+    }
+    match jump.targets.first()? {
+        Target::Pc(pc) => Some(*pc),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eof::EofBuilder;
+    use primitives::{fixed_bytes, hex};
+
+    #[test]
+    fn legacy_dispatcher() {
+        let code = hex!(
+            "5f35 60e0 1c"                // selector
+            "80 63a9059cbb 14 6100 1c 57" // 0x05: jump if equal
+            "6370a08231 81 18 6100 1b 57" // 0x10: skip if not equal
+            "00"                          // 0x1b: balanceOf
+            "5b 00"                       // 0x1c: transfer
+            "80 6312345678 11 6100 1c 57" // GT is not a selector comparison
+        );
+        let entries = recover_selectors(&Disassembly::legacy(code.into()));
+        assert_eq!(
+            entries,
+            [
+                FunctionEntry {
+                    selector: fixed_bytes!("a9059cbb"),
+                    code_section: 0,
+                    pc: 0x1c,
+                },
+                FunctionEntry {
+                    selector: fixed_bytes!("70a08231"),
+                    code_section: 0,
+                    pc: 0x1b,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn eof_dispatcher() {
+        let eof = EofBuilder::new()
+            .non_returning_code_section(0, hex!("5f35 60e0 1c 80 63a9059cbb 14 e10002 5f00 00"))
+            .build()
+            .unwrap();
+        let entries = recover_selectors(&Disassembly::eof(&eof).unwrap());
+        assert_eq!(
+            entries,
+            [FunctionEntry {
+                selector: fixed_bytes!("a9059cbb"),
+                code_section: 0,
+                pc: 0x11,
+            }]
+        );
+    }
+}
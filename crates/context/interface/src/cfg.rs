@@ -1,8 +1,9 @@
 use auto_impl::auto_impl;
 use core::fmt::Debug;
 use core::hash::Hash;
+use core::time::Duration;
 use primitives::{TxKind, U256};
-use specification::hardfork::SpecId;
+use specification::{gas_schedule::GasSchedule, hardfork::SpecId};
 
 #[auto_impl(&, &mut, Box, Arc)]
 pub trait Cfg {
@@ -18,8 +19,57 @@ pub trait Cfg {
     /// EIP-7840: Add blob schedule to execution client configuration files
     fn blob_max_count(&self, spec_id: SpecId) -> u8;
 
+    /// Returns the maximum depth of nested calls and creates.
+    ///
+    /// By default it is `1024`.
+    fn call_stack_limit(&self) -> u64;
+
+    /// Returns the maximum number of items on the interpreter stack.
+    ///
+    /// By default it is `1024`.
+    fn stack_limit(&self) -> usize;
+
+    /// Returns the maximum size of deployed contract code.
+    ///
+    /// EIP-170: Contract code size limit
     fn max_code_size(&self) -> usize;
 
+    /// Returns the maximum size of initcode.
+    ///
+    /// EIP-3860: Limit and meter initcode
+    fn max_initcode_size(&self) -> usize;
+
+    /// Returns the gas schedule used by instructions and intrinsic gas calculation.
+    ///
+    /// By default it is the mainnet schedule of the current [`Cfg::spec`].
+    fn gas_schedule(&self) -> &GasSchedule;
+
+    /// Returns the maximum number of instructions executed in a transaction.
+    ///
+    /// Exceeding it halts execution with
+    /// [`ExecutionBudgetError::InstructionLimit`][crate::result::ExecutionBudgetError::InstructionLimit].
+    fn max_instructions(&self) -> Option<u64>;
+
+    /// Returns the maximum wall-clock time spent executing a transaction.
+    ///
+    /// Exceeding it halts execution with
+    /// [`ExecutionBudgetError::TimeLimit`][crate::result::ExecutionBudgetError::TimeLimit].
+    /// Elapsed time is only measured with the `std` feature.
+    fn max_execution_time(&self) -> Option<Duration>;
+
+    /// Returns `true` if legacy bytecode is executed from a pre-decoded instruction stream
+    /// instead of the bytecode dispatch loop.
+    ///
+    /// Both modes produce the same results, instructions overriding `PUSH`, `DUP`, `SWAP`,
+    /// `POP`, `JUMP`, `JUMPI` or `JUMPDEST` are not used in the pre-decoded mode.
+    fn is_decoded_dispatch_enabled(&self) -> bool;
+
+    /// Returns `true` if decoded dispatch checks the static gas and stack bounds of basic
+    /// blocks once on their entry.
+    ///
+    /// Out of gas and stack errors still happen at the same instruction.
+    fn is_block_precharge_enabled(&self) -> bool;
+
     fn is_eip3607_disabled(&self) -> bool;
 
     fn is_balance_check_disabled(&self) -> bool;
@@ -516,6 +516,8 @@ pub enum HaltReason {
     SubRoutineStackOverflow,
     /// Check for target address validity is only done inside subcall.
     InvalidEXTCALLTarget,
+    /// Execution budget configured in [`Cfg`][crate::Cfg] was exhausted.
+    ExecutionBudgetExceeded(ExecutionBudgetError),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -535,3 +537,13 @@ pub enum OutOfGasError {
     // When performing SSTORE the gasleft is less than or equal to 2300
     ReentrancySentry,
 }
+
+/// Execution budget that was exhausted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExecutionBudgetError {
+    /// Maximum number of executed instructions was reached.
+    InstructionLimit,
+    /// Maximum execution time has elapsed.
+    TimeLimit,
+}
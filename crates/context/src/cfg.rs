@@ -1,7 +1,8 @@
 pub use context_interface::Cfg;
 
-use interpreter::MAX_CODE_SIZE;
-use specification::hardfork::SpecId;
+use core::time::Duration;
+use interpreter::{MAX_CODE_SIZE, STACK_LIMIT};
+use specification::{constants::CALL_STACK_LIMIT, gas_schedule::GasSchedule, hardfork::SpecId};
 use std::{vec, vec::Vec};
 
 /// EVM configuration
@@ -23,6 +24,50 @@ pub struct CfgEnv<SPEC: Into<SpecId> = SpecId> {
     ///
     /// By default it is `0x6000` (~25kb).
     pub limit_contract_code_size: Option<usize>,
+    /// If some it will effects EIP-3860: Limit and meter initcode.
+    ///
+    /// By default it is twice the contract code size limit.
+    pub limit_contract_initcode_size: Option<usize>,
+    /// Maximum depth of nested calls and creates.
+    ///
+    /// By default it is `1024`.
+    pub call_stack_limit: u64,
+    /// Maximum number of items on the interpreter stack.
+    ///
+    /// This limit is applied to the interpreter stack and to EOF validation.
+    ///
+    /// By default it is `1024`.
+    pub stack_limit: usize,
+    /// If some it overrides the mainnet gas schedule of the [`CfgEnv::spec`].
+    ///
+    /// By default it is `None`.
+    pub gas_schedule: Option<GasSchedule>,
+    /// Maximum number of instructions executed in a transaction, across all call frames.
+    ///
+    /// Useful to bound `eth_call` like requests that are run with huge gas limits.
+    ///
+    /// By default it is `None`, meaning unlimited.
+    pub max_instructions: Option<u64>,
+    /// Maximum wall-clock time spent executing a transaction.
+    ///
+    /// Elapsed time is measured only when the `std` feature of the interpreter is enabled.
+    ///
+    /// By default it is `None`, meaning unlimited.
+    pub max_execution_time: Option<Duration>,
+    /// Executes legacy bytecode from a pre-decoded instruction stream with resolved jumps
+    /// and pre-read `PUSH` immediates.
+    ///
+    /// It is not used when the execution budget is limited.
+    ///
+    /// By default, it is set to `false`.
+    pub decoded_dispatch: bool,
+    /// Checks the static gas and stack bounds of basic blocks of legacy bytecode once on
+    /// their entry instead of on every instruction.
+    ///
+    /// It is only used with [`decoded_dispatch`](Self::decoded_dispatch).
+    ///
+    /// By default, it is set to `false`.
+    pub block_precharge: bool,
     /// Skips the nonce validation against the account's nonce
     pub disable_nonce_check: bool,
     /// Blob target count. EIP-7840 Add blob schedule to EL config files.
@@ -87,6 +132,14 @@ impl<SPEC: Into<SpecId>> CfgEnv<SPEC> {
         CfgEnv {
             chain_id: self.chain_id,
             limit_contract_code_size: self.limit_contract_code_size,
+            limit_contract_initcode_size: self.limit_contract_initcode_size,
+            call_stack_limit: self.call_stack_limit,
+            stack_limit: self.stack_limit,
+            gas_schedule: self.gas_schedule,
+            max_instructions: self.max_instructions,
+            max_execution_time: self.max_execution_time,
+            decoded_dispatch: self.decoded_dispatch,
+            block_precharge: self.block_precharge,
             spec,
             disable_nonce_check: self.disable_nonce_check,
             blob_target_and_max_count: self.blob_target_and_max_count,
@@ -137,10 +190,45 @@ impl<SPEC: Into<SpecId> + Copy> Cfg for CfgEnv<SPEC> {
             .unwrap_or(6)
     }
 
+    fn call_stack_limit(&self) -> u64 {
+        self.call_stack_limit
+    }
+
+    fn stack_limit(&self) -> usize {
+        self.stack_limit
+    }
+
     fn max_code_size(&self) -> usize {
         self.limit_contract_code_size.unwrap_or(MAX_CODE_SIZE)
     }
 
+    fn max_initcode_size(&self) -> usize {
+        self.limit_contract_initcode_size
+            .unwrap_or_else(|| self.max_code_size().saturating_mul(2))
+    }
+
+    fn gas_schedule(&self) -> &GasSchedule {
+        self.gas_schedule
+            .as_ref()
+            .unwrap_or_else(|| GasSchedule::for_spec(self.spec.into()))
+    }
+
+    fn max_instructions(&self) -> Option<u64> {
+        self.max_instructions
+    }
+
+    fn max_execution_time(&self) -> Option<Duration> {
+        self.max_execution_time
+    }
+
+    fn is_decoded_dispatch_enabled(&self) -> bool {
+        self.decoded_dispatch
+    }
+
+    fn is_block_precharge_enabled(&self) -> bool {
+        self.block_precharge
+    }
+
     fn is_eip3607_disabled(&self) -> bool {
         cfg_if::cfg_if! {
             if #[cfg(feature = "optional_eip3607")] {
@@ -201,6 +289,14 @@ impl Default for CfgEnv {
         Self {
             chain_id: 1,
             limit_contract_code_size: None,
+            limit_contract_initcode_size: None,
+            call_stack_limit: CALL_STACK_LIMIT,
+            stack_limit: STACK_LIMIT,
+            gas_schedule: None,
+            max_instructions: None,
+            max_execution_time: None,
+            decoded_dispatch: false,
+            block_precharge: false,
             spec: SpecId::PRAGUE,
             disable_nonce_check: false,
             blob_target_and_max_count: vec![(SpecId::CANCUN, 3, 6), (SpecId::PRAGUE, 6, 9)],
@@ -232,4 +328,28 @@ mod test {
         assert_eq!(cfg.blob_max_count(SpecId::PRAGUE), (9));
         assert_eq!(cfg.blob_max_count(SpecId::OSAKA), (9));
     }
+
+    #[test]
+    fn initcode_size_follows_code_size() {
+        let mut cfg = CfgEnv::default();
+        assert_eq!(cfg.max_initcode_size(), 2 * MAX_CODE_SIZE);
+
+        cfg.limit_contract_code_size = Some(0x10000);
+        assert_eq!(cfg.max_initcode_size(), 0x20000);
+
+        cfg.limit_contract_initcode_size = Some(0x30000);
+        assert_eq!(cfg.max_initcode_size(), 0x30000);
+    }
+
+    #[test]
+    fn gas_schedule_follows_spec() {
+        let mut cfg = CfgEnv::default().with_spec(SpecId::BERLIN);
+        assert_eq!(*cfg.gas_schedule(), GasSchedule::new(SpecId::BERLIN));
+
+        cfg.gas_schedule = Some(GasSchedule {
+            cold_sload: 3000,
+            ..GasSchedule::new(SpecId::BERLIN)
+        });
+        assert_eq!(cfg.gas_schedule().cold_sload, 3000);
+    }
 }
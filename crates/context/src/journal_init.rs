@@ -16,6 +16,8 @@ impl<DB> JournaledState<DB> {
             spec: self.spec,
             warm_preloaded_addresses: self.warm_preloaded_addresses,
             precompiles: self.precompiles,
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache,
         }
     }
 
@@ -30,6 +32,8 @@ impl<DB> JournaledState<DB> {
             spec: self.spec,
             warm_preloaded_addresses: self.warm_preloaded_addresses.clone(),
             precompiles: self.precompiles.clone(),
+            #[cfg(feature = "std")]
+            analysis_cache: self.analysis_cache.clone(),
         }
     }
 }
@@ -57,6 +57,12 @@ pub struct JournaledState<DB> {
     pub warm_preloaded_addresses: HashSet<Address>,
     /// Precompile addresses
     pub precompiles: HashSet<Address>,
+    /// If set, code is looked up here before asking the database.
+    ///
+    /// Allows sharing analyzed code between journals and threads.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub analysis_cache: Option<std::sync::Arc<bytecode::AnalysisCache>>,
 }
 
 impl<DB: Database> Journal for JournaledState<DB> {
@@ -245,7 +251,12 @@ impl<DB: Database> Journal for JournaledState<DB> {
             database: _,
             warm_preloaded_addresses: _,
             precompiles: _,
+            #[cfg(feature = "std")]
+            analysis_cache,
         } = self;
+        // Kept like the fields above, bound by name as rustfmt misindents `analysis_cache: _`.
+        #[cfg(feature = "std")]
+        let _ = analysis_cache;
 
         *transient_storage = TransientStorage::default();
         *journal = vec![vec![]];
@@ -277,6 +288,8 @@ impl<DB: Database> JournaledState<DB> {
             spec,
             warm_preloaded_addresses: HashSet::default(),
             precompiles: HashSet::default(),
+            #[cfg(feature = "std")]
+            analysis_cache: None,
         }
     }
 
@@ -891,7 +904,16 @@ impl<DB: Database> JournaledState<DB> {
                     let empty = Bytecode::default();
                     info.code = Some(empty);
                 } else {
-                    let code = self.database.code_by_hash(info.code_hash)?;
+                    let code_hash = info.code_hash;
+                    #[cfg(feature = "std")]
+                    let code = match &self.analysis_cache {
+                        Some(cache) => cache.get_or_insert_with(code_hash, || {
+                            self.database.code_by_hash(code_hash)
+                        })?,
+                        None => self.database.code_by_hash(code_hash)?,
+                    };
+                    #[cfg(not(feature = "std"))]
+                    let code = self.database.code_by_hash(code_hash)?;
                     info.code = Some(code);
                 }
             }
@@ -1132,6 +1154,8 @@ impl<DB> JournaledState<DB> {
             spec: init.spec,
             warm_preloaded_addresses: init.warm_preloaded_addresses.clone(),
             precompiles: init.precompiles.clone(),
+            #[cfg(feature = "std")]
+            analysis_cache: init.analysis_cache.clone(),
         }
     }
 }
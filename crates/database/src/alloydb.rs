@@ -7,9 +7,10 @@ use alloy_provider::{
     Network, Provider,
 };
 use alloy_transport::{Transport, TransportError};
+use bytecode::AnalysisCache;
 use core::error::Error;
 use database_interface::{async_db::DatabaseAsyncRef, DBErrorMarker};
-use primitives::{Address, B256, U256};
+use primitives::{keccak256, Address, Bytes, B256, U256};
 use state::{AccountInfo, Bytecode};
 use std::fmt::Display;
 
@@ -80,8 +81,12 @@ impl<T: Transport + Clone, N: Network, P: Provider<T, N>> DatabaseAsyncRef for A
         let (nonce, balance, code) = tokio::join!(nonce, balance, code,);
 
         let balance = balance?;
-        let code = Bytecode::new_raw(code?.0.into());
-        let code_hash = code.hash_slow();
+        let code: Bytes = code?.0.into();
+        let code_hash = keccak256(&code);
+        // Contracts deployed at many addresses are analyzed once.
+        let code = AnalysisCache::global()
+            .analyze(code_hash, code)
+            .expect("Expect correct EOF bytecode");
         let nonce = nonce?;
 
         Ok(Some(AccountInfo::new(balance, nonce, code_hash, code)))
@@ -1,7 +1,11 @@
+#[cfg(feature = "std")]
+use bytecode::AnalysisCache;
 use core::convert::Infallible;
 use database_interface::{Database, DatabaseCommit, DatabaseRef, EmptyDB};
 use primitives::{address, hash_map::Entry, Address, HashMap, Log, B256, KECCAK_EMPTY, U256};
 use state::{Account, AccountInfo, Bytecode};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 use std::vec::Vec;
 
 /// A [Database] implementation that stores all state changes in memory.
@@ -30,6 +34,13 @@ pub struct CacheDB<ExtDB> {
     ///
     /// Note: This is read-only, data is never written to this database.
     pub db: ExtDB,
+    /// If set, code missing from `contracts` is looked up here before asking the underlying database,
+    /// and code loaded with accounts is shared through it.
+    ///
+    /// Defaults to the [global](AnalysisCache::global) cache.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "global_analysis_cache"))]
+    pub analysis_cache: Option<Arc<AnalysisCache>>,
 }
 
 impl<ExtDB: Default> Default for CacheDB<ExtDB> {
@@ -53,6 +64,7 @@ impl<ExtDb> CacheDB<CacheDB<ExtDb>> {
             logs,
             block_hashes,
             db: mut inner,
+            ..
         } = self;
 
         inner.accounts.extend(accounts);
@@ -80,9 +92,26 @@ impl<ExtDB> CacheDB<ExtDB> {
             logs: Vec::default(),
             block_hashes: HashMap::default(),
             db,
+            #[cfg(feature = "std")]
+            analysis_cache: global_analysis_cache(),
         }
     }
 
+    /// Shares analyzed code with other databases through the given cache instead of the
+    /// [global](AnalysisCache::global) one.
+    #[cfg(feature = "std")]
+    pub fn with_analysis_cache(mut self, cache: Arc<AnalysisCache>) -> Self {
+        self.analysis_cache = Some(cache);
+        self
+    }
+
+    /// Doesn't share analyzed code with other databases.
+    #[cfg(feature = "std")]
+    pub fn without_analysis_cache(mut self) -> Self {
+        self.analysis_cache = None;
+        self
+    }
+
     /// Inserts the account's code into the cache.
     ///
     /// Accounts objects and code are stored separately in the cache, this will take the code from the account and instead map it to the code hash.
@@ -124,14 +153,18 @@ impl<ExtDB: DatabaseRef> CacheDB<ExtDB> {
         let db = &self.db;
         match self.accounts.entry(address) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
-            Entry::Vacant(entry) => Ok(entry.insert(
-                db.basic_ref(address)?
-                    .map(|info| DbAccount {
+            Entry::Vacant(entry) => {
+                let info = db.basic_ref(address)?;
+                #[cfg(feature = "std")]
+                let info = share_code(&self.analysis_cache, info);
+                Ok(entry.insert(
+                    info.map(|info| DbAccount {
                         info,
                         ..Default::default()
                     })
                     .unwrap_or_else(DbAccount::new_not_existing),
-            )),
+                ))
+            }
         }
     }
 
@@ -204,15 +237,18 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         let basic = match self.accounts.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(
-                self.db
-                    .basic_ref(address)?
-                    .map(|info| DbAccount {
+            Entry::Vacant(entry) => {
+                let info = self.db.basic_ref(address)?;
+                #[cfg(feature = "std")]
+                let info = share_code(&self.analysis_cache, info);
+                entry.insert(
+                    info.map(|info| DbAccount {
                         info,
                         ..Default::default()
                     })
                     .unwrap_or_else(DbAccount::new_not_existing),
-            ),
+                )
+            }
         };
         Ok(basic.info())
     }
@@ -222,7 +258,15 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
             Entry::Occupied(entry) => Ok(entry.get().clone()),
             Entry::Vacant(entry) => {
                 // If you return code bytes when basic fn is called this function is not needed.
-                Ok(entry.insert(self.db.code_by_hash_ref(code_hash)?).clone())
+                #[cfg(feature = "std")]
+                let code = match &self.analysis_cache {
+                    Some(cache) => cache
+                        .get_or_insert_with(code_hash, || self.db.code_by_hash_ref(code_hash))?,
+                    None => self.db.code_by_hash_ref(code_hash)?,
+                };
+                #[cfg(not(feature = "std"))]
+                let code = self.db.code_by_hash_ref(code_hash)?;
+                Ok(entry.insert(code).clone())
             }
         }
     }
@@ -253,6 +297,8 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
             Entry::Vacant(acc_entry) => {
                 // Acc needs to be loaded for us to access slots.
                 let info = self.db.basic_ref(address)?;
+                #[cfg(feature = "std")]
+                let info = share_code(&self.analysis_cache, info);
                 let (account, value) = if info.is_some() {
                     let value = self.db.storage_ref(address, index)?;
                     let mut account: DbAccount = info.into();
@@ -279,19 +325,57 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
     }
 }
 
+/// Returns the [global](AnalysisCache::global) analysis cache.
+#[cfg(feature = "std")]
+fn global_analysis_cache() -> Option<Arc<AnalysisCache>> {
+    Some(AnalysisCache::global().clone())
+}
+
+/// Shares the code loaded with an account through the analysis cache.
+#[cfg(feature = "std")]
+pub(crate) fn share_code(
+    cache: &Option<Arc<AnalysisCache>>,
+    info: Option<AccountInfo>,
+) -> Option<AccountInfo> {
+    let Some(cache) = cache else {
+        return info;
+    };
+    info.map(|mut info| {
+        if info.code_hash != KECCAK_EMPTY && !info.code_hash.is_zero() {
+            if let Some(code) = info.code.take() {
+                info.code = Some(cache.share(info.code_hash, code));
+            }
+        }
+        info
+    })
+}
+
 impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
     type Error = ExtDB::Error;
 
     fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         match self.accounts.get(&address) {
             Some(acc) => Ok(acc.info()),
-            None => self.db.basic_ref(address),
+            None => {
+                let info = self.db.basic_ref(address)?;
+                #[cfg(feature = "std")]
+                let info = share_code(&self.analysis_cache, info);
+                Ok(info)
+            }
         }
     }
 
     fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
         match self.contracts.get(&code_hash) {
             Some(entry) => Ok(entry.clone()),
+            #[cfg(feature = "std")]
+            None => match &self.analysis_cache {
+                Some(cache) => {
+                    cache.get_or_insert_with(code_hash, || self.db.code_by_hash_ref(code_hash))
+                }
+                None => self.db.code_by_hash_ref(code_hash),
+            },
+            #[cfg(not(feature = "std"))]
             None => self.db.code_by_hash_ref(code_hash),
         }
     }
@@ -451,9 +535,11 @@ impl Database for BenchmarkDB {
 #[cfg(test)]
 mod tests {
     use super::{CacheDB, EmptyDB};
+    use bytecode::AnalysisCache;
     use database_interface::Database;
     use primitives::{Address, HashMap, U256};
-    use state::AccountInfo;
+    use state::{AccountInfo, Bytecode};
+    use std::sync::Arc;
 
     #[test]
     fn test_insert_account_storage() {
@@ -507,6 +593,42 @@ mod tests {
         assert_eq!(new_state.storage(account, key1), Ok(value1));
     }
 
+    #[test]
+    fn test_shared_analysis_cache() {
+        let cache = Arc::new(AnalysisCache::default());
+        let mut info = AccountInfo::from_bytecode(Bytecode::new_legacy([0x5b, 0x00].into()));
+        let mut inner = CacheDB::new(EmptyDB::default());
+        inner.insert_contract(&mut info);
+
+        let mut first = CacheDB::new(inner).with_analysis_cache(cache.clone());
+        let code = first.code_by_hash(info.code_hash).unwrap();
+        assert_eq!(cache.get(&info.code_hash), Some(code.clone()));
+
+        // Underlying database does not have the code, it comes from the shared cache.
+        let mut second = CacheDB::new(EmptyDB::default()).with_analysis_cache(cache);
+        assert_eq!(second.code_by_hash(info.code_hash), Ok(code));
+    }
+
+    #[test]
+    fn test_account_code_shared_through_analysis_cache() {
+        let address = Address::with_last_byte(42);
+        let info = AccountInfo::from_bytecode(Bytecode::new_legacy([0x5b, 0x00].into()));
+        let mut inner = CacheDB::new(EmptyDB::default());
+        inner.insert_account_info(address, info.clone());
+
+        let default = CacheDB::new(EmptyDB::default());
+        assert!(Arc::ptr_eq(
+            default.analysis_cache.as_ref().unwrap(),
+            AnalysisCache::global()
+        ));
+        assert!(default.without_analysis_cache().analysis_cache.is_none());
+
+        let cache = Arc::new(AnalysisCache::default());
+        let mut db = CacheDB::new(inner).with_analysis_cache(cache.clone());
+        let loaded = db.basic(address).unwrap().unwrap();
+        assert_eq!(cache.get(&info.code_hash), loaded.code);
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serialize_deserialize_cachedb() {
@@ -2,10 +2,14 @@ use super::{
     bundle_state::BundleRetention, cache::CacheState, plain_account::PlainStorage, BundleState,
     CacheAccount, StateBuilder, TransitionAccount, TransitionState,
 };
+#[cfg(feature = "std")]
+use bytecode::AnalysisCache;
 use bytecode::Bytecode;
 use database_interface::{Database, DatabaseCommit, EmptyDB};
 use primitives::{hash_map, Address, HashMap, B256, BLOCK_HASH_HISTORY, U256};
 use state::{Account, AccountInfo};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 use std::{
     boxed::Box,
     collections::{btree_map, BTreeMap},
@@ -63,6 +67,13 @@ pub struct State<DB> {
     ///
     /// The fork block is different or some blocks are not saved inside database.
     pub block_hashes: BTreeMap<u64, B256>,
+    /// If set, code that is not cached or in the bundle is looked up here
+    /// before asking the database, and code loaded with accounts is shared through it.
+    ///
+    /// Unlike the cache, it can outlive the state and be shared between threads.
+    /// Defaults to the [global](AnalysisCache::global) cache.
+    #[cfg(feature = "std")]
+    pub analysis_cache: Option<Arc<AnalysisCache>>,
 }
 
 // Have ability to call State::builder without having to specify the type.
@@ -199,6 +210,8 @@ impl<DB: Database> State<DB> {
                 }
                 // If not found in bundle, load it from database
                 let info = self.database.basic(address)?;
+                #[cfg(feature = "std")]
+                let info = crate::in_memory_db::share_code(&self.analysis_cache, info);
                 let account = match info {
                     None => CacheAccount::new_loaded_not_existing(),
                     Some(acc) if acc.is_empty() => {
@@ -246,6 +259,13 @@ impl<DB: Database> Database for State<DB> {
                     }
                 }
                 // If not found in bundle ask database
+                #[cfg(feature = "std")]
+                let code = match &self.analysis_cache {
+                    Some(cache) => cache
+                        .get_or_insert_with(code_hash, || self.database.code_by_hash(code_hash))?,
+                    None => self.database.code_by_hash(code_hash)?,
+                };
+                #[cfg(not(feature = "std"))]
                 let code = self.database.code_by_hash(code_hash)?;
                 entry.insert(code.clone());
                 Ok(code)
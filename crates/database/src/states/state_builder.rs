@@ -1,7 +1,11 @@
 use super::{cache::CacheState, state::DBBox, BundleState, State, TransitionState};
+#[cfg(feature = "std")]
+use bytecode::AnalysisCache;
 use database_interface::{DBErrorMarker, Database, DatabaseRef, EmptyDB, WrapDatabaseRef};
 use primitives::B256;
 use std::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 /// Allows building of State and initializing it with different options.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -29,6 +33,11 @@ pub struct StateBuilder<DB> {
     with_background_transition_merge: bool,
     /// If we want to set different block hashes,
     with_block_hashes: BTreeMap<u64, B256>,
+    /// Cache of analyzed code shared with other states.
+    ///
+    /// Default is the [global](AnalysisCache::global) cache.
+    #[cfg(feature = "std")]
+    with_analysis_cache: Option<Arc<AnalysisCache>>,
 }
 
 impl StateBuilder<EmptyDB> {
@@ -58,6 +67,8 @@ impl<DB: Database> StateBuilder<DB> {
             with_bundle_update: false,
             with_background_transition_merge: false,
             with_block_hashes: BTreeMap::new(),
+            #[cfg(feature = "std")]
+            with_analysis_cache: Some(AnalysisCache::global().clone()),
         }
     }
 
@@ -73,6 +84,8 @@ impl<DB: Database> StateBuilder<DB> {
             with_bundle_update: self.with_bundle_update,
             with_background_transition_merge: self.with_background_transition_merge,
             with_block_hashes: self.with_block_hashes,
+            #[cfg(feature = "std")]
+            with_analysis_cache: self.with_analysis_cache,
         }
     }
 
@@ -157,6 +170,28 @@ impl<DB: Database> StateBuilder<DB> {
         }
     }
 
+    /// Shares analyzed code with other states through the given cache instead of the
+    /// [global](AnalysisCache::global) one.
+    ///
+    /// Useful when a new state is built for every block, as code loaded
+    /// by previous blocks does not need to be fetched and analyzed again.
+    #[cfg(feature = "std")]
+    pub fn with_analysis_cache(self, cache: Arc<AnalysisCache>) -> Self {
+        Self {
+            with_analysis_cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// Doesn't share analyzed code with other states.
+    #[cfg(feature = "std")]
+    pub fn without_analysis_cache(self) -> Self {
+        Self {
+            with_analysis_cache: None,
+            ..self
+        }
+    }
+
     pub fn build(mut self) -> State<DB> {
         let use_preloaded_bundle = if self.with_cache_prestate.is_some() {
             self.with_bundle_prestate = None;
@@ -173,6 +208,8 @@ impl<DB: Database> StateBuilder<DB> {
             bundle_state: self.with_bundle_prestate.unwrap_or_default(),
             use_preloaded_bundle,
             block_hashes: self.with_block_hashes,
+            #[cfg(feature = "std")]
+            analysis_cache: self.with_analysis_cache,
         }
     }
 }
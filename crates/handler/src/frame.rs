@@ -1,7 +1,7 @@
 use crate::instructions::{InstructionExecutor, InstructionExecutorGetter};
 
 use super::frame_data::*;
-use bytecode::{Eof, EOF_MAGIC_BYTES};
+use bytecode::{Eof, EofValidationLimits, EOF_MAGIC_BYTES};
 use context_interface::{
     journaled_state::{Journal, JournalCheckpoint},
     BlockGetter, Cfg, CfgGetter, ErrorGetter, JournalDBError, JournalGetter, Transaction,
@@ -12,20 +12,16 @@ use handler_interface::{
     Frame, FrameInitOrResult, ItemOrResult, PrecompileProvider, PrecompileProviderGetter,
 };
 use interpreter::{
-    gas,
     interpreter::{EthInterpreter, ExtBytecode},
-    interpreter_types::{LoopControl, ReturnData, RuntimeFlag},
+    interpreter_types::{BytecodeHash, LoopControl, ReturnData, RuntimeFlag},
     return_ok, return_revert, CallInputs, CallOutcome, CallValue, CreateInputs, CreateOutcome,
     CreateScheme, EOFCreateInputs, EOFCreateKind, FrameInput, Gas, Host, InputsImpl,
     InstructionResult, Interpreter, InterpreterAction, InterpreterResult, InterpreterTypes,
-    SharedMemory,
+    SharedMemory, SharedStack,
 };
 use precompile::PrecompileErrors;
 use primitives::{keccak256, Address, Bytes, B256, U256};
-use specification::{
-    constants::CALL_STACK_LIMIT,
-    hardfork::SpecId::{self, HOMESTEAD, LONDON, OSAKA, SPURIOUS_DRAGON},
-};
+use specification::hardfork::SpecId::{self, HOMESTEAD, LONDON, OSAKA, SPURIOUS_DRAGON};
 use state::Bytecode;
 use std::borrow::ToOwned;
 use std::{rc::Rc, sync::Arc};
@@ -41,6 +37,8 @@ pub struct EthFrame<CTX, ERROR, IW: InterpreterTypes, FRAMECTX> {
     pub interpreter: Interpreter<IW>,
     // This is worth making as a generic type FrameSharedContext.
     pub memory: Rc<RefCell<SharedMemory>>,
+    /// Stack buffer shared between frames.
+    pub shared_stack: Rc<RefCell<SharedStack>>,
 }
 
 impl<CTX, IW, ERROR, FRAMECTX> EthFrame<CTX, ERROR, IW, FRAMECTX>
@@ -54,6 +52,7 @@ where
         interpreter: Interpreter<IW>,
         checkpoint: JournalCheckpoint,
         memory: Rc<RefCell<SharedMemory>>,
+        shared_stack: Rc<RefCell<SharedStack>>,
     ) -> Self {
         Self {
             _phantom: core::marker::PhantomData,
@@ -62,6 +61,7 @@ where
             interpreter,
             checkpoint,
             memory,
+            shared_stack,
         }
     }
 }
@@ -91,6 +91,7 @@ where
         frame_context: &mut FRAMECTX,
         depth: usize,
         memory: Rc<RefCell<SharedMemory>>,
+        shared_stack: Rc<RefCell<SharedStack>>,
         inputs: &CallInputs,
     ) -> Result<ItemOrResult<Self, FrameResult>, ERROR> {
         let gas = Gas::new(inputs.gas_limit);
@@ -107,7 +108,7 @@ where
         };
 
         // Check depth
-        if depth > CALL_STACK_LIMIT as usize {
+        if depth as u64 > context.cfg().call_stack_limit() {
             return return_result(InstructionResult::CallTooDeep);
         }
 
@@ -186,22 +187,33 @@ where
             call_value: inputs.value.get(),
         };
 
+        let mut interpreter = Interpreter::new(
+            memory.clone(),
+            ExtBytecode::new_with_hash(bytecode, code_hash),
+            interpreter_input,
+            inputs.is_static,
+            false,
+            context.cfg().spec().into(),
+            inputs.gas_limit,
+            shared_stack
+                .borrow_mut()
+                .new_stack(context.cfg().stack_limit()),
+        );
+        let gas_schedule = context.cfg().gas_schedule();
+        interpreter
+            .control
+            .gas()
+            .set_memory_cost(gas_schedule.memory, gas_schedule.memory_quadratic_divisor);
+
         Ok(ItemOrResult::Item(Self::new(
             FrameData::Call(CallFrame {
                 return_memory_range: inputs.return_memory_offset.clone(),
             }),
             depth,
-            Interpreter::new(
-                memory.clone(),
-                ExtBytecode::new_with_hash(bytecode, code_hash),
-                interpreter_input,
-                inputs.is_static,
-                false,
-                context.cfg().spec().into(),
-                inputs.gas_limit,
-            ),
+            interpreter,
             checkpoint,
             memory,
+            shared_stack,
         )))
     }
 
@@ -211,6 +223,7 @@ where
         context: &mut CTX,
         depth: usize,
         memory: Rc<RefCell<SharedMemory>>,
+        shared_stack: Rc<RefCell<SharedStack>>,
         inputs: &CreateInputs,
     ) -> Result<ItemOrResult<Self, FrameResult>, ERROR> {
         let spec = context.cfg().spec().into();
@@ -226,7 +239,7 @@ where
         };
 
         // Check depth
-        if depth > CALL_STACK_LIMIT as usize {
+        if depth as u64 > context.cfg().call_stack_limit() {
             return return_error(InstructionResult::CallTooDeep);
         }
 
@@ -290,20 +303,31 @@ where
             call_value: inputs.value,
         };
 
+        let mut interpreter = Interpreter::new(
+            memory.clone(),
+            bytecode,
+            interpreter_input,
+            false,
+            false,
+            spec,
+            inputs.gas_limit,
+            shared_stack
+                .borrow_mut()
+                .new_stack(context.cfg().stack_limit()),
+        );
+        let gas_schedule = context.cfg().gas_schedule();
+        interpreter
+            .control
+            .gas()
+            .set_memory_cost(gas_schedule.memory, gas_schedule.memory_quadratic_divisor);
+
         Ok(ItemOrResult::Item(Self::new(
             FrameData::Create(CreateFrame { created_address }),
             depth,
-            Interpreter::new(
-                memory.clone(),
-                bytecode,
-                interpreter_input,
-                false,
-                false,
-                spec,
-                inputs.gas_limit,
-            ),
+            interpreter,
             checkpoint,
             memory,
+            shared_stack,
         )))
     }
 
@@ -313,6 +337,7 @@ where
         context: &mut CTX,
         depth: usize,
         memory: Rc<RefCell<SharedMemory>>,
+        shared_stack: Rc<RefCell<SharedStack>>,
         inputs: &EOFCreateInputs,
     ) -> Result<ItemOrResult<Self, FrameResult>, ERROR> {
         let spec = context.cfg().spec().into();
@@ -343,7 +368,11 @@ where
                     return return_error(InstructionResult::InvalidEOFInitCode);
                 };
 
-                if eof.validate().is_err() {
+                let limits = EofValidationLimits {
+                    max_initcode_size: context.cfg().max_initcode_size(),
+                    stack_limit: context.cfg().stack_limit(),
+                };
+                if eof.validate_with_limits(limits).is_err() {
                     // TODO : (EOF) New error type.
                     context.journal().inc_account_nonce(inputs.caller)?;
                     return return_error(InstructionResult::InvalidEOFInitCode);
@@ -358,7 +387,7 @@ where
         };
 
         // Check depth
-        if depth > CALL_STACK_LIMIT as usize {
+        if depth as u64 > context.cfg().call_stack_limit() {
             return return_error(InstructionResult::CallTooDeep);
         }
 
@@ -403,20 +432,31 @@ where
             call_value: inputs.value,
         };
 
+        let mut interpreter = Interpreter::new(
+            memory.clone(),
+            ExtBytecode::new(Bytecode::Eof(Arc::new(initcode))),
+            interpreter_input,
+            false,
+            true,
+            spec,
+            inputs.gas_limit,
+            shared_stack
+                .borrow_mut()
+                .new_stack(context.cfg().stack_limit()),
+        );
+        let gas_schedule = context.cfg().gas_schedule();
+        interpreter
+            .control
+            .gas()
+            .set_memory_cost(gas_schedule.memory, gas_schedule.memory_quadratic_divisor);
+
         Ok(ItemOrResult::Item(Self::new(
             FrameData::EOFCreate(EOFCreateFrame { created_address }),
             depth,
-            Interpreter::new(
-                memory.clone(),
-                ExtBytecode::new(Bytecode::Eof(Arc::new(initcode))),
-                interpreter_input,
-                false,
-                true,
-                spec,
-                inputs.gas_limit,
-            ),
+            interpreter,
             checkpoint,
             memory,
+            shared_stack,
         )))
     }
 
@@ -424,18 +464,33 @@ where
         depth: usize,
         frame_init: FrameInput,
         memory: Rc<RefCell<SharedMemory>>,
+        shared_stack: Rc<RefCell<SharedStack>>,
         context: &mut CTX,
         frame_context: &mut FRAMECTX,
     ) -> Result<ItemOrResult<Self, FrameResult>, ERROR> {
-        match frame_init {
+        let mut frame = match frame_init {
             FrameInput::Call(inputs) => {
-                Self::make_call_frame(context, frame_context, depth, memory, &inputs)
+                Self::make_call_frame(context, frame_context, depth, memory, shared_stack, &inputs)
+            }
+            FrameInput::Create(inputs) => {
+                Self::make_create_frame(context, depth, memory, shared_stack, &inputs)
             }
-            FrameInput::Create(inputs) => Self::make_create_frame(context, depth, memory, &inputs),
             FrameInput::EOFCreate(inputs) => {
-                Self::make_eofcreate_frame(context, depth, memory, &inputs)
+                Self::make_eofcreate_frame(context, depth, memory, shared_stack, &inputs)
+            }
+        };
+        if let Ok(ItemOrResult::Item(frame)) = &mut frame {
+            let bytecode = &mut frame.interpreter.bytecode;
+            if let Bytecode::LegacyAnalyzed(legacy) = &**bytecode {
+                let jump_table = frame_context
+                    .executor()
+                    .custom_jump_table(bytecode.bytecode_hash(), legacy.original_byte_slice());
+                if let Some(jump_table) = jump_table {
+                    bytecode.set_legacy_jump_table(jump_table);
+                }
             }
         }
+        frame
     }
 }
 
@@ -506,6 +561,7 @@ where
         frame_input: Self::FrameInit,
     ) -> Result<ItemOrResult<Self, Self::FrameResult>, Self::Error> {
         let memory = Rc::new(RefCell::new(SharedMemory::new()));
+        let shared_stack = Rc::new(RefCell::new(SharedStack::new()));
 
         frame_context.precompiles().set_spec(context.cfg().spec());
         context
@@ -513,7 +569,7 @@ where
             .warm_precompiles(frame_context.precompiles().warm_addresses().collect());
 
         memory.borrow_mut().new_context();
-        Self::init_with_context(0, frame_input, memory, context, frame_context)
+        Self::init_with_context(0, frame_input, memory, shared_stack, context, frame_context)
     }
 
     fn final_return(
@@ -535,6 +591,7 @@ where
             self.depth + 1,
             frame_init,
             self.memory.clone(),
+            self.shared_stack.clone(),
             context,
             frame_context,
         )
@@ -551,10 +608,20 @@ where
         let next_action = frame_context.executor().run(context, &mut self.interpreter);
 
         let mut interpreter_result = match next_action {
-            InterpreterAction::NewFrame(new_frame) => return Ok(ItemOrResult::Item(new_frame)),
+            InterpreterAction::NewFrame(new_frame) => {
+                // Sub call stack starts on top of this one.
+                self.shared_stack
+                    .borrow_mut()
+                    .suspend(&mut self.interpreter.stack);
+                return Ok(ItemOrResult::Item(new_frame));
+            }
             InterpreterAction::Return { result } => result,
             InterpreterAction::None => unreachable!("InterpreterAction::None is not expected"),
         };
+        // Frame is done, its part of the stack buffer can be reused by the next frame.
+        self.shared_stack
+            .borrow_mut()
+            .free_stack(&mut self.interpreter.stack);
 
         // Handle return from frame
         let result = match &self.data {
@@ -573,12 +640,14 @@ where
             }
             FrameData::Create(frame) => {
                 let max_code_size = context.cfg().max_code_size();
+                let code_deposit_cost = context.cfg().gas_schedule().code_deposit;
                 return_create(
                     context.journal(),
                     self.checkpoint,
                     &mut interpreter_result,
                     frame.created_address,
                     max_code_size,
+                    code_deposit_cost,
                     spec,
                 );
 
@@ -589,12 +658,14 @@ where
             }
             FrameData::EOFCreate(frame) => {
                 let max_code_size = context.cfg().max_code_size();
+                let code_deposit_cost = context.cfg().gas_schedule().code_deposit;
                 return_eofcreate(
                     context.journal(),
                     self.checkpoint,
                     &mut interpreter_result,
                     frame.created_address,
                     max_code_size,
+                    code_deposit_cost,
                 );
 
                 ItemOrResult::Result(FrameResult::EOFCreate(CreateOutcome::new(
@@ -614,6 +685,9 @@ where
         result: Self::FrameResult,
     ) -> Result<(), Self::Error> {
         self.memory.borrow_mut().free_context();
+        self.shared_stack
+            .borrow_mut()
+            .resume(&mut self.interpreter.stack);
         context.take_error()?;
 
         // Insert result to the top frame.
@@ -740,6 +814,7 @@ pub fn return_create<JOURNAL: Journal>(
     interpreter_result: &mut InterpreterResult,
     address: Address,
     max_code_size: usize,
+    code_deposit_cost: u64,
     spec_id: SpecId,
 ) {
     // If return is not ok revert and return.
@@ -764,7 +839,7 @@ pub fn return_create<JOURNAL: Journal>(
         interpreter_result.result = InstructionResult::CreateContractSizeLimit;
         return;
     }
-    let gas_for_code = interpreter_result.output.len() as u64 * gas::CODEDEPOSIT;
+    let gas_for_code = interpreter_result.output.len() as u64 * code_deposit_cost;
     if !interpreter_result.gas.record_cost(gas_for_code) {
         // Record code deposit gas cost and check if we are out of gas.
         // EIP-2 point 3: If contract creation does not have enough gas to pay for the
@@ -796,6 +871,7 @@ pub fn return_eofcreate<JOURNAL: Journal>(
     interpreter_result: &mut InterpreterResult,
     address: Address,
     max_code_size: usize,
+    code_deposit_cost: u64,
 ) {
     // Note we still execute RETURN opcode and return the bytes.
     // In EOF those opcodes should abort execution.
@@ -816,7 +892,7 @@ pub fn return_eofcreate<JOURNAL: Journal>(
     }
 
     // Deduct gas for code deployment.
-    let gas_for_code = interpreter_result.output.len() as u64 * gas::CODEDEPOSIT;
+    let gas_for_code = interpreter_result.output.len() as u64 * code_deposit_cost;
     if !interpreter_result.gas.record_cost(gas_for_code) {
         journal.checkpoint_revert(checkpoint);
         interpreter_result.result = InstructionResult::OutOfGas;
@@ -129,7 +129,8 @@ pub trait EthHandler {
         &self,
         context: &Self::Context,
     ) -> Result<InitialAndFloorGas, Self::Error> {
-        validation::validate_initial_tx_gas(context.tx(), context.cfg().spec().into())
+        let cfg = context.cfg();
+        validation::validate_initial_tx_gas(context.tx(), cfg.spec().into(), cfg.gas_schedule())
             .map_err(From::from)
     }
 
@@ -14,7 +14,24 @@ use state::EvmState;
 use std::vec::Vec;
 
 pub struct MainnetHandler<CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS> {
-    pub _phantom: core::marker::PhantomData<(CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS)>,
+    /// Instruction executor cloned for every transaction.
+    pub instructions: INSTRUCTIONS,
+    pub _phantom: core::marker::PhantomData<(CTX, ERROR, FRAME, PRECOMPILES)>,
+}
+
+impl<CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS>
+    MainnetHandler<CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS>
+{
+    /// Creates a handler that executes frames with clones of `instructions`.
+    ///
+    /// Used to run custom opcodes, see
+    /// [`EthInstructionExecutor::register_opcode`](crate::instructions::EthInstructionExecutor::register_opcode).
+    pub fn new(instructions: INSTRUCTIONS) -> Self {
+        Self {
+            instructions,
+            _phantom: core::marker::PhantomData,
+        }
+    }
 }
 
 impl<CTX, ERROR, FRAME, PRECOMPILES, INSTRUCTIONS> EthHandler
@@ -44,15 +61,17 @@ where
     type Precompiles = PRECOMPILES;
     type Instructions = INSTRUCTIONS;
     type HaltReason = HaltReason;
+
+    fn instructions(&self, _context: &mut Self::Context) -> Self::Instructions {
+        self.instructions.clone()
+    }
 }
 
 impl<CTX: Host + CfgGetter, ERROR, FRAME, INSTRUCTIONS: Default> Default
     for MainnetHandler<CTX, ERROR, FRAME, EthPrecompileProvider<CTX, ERROR>, INSTRUCTIONS>
 {
     fn default() -> Self {
-        Self {
-            _phantom: core::marker::PhantomData,
-        }
+        Self::new(INSTRUCTIONS::default())
     }
 }
 
@@ -1,8 +1,20 @@
+use bytecode::{
+    opcode::{
+        custom::{CustomOpCodeError, CustomOpCodes},
+        OpCodeInfo,
+    },
+    JumpTable,
+};
+use context_interface::Cfg;
+use core::cell::RefCell;
 use interpreter::{
-    table::{make_instruction_table, InstructionTable},
-    Host, Interpreter, InterpreterAction, InterpreterTypes,
+    interpreter_types::{BytecodeHash, LegacyBytecode, RuntimeFlag},
+    table::{make_instruction_table, Instruction, InstructionTable},
+    DecodedCode, ExecutionBudget, Host, Interpreter, InterpreterAction, InterpreterTypes,
 };
-use std::rc::Rc;
+use primitives::{HashMap, B256};
+use specification::hardfork::SpecId;
+use std::{rc::Rc, sync::Arc};
 
 pub trait InstructionExecutor: Clone + Default {
     type InterpreterTypes: InterpreterTypes;
@@ -14,10 +26,149 @@ pub trait InstructionExecutor: Clone + Default {
         context: &mut Self::CTX,
         interpreter: &mut Interpreter<Self::InterpreterTypes>,
     ) -> Self::Output;
+
+    /// Returns the custom opcodes executed in addition to the builtin ones.
+    ///
+    /// Legacy bytecode of new frames is analyzed with them.
+    fn custom_opcodes(&self) -> Option<&CustomOpCodes> {
+        None
+    }
+
+    /// Returns the jump table of legacy bytecode analyzed with the custom opcodes, `None` if
+    /// the builtin analysis of the bytecode is correct.
+    ///
+    /// `code_hash` is `None` for init code.
+    fn custom_jump_table(&self, code_hash: Option<B256>, bytecode: &[u8]) -> Option<JumpTable> {
+        let _ = code_hash;
+        self.custom_opcodes()
+            .filter(|custom| custom.has_immediates())
+            .map(|custom| custom.analyze_legacy(bytecode))
+    }
+}
+
+/// Custom opcodes with their instructions and static gas.
+///
+/// See [`EthInstructionExecutor::register_opcode`].
+pub struct CustomInstructions<WIRE: InterpreterTypes, HOST> {
+    opcodes: CustomOpCodes,
+    instructions: [Option<Instruction<WIRE, HOST>>; 256],
+    static_gas: [u64; 256],
+    /// Jump tables of legacy bytecode analyzed with the custom opcodes, by code hash.
+    jump_tables: RefCell<HashMap<B256, JumpTable>>,
+}
+
+impl<WIRE: InterpreterTypes, HOST> Clone for CustomInstructions<WIRE, HOST> {
+    fn clone(&self) -> Self {
+        Self {
+            opcodes: self.opcodes,
+            instructions: self.instructions,
+            static_gas: self.static_gas,
+            jump_tables: self.jump_tables.clone(),
+        }
+    }
+}
+
+impl<WIRE: InterpreterTypes, HOST> Default for CustomInstructions<WIRE, HOST> {
+    fn default() -> Self {
+        Self {
+            opcodes: CustomOpCodes::new(),
+            instructions: [None; 256],
+            static_gas: [0; 256],
+            jump_tables: RefCell::default(),
+        }
+    }
+}
+
+impl<WIRE: InterpreterTypes, HOST> CustomInstructions<WIRE, HOST> {
+    /// Registers a custom opcode, see [`CustomOpCodes::register`].
+    pub fn register(
+        &mut self,
+        opcode: u8,
+        info: OpCodeInfo,
+        static_gas: u64,
+        instruction: Instruction<WIRE, HOST>,
+    ) -> Result<(), CustomOpCodeError> {
+        self.opcodes.register(opcode, info)?;
+        self.instructions[opcode as usize] = Some(instruction);
+        self.static_gas[opcode as usize] = static_gas;
+        // Immediate sizes may have changed.
+        self.jump_tables.get_mut().clear();
+        Ok(())
+    }
+
+    /// Returns the information of registered opcodes.
+    pub fn opcodes(&self) -> &CustomOpCodes {
+        &self.opcodes
+    }
+
+    /// Returns the registered opcodes with their instructions.
+    pub fn instructions(&self) -> impl Iterator<Item = (u8, Instruction<WIRE, HOST>)> + '_ {
+        self.instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(opcode, instruction)| Some((opcode as u8, (*instruction)?)))
+    }
+
+    /// Returns the static gas of every opcode, zero for builtin opcodes.
+    pub fn static_gas(&self) -> &[u64; 256] {
+        &self.static_gas
+    }
+
+    /// Returns the jump table of legacy bytecode analyzed with the custom opcodes, `None` if
+    /// no custom opcode has immediates.
+    ///
+    /// Jump tables are cached by `code_hash` and shared by all clones of the executor.
+    pub fn jump_table(&self, code_hash: Option<B256>, bytecode: &[u8]) -> Option<JumpTable> {
+        if !self.opcodes.has_immediates() {
+            return None;
+        }
+        let analyze = || self.opcodes.analyze_legacy(bytecode);
+        // Init code of `CREATE` is not hashed, its hash is zero and can't be used as a key.
+        let Some(code_hash) = code_hash.filter(|code_hash| !code_hash.is_zero()) else {
+            return Some(analyze());
+        };
+        Some(
+            self.jump_tables
+                .borrow_mut()
+                .entry(code_hash)
+                .or_insert_with(analyze)
+                .clone(),
+        )
+    }
+}
+
+/// Natively compiled bytecode.
+///
+/// It is called with the same interpreter state and host the interpreter would run with,
+/// and returns the next action the same way [`Interpreter::run`] does.
+pub type CompiledFunction<WIRE, HOST> = fn(&mut Interpreter<WIRE>, &mut HOST) -> InterpreterAction;
+
+/// Source of ahead-of-time or just-in-time compiled bytecode.
+///
+/// See [`EthInstructionExecutor::with_compiled_code`].
+pub trait CompiledCodeProvider<WIRE: InterpreterTypes, HOST> {
+    /// Returns the compiled function of the bytecode with `code_hash` for `spec_id`,
+    /// or `None` if the bytecode should be interpreted.
+    fn compiled_function(
+        &self,
+        code_hash: B256,
+        spec_id: SpecId,
+    ) -> Option<CompiledFunction<WIRE, HOST>>;
 }
 
 pub struct EthInstructionExecutor<WIRE: InterpreterTypes, HOST> {
     instruction_table: Rc<InstructionTable<WIRE, HOST>>,
+    /// Set only if custom opcodes are registered.
+    custom: Option<Rc<CustomInstructions<WIRE, HOST>>>,
+    /// Execution budget of the transaction, created from [`Cfg`] on first run.
+    budget: Option<ExecutionBudget>,
+    /// Compiled bytecode that is run instead of the interpreter.
+    compiled_code: Option<Rc<dyn CompiledCodeProvider<WIRE, HOST>>>,
+    /// Decoded legacy bytecode used when [`Cfg::is_decoded_dispatch_enabled`] is set.
+    ///
+    /// With `std` it is shared through the [global](bytecode::AnalysisCache::global) analysis
+    /// cache, this map only saves locking it on every frame.
+    decoded_code: HashMap<B256, Arc<DecodedCode<WIRE>>>,
 }
 
 pub trait InstructionExecutorGetter {
@@ -33,6 +184,10 @@ where
     fn clone(&self) -> Self {
         Self {
             instruction_table: self.instruction_table.clone(),
+            custom: self.custom.clone(),
+            budget: self.budget,
+            compiled_code: self.compiled_code.clone(),
+            decoded_code: self.decoded_code.clone(),
         }
     }
 }
@@ -45,13 +200,55 @@ where
     pub fn new() -> Self {
         Self {
             instruction_table: Rc::new(make_instruction_table::<WIRE, HOST>()),
+            custom: None,
+            budget: None,
+            compiled_code: None,
+            decoded_code: HashMap::default(),
         }
     }
+
+    /// Runs compiled bytecode from `provider` when it has it, falling back to the interpreter.
+    ///
+    /// The compiled function is called every time the frame is run, including when it resumes
+    /// after a sub call returns, and is responsible for continuing from the interpreter state.
+    /// The execution budget and the static gas of custom opcodes are not applied to it.
+    pub fn with_compiled_code(
+        mut self,
+        provider: Rc<dyn CompiledCodeProvider<WIRE, HOST>>,
+    ) -> Self {
+        self.compiled_code = Some(provider);
+        self
+    }
+
+    /// Registers a custom opcode.
+    ///
+    /// `static_gas` is charged before `instruction` is executed. Legacy bytecode run by this
+    /// executor is analyzed with the immediate size from `info`. The registration is local
+    /// to this executor and its clones.
+    ///
+    /// Fails if `opcode` or the name from `info` is used by a builtin opcode.
+    pub fn register_opcode(
+        &mut self,
+        opcode: u8,
+        info: OpCodeInfo,
+        static_gas: u64,
+        instruction: Instruction<WIRE, HOST>,
+    ) -> Result<(), CustomOpCodeError> {
+        let custom = self.custom.get_or_insert_with(Default::default);
+        Rc::make_mut(custom).register(opcode, info, static_gas, instruction)?;
+        Rc::make_mut(&mut self.instruction_table)[opcode as usize] = instruction;
+        Ok(())
+    }
+
+    /// Returns the registered custom opcodes.
+    pub fn custom_instructions(&self) -> Option<&Rc<CustomInstructions<WIRE, HOST>>> {
+        self.custom.as_ref()
+    }
 }
 
 impl<IT, CTX> InstructionExecutor for EthInstructionExecutor<IT, CTX>
 where
-    IT: InterpreterTypes,
+    IT: InterpreterTypes + 'static,
     CTX: Host,
 {
     type InterpreterTypes = IT;
@@ -65,7 +262,62 @@ where
         context: &mut Self::CTX,
         interpreter: &mut Interpreter<Self::InterpreterTypes>,
     ) -> Self::Output {
-        interpreter.run_plain(self.instruction_table.as_ref(), context)
+        // Init code of `CREATE` is not hashed, its hash is zero and can't be used as a key.
+        let code_hash = interpreter
+            .bytecode
+            .bytecode_hash()
+            .filter(|code_hash| !code_hash.is_zero());
+        if let Some(provider) = &self.compiled_code {
+            let compiled = code_hash.and_then(|code_hash| {
+                provider.compiled_function(code_hash, interpreter.runtime_flag.spec_id())
+            });
+            if let Some(compiled) = compiled {
+                return compiled(interpreter, context);
+            }
+        }
+        let budget = self.budget.get_or_insert_with(|| {
+            let cfg = context.cfg();
+            ExecutionBudget::new(cfg.max_instructions(), cfg.max_execution_time())
+        });
+        if context.cfg().is_decoded_dispatch_enabled()
+            && !interpreter.runtime_flag.is_eof()
+            && budget.is_unlimited()
+            && self.custom.is_none()
+        {
+            let decode = || DecodedCode::new(interpreter.bytecode.bytecode_slice());
+            let code = match code_hash {
+                Some(code_hash) => self
+                    .decoded_code
+                    .entry(code_hash)
+                    .or_insert_with(|| {
+                        #[cfg(feature = "std")]
+                        return bytecode::AnalysisCache::global().get_or_derive(code_hash, decode);
+                        #[cfg(not(feature = "std"))]
+                        Arc::new(decode())
+                    })
+                    .clone(),
+                None => Arc::new(decode()),
+            };
+            let table = self.instruction_table.as_ref();
+            if context.cfg().is_block_precharge_enabled() {
+                return interpreter.run_decoded_precharged(&code, table, context);
+            }
+            return interpreter.run_decoded(&code, table, context);
+        }
+        interpreter.run_with_budget(
+            self.instruction_table.as_ref(),
+            self.custom.as_deref().map(CustomInstructions::static_gas),
+            context,
+            budget,
+        )
+    }
+
+    fn custom_opcodes(&self) -> Option<&CustomOpCodes> {
+        self.custom.as_deref().map(CustomInstructions::opcodes)
+    }
+
+    fn custom_jump_table(&self, code_hash: Option<B256>, bytecode: &[u8]) -> Option<JumpTable> {
+        self.custom.as_ref()?.jump_table(code_hash, bytecode)
     }
 }
 
@@ -78,3 +330,221 @@ where
         Self::new()
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use bytecode::{
+        opcode::{stack_io, OpCode, OpCodeInfo, STOP},
+        Bytecode,
+    };
+    use context::Context;
+    use core::cell::RefCell;
+    use interpreter::{
+        interpreter::{EthInterpreter, ExtBytecode},
+        interpreter_types::{Jumps, LoopControl},
+        InputsImpl, InstructionResult, InterpreterResult, SharedMemory, Stack,
+    };
+    use primitives::{Bytes, U256};
+
+    fn push_42<H: Host + ?Sized>(interpreter: &mut Interpreter<EthInterpreter>, _host: &mut H) {
+        if !interpreter.stack.push(U256::from(42)) {
+            interpreter
+                .control
+                .set_instruction_result(InstructionResult::StackOverflow);
+        }
+    }
+
+    #[test]
+    fn register_custom_opcode() {
+        // 0x0D is not used by any builtin opcode.
+        const PUSH42: u8 = 0x0D;
+        let mut executor = EthInstructionExecutor::<EthInterpreter, Context>::new();
+        executor
+            .register_opcode(
+                PUSH42,
+                stack_io(OpCodeInfo::new("PUSH42"), 0, 1),
+                7,
+                push_42,
+            )
+            .unwrap();
+        assert_eq!(
+            executor
+                .custom_opcodes()
+                .and_then(|custom| custom.get(PUSH42)),
+            Some(stack_io(OpCodeInfo::new("PUSH42"), 0, 1))
+        );
+        // Registration is local to the executor.
+        assert!(OpCode::new(PUSH42).is_none());
+        assert!(EthInstructionExecutor::<EthInterpreter, Context>::new()
+            .custom_opcodes()
+            .is_none());
+
+        let mut interpreter = Interpreter::new(
+            Rc::new(RefCell::new(SharedMemory::new())),
+            ExtBytecode::new(Bytecode::new_legacy([PUSH42, STOP].into())),
+            InputsImpl::default(),
+            false,
+            false,
+            SpecId::PRAGUE,
+            100,
+            Stack::new(),
+        );
+        let mut context = Context::default();
+        let InterpreterAction::Return { result } = executor.run(&mut context, &mut interpreter)
+        else {
+            panic!("expected return action");
+        };
+        assert_eq!(result.result, InstructionResult::Stop);
+        assert_eq!(result.gas.spent(), 7);
+        assert_eq!(interpreter.stack.pop(), Ok(U256::from(42)));
+    }
+
+    /// Has compiled code for a single code hash.
+    struct SingleCompiled(B256);
+
+    impl CompiledCodeProvider<EthInterpreter, Context> for SingleCompiled {
+        fn compiled_function(
+            &self,
+            code_hash: B256,
+            _spec_id: SpecId,
+        ) -> Option<CompiledFunction<EthInterpreter, Context>> {
+            (code_hash == self.0).then_some(return_42)
+        }
+    }
+
+    fn return_42(
+        interpreter: &mut Interpreter<EthInterpreter>,
+        _host: &mut Context,
+    ) -> InterpreterAction {
+        InterpreterAction::Return {
+            result: InterpreterResult::new(
+                InstructionResult::Return,
+                Bytes::from_static(&[42]),
+                *interpreter.control.gas(),
+            ),
+        }
+    }
+
+    #[test]
+    fn compiled_code_with_fallback() {
+        let compiled_hash = B256::with_last_byte(1);
+        let mut executor = EthInstructionExecutor::<EthInterpreter, Context>::new()
+            .with_compiled_code(Rc::new(SingleCompiled(compiled_hash)));
+        let mut context = Context::default();
+
+        for (code_hash, expected) in [
+            (compiled_hash, InstructionResult::Return),
+            (B256::with_last_byte(2), InstructionResult::Stop),
+        ] {
+            let mut interpreter = Interpreter::new(
+                Rc::new(RefCell::new(SharedMemory::new())),
+                ExtBytecode::new_with_hash(Bytecode::new_legacy([STOP].into()), code_hash),
+                InputsImpl::default(),
+                false,
+                false,
+                SpecId::PRAGUE,
+                100,
+                Stack::new(),
+            );
+            let InterpreterAction::Return { result } = executor.run(&mut context, &mut interpreter)
+            else {
+                panic!("expected return action");
+            };
+            assert_eq!(result.result, expected);
+        }
+    }
+
+    /// Runs legacy bytecode and returns the result, the stack and the instruction pointer.
+    fn run_legacy(
+        code: &[u8],
+        spec_id: SpecId,
+        gas_limit: u64,
+        decoded_dispatch: bool,
+        block_precharge: bool,
+    ) -> (InterpreterResult, Vec<U256>, usize) {
+        let mut executor = EthInstructionExecutor::<EthInterpreter, Context>::new();
+        let mut context = Context::default();
+        context.modify_cfg(|cfg| {
+            cfg.decoded_dispatch = decoded_dispatch;
+            cfg.block_precharge = block_precharge;
+        });
+        let mut interpreter = Interpreter::new(
+            Rc::new(RefCell::new(SharedMemory::new())),
+            {
+                // Decoded code is cached globally by the hash, so it must be the real one.
+                let bytecode = Bytecode::new_legacy(code.to_vec().into());
+                let code_hash = bytecode.hash_slow();
+                ExtBytecode::new_with_hash(bytecode, code_hash)
+            },
+            InputsImpl::default(),
+            false,
+            false,
+            spec_id,
+            gas_limit,
+            Stack::new(),
+        );
+        let InterpreterAction::Return { result } = executor.run(&mut context, &mut interpreter)
+        else {
+            panic!("expected return action");
+        };
+        (
+            result,
+            interpreter.stack.data().to_vec(),
+            interpreter.bytecode.pc(),
+        )
+    }
+
+    #[test]
+    fn decoded_dispatch_matches_loop() {
+        let programs = [
+            // Counts down from 10 in a loop.
+            "600a5b600190038060025700",
+            // Jumps into push data.
+            "600456605b00",
+            // Jumps out of the bytecode.
+            "61ffff56",
+            // Jumps to a target that doesn't fit into usize.
+            "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff56",
+            // Stack underflow in JUMPI and JUMP.
+            "57",
+            "56",
+            // Reads the instruction pointer, ends with truncated PUSH4.
+            "5860015801630102",
+            // Returns memory.
+            "602a5f5260205ff3",
+            // Pushes until the stack overflows.
+            "5b5f600056",
+            // Conditional jump that is not taken.
+            "60005f5700",
+            // Duplicates, swaps and pops, then underflows in SWAP2, DUP3 and POP.
+            "6001600281819150509100",
+            "600160028200",
+            "5050",
+            // PUSH0 halts before Shanghai.
+            "60015f60020100",
+            // Reads remaining gas and calldata.
+            "5a60015a0335600101",
+            // TLOAD halts before Cancun inside a block.
+            "600160025c600300",
+            // Underflows in ADD after a push.
+            "600101",
+        ];
+        for program in programs {
+            let code = primitives::hex::decode(program).unwrap();
+            for spec_id in [SpecId::PRAGUE, SpecId::BERLIN] {
+                for gas_limit in (0..64).chain([100_000]) {
+                    let expected = run_legacy(&code, spec_id, gas_limit, false, false);
+                    for block_precharge in [false, true] {
+                        assert_eq!(
+                            run_legacy(&code, spec_id, gas_limit, true, block_precharge),
+                            expected,
+                            "program {program} in {spec_id:?} with gas limit {gas_limit}, \
+                             block precharge {block_precharge}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -53,7 +53,7 @@ where
 
     fn run(
         &mut self,
-        _context: &mut Self::Context,
+        context: &mut Self::Context,
         address: &Address,
         bytes: &Bytes,
         gas_limit: u64,
@@ -68,10 +68,26 @@ where
             output: Bytes::new(),
         };
 
-        match (*precompile)(bytes, gas_limit) {
+        // A price set in the gas schedule replaces the one of the precompile. It is charged up
+        // front and the precompile runs with the gas that remains.
+        let override_cost = context
+            .cfg()
+            .gas_schedule()
+            .precompile_gas(address)
+            .map(|price| price.cost(bytes.len()));
+        if let Some(cost) = override_cost {
+            if !result.gas.record_cost(cost) {
+                result.result = InstructionResult::PrecompileOOG;
+                return Ok(Some(result));
+            }
+        }
+
+        match (*precompile)(bytes, result.gas.remaining()) {
             Ok(output) => {
-                let underflow = result.gas.record_cost(output.gas_used);
-                assert!(underflow, "Gas underflow is not possible");
+                if override_cost.is_none() {
+                    let underflow = result.gas.record_cost(output.gas_used);
+                    assert!(underflow, "Gas underflow is not possible");
+                }
                 result.result = InstructionResult::Return;
                 result.output = output.bytes;
             }
@@ -7,7 +7,7 @@ use context_interface::{
 use core::cmp::{self, Ordering};
 use interpreter::gas::{self, InitialAndFloorGas};
 use primitives::{B256, U256};
-use specification::{eip4844, hardfork::SpecId};
+use specification::{eip4844, gas_schedule::GasSchedule, hardfork::SpecId};
 use state::AccountInfo;
 use std::boxed::Box;
 
@@ -222,7 +222,7 @@ pub fn validate_tx_env<CTX: TransactionGetter + BlockGetter + CfgGetter, Error>(
 
     // EIP-3860: Limit and meter initcode
     if spec_id.is_enabled_in(SpecId::SHANGHAI) && tx.kind().is_create() {
-        let max_initcode_size = context.cfg().max_code_size().saturating_mul(2);
+        let max_initcode_size = context.cfg().max_initcode_size();
         if context.tx().input().len() > max_initcode_size {
             return Err(InvalidTransaction::CreateInitCodeSizeLimit);
         }
@@ -295,10 +295,12 @@ pub fn validate_tx_against_account<CTX: TransactionGetter + CfgGetter>(
 pub fn validate_initial_tx_gas(
     tx: impl Transaction,
     spec: SpecId,
+    gas_schedule: &GasSchedule,
 ) -> Result<InitialAndFloorGas, InvalidTransaction> {
     let (accounts, storages) = tx.access_list_nums().unwrap_or_default();
 
     let gas = gas::calculate_initial_tx_gas(
+        gas_schedule,
         spec,
         tx.input(),
         tx.kind().is_create(),
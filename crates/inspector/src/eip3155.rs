@@ -1,6 +1,6 @@
 use crate::{inspectors::GasInspector, Inspector};
 use revm::{
-    bytecode::opcode::OpCode,
+    bytecode::opcode::custom::CustomOpCodes,
     context::Cfg,
     context_interface::{CfgGetter, Journal, JournalGetter, Transaction, TransactionGetter},
     interpreter::{
@@ -28,6 +28,8 @@ pub struct TracerEip3155<CTX, INTR> {
     skip: bool,
     include_memory: bool,
     memory: Option<String>,
+    /// Custom opcodes named in the trace in addition to the builtin ones.
+    custom_opcodes: CustomOpCodes,
     _phantom: std::marker::PhantomData<(CTX, INTR)>,
 }
 
@@ -145,6 +147,7 @@ where
             refunded: 0,
             mem_size: 0,
             skip: false,
+            custom_opcodes: CustomOpCodes::new(),
             _phantom: Default::default(),
         }
     }
@@ -161,6 +164,13 @@ where
         self
     }
 
+    /// Names custom opcodes in the `opName` field, see
+    /// [`InstructionExecutor::custom_opcodes`](revm::handler::instructions::InstructionExecutor::custom_opcodes).
+    pub fn with_custom_opcodes(mut self, custom_opcodes: CustomOpCodes) -> Self {
+        self.custom_opcodes = custom_opcodes;
+        self
+    }
+
     fn write_value(&mut self, value: &impl serde::Serialize) -> std::io::Result<()> {
         serde_json::to_writer(&mut *self.output, value)?;
         self.output.write_all(b"\n")?;
@@ -238,7 +248,10 @@ where
             refund: hex_number(self.refunded as u64),
             mem_size: self.mem_size.to_string(),
 
-            op_name: OpCode::new(self.opcode).map(|i| i.as_str()),
+            op_name: self
+                .custom_opcodes
+                .info(self.opcode)
+                .map(|info| info.name()),
             error: if !interp.control.instruction_result().is_ok() {
                 Some(format!("{:?}", interp.control.instruction_result()))
             } else {
@@ -286,3 +299,70 @@ fn hex_number_u256(b: &U256) -> String {
         format!("0x{s}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::RefCell;
+    use revm::{
+        bytecode::{
+            opcode::{immediate_size, OpCodeInfo, STOP},
+            Bytecode,
+        },
+        interpreter::{
+            interpreter::{EthInterpreter, ExtBytecode},
+            InputsImpl, SharedMemory,
+        },
+        specification::hardfork::SpecId,
+        Context,
+    };
+    use std::rc::Rc;
+
+    /// Trace output shared with the test.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn names_custom_opcodes() {
+        let mut custom = CustomOpCodes::new();
+        custom
+            .register(0x0C, immediate_size(OpCodeInfo::new("PUSHB"), 1))
+            .unwrap();
+        let output = SharedBuffer::default();
+        let mut tracer = TracerEip3155::new(Box::new(output.clone())).with_custom_opcodes(custom);
+
+        let mut context = Context::default();
+        let mut interpreter = Interpreter::<EthInterpreter>::new(
+            Rc::new(RefCell::new(SharedMemory::new())),
+            ExtBytecode::new(Bytecode::new_legacy([0x0C, 42, STOP].into())),
+            InputsImpl::default(),
+            false,
+            false,
+            SpecId::PRAGUE,
+            100,
+            Stack::new(),
+        );
+        for pc in [0, 2] {
+            interpreter.bytecode.absolute_jump(pc);
+            tracer.step(&mut interpreter, &mut context);
+            tracer.step_end(&mut interpreter, &mut context);
+        }
+
+        let trace = String::from_utf8(output.0.take()).unwrap();
+        let names: Vec<_> = trace
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["opName"].clone())
+            .collect();
+        assert_eq!(names, ["PUSHB", "STOP"]);
+    }
+}
@@ -124,3 +124,130 @@ pub fn inspect_main_commit<
         res.result
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Inspector;
+    use database::{BenchmarkDB, EEADDRESS, FFADDRESS};
+    use revm::{
+        bytecode::{
+            opcode::{
+                immediate_size, stack_io, OpCodeInfo, JUMP, JUMPDEST, PUSH0, PUSH1, SSTORE, STOP,
+            },
+            Bytecode,
+        },
+        context_interface::result::ExecutionBudgetError,
+        handler::instructions::CustomInstructions,
+        interpreter::{
+            interpreter_types::{Immediates, Jumps, LoopControl},
+            Host, InstructionResult, Interpreter,
+        },
+        primitives::{TxKind, U256},
+    };
+    use std::rc::Rc;
+
+    /// Counts the steps of the execution.
+    struct StepCounter<'a>(&'a mut u64);
+
+    impl<CTX, INTR: InterpreterTypes> Inspector<CTX, INTR> for StepCounter<'_> {
+        fn step(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX) {
+            *self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn instruction_budget_halts_inspected_infinite_loop() {
+        let bytecode = Bytecode::new_legacy([JUMPDEST, PUSH1, 0x00, JUMP].into());
+
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| cfg.max_instructions = Some(1000))
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.gas_limit = u64::MAX / 2;
+                tx.caller = EEADDRESS;
+                tx.kind = TxKind::Call(FFADDRESS);
+            });
+
+        let mut steps = 0;
+        let result = ctx
+            .inspect_previous(StepCounter(&mut steps))
+            .unwrap()
+            .result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::ExecutionBudgetExceeded(ExecutionBudgetError::InstructionLimit),
+                ..
+            }
+        ));
+        assert_eq!(steps, 1000);
+    }
+
+    /// Pushes the one byte immediate of the custom opcode.
+    fn push_immediate<H: Host>(interpreter: &mut Interpreter<EthInterpreter>, _host: &mut H) {
+        let value = interpreter.bytecode.read_u8();
+        interpreter.bytecode.relative_jump(1);
+        if !interpreter.stack.push(U256::from(value)) {
+            interpreter
+                .control
+                .set_instruction_result(InstructionResult::StackOverflow);
+        }
+    }
+
+    /// Inspects the transaction with `PUSHB` registered as `0x0C`.
+    fn inspect_custom<
+        DB: Database,
+        CTX: EthContext
+            + JournalExtGetter
+            + DatabaseGetter<Database = DB>
+            + InspectorCtx<IT = EthInterpreter>,
+    >(
+        ctx: &mut CTX,
+    ) -> Result<ResultAndState<HaltReason>, EVMError<<DB as Database>::Error, InvalidTransaction>>
+    {
+        let mut custom = CustomInstructions::default();
+        custom
+            .register(
+                0x0C,
+                stack_io(immediate_size(OpCodeInfo::new("PUSHB"), 1), 0, 1),
+                3,
+                push_immediate::<CTX>,
+            )
+            .unwrap();
+        InspectorHandlerImpl::<_, _, EthFrame<_, _, _, _>, _, _, EthInterpreter>::new(
+            MainnetHandler::<_, _, _, _, InspectorInstructionExecutor<EthInterpreter, CTX>>::default(),
+            make_instruction_table(),
+        )
+        .with_custom_instructions(Rc::new(custom))
+        .run(ctx)
+    }
+
+    #[test]
+    fn inspect_custom_opcode() {
+        // PUSHB 42, PUSH0, SSTORE, STOP
+        let bytecode = Bytecode::new_legacy([0x0C, 42, PUSH0, SSTORE, STOP].into());
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.gas_limit = 100_000;
+                tx.caller = EEADDRESS;
+                tx.kind = TxKind::Call(FFADDRESS);
+            });
+
+        let mut steps = 0;
+        let ResultAndState { result, state } = inspect_custom(&mut InspectorContext::new(
+            &mut ctx,
+            StepCounter(&mut steps),
+        ))
+        .unwrap();
+        assert!(result.is_success());
+        // Static gas of `PUSHB`, `PUSH0` and cold `SSTORE` of a new value.
+        assert_eq!(result.gas_used(), 21_000 + 3 + 2 + 22_100);
+        assert_eq!(
+            state[&FFADDRESS].storage[&U256::ZERO].present_value(),
+            U256::from(42)
+        );
+        assert_eq!(steps, 4);
+    }
+}
@@ -8,6 +8,7 @@ use revm::{
     database_interface::Database,
     handler::{
         handler::{EthContext, EthError, EthHandler},
+        instructions::CustomInstructions,
         EthFrame, FrameContext, FrameResult,
     },
     handler_interface::{Frame, ItemOrResult, PrecompileProvider},
@@ -18,6 +19,7 @@ use revm::{
     primitives::{Address, Log, U256},
     Context,
 };
+use std::rc::Rc;
 
 /// EVM [Interpreter] callbacks.
 #[auto_impl(&mut, Box)]
@@ -187,6 +189,8 @@ impl<BLOCK, TX, CFG, DB: Database, JOURNAL: Journal<Database = DB> + JournalExt,
 pub struct InspectorHandlerImpl<CTX, ERROR, FRAME, HANDLER, PRECOMPILES, INTR: InterpreterTypes> {
     pub handler: HANDLER,
     pub base_instructions: InstructionTable<INTR, CTX>,
+    /// Custom opcodes executed in addition to `base_instructions`.
+    pub custom_instructions: Option<Rc<CustomInstructions<INTR, CTX>>>,
     _phantom: core::marker::PhantomData<(CTX, ERROR, FRAME, PRECOMPILES)>,
 }
 
@@ -197,9 +201,17 @@ impl<CTX, ERROR, FRAME, HANDLER, PRECOMPILES, INTR: InterpreterTypes>
         Self {
             handler,
             base_instructions,
+            custom_instructions: None,
             _phantom: core::marker::PhantomData,
         }
     }
+
+    /// Executes custom opcodes registered on an
+    /// [`EthInstructionExecutor`](revm::handler::instructions::EthInstructionExecutor).
+    pub fn with_custom_instructions(mut self, custom: Rc<CustomInstructions<INTR, CTX>>) -> Self {
+        self.custom_instructions = Some(custom);
+        self
+    }
 }
 
 pub trait FrameInterpreterGetter {
@@ -244,7 +256,11 @@ where
     type HaltReason = <HANDLER as EthHandler>::HaltReason;
 
     fn instructions(&self, _context: &mut Self::Context) -> Self::Instructions {
-        InspectorInstructionExecutor::new(self.base_instructions)
+        let executor = InspectorInstructionExecutor::new(self.base_instructions);
+        match &self.custom_instructions {
+            Some(custom) => executor.with_custom_instructions(custom.clone()),
+            None => executor,
+        }
     }
 
     fn frame_init_first(
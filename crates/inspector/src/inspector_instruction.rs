@@ -1,14 +1,19 @@
 use core::mem::MaybeUninit;
 use revm::{
-    bytecode::opcode::OpCode,
-    context_interface::JournalGetter,
-    handler::instructions::InstructionExecutor,
+    bytecode::{
+        opcode::{custom::CustomOpCodes, OpCode},
+        JumpTable,
+    },
+    context_interface::{Cfg, CfgGetter, JournalGetter},
+    handler::instructions::{CustomInstructions, InstructionExecutor},
     interpreter::{
         instructions::host::{log, selfdestruct},
         interpreter_types::{Jumps, LoopControl},
         table::{make_instruction_table, CustomInstruction, InstructionTable},
-        Host, Instruction, InstructionResult, Interpreter, InterpreterAction, InterpreterTypes,
+        ExecutionBudget, Host, Instruction, InstructionResult, Interpreter, InterpreterAction,
+        InterpreterTypes,
     },
+    primitives::B256,
     JournalEntry,
 };
 use std::rc::Rc;
@@ -41,6 +46,10 @@ where
 
 pub struct InspectorInstructionExecutor<WIRE: InterpreterTypes, HOST> {
     instruction_table: Rc<[InspectorInstruction<WIRE, HOST>; 256]>,
+    /// Custom opcodes executed in addition to the builtin ones.
+    custom: Option<Rc<CustomInstructions<WIRE, HOST>>>,
+    /// Execution budget of the transaction, created from [`Cfg`] on first run.
+    budget: Option<ExecutionBudget>,
 }
 
 impl<WIRE, HOST> Clone for InspectorInstructionExecutor<WIRE, HOST>
@@ -50,6 +59,8 @@ where
     fn clone(&self) -> Self {
         Self {
             instruction_table: self.instruction_table.clone(),
+            custom: self.custom.clone(),
+            budget: self.budget,
         }
     }
 }
@@ -144,7 +155,25 @@ where
 
         Self {
             instruction_table: Rc::new(table),
+            custom: None,
+            budget: None,
+        }
+    }
+
+    /// Executes `custom` opcodes in addition to the builtin ones.
+    ///
+    /// Static gas of a custom opcode is charged after the step hook is called.
+    pub fn with_custom_instructions(mut self, custom: Rc<CustomInstructions<WIRE, HOST>>) -> Self {
+        let mut table: [InspectorInstruction<WIRE, HOST>; 256] =
+            core::array::from_fn(|i| InspectorInstruction {
+                instruction: self.instruction_table[i].instruction,
+            });
+        for (opcode, instruction) in custom.instructions() {
+            table[opcode as usize] = InspectorInstruction { instruction };
         }
+        self.instruction_table = Rc::new(table);
+        self.custom = Some(custom);
+        self
     }
 }
 
@@ -162,10 +191,20 @@ where
         context: &mut Self::CTX,
         interpreter: &mut Interpreter<Self::InterpreterTypes>,
     ) -> Self::Output {
+        let budget = self.budget.get_or_insert_with(|| {
+            let cfg = context.cfg();
+            ExecutionBudget::new(cfg.max_instructions(), cfg.max_execution_time())
+        });
+
         interpreter.reset_control();
 
         // Main loop
         while interpreter.control.instruction_result().is_continue() {
+            if let Some(result) = budget.record_instruction() {
+                interpreter.control.set_instruction_result(result);
+                break;
+            }
+
             // Get current opcode.
             let opcode = interpreter.bytecode.opcode();
 
@@ -180,6 +219,16 @@ where
             // it will do noop and just stop execution of this contract
             interpreter.bytecode.relative_jump(1);
 
+            if let Some(custom) = &self.custom {
+                let gas = custom.static_gas()[opcode as usize];
+                if !interpreter.control.gas().record_cost(gas) {
+                    interpreter
+                        .control
+                        .set_instruction_result(InstructionResult::OutOfGas);
+                    break;
+                }
+            }
+
             // Execute instruction.
             self.instruction_table[opcode as usize].exec(interpreter, context);
 
@@ -189,6 +238,14 @@ where
 
         interpreter.take_next_action()
     }
+
+    fn custom_opcodes(&self) -> Option<&CustomOpCodes> {
+        self.custom.as_deref().map(CustomInstructions::opcodes)
+    }
+
+    fn custom_jump_table(&self, code_hash: Option<B256>, bytecode: &[u8]) -> Option<JumpTable> {
+        self.custom.as_ref()?.jump_table(code_hash, bytecode)
+    }
 }
 
 impl<WIRE, HOST> Default for InspectorInstructionExecutor<WIRE, HOST>
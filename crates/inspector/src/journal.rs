@@ -1,6 +1,9 @@
 use auto_impl::auto_impl;
 use revm::{
-    context::JournaledState, database_interface::Database, primitives::Log, state::EvmState,
+    context::JournaledState,
+    database_interface::Database,
+    primitives::Log,
+    state::{EvmState, TransientStorage},
     JournalEntry,
 };
 
@@ -13,6 +16,11 @@ pub trait JournalExt {
     fn evm_state(&self) -> &EvmState;
 
     fn evm_state_mut(&mut self) -> &mut EvmState;
+
+    /// Returns the transient storage, `None` if the journal doesn't expose it.
+    fn transient_storage(&self) -> Option<&TransientStorage> {
+        None
+    }
 }
 
 impl<DB: Database> JournalExt for JournaledState<DB> {
@@ -31,6 +39,10 @@ impl<DB: Database> JournalExt for JournaledState<DB> {
     fn evm_state_mut(&mut self) -> &mut EvmState {
         &mut self.state
     }
+
+    fn transient_storage(&self) -> Option<&TransientStorage> {
+        Some(&self.transient_storage)
+    }
 }
 
 #[auto_impl(&, &mut, Box, Arc)]
@@ -6,6 +6,8 @@ mod constants;
 pub use calc::*;
 pub use constants::*;
 
+use core::num::NonZeroU64;
+
 /// Represents the state of gas during execution.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -145,6 +147,15 @@ impl Gas {
         success
     }
 
+    /// Sets the linear word cost and quadratic divisor used for memory expansion.
+    ///
+    /// Should be called before memory is expanded.
+    #[inline]
+    pub fn set_memory_cost(&mut self, word_cost: u64, quadratic_divisor: NonZeroU64) {
+        self.memory.word_cost = word_cost;
+        self.memory.quadratic_divisor = quadratic_divisor;
+    }
+
     /// Record memory expansion
     #[inline]
     #[must_use = "internally uses record_cost that flags out of gas error"]
@@ -174,20 +185,37 @@ pub enum MemoryExtensionResult {
 /// It contains the current memory length and its memory expansion cost.
 ///
 /// It allows us to split gas accounting from memory structure.
-#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemoryGas {
     /// Current memory length
     pub words_num: usize,
     /// Current memory expansion cost
     pub expansion_cost: u64,
+    /// Linear cost of a memory word
+    pub word_cost: u64,
+    /// Divisor of the quadratic part of memory expansion cost
+    pub quadratic_divisor: NonZeroU64,
+}
+
+impl Default for MemoryGas {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MemoryGas {
     pub const fn new() -> Self {
+        Self::new_with_cost(MEMORY, MEMORY_QUADRATIC_DIVISOR)
+    }
+
+    /// Creates a new `MemoryGas` with custom linear word cost and quadratic divisor.
+    pub const fn new_with_cost(word_cost: u64, quadratic_divisor: NonZeroU64) -> Self {
         Self {
             words_num: 0,
             expansion_cost: 0,
+            word_cost,
+            quadratic_divisor,
         }
     }
 
@@ -197,7 +225,8 @@ impl MemoryGas {
             return None;
         }
         self.words_num = new_num;
-        let mut cost = crate::gas::calc::memory_gas(new_num);
+        let mut cost =
+            crate::gas::calc::memory_gas_with_cost(new_num, self.word_cost, self.quadratic_divisor);
         core::mem::swap(&mut self.expansion_cost, &mut cost);
         // Safe to subtract because we know that new_len > length
         // Notice the swap above.
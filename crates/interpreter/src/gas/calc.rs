@@ -1,20 +1,17 @@
 use super::constants::*;
 use crate::{num_words, tri, SStoreResult, SelfDestructResult, StateLoad};
 use context_interface::journaled_state::AccountLoad;
+use core::num::NonZeroU64;
 use primitives::U256;
-use specification::{eip7702, hardfork::SpecId};
+use specification::{gas_schedule::GasSchedule, hardfork::SpecId};
 
 /// `SSTORE` opcode refund calculation.
 #[allow(clippy::collapsible_else_if)]
 #[inline]
-pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
+pub fn sstore_refund(schedule: &GasSchedule, spec_id: SpecId, vals: &SStoreResult) -> i64 {
+    // EIP-3529: Reduction in refunds
+    let sstore_clears_schedule = schedule.sstore_clears_refund as i64;
     if spec_id.is_enabled_in(SpecId::ISTANBUL) {
-        // EIP-3529: Reduction in refunds
-        let sstore_clears_schedule = if spec_id.is_enabled_in(SpecId::LONDON) {
-            (SSTORE_RESET - COLD_SLOAD_COST + ACCESS_LIST_STORAGE_KEY) as i64
-        } else {
-            REFUND_SSTORE_CLEARS
-        };
         if vals.is_new_eq_present() {
             0
         } else {
@@ -33,14 +30,17 @@ pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
 
                 if vals.is_original_eq_new() {
                     let (gas_sstore_reset, gas_sload) = if spec_id.is_enabled_in(SpecId::BERLIN) {
-                        (SSTORE_RESET - COLD_SLOAD_COST, WARM_STORAGE_READ_COST)
+                        (
+                            schedule.sstore_reset.saturating_sub(schedule.cold_sload),
+                            schedule.warm_storage_read,
+                        )
                     } else {
-                        (SSTORE_RESET, sload_cost(spec_id, false))
+                        (schedule.sstore_reset, sload_cost(schedule, spec_id, false))
                     };
                     if vals.is_original_zero() {
-                        refund += (SSTORE_SET - gas_sload) as i64;
+                        refund += schedule.sstore_set.saturating_sub(gas_sload) as i64;
                     } else {
-                        refund += (gas_sstore_reset - gas_sload) as i64;
+                        refund += gas_sstore_reset.saturating_sub(gas_sload) as i64;
                     }
                 }
 
@@ -49,7 +49,7 @@ pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
         }
     } else {
         if !vals.is_present_zero() && vals.is_new_zero() {
-            REFUND_SSTORE_CLEARS
+            sstore_clears_schedule
         } else {
             0
         }
@@ -58,8 +58,10 @@ pub fn sstore_refund(spec_id: SpecId, vals: &SStoreResult) -> i64 {
 
 /// `CREATE2` opcode cost calculation.
 #[inline]
-pub const fn create2_cost(len: usize) -> Option<u64> {
-    CREATE.checked_add(tri!(cost_per_word(len, KECCAK256WORD)))
+pub const fn create2_cost(schedule: &GasSchedule, len: usize) -> Option<u64> {
+    schedule
+        .create
+        .checked_add(tri!(cost_per_word(len, schedule.keccak256_word)))
 }
 
 #[inline]
@@ -87,17 +89,13 @@ const fn log2floor(value: U256) -> u64 {
 
 /// `EXP` opcode cost calculation.
 #[inline]
-pub fn exp_cost(spec_id: SpecId, power: U256) -> Option<u64> {
+pub fn exp_cost(schedule: &GasSchedule, power: U256) -> Option<u64> {
     if power.is_zero() {
-        Some(EXP)
+        Some(schedule.exp)
     } else {
         // EIP-160: EXP cost increase
-        let gas_byte = U256::from(if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
-            50
-        } else {
-            10
-        });
-        let gas = U256::from(EXP)
+        let gas_byte = U256::from(schedule.exp_byte);
+        let gas = U256::from(schedule.exp)
             .checked_add(gas_byte.checked_mul(U256::from(log2floor(power) / 8 + 1))?)?;
 
         u64::try_from(gas).ok()
@@ -106,38 +104,46 @@ pub fn exp_cost(spec_id: SpecId, power: U256) -> Option<u64> {
 
 /// `*COPY` opcodes cost calculation.
 #[inline]
-pub const fn copy_cost_verylow(len: usize) -> Option<u64> {
-    copy_cost(VERYLOW, len)
+pub const fn copy_cost_verylow(schedule: &GasSchedule, len: usize) -> Option<u64> {
+    copy_cost(schedule, VERYLOW, len)
 }
 
 /// `EXTCODECOPY` opcode cost calculation.
 #[inline]
-pub const fn extcodecopy_cost(spec_id: SpecId, len: usize, is_cold: bool) -> Option<u64> {
+pub const fn extcodecopy_cost(
+    schedule: &GasSchedule,
+    spec_id: SpecId,
+    len: usize,
+    is_cold: bool,
+) -> Option<u64> {
     let base_gas = if spec_id.is_enabled_in(SpecId::BERLIN) {
-        warm_cold_cost(is_cold)
-    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        700
+        warm_cold_cost(schedule, is_cold)
     } else {
-        20
+        schedule.extcodecopy
     };
-    copy_cost(base_gas, len)
+    copy_cost(schedule, base_gas, len)
 }
 
 #[inline]
-pub const fn copy_cost(base_cost: u64, len: usize) -> Option<u64> {
-    base_cost.checked_add(tri!(cost_per_word(len, COPY)))
+pub const fn copy_cost(schedule: &GasSchedule, base_cost: u64, len: usize) -> Option<u64> {
+    base_cost.checked_add(tri!(cost_per_word(len, schedule.copy)))
 }
 
 /// `LOG` opcode cost calculation.
 #[inline]
-pub const fn log_cost(n: u8, len: u64) -> Option<u64> {
-    tri!(LOG.checked_add(tri!(LOGDATA.checked_mul(len)))).checked_add(LOGTOPIC * n as u64)
+pub const fn log_cost(schedule: &GasSchedule, n: u8, len: u64) -> Option<u64> {
+    tri!(schedule
+        .log
+        .checked_add(tri!(schedule.log_data.checked_mul(len))))
+    .checked_add(tri!(schedule.log_topic.checked_mul(n as u64)))
 }
 
 /// `KECCAK256` opcode cost calculation.
 #[inline]
-pub const fn keccak256_cost(len: usize) -> Option<u64> {
-    KECCAK256.checked_add(tri!(cost_per_word(len, KECCAK256WORD)))
+pub const fn keccak256_cost(schedule: &GasSchedule, len: usize) -> Option<u64> {
+    schedule
+        .keccak256
+        .checked_add(tri!(cost_per_word(len, schedule.keccak256_word)))
 }
 
 /// Calculate the cost of buffer per word.
@@ -150,84 +156,94 @@ pub const fn cost_per_word(len: usize, multiple: u64) -> Option<u64> {
 ///
 /// Apply extra gas cost of 2 for every 32-byte chunk of initcode.
 ///
-/// This cannot overflow as the initcode length is assumed to be checked.
+/// Saturates, as the word cost of a custom gas schedule can be arbitrarily large.
 #[inline]
-pub const fn initcode_cost(len: usize) -> u64 {
-    let Some(cost) = cost_per_word(len, INITCODE_WORD_COST) else {
-        panic!("initcode cost overflow")
-    };
-    cost
+pub const fn initcode_cost(schedule: &GasSchedule, len: usize) -> u64 {
+    schedule.initcode_word.saturating_mul(num_words(len) as u64)
 }
 
 /// `SLOAD` opcode cost calculation.
 #[inline]
-pub const fn sload_cost(spec_id: SpecId, is_cold: bool) -> u64 {
+pub const fn sload_cost(schedule: &GasSchedule, spec_id: SpecId, is_cold: bool) -> u64 {
     if spec_id.is_enabled_in(SpecId::BERLIN) {
         if is_cold {
-            COLD_SLOAD_COST
+            schedule.cold_sload
         } else {
-            WARM_STORAGE_READ_COST
+            schedule.warm_storage_read
         }
-    } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
-        // EIP-1884: Repricing for trie-size-dependent opcodes
-        ISTANBUL_SLOAD_GAS
-    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        // EIP-150: Gas cost changes for IO-heavy operations
-        200
     } else {
-        50
+        // EIP-150: Gas cost changes for IO-heavy operations
+        // EIP-1884: Repricing for trie-size-dependent opcodes
+        schedule.sload
     }
 }
 
 /// `SSTORE` opcode cost calculation.
 #[inline]
-pub fn sstore_cost(spec_id: SpecId, vals: &SStoreResult, is_cold: bool) -> u64 {
+pub fn sstore_cost(
+    schedule: &GasSchedule,
+    spec_id: SpecId,
+    vals: &SStoreResult,
+    is_cold: bool,
+) -> u64 {
     if spec_id.is_enabled_in(SpecId::BERLIN) {
         // Berlin specification logic
-        let mut gas_cost = istanbul_sstore_cost::<WARM_STORAGE_READ_COST, WARM_SSTORE_RESET>(vals);
+        let mut gas_cost = istanbul_sstore_cost(
+            schedule,
+            schedule.warm_storage_read,
+            schedule.sstore_reset.saturating_sub(schedule.cold_sload),
+            vals,
+        );
 
         if is_cold {
-            gas_cost += COLD_SLOAD_COST;
+            gas_cost = gas_cost.saturating_add(schedule.cold_sload);
         }
         gas_cost
     } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
         // Istanbul logic
-        istanbul_sstore_cost::<ISTANBUL_SLOAD_GAS, SSTORE_RESET>(vals)
+        istanbul_sstore_cost(schedule, schedule.sload, schedule.sstore_reset, vals)
     } else {
         // Frontier logic
-        frontier_sstore_cost(vals)
+        frontier_sstore_cost(schedule, vals)
     }
 }
 
 /// EIP-2200: Structured Definitions for Net Gas Metering
 #[inline]
-fn istanbul_sstore_cost<const SLOAD_GAS: u64, const SSTORE_RESET_GAS: u64>(
+fn istanbul_sstore_cost(
+    schedule: &GasSchedule,
+    sload_gas: u64,
+    sstore_reset_gas: u64,
     vals: &SStoreResult,
 ) -> u64 {
     if vals.is_new_eq_present() {
-        SLOAD_GAS
+        sload_gas
     } else if vals.is_original_eq_present() && vals.is_original_zero() {
-        SSTORE_SET
+        schedule.sstore_set
     } else if vals.is_original_eq_present() {
-        SSTORE_RESET_GAS
+        sstore_reset_gas
     } else {
-        SLOAD_GAS
+        sload_gas
     }
 }
 
 /// Frontier sstore cost just had two cases set and reset values.
 #[inline]
-fn frontier_sstore_cost(vals: &SStoreResult) -> u64 {
+fn frontier_sstore_cost(schedule: &GasSchedule, vals: &SStoreResult) -> u64 {
     if vals.is_present_zero() && !vals.is_new_zero() {
-        SSTORE_SET
+        schedule.sstore_set
     } else {
-        SSTORE_RESET
+        schedule.sstore_reset
     }
 }
 
 /// `SELFDESTRUCT` opcode cost calculation.
 #[inline]
-pub const fn selfdestruct_cost(spec_id: SpecId, res: StateLoad<SelfDestructResult>) -> u64 {
+pub const fn selfdestruct_cost(
+    schedule: &GasSchedule,
+    spec_id: SpecId,
+    res: StateLoad<SelfDestructResult>,
+) -> u64 {
     // EIP-161: State trie clearing (invariant-preserving alternative)
     let should_charge_topup = if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
         res.data.had_value && !res.data.target_exists
@@ -238,21 +254,17 @@ pub const fn selfdestruct_cost(spec_id: SpecId, res: StateLoad<SelfDestructResul
     // EIP-150: Gas cost changes for IO-heavy operations
     let selfdestruct_gas_topup = if spec_id.is_enabled_in(SpecId::TANGERINE) && should_charge_topup
     {
-        25000
+        schedule.new_account
     } else {
         0
     };
 
     // EIP-150: Gas cost changes for IO-heavy operations
-    let selfdestruct_gas = if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        5000
-    } else {
-        0
-    };
+    let selfdestruct_gas = schedule.selfdestruct;
 
-    let mut gas = selfdestruct_gas + selfdestruct_gas_topup;
+    let mut gas = selfdestruct_gas.saturating_add(selfdestruct_gas_topup);
     if spec_id.is_enabled_in(SpecId::BERLIN) && res.is_cold {
-        gas += COLD_ACCOUNT_ACCESS_COST
+        gas = gas.saturating_add(schedule.cold_account_access)
     }
     gas
 }
@@ -279,6 +291,7 @@ pub const fn selfdestruct_cost(spec_id: SpecId, res: StateLoad<SelfDestructResul
 /// as they were present before SPURIOUS_DRAGON hardfork.
 #[inline]
 pub const fn call_cost(
+    schedule: &GasSchedule,
     spec_id: SpecId,
     transfers_value: bool,
     account_load: StateLoad<AccountLoad>,
@@ -286,17 +299,15 @@ pub const fn call_cost(
     let is_empty = account_load.data.is_empty;
     // Account access.
     let mut gas = if spec_id.is_enabled_in(SpecId::BERLIN) {
-        warm_cold_cost_with_delegation(account_load)
-    } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
-        // EIP-150: Gas cost changes for IO-heavy operations
-        700
+        warm_cold_cost_with_delegation(schedule, account_load)
     } else {
-        40
+        // EIP-150: Gas cost changes for IO-heavy operations
+        schedule.call
     };
 
     // Transfer value cost
     if transfers_value {
-        gas += CALLVALUE;
+        gas = gas.saturating_add(schedule.call_value);
     }
 
     // New account cost
@@ -305,10 +316,10 @@ pub const fn call_cost(
         if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
             // Account only if there is value transferred.
             if transfers_value {
-                gas += NEWACCOUNT;
+                gas = gas.saturating_add(schedule.new_account);
             }
         } else {
-            gas += NEWACCOUNT;
+            gas = gas.saturating_add(schedule.new_account);
         }
     }
 
@@ -317,11 +328,11 @@ pub const fn call_cost(
 
 /// Berlin warm and cold storage access cost for account access.
 #[inline]
-pub const fn warm_cold_cost(is_cold: bool) -> u64 {
+pub const fn warm_cold_cost(schedule: &GasSchedule, is_cold: bool) -> u64 {
     if is_cold {
-        COLD_ACCOUNT_ACCESS_COST
+        schedule.cold_account_access
     } else {
-        WARM_STORAGE_READ_COST
+        schedule.warm_storage_read
     }
 }
 
@@ -329,10 +340,13 @@ pub const fn warm_cold_cost(is_cold: bool) -> u64 {
 ///
 /// If delegation is Some, add additional cost for delegation account load.
 #[inline]
-pub const fn warm_cold_cost_with_delegation(load: StateLoad<AccountLoad>) -> u64 {
-    let mut gas = warm_cold_cost(load.is_cold);
+pub const fn warm_cold_cost_with_delegation(
+    schedule: &GasSchedule,
+    load: StateLoad<AccountLoad>,
+) -> u64 {
+    let mut gas = warm_cold_cost(schedule, load.is_cold);
     if let Some(is_cold) = load.data.is_delegate_account_cold {
-        gas += warm_cold_cost(is_cold);
+        gas = gas.saturating_add(warm_cold_cost(schedule, is_cold));
     }
     gas
 }
@@ -340,10 +354,21 @@ pub const fn warm_cold_cost_with_delegation(load: StateLoad<AccountLoad>) -> u64
 /// Memory expansion cost calculation for a given number of words.
 #[inline]
 pub const fn memory_gas(num_words: usize) -> u64 {
+    memory_gas_with_cost(num_words, MEMORY, MEMORY_QUADRATIC_DIVISOR)
+}
+
+/// Memory expansion cost calculation for a given number of words
+/// with custom linear word cost and quadratic divisor.
+#[inline]
+pub const fn memory_gas_with_cost(
+    num_words: usize,
+    word_cost: u64,
+    quadratic_divisor: NonZeroU64,
+) -> u64 {
     let num_words = num_words as u64;
-    MEMORY
+    word_cost
         .saturating_mul(num_words)
-        .saturating_add(num_words.saturating_mul(num_words) / 512)
+        .saturating_add(num_words.saturating_mul(num_words) / quadratic_divisor.get())
 }
 
 /// Init and floor gas from transaction
@@ -364,6 +389,7 @@ pub struct InitialAndFloorGas {
 /// - Intrinsic gas
 /// - Number of tokens in calldata
 pub fn calculate_initial_tx_gas(
+    schedule: &GasSchedule,
     spec_id: SpecId,
     input: &[u8],
     is_create: bool,
@@ -374,37 +400,42 @@ pub fn calculate_initial_tx_gas(
     let mut gas = InitialAndFloorGas::default();
 
     // Initdate stipend
-    let tokens_in_calldata = get_tokens_in_calldata(input, spec_id.is_enabled_in(SpecId::ISTANBUL));
-    gas.initial_gas += tokens_in_calldata * STANDARD_TOKEN_COST;
+    let tokens_in_calldata =
+        get_tokens_in_calldata_with_multiplier(input, schedule.tx_non_zero_byte_multiplier);
+    // Entries of a custom gas schedule can be arbitrarily large, so the costs saturate.
+    gas.initial_gas = tokens_in_calldata.saturating_mul(schedule.tx_token);
 
     // Get number of access list account and storages.
-    gas.initial_gas += access_list_accounts * ACCESS_LIST_ADDRESS;
-    gas.initial_gas += access_list_storages * ACCESS_LIST_STORAGE_KEY;
+    gas.initial_gas = gas
+        .initial_gas
+        .saturating_add(access_list_accounts.saturating_mul(schedule.tx_access_list_address))
+        .saturating_add(access_list_storages.saturating_mul(schedule.tx_access_list_storage_key));
 
     // Base stipend
-    gas.initial_gas += if is_create {
-        if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
-            // EIP-2: Homestead Hard-fork Changes
-            53000
-        } else {
-            21000
-        }
-    } else {
-        21000
-    };
+    gas.initial_gas = gas.initial_gas.saturating_add(schedule.tx_base);
+    if is_create {
+        // EIP-2: Homestead Hard-fork Changes
+        gas.initial_gas = gas.initial_gas.saturating_add(schedule.tx_create);
+    }
 
     // EIP-3860: Limit and meter initcode
     // Init code stipend for bytecode analysis
     if spec_id.is_enabled_in(SpecId::SHANGHAI) && is_create {
-        gas.initial_gas += initcode_cost(input.len())
+        gas.initial_gas = gas
+            .initial_gas
+            .saturating_add(initcode_cost(schedule, input.len()));
     }
 
     // EIP-7702
     if spec_id.is_enabled_in(SpecId::PRAGUE) {
-        gas.initial_gas += authorization_list_num * eip7702::PER_EMPTY_ACCOUNT_COST;
+        gas.initial_gas = gas
+            .initial_gas
+            .saturating_add(authorization_list_num.saturating_mul(schedule.tx_authorization));
 
         // Calculate gas floor for EIP-7623
-        gas.floor_gas = calc_tx_floor_cost(tokens_in_calldata);
+        gas.floor_gas = tokens_in_calldata
+            .saturating_mul(schedule.tx_floor_cost_per_token)
+            .saturating_add(schedule.tx_base);
     }
 
     gas
@@ -413,15 +444,22 @@ pub fn calculate_initial_tx_gas(
 /// Retrieve the total number of tokens in calldata.
 #[inline]
 pub fn get_tokens_in_calldata(input: &[u8], is_istanbul: bool) -> u64 {
-    let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
-    let non_zero_data_len = input.len() as u64 - zero_data_len;
     let non_zero_data_multiplier = if is_istanbul {
         // EIP-2028: Transaction data gas cost reduction
         NON_ZERO_BYTE_MULTIPLIER_ISTANBUL
     } else {
         NON_ZERO_BYTE_MULTIPLIER
     };
-    zero_data_len + non_zero_data_len * non_zero_data_multiplier
+    get_tokens_in_calldata_with_multiplier(input, non_zero_data_multiplier)
+}
+
+/// Retrieve the total number of tokens in calldata where a non-zero byte
+/// is counted as `non_zero_data_multiplier` tokens.
+#[inline]
+pub fn get_tokens_in_calldata_with_multiplier(input: &[u8], non_zero_data_multiplier: u64) -> u64 {
+    let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
+    let non_zero_data_len = input.len() as u64 - zero_data_len;
+    zero_data_len.saturating_add(non_zero_data_len.saturating_mul(non_zero_data_multiplier))
 }
 
 /// Calculate the transaction cost floor as specified in EIP-7623.
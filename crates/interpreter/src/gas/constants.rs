@@ -1,3 +1,5 @@
+use core::num::NonZeroU64;
+
 pub const ZERO: u64 = 0;
 pub const BASE: u64 = 2;
 
@@ -18,6 +20,11 @@ pub const CALLVALUE: u64 = 9000;
 pub const NEWACCOUNT: u64 = 25000;
 pub const EXP: u64 = 10;
 pub const MEMORY: u64 = 3;
+/// Divisor of the quadratic part of memory expansion cost.
+pub const MEMORY_QUADRATIC_DIVISOR: NonZeroU64 = match NonZeroU64::new(512) {
+    Some(divisor) => divisor,
+    None => unreachable!(),
+};
 pub const LOG: u64 = 375;
 pub const LOGDATA: u64 = 8;
 pub const LOGTOPIC: u64 = 375;
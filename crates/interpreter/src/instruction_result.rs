@@ -1,6 +1,6 @@
 use context_interface::{
     journaled_state::TransferError,
-    result::{HaltReason, HaltReasonTrait, OutOfGasError, SuccessReason},
+    result::{ExecutionBudgetError, HaltReason, HaltReasonTrait, OutOfGasError, SuccessReason},
 };
 use core::fmt::Debug;
 
@@ -98,6 +98,10 @@ pub enum InstructionResult {
     EofAuxDataTooSmall,
     /// `EXT*CALL` target address needs to be padded with 0s.
     InvalidEXTCALLTarget,
+    /// Maximum number of executed instructions was reached.
+    InstructionLimit,
+    /// Maximum execution time has elapsed.
+    TimeLimit,
 }
 
 impl From<TransferError> for InstructionResult {
@@ -156,6 +160,10 @@ impl From<HaltReason> for InstructionResult {
             HaltReason::EofAuxDataTooSmall => Self::EofAuxDataTooSmall,
             HaltReason::SubRoutineStackOverflow => Self::SubRoutineStackOverflow,
             HaltReason::InvalidEXTCALLTarget => Self::InvalidEXTCALLTarget,
+            HaltReason::ExecutionBudgetExceeded(error) => match error {
+                ExecutionBudgetError::InstructionLimit => Self::InstructionLimit,
+                ExecutionBudgetError::TimeLimit => Self::TimeLimit,
+            },
         }
     }
 }
@@ -215,6 +223,8 @@ macro_rules! return_error {
             | $crate::InstructionResult::EofAuxDataTooSmall
             | $crate::InstructionResult::EofAuxDataOverflow
             | $crate::InstructionResult::InvalidEXTCALLTarget
+            | $crate::InstructionResult::InstructionLimit
+            | $crate::InstructionResult::TimeLimit
     };
 }
 
@@ -392,6 +402,12 @@ impl<HaltReasonT: HaltReasonTrait> From<InstructionResult> for SuccessOrHalt<Hal
             InstructionResult::InvalidExtDelegateCallTarget => {
                 Self::Internal(InternalResult::InvalidExtDelegateCallTarget)
             }
+            InstructionResult::InstructionLimit => Self::Halt(
+                HaltReason::ExecutionBudgetExceeded(ExecutionBudgetError::InstructionLimit).into(),
+            ),
+            InstructionResult::TimeLimit => Self::Halt(
+                HaltReason::ExecutionBudgetExceeded(ExecutionBudgetError::TimeLimit).into(),
+            ),
         }
     }
 }
@@ -460,6 +476,8 @@ mod tests {
             InstructionResult::CreateContractStartingWithEF,
             InstructionResult::CreateInitCodeSizeLimit,
             InstructionResult::FatalExternalError,
+            InstructionResult::InstructionLimit,
+            InstructionResult::TimeLimit,
         ];
 
         for result in error_results {
@@ -2,9 +2,10 @@ use super::i256::{i256_div, i256_mod};
 use crate::{
     gas,
     interpreter::Interpreter,
-    interpreter_types::{InterpreterTypes, LoopControl, RuntimeFlag, StackTrait},
+    interpreter_types::{InterpreterTypes, LoopControl, StackTrait},
     Host,
 };
+use context_interface::Cfg;
 use primitives::U256;
 
 pub fn add<WIRE: InterpreterTypes, H: Host + ?Sized>(
@@ -94,11 +95,10 @@ pub fn mulmod<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
 pub fn exp<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
-    let spec_id = interpreter.runtime_flag.spec_id();
     popn_top!([op1], op2, interpreter);
-    gas_or_fail!(interpreter, gas::exp_cost(spec_id, *op2));
+    gas_or_fail!(interpreter, gas::exp_cost(host.cfg().gas_schedule(), *op2));
     *op2 = op1.pow(*op2);
 }
 
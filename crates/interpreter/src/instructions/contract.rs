@@ -3,7 +3,7 @@ mod call_helpers;
 pub use call_helpers::{calc_call_gas, get_memory_input_and_out_ranges, resize_memory};
 
 use crate::{
-    gas::{self, cost_per_word, EOF_CREATE_GAS, KECCAK256WORD, MIN_CALLEE_GAS},
+    gas::{self, cost_per_word, EOF_CREATE_GAS, MIN_CALLEE_GAS},
     instructions::utility::IntoAddress,
     interpreter::Interpreter,
     interpreter_action::FrameInput,
@@ -24,7 +24,7 @@ use std::boxed::Box;
 /// EOF Create instruction
 pub fn eofcreate<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     require_eof!(interpreter);
     require_non_staticcall!(interpreter);
@@ -58,7 +58,10 @@ pub fn eofcreate<WIRE: InterpreterTypes, H: Host + ?Sized>(
     }
 
     // Deduct gas for hash that is needed to calculate address.
-    gas_or_fail!(interpreter, cost_per_word(container.len(), KECCAK256WORD));
+    gas_or_fail!(
+        interpreter,
+        cost_per_word(container.len(), host.cfg().gas_schedule().keccak256_word)
+    );
 
     let created_address = interpreter
         .input
@@ -189,6 +192,7 @@ pub fn extcall_gas_calc<WIRE: InterpreterTypes, H: Host + ?Sized>(
     // account_load.is_empty will be accounted if there is transfer value
     // Berlin can be hardcoded as extcall came after berlin.
     let call_cost = gas::call_cost(
+        host.cfg().gas_schedule(),
         interpreter.runtime_flag.spec_id(),
         transfers_value,
         account_load,
@@ -388,14 +392,17 @@ pub fn create<WIRE: InterpreterTypes, const IS_CREATE2: bool, H: Host + ?Sized>(
             .is_enabled_in(SpecId::SHANGHAI)
         {
             // Limit is set as double of max contract bytecode size
-            let max_initcode_size = host.cfg().max_code_size().saturating_mul(2);
+            let max_initcode_size = host.cfg().max_initcode_size();
             if len > max_initcode_size {
                 interpreter
                     .control
                     .set_instruction_result(InstructionResult::CreateInitCodeSizeLimit);
                 return;
             }
-            gas!(interpreter, gas::initcode_cost(len));
+            gas!(
+                interpreter,
+                gas::initcode_cost(host.cfg().gas_schedule(), len)
+            );
         }
 
         let code_offset = as_usize_or_fail!(interpreter, code_offset);
@@ -407,10 +414,13 @@ pub fn create<WIRE: InterpreterTypes, const IS_CREATE2: bool, H: Host + ?Sized>(
     let scheme = if IS_CREATE2 {
         popn!([salt], interpreter);
         // SAFETY: `len` is reasonable in size as gas for it is already deducted.
-        gas_or_fail!(interpreter, gas::create2_cost(len));
+        gas_or_fail!(
+            interpreter,
+            gas::create2_cost(host.cfg().gas_schedule(), len)
+        );
         CreateScheme::Create2 { salt }
     } else {
-        gas!(interpreter, gas::CREATE);
+        gas!(interpreter, host.cfg().gas_schedule().create);
         CreateScheme::Create
     };
 
@@ -467,9 +477,14 @@ pub fn call<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let Some(mut gas_limit) =
-        calc_call_gas(interpreter, account_load, has_transfer, local_gas_limit)
-    else {
+    let schedule = host.cfg().gas_schedule();
+    let Some(mut gas_limit) = calc_call_gas(
+        interpreter,
+        schedule,
+        account_load,
+        has_transfer,
+        local_gas_limit,
+    ) else {
         return;
     };
 
@@ -477,7 +492,7 @@ pub fn call<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     // Add call stipend if there is value to be transferred.
     if has_transfer {
-        gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
+        gas_limit = gas_limit.saturating_add(schedule.call_stipend);
     }
 
     // Call host to interact with target contract
@@ -520,8 +535,14 @@ pub fn call_code<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     // Set `is_empty` to false as we are not creating this account.
     load.is_empty = false;
-    let Some(mut gas_limit) = calc_call_gas(interpreter, load, !value.is_zero(), local_gas_limit)
-    else {
+    let schedule = host.cfg().gas_schedule();
+    let Some(mut gas_limit) = calc_call_gas(
+        interpreter,
+        schedule,
+        load,
+        !value.is_zero(),
+        local_gas_limit,
+    ) else {
         return;
     };
 
@@ -529,7 +550,7 @@ pub fn call_code<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     // Add call stipend if there is value to be transferred.
     if !value.is_zero() {
-        gas_limit = gas_limit.saturating_add(gas::CALL_STIPEND);
+        gas_limit = gas_limit.saturating_add(schedule.call_stipend);
     }
 
     // Call host to interact with target contract
@@ -572,7 +593,13 @@ pub fn delegate_call<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     // Set is_empty to false as we are not creating this account.
     load.is_empty = false;
-    let Some(gas_limit) = calc_call_gas(interpreter, load, false, local_gas_limit) else {
+    let Some(gas_limit) = calc_call_gas(
+        interpreter,
+        host.cfg().gas_schedule(),
+        load,
+        false,
+        local_gas_limit,
+    ) else {
         return;
     };
 
@@ -618,7 +645,13 @@ pub fn static_call<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     // Set `is_empty` to false as we are not creating this account.
     load.is_empty = false;
-    let Some(gas_limit) = calc_call_gas(interpreter, load, false, local_gas_limit) else {
+    let Some(gas_limit) = calc_call_gas(
+        interpreter,
+        host.cfg().gas_schedule(),
+        load,
+        false,
+        local_gas_limit,
+    ) else {
         return;
     };
     gas!(interpreter, gas_limit);
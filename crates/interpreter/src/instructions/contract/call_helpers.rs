@@ -6,7 +6,7 @@ use crate::{
 use context_interface::{host::StateLoad, journaled_state::AccountLoad};
 use core::{cmp::min, ops::Range};
 use primitives::{Bytes, U256};
-use specification::hardfork::SpecId::*;
+use specification::{gas_schedule::GasSchedule, hardfork::SpecId::*};
 
 #[inline]
 pub fn get_memory_input_and_out_ranges(
@@ -47,11 +47,13 @@ pub fn resize_memory(
 #[inline]
 pub fn calc_call_gas(
     interpreter: &mut Interpreter<impl InterpreterTypes>,
+    schedule: &GasSchedule,
     account_load: StateLoad<AccountLoad>,
     has_transfer: bool,
     local_gas_limit: u64,
 ) -> Option<u64> {
     let call_cost = gas::call_cost(
+        schedule,
         interpreter.runtime_flag.spec_id(),
         has_transfer,
         account_load,
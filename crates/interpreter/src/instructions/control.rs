@@ -7,6 +7,7 @@ use crate::{
     },
     Host, InstructionResult, InterpreterAction, InterpreterResult,
 };
+use context_interface::Cfg;
 use primitives::{Bytes, U256};
 
 pub fn rjump<WIRE: InterpreterTypes, H: ?Sized>(
@@ -101,7 +102,7 @@ pub fn jumpdest_or_nop<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
 pub fn callf<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     require_eof!(interpreter);
     gas!(interpreter, gas::LOW);
@@ -115,7 +116,9 @@ pub fn callf<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     // Check max stack height for target code section.
     // Safe to subtract as max_stack_height is always more than inputs.
-    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize > 1024 {
+    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize
+        > host.cfg().stack_limit()
+    {
         interpreter
             .control
             .set_instruction_result(InstructionResult::StackOverflow);
@@ -156,7 +159,7 @@ pub fn retf<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
 pub fn jumpf<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     require_eof!(interpreter);
     gas!(interpreter, gas::LOW);
@@ -171,7 +174,9 @@ pub fn jumpf<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
     // Check max stack height for target code section.
     // Safe to subtract as max_stack_height is always more than inputs.
-    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize > 1024 {
+    if interpreter.stack.len() + (types.max_stack_size - types.inputs as u16) as usize
+        > host.cfg().stack_limit()
+    {
         interpreter
             .control
             .set_instruction_result(InstructionResult::StackOverflow);
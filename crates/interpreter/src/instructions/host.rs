@@ -1,5 +1,5 @@
 use crate::{
-    gas::{self, warm_cold_cost, CALL_STIPEND},
+    gas::{self, warm_cold_cost},
     instructions::utility::{IntoAddress, IntoU256},
     interpreter::Interpreter,
     interpreter_types::{
@@ -7,6 +7,7 @@ use crate::{
     },
     Host, InstructionResult,
 };
+use context_interface::Cfg;
 use core::cmp::min;
 use primitives::{Bytes, Log, LogData, B256, U256};
 use specification::hardfork::SpecId::*;
@@ -23,18 +24,14 @@ pub fn balance<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let spec_id = interpreter.runtime_flag.spec_id();
+    let schedule = host.cfg().gas_schedule();
     gas!(
         interpreter,
-        if spec_id.is_enabled_in(BERLIN) {
-            warm_cold_cost(balance.is_cold)
-        } else if spec_id.is_enabled_in(ISTANBUL) {
-            // EIP-1884: Repricing for trie-size-dependent opcodes
-            700
-        } else if spec_id.is_enabled_in(TANGERINE) {
-            400
+        if interpreter.runtime_flag.spec_id().is_enabled_in(BERLIN) {
+            warm_cold_cost(schedule, balance.is_cold)
         } else {
-            20
+            // EIP-1884: Repricing for trie-size-dependent opcodes
+            schedule.balance
         }
     );
     *top = balance.data;
@@ -68,13 +65,11 @@ pub fn extcodesize<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let spec_id = interpreter.runtime_flag.spec_id();
-    if spec_id.is_enabled_in(BERLIN) {
-        gas!(interpreter, warm_cold_cost(code.is_cold));
-    } else if spec_id.is_enabled_in(TANGERINE) {
-        gas!(interpreter, 700);
+    let schedule = host.cfg().gas_schedule();
+    if interpreter.runtime_flag.spec_id().is_enabled_in(BERLIN) {
+        gas!(interpreter, warm_cold_cost(schedule, code.is_cold));
     } else {
-        gas!(interpreter, 20);
+        gas!(interpreter, schedule.extcodesize);
     }
 
     *top = U256::from(code.len());
@@ -94,13 +89,11 @@ pub fn extcodehash<WIRE: InterpreterTypes, H: Host + ?Sized>(
             .set_instruction_result(InstructionResult::FatalExternalError);
         return;
     };
-    let spec_id = interpreter.runtime_flag.spec_id();
-    if spec_id.is_enabled_in(BERLIN) {
-        gas!(interpreter, warm_cold_cost(code_hash.is_cold));
-    } else if spec_id.is_enabled_in(ISTANBUL) {
-        gas!(interpreter, 700);
+    let schedule = host.cfg().gas_schedule();
+    if interpreter.runtime_flag.spec_id().is_enabled_in(BERLIN) {
+        gas!(interpreter, warm_cold_cost(schedule, code_hash.is_cold));
     } else {
-        gas!(interpreter, 400);
+        gas!(interpreter, schedule.extcodehash);
     }
     *top = code_hash.into_u256();
 }
@@ -121,7 +114,12 @@ pub fn extcodecopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     let len = as_usize_or_fail!(interpreter, len_u256);
     gas_or_fail!(
         interpreter,
-        gas::extcodecopy_cost(interpreter.runtime_flag.spec_id(), len, code.is_cold)
+        gas::extcodecopy_cost(
+            host.cfg().gas_schedule(),
+            interpreter.runtime_flag.spec_id(),
+            len,
+            code.is_cold
+        )
     );
     if len == 0 {
         return;
@@ -140,7 +138,7 @@ pub fn blockhash<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
     host: &mut H,
 ) {
-    gas!(interpreter, gas::BLOCKHASH);
+    gas!(interpreter, host.cfg().gas_schedule().blockhash);
     popn_top!([], number, interpreter);
 
     let number_u64 = as_u64_saturated!(number);
@@ -166,7 +164,11 @@ pub fn sload<WIRE: InterpreterTypes, H: Host + ?Sized>(
     };
     gas!(
         interpreter,
-        gas::sload_cost(interpreter.runtime_flag.spec_id(), value.is_cold)
+        gas::sload_cost(
+            host.cfg().gas_schedule(),
+            interpreter.runtime_flag.spec_id(),
+            value.is_cold
+        )
     );
     *index = value.data;
 }
@@ -185,9 +187,10 @@ pub fn sstore<WIRE: InterpreterTypes, H: Host + ?Sized>(
         return;
     };
 
+    let schedule = host.cfg().gas_schedule();
     // EIP-1706 Disable SSTORE with gasleft lower than call stipend
     if interpreter.runtime_flag.spec_id().is_enabled_in(ISTANBUL)
-        && interpreter.control.gas().remaining() <= CALL_STIPEND
+        && interpreter.control.gas().remaining() <= schedule.call_stipend
     {
         interpreter
             .control
@@ -197,6 +200,7 @@ pub fn sstore<WIRE: InterpreterTypes, H: Host + ?Sized>(
     gas!(
         interpreter,
         gas::sstore_cost(
+            schedule,
             interpreter.runtime_flag.spec_id(),
             &state_load.data,
             state_load.is_cold
@@ -204,6 +208,7 @@ pub fn sstore<WIRE: InterpreterTypes, H: Host + ?Sized>(
     );
 
     interpreter.control.gas().record_refund(gas::sstore_refund(
+        schedule,
         interpreter.runtime_flag.spec_id(),
         &state_load.data,
     ));
@@ -246,7 +251,10 @@ pub fn log<const N: usize, H: Host + ?Sized>(
 
     popn!([offset, len], interpreter);
     let len = as_usize_or_fail!(interpreter, len);
-    gas_or_fail!(interpreter, gas::log_cost(N as u8, len as u64));
+    gas_or_fail!(
+        interpreter,
+        gas::log_cost(host.cfg().gas_schedule(), N as u8, len as u64)
+    );
     let data = if len == 0 {
         Bytes::new()
     } else {
@@ -290,14 +298,18 @@ pub fn selfdestruct<WIRE: InterpreterTypes, H: Host + ?Sized>(
         return;
     };
 
+    let schedule = host.cfg().gas_schedule();
     // EIP-3529: Reduction in refunds
     if !interpreter.runtime_flag.spec_id().is_enabled_in(LONDON) && !res.previously_destroyed {
-        interpreter.control.gas().record_refund(gas::SELFDESTRUCT)
+        interpreter
+            .control
+            .gas()
+            .record_refund(schedule.selfdestruct_refund as i64)
     }
 
     gas!(
         interpreter,
-        gas::selfdestruct_cost(interpreter.runtime_flag.spec_id(), res)
+        gas::selfdestruct_cost(schedule, interpreter.runtime_flag.spec_id(), res)
     );
 
     interpreter
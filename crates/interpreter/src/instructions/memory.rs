@@ -4,6 +4,7 @@ use crate::{
     interpreter_types::{InterpreterTypes, LoopControl, MemoryTrait, RuntimeFlag, StackTrait},
     Host,
 };
+use context_interface::Cfg;
 use core::cmp::max;
 use primitives::U256;
 
@@ -51,7 +52,7 @@ pub fn msize<WIRE: InterpreterTypes, H: Host + ?Sized>(
 // EIP-5656: MCOPY - Memory copying instruction
 pub fn mcopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     check!(interpreter, CANCUN);
     popn!([dst, src, len], interpreter);
@@ -59,7 +60,10 @@ pub fn mcopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     // Into usize or fail
     let len = as_usize_or_fail!(interpreter, len);
     // Deduce gas
-    gas_or_fail!(interpreter, gas::copy_cost_verylow(len));
+    gas_or_fail!(
+        interpreter,
+        gas::copy_cost_verylow(host.cfg().gas_schedule(), len)
+    );
     if len == 0 {
         return;
     }
@@ -7,16 +7,21 @@ use crate::{
     },
     Host, InstructionResult,
 };
+use context_interface::Cfg;
 use core::ptr;
 use primitives::{B256, KECCAK_EMPTY, U256};
+use specification::gas_schedule::GasSchedule;
 
 pub fn keccak256<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     popn_top!([offset], top, interpreter);
     let len = as_usize_or_fail!(interpreter, top);
-    gas_or_fail!(interpreter, gas::keccak256_cost(len));
+    gas_or_fail!(
+        interpreter,
+        gas::keccak256_cost(host.cfg().gas_schedule(), len)
+    );
     let hash = if len == 0 {
         KECCAK_EMPTY
     } else {
@@ -59,11 +64,13 @@ pub fn codesize<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
 pub fn codecopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     popn!([memory_offset, code_offset, len], interpreter);
     let len = as_usize_or_fail!(interpreter, len);
-    let Some(memory_offset) = memory_resize(interpreter, memory_offset, len) else {
+    let Some(memory_offset) =
+        memory_resize(interpreter, host.cfg().gas_schedule(), memory_offset, len)
+    else {
         return;
     };
     let code_offset = as_usize_saturated!(code_offset);
@@ -118,11 +125,13 @@ pub fn callvalue<WIRE: InterpreterTypes, H: Host + ?Sized>(
 
 pub fn calldatacopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     popn!([memory_offset, data_offset, len], interpreter);
     let len = as_usize_or_fail!(interpreter, len);
-    let Some(memory_offset) = memory_resize(interpreter, memory_offset, len) else {
+    let Some(memory_offset) =
+        memory_resize(interpreter, host.cfg().gas_schedule(), memory_offset, len)
+    else {
         return;
     };
 
@@ -149,7 +158,7 @@ pub fn returndatasize<WIRE: InterpreterTypes, H: Host + ?Sized>(
 /// EIP-211: New opcodes: RETURNDATASIZE and RETURNDATACOPY
 pub fn returndatacopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
     interpreter: &mut Interpreter<WIRE>,
-    _host: &mut H,
+    host: &mut H,
 ) {
     check!(interpreter, BYZANTIUM);
     popn!([memory_offset, offset, len], interpreter);
@@ -167,7 +176,9 @@ pub fn returndatacopy<WIRE: InterpreterTypes, H: Host + ?Sized>(
         return;
     }
 
-    let Some(memory_offset) = memory_resize(interpreter, memory_offset, len) else {
+    let Some(memory_offset) =
+        memory_resize(interpreter, host.cfg().gas_schedule(), memory_offset, len)
+    else {
         return;
     };
 
@@ -220,11 +231,12 @@ pub fn gas<WIRE: InterpreterTypes, H: Host + ?Sized>(
 // common logic for copying data from a source buffer to the EVM's memory
 pub fn memory_resize(
     interpreter: &mut Interpreter<impl InterpreterTypes>,
+    schedule: &GasSchedule,
     memory_offset: U256,
     len: usize,
 ) -> Option<usize> {
     // Safe to cast usize to u64
-    gas_or_fail!(interpreter, gas::copy_cost_verylow(len), None);
+    gas_or_fail!(interpreter, gas::copy_cost_verylow(schedule, len), None);
     if len == 0 {
         return None;
     }
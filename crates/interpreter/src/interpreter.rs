@@ -1,3 +1,5 @@
+mod decoded;
+mod execution_budget;
 pub mod ext_bytecode;
 mod input;
 mod loop_control;
@@ -6,6 +8,7 @@ mod runtime_flags;
 #[cfg(feature = "serde")]
 pub mod serde;
 mod shared_memory;
+mod shared_stack;
 mod stack;
 mod subroutine_stack;
 
@@ -15,6 +18,8 @@ use crate::{
     Gas, Host, Instruction, InstructionResult, InterpreterAction,
 };
 use core::cell::RefCell;
+pub use decoded::DecodedCode;
+pub use execution_budget::ExecutionBudget;
 pub use ext_bytecode::ExtBytecode;
 pub use input::InputsImpl;
 use loop_control::LoopControl as LoopControlImpl;
@@ -22,6 +27,7 @@ use primitives::Bytes;
 use return_data::ReturnDataImpl;
 pub use runtime_flags::RuntimeFlags;
 pub use shared_memory::{num_words, MemoryGetter, SharedMemory, EMPTY_SHARED_MEMORY};
+pub use shared_stack::SharedStack;
 use specification::hardfork::SpecId;
 pub use stack::{Stack, STACK_LIMIT};
 use std::rc::Rc;
@@ -43,6 +49,7 @@ pub struct Interpreter<WIRE: InterpreterTypes> {
 
 impl<EXT: Default, MG: MemoryGetter> Interpreter<EthInterpreter<EXT, MG>> {
     /// Create new interpreter
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         memory: Rc<RefCell<MG>>,
         bytecode: ExtBytecode,
@@ -51,6 +58,7 @@ impl<EXT: Default, MG: MemoryGetter> Interpreter<EthInterpreter<EXT, MG>> {
         is_eof_init: bool,
         spec_id: SpecId,
         gas_limit: u64,
+        stack: Stack,
     ) -> Self {
         let runtime_flag = RuntimeFlags {
             spec_id,
@@ -61,7 +69,7 @@ impl<EXT: Default, MG: MemoryGetter> Interpreter<EthInterpreter<EXT, MG>> {
 
         Self {
             bytecode,
-            stack: Stack::new(),
+            stack,
             return_data: ReturnDataImpl::default(),
             memory,
             input: inputs,
@@ -164,6 +172,46 @@ impl<IW: InterpreterTypes> Interpreter<IW> {
 
         self.take_next_action()
     }
+
+    /// Executes the interpreter until it returns, stops or exhausts the execution budget.
+    ///
+    /// Budget is checked before every instruction and halts execution with
+    /// [`InstructionResult::InstructionLimit`] or [`InstructionResult::TimeLimit`].
+    ///
+    /// If `static_gas` is set, gas of the opcode is charged before the instruction is executed.
+    /// It is used for custom opcodes, builtin instructions charge gas by themselves.
+    pub fn run_with_budget<H: Host>(
+        &mut self,
+        instruction_table: &InstructionTable<IW, H>,
+        static_gas: Option<&[u64; 256]>,
+        host: &mut H,
+        budget: &mut ExecutionBudget,
+    ) -> InterpreterAction {
+        if budget.is_unlimited() && static_gas.is_none() {
+            return self.run_plain(instruction_table, host);
+        }
+
+        self.reset_control();
+
+        // Main loop
+        while self.control.instruction_result().is_continue() {
+            if let Some(result) = budget.record_instruction() {
+                self.control.set_instruction_result(result);
+                break;
+            }
+            if let Some(static_gas) = static_gas {
+                let gas = static_gas[self.bytecode.opcode() as usize];
+                if !self.control.gas().record_cost(gas) {
+                    self.control
+                        .set_instruction_result(InstructionResult::OutOfGas);
+                    break;
+                }
+            }
+            self.step(instruction_table, host);
+        }
+
+        self.take_next_action()
+    }
 }
 
 /// The result of an interpreter operation.
@@ -253,6 +301,7 @@ mod tests {
             false,
             SpecId::LATEST,
             u64::MAX,
+            Stack::new(),
         );
 
         let serialized = bincode::serialize(&interpreter).unwrap();
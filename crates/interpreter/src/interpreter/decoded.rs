@@ -0,0 +1,683 @@
+use super::Interpreter;
+use crate::{
+    gas,
+    interpreter_types::{InterpreterTypes, Jumps, LoopControl, StackTrait},
+    table::InstructionTable,
+    Host, InstructionResult, InterpreterAction,
+};
+use bytecode::{
+    legacy::{analyze_basic_blocks, legacy_immediate_sizes, BasicBlock},
+    opcode::{DUP1, DUP16, JUMP, JUMPDEST, JUMPI, POP, PUSH1, PUSH32, STOP, SWAP1, SWAP16},
+};
+use core::fmt;
+use primitives::U256;
+use std::{vec, vec::Vec};
+
+/// Marks bytecode offsets that are not the start of an instruction.
+const NO_OP: u32 = u32::MAX;
+
+/// Marks instructions in [`DecodedCode::op_index`] that are `JUMPDEST`.
+const JUMPDEST_FLAG: u32 = 1 << 31;
+
+/// Marks instructions that don't start a block in [`DecodedCode::block_at`].
+const NO_BLOCK: u32 = u32::MAX;
+
+/// Handler of a decoded instruction, called with the index of the instruction and its operand.
+type Handler<IW> = fn(&mut Interpreter<IW>, &DecodedCode<IW>, usize, u32) -> Next;
+
+/// What the dispatch loop does after a handler returns.
+enum Next {
+    /// Continues with the instruction at the index.
+    Op(usize),
+    /// Executes the opcode with the instruction table, then continues with the next
+    /// instruction.
+    Table(u8),
+    /// Stops, the instruction result is set.
+    Stop,
+}
+
+/// Decoded instruction of [`DecodedCode`].
+struct DecodedOp<IW: InterpreterTypes> {
+    /// Executes the instruction.
+    handler: Handler<IW>,
+    /// Index of the pre-read immediate in [`DecodedCode::constants`] for `PUSH`, depth of
+    /// the item for `DUP` and `SWAP` and the opcode for instructions of the instruction table.
+    operand: u32,
+}
+
+impl<IW: InterpreterTypes> Clone for DecodedOp<IW> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<IW: InterpreterTypes> Copy for DecodedOp<IW> {}
+
+impl<IW: InterpreterTypes> fmt::Debug for DecodedOp<IW> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodedOp")
+            .field("handler", &(self.handler as *const ()))
+            .field("operand", &self.operand)
+            .finish()
+    }
+}
+
+/// Basic block of [`DecodedCode`], checked and charged once on entry when pre-charging.
+#[derive(Clone, Copy, Debug)]
+struct DecodedBlock {
+    /// Static gas and stack bounds of the block.
+    block: BasicBlock,
+    /// Number of instructions in the block.
+    len: u32,
+    /// Static gas of the instructions executed inline.
+    inline_gas: u64,
+}
+
+/// Legacy bytecode decoded into a directly threaded instruction stream.
+///
+/// Every instruction holds a pointer to its handler and everything needed to execute it:
+/// `PUSH` immediates are read ahead of time and jumps are resolved to indexes of the
+/// stream, so [`Interpreter::run_decoded`] calls the handlers one after the other without
+/// decoding opcodes or immediates. Handlers of opcodes that are not executed inline hand
+/// them back to the dispatch loop, which executes them with the instruction table.
+///
+/// The stream always ends with `STOP` after the last instruction, the same as the padding
+/// of analyzed bytecode.
+///
+/// It also holds the [basic blocks](bytecode::legacy::BasicBlocks) of the bytecode for
+/// [`Interpreter::run_decoded_precharged`].
+pub struct DecodedCode<IW: InterpreterTypes> {
+    /// Decoded instructions.
+    ops: Vec<DecodedOp<IW>>,
+    /// Handlers of the instructions that skip the gas and stack checks done on block entry.
+    unchecked: Vec<Handler<IW>>,
+    /// Static gas charged by the inline handler of every instruction, zero if it is executed
+    /// with the instruction table.
+    inline_gas: Vec<u8>,
+    /// Index in [`DecodedCode::blocks`] of the block starting at every instruction,
+    /// [`NO_BLOCK`] if none does.
+    block_at: Vec<u32>,
+    /// Basic blocks with inline instructions.
+    blocks: Vec<DecodedBlock>,
+    /// Bytecode offset of every decoded instruction.
+    pcs: Vec<u32>,
+    /// Immediates of `PUSH` instructions.
+    constants: Vec<U256>,
+    /// Index of the instruction starting at every bytecode offset, [`NO_OP`] for immediates.
+    ///
+    /// Indexes of `JUMPDEST` are marked with [`JUMPDEST_FLAG`].
+    op_index: Vec<u32>,
+}
+
+impl<IW: InterpreterTypes> Clone for DecodedCode<IW> {
+    fn clone(&self) -> Self {
+        Self {
+            ops: self.ops.clone(),
+            unchecked: self.unchecked.clone(),
+            inline_gas: self.inline_gas.clone(),
+            block_at: self.block_at.clone(),
+            blocks: self.blocks.clone(),
+            pcs: self.pcs.clone(),
+            constants: self.constants.clone(),
+            op_index: self.op_index.clone(),
+        }
+    }
+}
+
+impl<IW: InterpreterTypes> fmt::Debug for DecodedCode<IW> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodedCode")
+            .field("ops", &self.ops)
+            .field("inline_gas", &self.inline_gas)
+            .field("block_at", &self.block_at)
+            .field("blocks", &self.blocks)
+            .field("pcs", &self.pcs)
+            .field("constants", &self.constants)
+            .field("op_index", &self.op_index)
+            .finish()
+    }
+}
+
+impl<IW: InterpreterTypes> DecodedCode<IW> {
+    /// Decodes legacy bytecode.
+    ///
+    /// Only immediates of `PUSH1` to `PUSH32` are skipped, custom opcodes are not supported.
+    /// Immediates cut off by the end of the bytecode are padded with zeros.
+    pub fn new(bytecode: &[u8]) -> Self {
+        let immediate_sizes = legacy_immediate_sizes();
+        let mut code = Self {
+            ops: Vec::new(),
+            unchecked: Vec::new(),
+            inline_gas: Vec::new(),
+            block_at: Vec::new(),
+            blocks: Vec::new(),
+            pcs: Vec::new(),
+            constants: Vec::new(),
+            op_index: Vec::with_capacity(bytecode.len() + 1),
+        };
+
+        let mut pc = 0;
+        while pc < bytecode.len() {
+            let flag = if bytecode[pc] == JUMPDEST {
+                JUMPDEST_FLAG
+            } else {
+                0
+            };
+            code.op_index.resize(pc, NO_OP);
+            code.op_index.push(code.ops.len() as u32 | flag);
+            let opcode = bytecode[pc];
+            let immediate_size = immediate_sizes[opcode as usize] as usize;
+            let (handler, unchecked, operand): (Handler<IW>, Handler<IW>, u32) = match opcode {
+                PUSH1..=PUSH32 => {
+                    let start = (pc + 1).min(bytecode.len());
+                    let end = (pc + 1 + immediate_size).min(bytecode.len());
+                    let mut immediate = [0; 32];
+                    immediate[..end - start].copy_from_slice(&bytecode[start..end]);
+                    code.constants
+                        .push(U256::from_be_slice(&immediate[..immediate_size]));
+                    (
+                        Interpreter::decoded_push,
+                        Interpreter::decoded_push_unchecked,
+                        code.constants.len() as u32 - 1,
+                    )
+                }
+                JUMP => (
+                    Interpreter::decoded_jump,
+                    Interpreter::decoded_jump_unchecked,
+                    0,
+                ),
+                JUMPI => (
+                    Interpreter::decoded_jumpi,
+                    Interpreter::decoded_jumpi_unchecked,
+                    0,
+                ),
+                JUMPDEST => (
+                    Interpreter::decoded_jumpdest,
+                    Interpreter::decoded_jumpdest_unchecked,
+                    0,
+                ),
+                DUP1..=DUP16 => (
+                    Interpreter::decoded_dup,
+                    Interpreter::decoded_dup_unchecked,
+                    (opcode - DUP1 + 1) as u32,
+                ),
+                SWAP1..=SWAP16 => (
+                    Interpreter::decoded_swap,
+                    Interpreter::decoded_swap_unchecked,
+                    (opcode - SWAP1 + 1) as u32,
+                ),
+                POP => (
+                    Interpreter::decoded_pop,
+                    Interpreter::decoded_pop_unchecked,
+                    0,
+                ),
+                _ => (
+                    Interpreter::decoded_table,
+                    Interpreter::decoded_table,
+                    opcode as u32,
+                ),
+            };
+            let inline_gas = match opcode {
+                PUSH1..=PUSH32 | DUP1..=DUP16 | SWAP1..=SWAP16 => gas::VERYLOW,
+                JUMP => gas::MID,
+                JUMPI => gas::HIGH,
+                JUMPDEST => gas::JUMPDEST,
+                POP => gas::BASE,
+                _ => 0,
+            };
+            code.ops.push(DecodedOp { handler, operand });
+            code.unchecked.push(unchecked);
+            code.inline_gas.push(inline_gas as u8);
+            code.pcs.push(pc as u32);
+            pc += 1 + immediate_size;
+        }
+        code.op_index.resize(pc, NO_OP);
+        code.op_index.push(code.ops.len() as u32);
+        code.ops.push(DecodedOp {
+            handler: Interpreter::decoded_table,
+            operand: STOP as u32,
+        });
+        code.unchecked.push(Interpreter::decoded_table);
+        code.inline_gas.push(0);
+        code.pcs.push(pc as u32);
+
+        code.block_at = vec![NO_BLOCK; code.ops.len()];
+        let stop = code.ops.len() - 1;
+        for block in analyze_basic_blocks(bytecode).0 {
+            let start = code.op_at(block.start as usize).unwrap_or(stop);
+            let end = code.op_at(block.end as usize).unwrap_or(stop);
+            let inline_gas = code.inline_gas[start..end]
+                .iter()
+                .map(|&gas| gas as u64)
+                .sum();
+            // Blocks of table instructions only have nothing to pre-charge.
+            if inline_gas == 0 {
+                continue;
+            }
+            code.block_at[start] = code.blocks.len() as u32;
+            code.blocks.push(DecodedBlock {
+                block,
+                len: (end - start) as u32,
+                inline_gas,
+            });
+        }
+        code
+    }
+
+    /// Returns the number of decoded instructions, including the trailing `STOP`.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Returns `true` if there are no decoded instructions.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Returns the index of the instruction starting at bytecode offset `pc`.
+    #[inline]
+    pub fn op_at(&self, pc: usize) -> Option<usize> {
+        match self.op_index.get(pc) {
+            Some(&index) if index != NO_OP => Some((index & !JUMPDEST_FLAG) as usize),
+            _ => None,
+        }
+    }
+
+    /// Returns the block starting at the instruction with `index`.
+    #[inline]
+    fn block_at(&self, index: usize) -> Option<&DecodedBlock> {
+        self.blocks.get(self.block_at[index] as usize)
+    }
+
+    /// Returns the index of the instruction starting at bytecode offset `pc` if it is a
+    /// valid jump destination.
+    #[inline]
+    fn jumpdest_at(&self, pc: usize) -> Option<usize> {
+        match self.op_index.get(pc) {
+            Some(&index) if index != NO_OP && index & JUMPDEST_FLAG != 0 => {
+                Some((index & !JUMPDEST_FLAG) as usize)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<IW: InterpreterTypes> Interpreter<IW> {
+    /// Executes the interpreter with decoded bytecode until it returns or stops.
+    ///
+    /// `code` must be decoded from the legacy bytecode of this interpreter. Execution starts
+    /// at the current instruction pointer, so a frame can be resumed after a call.
+    ///
+    /// `PUSH`, `DUP`, `SWAP`, `POP`, `JUMP`, `JUMPI` and `JUMPDEST` are executed inline,
+    /// their entries in `instruction_table` are not used. All other opcodes are executed with
+    /// the table and must not move the instruction pointer. Builtin instructions move it
+    /// only in EOF bytecode.
+    pub fn run_decoded<H: Host>(
+        &mut self,
+        code: &DecodedCode<IW>,
+        instruction_table: &InstructionTable<IW, H>,
+        host: &mut H,
+    ) -> InterpreterAction {
+        self.run_decoded_inner::<false, H>(code, instruction_table, host)
+    }
+
+    /// Executes the interpreter with decoded bytecode, pre-charging basic blocks.
+    ///
+    /// On entry of a basic block the remaining gas is checked against the static gas of the
+    /// whole block and the stack against its bounds. If both fit, the static gas of inline
+    /// instructions is charged at once and they run without checks. Otherwise the block is
+    /// executed with the checks of every instruction, so out of gas and stack errors happen
+    /// at the same instruction as in [`Interpreter::run_decoded`]. If a table instruction
+    /// halts inside a block, the gas charged for the rest of the block is returned.
+    ///
+    /// Table instructions of opcodes with [static gas](bytecode::legacy::legacy_static_gas)
+    /// must charge exactly that gas.
+    pub fn run_decoded_precharged<H: Host>(
+        &mut self,
+        code: &DecodedCode<IW>,
+        instruction_table: &InstructionTable<IW, H>,
+        host: &mut H,
+    ) -> InterpreterAction {
+        self.run_decoded_inner::<true, H>(code, instruction_table, host)
+    }
+
+    #[inline(always)]
+    fn run_decoded_inner<const PRECHARGE: bool, H: Host>(
+        &mut self,
+        code: &DecodedCode<IW>,
+        instruction_table: &InstructionTable<IW, H>,
+        host: &mut H,
+    ) -> InterpreterAction {
+        let Some(mut index) = code.op_at(self.bytecode.pc()) else {
+            return self.run_plain(instruction_table, host);
+        };
+        self.reset_control();
+        // Offset of the instruction pointer. Inline instructions don't move it, it is moved
+        // to the next instruction before table instructions and when execution stops.
+        let mut pointer = self.bytecode.pc();
+        // Instructions of the pre-charged block after the current one.
+        let mut block_left = 0;
+
+        // Main loop
+        loop {
+            let op = code.ops[index];
+            let mut handler = op.handler;
+            if PRECHARGE {
+                if block_left == 0 {
+                    block_left = self.decoded_precharge(code, index);
+                }
+                if block_left != 0 {
+                    handler = code.unchecked[index];
+                    block_left -= 1;
+                }
+            }
+            match handler(self, code, index, op.operand) {
+                Next::Op(next) => index = next,
+                Next::Table(opcode) => {
+                    let next_pc = code.pcs[index] as usize + 1;
+                    self.bytecode
+                        .relative_jump(next_pc as isize - pointer as isize);
+                    pointer = next_pc;
+                    instruction_table[opcode as usize](self, host);
+                    if !self.control.instruction_result().is_continue() {
+                        break;
+                    }
+                    debug_assert_eq!(
+                        self.bytecode.pc(),
+                        pointer,
+                        "instruction 0x{opcode:02X} moved the instruction pointer"
+                    );
+                    index += 1;
+                }
+                Next::Stop => {
+                    // Leave the instruction pointer where the loop would leave it.
+                    self.bytecode.absolute_jump(code.pcs[index] as usize + 1);
+                    break;
+                }
+            }
+        }
+
+        if PRECHARGE && block_left != 0 {
+            let rest = code.inline_gas[index + 1..=index + block_left]
+                .iter()
+                .map(|&gas| gas as u64)
+                .sum();
+            self.control.gas().erase_cost(rest);
+        }
+        self.take_next_action()
+    }
+
+    /// Checks the block starting at `index` and charges its inline instructions.
+    ///
+    /// Returns the number of instructions in the block, zero if there is no block or it
+    /// doesn't fit into the remaining gas or the stack.
+    #[inline]
+    fn decoded_precharge(&mut self, code: &DecodedCode<IW>, index: usize) -> usize {
+        let Some(block) = code.block_at(index) else {
+            return 0;
+        };
+        if self.control.gas().remaining() < block.block.static_gas
+            || !block.block.fits_stack(self.stack.len(), self.stack.limit())
+        {
+            return 0;
+        }
+        // Can't fail, the inline gas is a part of the static gas.
+        let charged = self.control.gas().record_cost(block.inline_gas);
+        debug_assert!(charged);
+        block.len as usize
+    }
+
+    /// Hands the opcode back to the dispatch loop.
+    fn decoded_table(&mut self, _code: &DecodedCode<IW>, _index: usize, opcode: u32) -> Next {
+        Next::Table(opcode as u8)
+    }
+
+    /// Pushes a pre-read `PUSH` immediate.
+    fn decoded_push(&mut self, code: &DecodedCode<IW>, index: usize, constant: u32) -> Next {
+        crate::gas!(self, gas::VERYLOW, Next::Stop);
+        if !self.stack.push(code.constants[constant as usize]) {
+            self.control
+                .set_instruction_result(InstructionResult::StackOverflow);
+            return Next::Stop;
+        }
+        Next::Op(index + 1)
+    }
+
+    /// Executes `JUMPDEST`.
+    fn decoded_jumpdest(&mut self, _code: &DecodedCode<IW>, index: usize, _: u32) -> Next {
+        crate::gas!(self, gas::JUMPDEST, Next::Stop);
+        Next::Op(index + 1)
+    }
+
+    /// Executes `DUP1` to `DUP16`.
+    fn decoded_dup(&mut self, _code: &DecodedCode<IW>, index: usize, n: u32) -> Next {
+        crate::gas!(self, gas::VERYLOW, Next::Stop);
+        if !self.stack.dup(n as usize) {
+            self.control
+                .set_instruction_result(InstructionResult::StackOverflow);
+            return Next::Stop;
+        }
+        Next::Op(index + 1)
+    }
+
+    /// Executes `SWAP1` to `SWAP16`.
+    fn decoded_swap(&mut self, _code: &DecodedCode<IW>, index: usize, n: u32) -> Next {
+        crate::gas!(self, gas::VERYLOW, Next::Stop);
+        if !self.stack.exchange(0, n as usize) {
+            self.control
+                .set_instruction_result(InstructionResult::StackOverflow);
+            return Next::Stop;
+        }
+        Next::Op(index + 1)
+    }
+
+    /// Executes `POP`.
+    fn decoded_pop(&mut self, _code: &DecodedCode<IW>, index: usize, _: u32) -> Next {
+        crate::gas!(self, gas::BASE, Next::Stop);
+        if self.stack.popn::<1>().is_none() {
+            self.control
+                .set_instruction_result(InstructionResult::StackUnderflow);
+            return Next::Stop;
+        }
+        Next::Op(index + 1)
+    }
+
+    /// Executes `JUMP`.
+    fn decoded_jump(&mut self, code: &DecodedCode<IW>, _index: usize, _: u32) -> Next {
+        crate::gas!(self, gas::MID, Next::Stop);
+        let Some([target]) = self.stack.popn() else {
+            self.control
+                .set_instruction_result(InstructionResult::StackUnderflow);
+            return Next::Stop;
+        };
+        self.decoded_jump_to(code, target)
+    }
+
+    /// Executes `JUMPI`.
+    fn decoded_jumpi(&mut self, code: &DecodedCode<IW>, index: usize, _: u32) -> Next {
+        crate::gas!(self, gas::HIGH, Next::Stop);
+        let Some([target, cond]) = self.stack.popn() else {
+            self.control
+                .set_instruction_result(InstructionResult::StackUnderflow);
+            return Next::Stop;
+        };
+        if cond.is_zero() {
+            return Next::Op(index + 1);
+        }
+        self.decoded_jump_to(code, target)
+    }
+
+    /// Pushes a pre-read `PUSH` immediate in a pre-charged block.
+    fn decoded_push_unchecked(
+        &mut self,
+        code: &DecodedCode<IW>,
+        index: usize,
+        constant: u32,
+    ) -> Next {
+        let pushed = self.stack.push(code.constants[constant as usize]);
+        debug_assert!(pushed, "stack bounds are checked on block entry");
+        Next::Op(index + 1)
+    }
+
+    /// Executes `JUMPDEST` in a pre-charged block.
+    fn decoded_jumpdest_unchecked(
+        &mut self,
+        _code: &DecodedCode<IW>,
+        index: usize,
+        _: u32,
+    ) -> Next {
+        Next::Op(index + 1)
+    }
+
+    /// Executes `DUP1` to `DUP16` in a pre-charged block.
+    fn decoded_dup_unchecked(&mut self, _code: &DecodedCode<IW>, index: usize, n: u32) -> Next {
+        let duplicated = self.stack.dup(n as usize);
+        debug_assert!(duplicated, "stack bounds are checked on block entry");
+        Next::Op(index + 1)
+    }
+
+    /// Executes `SWAP1` to `SWAP16` in a pre-charged block.
+    fn decoded_swap_unchecked(&mut self, _code: &DecodedCode<IW>, index: usize, n: u32) -> Next {
+        let swapped = self.stack.exchange(0, n as usize);
+        debug_assert!(swapped, "stack bounds are checked on block entry");
+        Next::Op(index + 1)
+    }
+
+    /// Executes `POP` in a pre-charged block.
+    fn decoded_pop_unchecked(&mut self, _code: &DecodedCode<IW>, index: usize, _: u32) -> Next {
+        let popped = self.stack.popn::<1>();
+        debug_assert!(popped.is_some(), "stack bounds are checked on block entry");
+        Next::Op(index + 1)
+    }
+
+    /// Executes `JUMP` in a pre-charged block.
+    fn decoded_jump_unchecked(&mut self, code: &DecodedCode<IW>, _index: usize, _: u32) -> Next {
+        let popped = self.stack.popn();
+        debug_assert!(popped.is_some(), "stack bounds are checked on block entry");
+        let [target] = popped.unwrap_or_default();
+        self.decoded_jump_to(code, target)
+    }
+
+    /// Executes `JUMPI` in a pre-charged block.
+    fn decoded_jumpi_unchecked(&mut self, code: &DecodedCode<IW>, index: usize, _: u32) -> Next {
+        let popped = self.stack.popn();
+        debug_assert!(popped.is_some(), "stack bounds are checked on block entry");
+        let [target, cond] = popped.unwrap_or_default();
+        if cond.is_zero() {
+            return Next::Op(index + 1);
+        }
+        self.decoded_jump_to(code, target)
+    }
+
+    /// Continues at the `JUMPDEST` at bytecode offset `target`.
+    #[inline]
+    fn decoded_jump_to(&mut self, code: &DecodedCode<IW>, target: U256) -> Next {
+        let target =
+            crate::as_usize_or_fail_ret!(self, target, InstructionResult::InvalidJump, Next::Stop);
+        match code.jumpdest_at(target) {
+            Some(index) => Next::Op(index),
+            None => {
+                self.control
+                    .set_instruction_result(InstructionResult::InvalidJump);
+                Next::Stop
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::EthInterpreter;
+    use bytecode::opcode::{ADD, PUSH2, PUSH4};
+
+    type Code = DecodedCode<EthInterpreter>;
+
+    /// Returns the handler address and operand of every instruction.
+    fn ops(code: &Code) -> Vec<(usize, u32)> {
+        code.ops
+            .iter()
+            .map(|op| (op.handler as usize, op.operand))
+            .collect()
+    }
+
+    fn op(handler: Handler<EthInterpreter>, operand: u32) -> (usize, u32) {
+        (handler as usize, operand)
+    }
+
+    #[test]
+    fn decodes_pushes_and_jumps() {
+        let code = Code::new(&[PUSH2, 0x01, 0x02, JUMPDEST, PUSH1, 0x03, JUMP, ADD]);
+        assert_eq!(
+            ops(&code),
+            [
+                op(Interpreter::decoded_push, 0),
+                op(Interpreter::decoded_jumpdest, 0),
+                op(Interpreter::decoded_push, 1),
+                op(Interpreter::decoded_jump, 0),
+                op(Interpreter::decoded_table, ADD as u32),
+                op(Interpreter::decoded_table, STOP as u32),
+            ]
+        );
+        assert_eq!(code.pcs, [0, 3, 4, 6, 7, 8]);
+        assert_eq!(code.constants, [U256::from(0x0102), U256::from(3)]);
+
+        assert_eq!(code.op_at(0), Some(0));
+        assert_eq!(code.op_at(1), None);
+        assert_eq!(code.op_at(3), Some(1));
+        assert_eq!(code.op_at(8), Some(5));
+        assert_eq!(code.op_at(9), None);
+        assert_eq!(code.jumpdest_at(0), None);
+        assert_eq!(code.jumpdest_at(3), Some(1));
+    }
+
+    #[test]
+    fn decodes_stack_operands() {
+        let code = Code::new(&[DUP1 + 2, SWAP16, POP, JUMPI]);
+        assert_eq!(
+            ops(&code),
+            [
+                op(Interpreter::decoded_dup, 3),
+                op(Interpreter::decoded_swap, 16),
+                op(Interpreter::decoded_pop, 0),
+                op(Interpreter::decoded_jumpi, 0),
+                op(Interpreter::decoded_table, STOP as u32),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_blocks() {
+        let code = Code::new(&[PUSH1, 0x01, PUSH1, 0x02, ADD, JUMPDEST, POP, STOP, ADD]);
+        assert_eq!(
+            code.block_at,
+            [0, NO_BLOCK, NO_BLOCK, 1, NO_BLOCK, NO_BLOCK, NO_BLOCK, NO_BLOCK]
+        );
+        let blocks = code
+            .blocks
+            .iter()
+            .map(|block| (block.len, block.inline_gas, block.block.static_gas))
+            .collect::<Vec<_>>();
+        // The block of the last `ADD` has no inline instructions.
+        assert_eq!(blocks, [(3, 6, 9), (3, 3, 3)]);
+        assert_eq!(code.inline_gas, [3, 3, 0, 1, 2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn pads_truncated_push() {
+        let code = Code::new(&[PUSH4, 0x01, 0x02]);
+        assert_eq!(code.constants, [U256::from(0x01020000)]);
+        assert_eq!(code.len(), 2);
+        assert_eq!(code.op_at(3), None);
+        assert_eq!(code.op_at(5), Some(1));
+    }
+
+    #[test]
+    fn empty_bytecode_stops() {
+        let code = Code::new(&[]);
+        assert_eq!(ops(&code), [op(Interpreter::decoded_table, STOP as u32)]);
+        assert_eq!(code.op_at(0), Some(0));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::InstructionResult;
+use core::time::Duration;
+
+/// Number of instructions executed between two reads of the clock.
+#[cfg(feature = "std")]
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// Instruction count and wall-clock budget shared by all call frames of a transaction.
+///
+/// Once exhausted it stays exhausted, so every frame that resumes execution
+/// halts with the same [`InstructionResult`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecutionBudget {
+    /// Maximum number of instructions that can be executed.
+    max_instructions: Option<u64>,
+    /// Number of executed instructions.
+    executed_instructions: u64,
+    /// Instant after which execution is halted.
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+    /// Set when budget is exhausted.
+    exhausted: Option<InstructionResult>,
+}
+
+impl ExecutionBudget {
+    /// Creates a new budget. Time is measured from this call.
+    ///
+    /// Without the `std` feature `max_execution_time` is ignored.
+    pub fn new(max_instructions: Option<u64>, max_execution_time: Option<Duration>) -> Self {
+        #[cfg(not(feature = "std"))]
+        let _ = max_execution_time;
+        Self {
+            max_instructions,
+            executed_instructions: 0,
+            #[cfg(feature = "std")]
+            deadline: max_execution_time
+                .and_then(|duration| std::time::Instant::now().checked_add(duration)),
+            exhausted: None,
+        }
+    }
+
+    /// Returns `true` if neither instructions nor time are limited.
+    #[inline]
+    pub fn is_unlimited(&self) -> bool {
+        #[cfg(feature = "std")]
+        if self.deadline.is_some() {
+            return false;
+        }
+        self.max_instructions.is_none()
+    }
+
+    /// Returns the number of instructions recorded so far.
+    #[inline]
+    pub fn executed_instructions(&self) -> u64 {
+        self.executed_instructions
+    }
+
+    /// Returns the halt result if the budget is exhausted.
+    #[inline]
+    pub fn exhausted(&self) -> Option<InstructionResult> {
+        self.exhausted
+    }
+
+    /// Records execution of one instruction.
+    ///
+    /// Returns the halt result if the instruction does not fit into the budget.
+    #[inline]
+    pub fn record_instruction(&mut self) -> Option<InstructionResult> {
+        if self.exhausted.is_some() {
+            return self.exhausted;
+        }
+        self.executed_instructions += 1;
+        if let Some(max) = self.max_instructions {
+            if self.executed_instructions > max {
+                self.exhausted = Some(InstructionResult::InstructionLimit);
+                return self.exhausted;
+            }
+        }
+        #[cfg(feature = "std")]
+        if let Some(deadline) = self.deadline {
+            // Reading the clock is expensive, so it is done periodically.
+            if self.executed_instructions % TIME_CHECK_INTERVAL == 1
+                && std::time::Instant::now() >= deadline
+            {
+                self.exhausted = Some(InstructionResult::TimeLimit);
+                return self.exhausted;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instruction_limit() {
+        let mut budget = ExecutionBudget::new(Some(2), None);
+        assert!(!budget.is_unlimited());
+        assert_eq!(budget.record_instruction(), None);
+        assert_eq!(budget.record_instruction(), None);
+        assert_eq!(
+            budget.record_instruction(),
+            Some(InstructionResult::InstructionLimit)
+        );
+        // Stays exhausted.
+        assert_eq!(
+            budget.record_instruction(),
+            Some(InstructionResult::InstructionLimit)
+        );
+        assert_eq!(budget.executed_instructions(), 3);
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut budget = ExecutionBudget::new(None, Some(Duration::ZERO));
+        assert!(!budget.is_unlimited());
+        assert_eq!(
+            budget.record_instruction(),
+            Some(InstructionResult::TimeLimit)
+        );
+    }
+
+    #[test]
+    fn unlimited() {
+        let mut budget = ExecutionBudget::default();
+        assert!(budget.is_unlimited());
+        for _ in 0..4096 {
+            assert_eq!(budget.record_instruction(), None);
+        }
+    }
+}
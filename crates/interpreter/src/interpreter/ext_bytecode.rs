@@ -3,11 +3,11 @@ use core::ops::Deref;
 use bytecode::{
     eof::TypesSection,
     utils::{read_i16, read_u16},
-    Bytecode,
+    Bytecode, JumpTable, LegacyAnalyzedBytecode,
 };
 use primitives::{Bytes, B256};
 
-use super::{EofCodeInfo, EofContainer, EofData, Immediates, Jumps, LegacyBytecode};
+use super::{BytecodeHash, EofCodeInfo, EofContainer, EofData, Immediates, Jumps, LegacyBytecode};
 
 #[cfg(feature = "serde")]
 mod serde;
@@ -59,6 +59,31 @@ impl ExtBytecode {
     pub fn hash(&mut self) -> Option<B256> {
         self.bytecode_hash
     }
+
+    /// Replaces the jump table of legacy bytecode, for example with one analyzed with custom
+    /// opcodes.
+    ///
+    /// Does nothing for other bytecode.
+    pub fn set_legacy_jump_table(&mut self, jump_table: JumpTable) {
+        let Bytecode::LegacyAnalyzed(legacy) = &self.base else {
+            return;
+        };
+        let legacy = LegacyAnalyzedBytecode::new(
+            legacy.bytecode().clone(),
+            legacy.original_len(),
+            jump_table,
+        );
+        let pc = self.pc();
+        self.base = Bytecode::LegacyAnalyzed(legacy);
+        self.absolute_jump(pc);
+    }
+}
+
+impl BytecodeHash for ExtBytecode {
+    #[inline]
+    fn bytecode_hash(&self) -> Option<B256> {
+        self.bytecode_hash
+    }
 }
 
 impl Jumps for ExtBytecode {
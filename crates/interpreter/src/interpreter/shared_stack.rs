@@ -0,0 +1,144 @@
+use super::Stack;
+use primitives::U256;
+use std::vec::Vec;
+
+/// Stack buffer shared between calls.
+///
+/// Like [`SharedMemory`](super::SharedMemory), a single buffer is used by all call frames
+/// of a transaction. The stack of a new frame starts where the stack of the calling frame
+/// ends, so nested and repeated calls reuse the same allocation.
+///
+/// The buffer is owned by the [`Stack`] of the running frame:
+/// * [`SharedStack::new_stack`] hands it to the stack of a new frame,
+/// * [`SharedStack::free_stack`] takes it back when the frame returns,
+/// * [`SharedStack::suspend`] takes it from the calling frame before a sub call starts,
+/// * [`SharedStack::resume`] gives it back to the calling frame when the sub call returns.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SharedStack {
+    /// Buffer while no frame owns it.
+    buffer: Vec<U256>,
+    /// Offsets and limits of suspended stacks.
+    suspended: Vec<(usize, usize)>,
+}
+
+impl SharedStack {
+    /// Creates a new empty shared stack.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new shared stack with a buffer that can hold `capacity` words.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(capacity),
+            suspended: Vec::with_capacity(32),
+        }
+    }
+
+    /// Returns the number of words the buffer can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Returns an empty stack that can hold at most `limit` words, on top of the
+    /// suspended stacks.
+    #[inline]
+    pub fn new_stack(&mut self, limit: usize) -> Stack {
+        let offset = self.buffer.len();
+        Stack::from_shared(core::mem::take(&mut self.buffer), offset, limit)
+    }
+
+    /// Takes the buffer back from `stack` of a returned frame.
+    ///
+    /// `stack` is left empty with a limit of zero, so it can't be pushed to anymore.
+    #[inline]
+    pub fn free_stack(&mut self, stack: &mut Stack) {
+        let (mut buffer, offset, _) = stack.take_buffer();
+        buffer.truncate(offset);
+        self.buffer = buffer;
+    }
+
+    /// Takes the buffer from `stack` of a frame that starts a sub call.
+    ///
+    /// `stack` is empty with a limit of zero until it is [resumed](Self::resume).
+    #[inline]
+    pub fn suspend(&mut self, stack: &mut Stack) {
+        let (buffer, offset, limit) = stack.take_buffer();
+        self.suspended.push((offset, limit));
+        self.buffer = buffer;
+    }
+
+    /// Gives the buffer back to the last suspended `stack`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no stack is suspended.
+    #[inline]
+    pub fn resume(&mut self, stack: &mut Stack) {
+        let (offset, limit) = self
+            .suspended
+            .pop()
+            .expect("resumed stack was not suspended");
+        *stack = Stack::from_shared(core::mem::take(&mut self.buffer), offset, limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_share_buffer() {
+        let mut shared = SharedStack::new();
+        let mut caller = shared.new_stack(1024);
+        assert!(caller.push(U256::from(1)));
+        assert!(caller.push(U256::from(2)));
+
+        shared.suspend(&mut caller);
+        assert!(caller.is_empty());
+        assert!(!caller.push(U256::ZERO));
+
+        let mut callee = shared.new_stack(1024);
+        assert!(callee.is_empty());
+        assert_eq!(callee.limit(), 1024);
+        assert_eq!(callee.pop(), Err(crate::InstructionResult::StackUnderflow));
+        assert!(!callee.dup(1));
+        assert!(callee.push(U256::from(3)));
+        assert_eq!(callee.data(), [U256::from(3)]);
+
+        shared.free_stack(&mut callee);
+        assert_eq!(callee.limit(), 0);
+        assert!(shared.capacity() >= 1026);
+
+        shared.resume(&mut caller);
+        assert_eq!(caller.data(), [U256::from(1), U256::from(2)]);
+        assert_eq!(caller.limit(), 1024);
+        assert!(caller.push(U256::from(4)));
+    }
+
+    #[test]
+    fn limit_is_per_frame() {
+        let mut shared = SharedStack::new();
+        let mut caller = shared.new_stack(2);
+        assert!(caller.push(U256::from(1)));
+        assert!(caller.push(U256::from(2)));
+        assert!(!caller.push(U256::from(3)));
+
+        shared.suspend(&mut caller);
+        let mut callee = shared.new_stack(2);
+        assert!(callee.push(U256::from(1)));
+        assert!(callee.push(U256::from(2)));
+        assert!(!callee.push(U256::from(3)));
+        assert_eq!(
+            callee.push_slice(&[1]),
+            Err(crate::InstructionResult::StackOverflow)
+        );
+        shared.free_stack(&mut callee);
+
+        shared.resume(&mut caller);
+        assert!(!caller.push(U256::from(3)));
+        assert_eq!(caller.len(), 2);
+    }
+}
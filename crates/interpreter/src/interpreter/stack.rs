@@ -1,5 +1,9 @@
 use crate::InstructionResult;
-use core::{fmt, ptr};
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    ptr,
+};
 use primitives::U256;
 use std::vec::Vec;
 
@@ -8,18 +12,51 @@ use super::StackTrait;
 /// EVM interpreter stack limit.
 pub const STACK_LIMIT: usize = 1024;
 
-/// EVM stack with [STACK_LIMIT] capacity of words.
-#[derive(Debug, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+/// EVM stack with [STACK_LIMIT] capacity of words by default.
+///
+/// The limit can be changed with [`Stack::with_limit`].
+///
+/// Stacks of call frames share one buffer through [`SharedStack`](super::SharedStack),
+/// the stack of a frame is the part of the buffer above the words of the calling frames.
 pub struct Stack {
     /// The underlying data of the stack.
+    ///
+    /// Words before `offset` belong to the calling frames.
     data: Vec<U256>,
+    /// Index of the bottom of the stack in `data`.
+    offset: usize,
+    /// Index in `data` after the last word that fits on the stack, `offset` plus the limit.
+    end: usize,
+}
+
+impl fmt::Debug for Stack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stack")
+            .field("data", &self.data())
+            .field("limit", &self.limit())
+            .finish()
+    }
+}
+
+impl PartialEq for Stack {
+    fn eq(&self, other: &Self) -> bool {
+        self.data() == other.data() && self.limit() == other.limit()
+    }
+}
+
+impl Eq for Stack {}
+
+impl Hash for Stack {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data().hash(state);
+        self.limit().hash(state);
+    }
 }
 
 impl fmt::Display for Stack {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("[")?;
-        for (i, x) in self.data.iter().enumerate() {
+        for (i, x) in self.data().iter().enumerate() {
             if i > 0 {
                 f.write_str(", ")?;
             }
@@ -38,11 +75,11 @@ impl Default for Stack {
 
 impl Clone for Stack {
     fn clone(&self) -> Self {
-        // Use `Self::new()` to ensure the cloned Stack maintains the STACK_LIMIT capacity,
+        // Use `Self::with_limit()` to ensure the cloned Stack maintains the `limit` capacity,
         // and then copy the data. This preserves the invariant that Stack always has
-        // STACK_LIMIT capacity, which is crucial for the safety and correctness of other methods.
-        let mut new_stack = Self::new();
-        new_stack.data.extend_from_slice(&self.data);
+        // `limit` capacity, which is crucial for the safety and correctness of other methods.
+        let mut new_stack = Self::with_limit(self.limit());
+        new_stack.data.extend_from_slice(self.data());
         new_stack
     }
 }
@@ -52,6 +89,10 @@ impl StackTrait for Stack {
         self.len()
     }
 
+    fn limit(&self) -> usize {
+        self.limit()
+    }
+
     #[inline]
     fn popn<const N: usize>(&mut self) -> Option<[U256; N]> {
         if self.len() < N {
@@ -87,40 +128,89 @@ impl Stack {
     /// Instantiate a new stack with the [default stack limit][STACK_LIMIT].
     #[inline]
     pub fn new() -> Self {
+        Self::with_limit(STACK_LIMIT)
+    }
+
+    /// Instantiate a new stack that can hold at most `limit` words.
+    #[inline]
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            // SAFETY: Expansion functions assume that capacity is at least `end`.
+            data: Vec::with_capacity(limit),
+            offset: 0,
+            end: limit,
+        }
+    }
+
+    /// Instantiate a stack that starts at `offset` of the shared `buffer`.
+    ///
+    /// Words of `buffer` after `offset` are on the stack.
+    #[inline]
+    pub(super) fn from_shared(mut buffer: Vec<U256>, offset: usize, limit: usize) -> Self {
+        debug_assert!(buffer.len() >= offset && buffer.len() - offset <= limit);
+        // SAFETY: Expansion functions assume that capacity is at least `end`.
+        buffer.reserve((offset + limit).saturating_sub(buffer.len()));
         Self {
-            // SAFETY: Expansion functions assume that capacity is `STACK_LIMIT`.
-            data: Vec::with_capacity(STACK_LIMIT),
+            data: buffer,
+            offset,
+            end: offset + limit,
         }
     }
 
+    /// Takes the shared buffer out of the stack, leaving it empty with a limit of zero.
+    ///
+    /// Returns the buffer with the offset and the limit of the stack.
+    #[inline]
+    pub(super) fn take_buffer(&mut self) -> (Vec<U256>, usize, usize) {
+        let limit = self.limit();
+        let stack = core::mem::replace(self, Self::with_limit(0));
+        (stack.data, stack.offset, limit)
+    }
+
+    /// Returns the maximum number of words on the stack.
+    #[inline]
+    pub fn limit(&self) -> usize {
+        self.end - self.offset
+    }
+
     /// Returns the length of the stack in words.
     #[inline]
     pub fn len(&self) -> usize {
-        self.data.len()
+        self.data.len() - self.offset
+    }
+
+    /// Returns the number of words the stack can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.data.capacity() - self.offset
     }
 
     /// Returns whether the stack is empty.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.data.len() == self.offset
     }
 
-    /// Returns a reference to the underlying data buffer.
+    /// Returns the words on the stack, the top of the stack is the last word.
     #[inline]
-    pub fn data(&self) -> &Vec<U256> {
-        &self.data
+    pub fn data(&self) -> &[U256] {
+        &self.data[self.offset..]
     }
 
-    /// Returns a mutable reference to the underlying data buffer.
+    /// Returns the words on the stack as a mutable slice.
     #[inline]
-    pub fn data_mut(&mut self) -> &mut Vec<U256> {
-        &mut self.data
+    pub fn data_mut(&mut self) -> &mut [U256] {
+        &mut self.data[self.offset..]
     }
 
-    /// Consumes the stack and returns the underlying data buffer.
+    /// Consumes the stack and returns its words.
     #[inline]
-    pub fn into_data(self) -> Vec<U256> {
-        self.data
+    pub fn into_data(mut self) -> Vec<U256> {
+        if self.offset == 0 {
+            self.data
+        } else {
+            self.data.split_off(self.offset)
+        }
     }
 
     /// Removes the topmost element from the stack and returns it, or `StackUnderflow` if it is
@@ -128,7 +218,11 @@ impl Stack {
     #[inline]
     #[cfg_attr(debug_assertions, track_caller)]
     pub fn pop(&mut self) -> Result<U256, InstructionResult> {
-        self.data.pop().ok_or(InstructionResult::StackUnderflow)
+        if self.is_empty() {
+            return Err(InstructionResult::StackUnderflow);
+        }
+        // SAFETY: Stack length is checked above.
+        Ok(unsafe { self.pop_unsafe() })
     }
 
     /// Removes the topmost element from the stack and returns it.
@@ -194,8 +288,8 @@ impl Stack {
     #[cfg_attr(debug_assertions, track_caller)]
     pub fn push(&mut self, value: U256) -> bool {
         // Allows the compiler to optimize out the `Vec::push` capacity check.
-        assume!(self.data.capacity() == STACK_LIMIT);
-        if self.data.len() == STACK_LIMIT {
+        assume!(self.data.capacity() >= self.end);
+        if self.data.len() == self.end {
             return false;
         }
         self.data.push(value);
@@ -207,7 +301,7 @@ impl Stack {
     /// `StackError::Underflow` is returned.
     #[inline]
     pub fn peek(&self, no_from_top: usize) -> Result<U256, InstructionResult> {
-        if self.data.len() > no_from_top {
+        if self.len() > no_from_top {
             Ok(self.data[self.data.len() - no_from_top - 1])
         } else {
             Err(InstructionResult::StackUnderflow)
@@ -225,7 +319,7 @@ impl Stack {
     pub fn dup(&mut self, n: usize) -> bool {
         assume!(n > 0, "attempted to dup 0");
         let len = self.data.len();
-        if len < n || len + 1 > STACK_LIMIT {
+        if len < self.offset + n || len + 1 > self.end {
             false
         } else {
             // SAFETY: Check for out of bounds is done above and it makes this safe to do.
@@ -262,7 +356,7 @@ impl Stack {
         assume!(m > 0, "overlapping exchange");
         let len = self.data.len();
         let n_m_index = n + m;
-        if n_m_index >= len {
+        if n_m_index >= len - self.offset {
             return false;
         }
         // SAFETY: `n` and `n_m` are checked to be within bounds, and they don't overlap.
@@ -287,7 +381,7 @@ impl Stack {
 
         let n_words = (slice.len() + 31) / 32;
         let new_len = self.data.len() + n_words;
-        if new_len > STACK_LIMIT {
+        if new_len > self.end {
             return Err(InstructionResult::StackOverflow);
         }
 
@@ -347,7 +441,7 @@ impl Stack {
     /// `StackError::Underflow` is returned.
     #[inline]
     pub fn set(&mut self, no_from_top: usize, val: U256) -> Result<(), InstructionResult> {
-        if self.data.len() > no_from_top {
+        if self.len() > no_from_top {
             let len = self.data.len();
             self.data[len - no_from_top - 1] = val;
             Ok(())
@@ -357,22 +451,52 @@ impl Stack {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Stack {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Stack", 2)?;
+        state.serialize_field("data", self.data())?;
+        state.serialize_field("limit", &self.limit())?;
+        state.end()
+    }
+}
+
 #[cfg(feature = "serde")]
 impl<'de> serde::Deserialize<'de> for Stack {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let mut data = Vec::<U256>::deserialize(deserializer)?;
-        if data.len() > STACK_LIMIT {
+        #[derive(serde::Deserialize)]
+        struct StackSerde {
+            data: Vec<U256>,
+            #[serde(default = "default_limit")]
+            limit: usize,
+        }
+
+        fn default_limit() -> usize {
+            STACK_LIMIT
+        }
+
+        let StackSerde { mut data, limit } = StackSerde::deserialize(deserializer)?;
+        if data.len() > limit {
             return Err(serde::de::Error::custom(std::format!(
                 "stack size exceeds limit: {} > {}",
                 data.len(),
-                STACK_LIMIT
+                limit
             )));
         }
-        data.reserve(STACK_LIMIT - data.len());
-        Ok(Self { data })
+        data.reserve(limit - data.len());
+        Ok(Self {
+            data,
+            offset: 0,
+            end: limit,
+        })
     }
 }
 
@@ -444,7 +568,7 @@ mod tests {
         let cloned_empty = empty_stack.clone();
         assert_eq!(empty_stack, cloned_empty);
         assert_eq!(cloned_empty.len(), 0);
-        assert_eq!(cloned_empty.data().capacity(), STACK_LIMIT);
+        assert_eq!(cloned_empty.capacity(), STACK_LIMIT);
 
         // Test cloning a partially filled stack
         let mut partial_stack = Stack::new();
@@ -454,7 +578,7 @@ mod tests {
         let mut cloned_partial = partial_stack.clone();
         assert_eq!(partial_stack, cloned_partial);
         assert_eq!(cloned_partial.len(), 10);
-        assert_eq!(cloned_partial.data().capacity(), STACK_LIMIT);
+        assert_eq!(cloned_partial.capacity(), STACK_LIMIT);
 
         // Test that modifying the clone doesn't affect the original
         assert!(cloned_partial.push(U256::from(100)));
@@ -470,10 +594,47 @@ mod tests {
         let mut cloned_full = full_stack.clone();
         assert_eq!(full_stack, cloned_full);
         assert_eq!(cloned_full.len(), STACK_LIMIT);
-        assert_eq!(cloned_full.data().capacity(), STACK_LIMIT);
+        assert_eq!(cloned_full.capacity(), STACK_LIMIT);
 
         // Test push to the full original or cloned stack should return StackOverflow
         assert!(!full_stack.push(U256::from(100)));
         assert!(!cloned_full.push(U256::from(100)));
     }
+
+    #[test]
+    fn stack_with_limit() {
+        let mut stack = Stack::with_limit(2);
+        assert!(stack.push(U256::from(1)));
+        assert!(stack.push(U256::from(2)));
+        assert!(!stack.push(U256::from(3)));
+        assert!(!stack.dup(1));
+        assert_eq!(
+            stack.push_slice(&[1]),
+            Err(InstructionResult::StackOverflow)
+        );
+
+        let mut stack = Stack::with_limit(STACK_LIMIT * 2);
+        for i in 0..STACK_LIMIT * 2 {
+            assert!(stack.push(U256::from(i)));
+        }
+        assert!(!stack.push(U256::ZERO));
+        assert_eq!(stack.clone().limit(), STACK_LIMIT * 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_keeps_limit() {
+        let mut stack = Stack::with_limit(2);
+        assert!(stack.push(U256::from(1)));
+        let json = serde_json::to_string(&stack).unwrap();
+        let deserialized: Stack = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, stack);
+        assert_eq!(deserialized.limit(), 2);
+
+        // Stacks serialized without a limit get the default one.
+        let deserialized: Stack = serde_json::from_str(r#"{"data":["0x1"]}"#).unwrap();
+        assert_eq!(deserialized.limit(), STACK_LIMIT);
+
+        assert!(serde_json::from_str::<Stack>(r#"{"data":["0x1","0x2"],"limit":1}"#).is_err());
+    }
 }
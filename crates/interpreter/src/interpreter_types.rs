@@ -31,6 +31,11 @@ pub trait LegacyBytecode {
     fn bytecode_slice(&self) -> &[u8];
 }
 
+pub trait BytecodeHash {
+    /// Returns hash of the bytecode if it is known.
+    fn bytecode_hash(&self) -> Option<B256>;
+}
+
 /// Trait for interpreter to be able to jump
 pub trait Jumps {
     /// Relative jumps does not require checking for overflow.
@@ -103,6 +108,9 @@ pub trait StackTrait {
     /// Returns stack length.
     fn len(&self) -> usize;
 
+    /// Returns the maximum number of items on the stack.
+    fn limit(&self) -> usize;
+
     /// Returns `true` if stack is empty.
     fn is_empty(&self) -> bool {
         self.len() == 0
@@ -208,7 +216,13 @@ pub trait Interp {
 pub trait InterpreterTypes {
     type Stack: StackTrait;
     type Memory: MemoryTrait;
-    type Bytecode: Jumps + Immediates + LegacyBytecode + EofData + EofContainer + EofCodeInfo;
+    type Bytecode: Jumps
+        + Immediates
+        + LegacyBytecode
+        + EofData
+        + EofContainer
+        + EofCodeInfo
+        + BytecodeHash;
     type ReturnData: ReturnData;
     type Input: InputsTrait;
     type SubRoutineStack: SubRoutineStack;
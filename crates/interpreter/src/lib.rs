@@ -33,8 +33,8 @@ pub use context_interface::{
 pub use gas::{Gas, InitialAndFloorGas};
 pub use instruction_result::*;
 pub use interpreter::{
-    num_words, InputsImpl, Interpreter, InterpreterResult, MemoryGetter, SharedMemory, Stack,
-    EMPTY_SHARED_MEMORY, STACK_LIMIT,
+    num_words, DecodedCode, ExecutionBudget, InputsImpl, Interpreter, InterpreterResult,
+    MemoryGetter, SharedMemory, SharedStack, Stack, EMPTY_SHARED_MEMORY, STACK_LIMIT,
 };
 pub use interpreter_action::{
     CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome, EOFCreateInputs,
@@ -39,9 +39,7 @@ where
 {
     pub fn new() -> Self {
         Self {
-            main: MainnetHandler {
-                _phantom: Default::default(),
-            },
+            main: MainnetHandler::new(INSTRUCTIONS::default()),
         }
     }
 }
@@ -95,6 +93,10 @@ where
         OpPrecompileProvider::default()
     }
 
+    fn instructions(&self, _context: &mut Self::Context) -> Self::Instructions {
+        self.main.instructions.clone()
+    }
+
     fn validate_env(&self, context: &Self::Context) -> Result<(), Self::Error> {
         // Do not perform any extra validation for deposit transactions, they are pre-verified on L1.
         let tx_type = context.tx().tx_type();
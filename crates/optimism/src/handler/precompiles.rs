@@ -135,3 +135,44 @@ impl<CTX, ERROR> Default for OpPrecompileProvider<CTX, ERROR> {
         Self::new_with_spec(OpSpec::Op(OpSpecId::ISTHMUS))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::into_optimism::DefaultOp;
+    use revm::{
+        interpreter::InstructionResult,
+        primitives::Bytes,
+        specification::gas_schedule::{GasSchedule, PrecompileGas},
+        Context,
+    };
+
+    #[test]
+    fn gas_schedule_overrides_precompile_price() {
+        let mut provider = OpPrecompileProvider::<_, PrecompileErrors>::new_with_spec(OpSpec::Op(
+            OpSpecId::GRANITE,
+        ));
+        let mut context = Context::default_op();
+        let p256verify = *secp256r1::P256VERIFY.address();
+
+        // P256VERIFY costs 3450 gas.
+        let result = provider
+            .run(&mut context, &p256verify, &Bytes::new(), 1_000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::PrecompileOOG);
+
+        context.modify_cfg(|cfg| {
+            cfg.gas_schedule = Some(GasSchedule {
+                precompiles: vec![(p256verify, PrecompileGas { base: 100, word: 0 })],
+                ..GasSchedule::new(SpecId::PRAGUE)
+            })
+        });
+        let result = provider
+            .run(&mut context, &p256verify, &Bytes::new(), 5_000)
+            .unwrap()
+            .unwrap();
+        assert_eq!(result.result, InstructionResult::Return);
+        assert_eq!(result.gas.spent(), 100);
+    }
+}
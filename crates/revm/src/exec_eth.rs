@@ -71,13 +71,25 @@ pub fn transact_main<CTX: EthContext>(
 mod test {
     use super::*;
     use bytecode::{
-        opcode::{PUSH1, SSTORE},
+        opcode::{
+            immediate_size, stack_io, OpCodeInfo, JUMP, JUMPDEST, PUSH0, PUSH1, SSTORE, STOP,
+        },
         Bytecode,
     };
-    use context_interface::TransactionType;
+    use context_interface::{
+        result::{ExecutionBudgetError, ExecutionResult, HaltReason, OutOfGasError},
+        TransactionType,
+    };
     use database::{BenchmarkDB, EEADDRESS, FFADDRESS};
-    use primitives::{address, TxKind, U256};
-    use specification::hardfork::SpecId;
+    use interpreter::{
+        interpreter_types::{Immediates, Jumps, LoopControl},
+        Host, InstructionResult, Interpreter,
+    };
+    use primitives::{address, Bytes, TxKind, U256};
+    use specification::{
+        gas_schedule::{GasSchedule, PrecompileGas},
+        hardfork::SpecId,
+    };
 
     #[test]
     fn sanity_eip7702_tx() {
@@ -106,4 +118,172 @@ mod test {
             U256::from(1)
         );
     }
+
+    #[test]
+    fn instruction_budget_halts_infinite_loop() {
+        let bytecode = Bytecode::new_legacy([JUMPDEST, PUSH1, 0x00, JUMP].into());
+
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| cfg.max_instructions = Some(1000))
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.gas_limit = u64::MAX / 2;
+                tx.caller = EEADDRESS;
+                tx.kind = TxKind::Call(FFADDRESS);
+            });
+
+        let result = ctx.exec_previous().unwrap().result;
+        assert!(matches!(
+            result,
+            ExecutionResult::Halt {
+                reason: HaltReason::ExecutionBudgetExceeded(ExecutionBudgetError::InstructionLimit),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn gas_schedule_with_cold_sload_above_sstore_reset() {
+        let bytecode = Bytecode::new_legacy([PUSH1, 0x01, PUSH1, 0x01, SSTORE].into());
+
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| {
+                cfg.gas_schedule = Some(GasSchedule {
+                    cold_sload: 10_000,
+                    ..GasSchedule::new(SpecId::PRAGUE)
+                })
+            })
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.gas_limit = 100_000;
+                tx.caller = EEADDRESS;
+                tx.kind = TxKind::Call(FFADDRESS);
+            });
+
+        assert!(ctx.exec_previous().unwrap().result.is_success());
+    }
+
+    #[test]
+    fn gas_schedule_overrides_precompile_price() {
+        let identity = address!("0000000000000000000000000000000000000004");
+        let schedule = GasSchedule {
+            precompiles: vec![(
+                identity,
+                PrecompileGas {
+                    base: 10_000,
+                    word: 0,
+                },
+            )],
+            ..GasSchedule::new(SpecId::PRAGUE)
+        };
+
+        let mut ctx = Context::default()
+            .modify_cfg_chained(|cfg| cfg.gas_schedule = Some(schedule))
+            .with_db(BenchmarkDB::new_bytecode(Bytecode::new()))
+            .modify_tx_chained(|tx| {
+                tx.gas_limit = 100_000;
+                tx.caller = EEADDRESS;
+                tx.kind = TxKind::Call(identity);
+                tx.data = Bytes::from([0; 64]);
+            });
+
+        // Intrinsic gas of 64 zero bytes and the price of the schedule.
+        let result = ctx.exec_previous().unwrap().result;
+        assert!(result.is_success());
+        assert_eq!(result.gas_used(), 21_000 + 64 * 4 + 10_000);
+
+        ctx.modify_tx(|tx| tx.gas_limit = 25_000);
+        assert!(matches!(
+            ctx.exec_previous().unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Precompile),
+                ..
+            }
+        ));
+
+        // The precompile still needs the 21 gas of its own price after the override is paid.
+        ctx.modify_tx(|tx| tx.gas_limit = 21_000 + 64 * 4 + 10_000 + 20);
+        assert!(matches!(
+            ctx.exec_previous().unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Precompile),
+                ..
+            }
+        ));
+    }
+
+    /// Pushes the one byte immediate of the custom opcode.
+    fn push_immediate<H: Host>(interpreter: &mut Interpreter<EthInterpreter>, _host: &mut H) {
+        let value = interpreter.bytecode.read_u8();
+        interpreter.bytecode.relative_jump(1);
+        if !interpreter.stack.push(U256::from(value)) {
+            interpreter
+                .control
+                .set_instruction_result(InstructionResult::StackOverflow);
+        }
+    }
+
+    /// Runs the transaction with `PUSHB` registered as `0x0C`.
+    fn transact_custom<CTX: EthContext>(
+        ctx: &mut CTX,
+    ) -> Result<
+        ResultAndState<HaltReason>,
+        EVMError<<<CTX as DatabaseGetter>::Database as Database>::Error, InvalidTransaction>,
+    > {
+        let mut executor = EthInstructionExecutor::new();
+        executor
+            .register_opcode(
+                0x0C,
+                stack_io(immediate_size(OpCodeInfo::new("PUSHB"), 1), 0, 1),
+                3,
+                push_immediate::<CTX>,
+            )
+            .unwrap();
+        MainnetHandler::<_, _, EthFrame<CTX, _, _, _>, EthPrecompileProvider<CTX, _>, _>::new(
+            executor,
+        )
+        .run(ctx)
+    }
+
+    #[test]
+    fn custom_opcode_with_immediate() {
+        // PUSHB 42, PUSH0, SSTORE, STOP
+        let bytecode = Bytecode::new_legacy([0x0C, 42, PUSH0, SSTORE, STOP].into());
+        let mut ctx = Context::default()
+            .with_db(BenchmarkDB::new_bytecode(bytecode))
+            .modify_tx_chained(|tx| {
+                tx.gas_limit = 100_000;
+                tx.caller = EEADDRESS;
+                tx.kind = TxKind::Call(FFADDRESS);
+            });
+
+        let ResultAndState { result, state } = transact_custom(&mut ctx).unwrap();
+        assert!(result.is_success());
+        // Static gas of `PUSHB`, `PUSH0` and cold `SSTORE` of a new value.
+        assert_eq!(result.gas_used(), 21_000 + 3 + 2 + 22_100);
+        assert_eq!(
+            state[&FFADDRESS].storage[&U256::ZERO].present_value(),
+            U256::from(42)
+        );
+
+        // Builtin executor doesn't know the opcode.
+        assert!(matches!(
+            ctx.exec_previous().unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OpcodeNotFound,
+                ..
+            }
+        ));
+
+        // Immediate of `PUSHB` is not a jump destination.
+        let bytecode = Bytecode::new_legacy([0x0C, JUMPDEST, PUSH1, 0x01, JUMP].into());
+        let mut ctx = ctx.with_db(BenchmarkDB::new_bytecode(bytecode));
+        assert!(matches!(
+            transact_custom(&mut ctx).unwrap().result,
+            ExecutionResult::Halt {
+                reason: HaltReason::InvalidJump,
+                ..
+            }
+        ));
+    }
 }
@@ -22,6 +22,7 @@ pub use state;
 
 mod exec;
 mod exec_eth;
+pub mod signed_tx;
 
 // Export items.
 
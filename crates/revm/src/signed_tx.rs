@@ -0,0 +1,530 @@
+//! Decoding of signed transactions into [`TxEnv`].
+//!
+//! Supports legacy (with and without EIP-155), EIP-2930, EIP-1559, EIP-4844 and EIP-7702
+//! transactions in their EIP-2718 encoding. Senders and authorities are recovered with
+//! [`precompile::secp256k1::ecrecover`].
+use alloy_rlp::{Decodable, Encodable, Header};
+use context::TxEnv;
+use context_interface::transaction::AuthorizationItem;
+use core::fmt;
+use precompile::secp256k1::ecrecover;
+use primitives::{alloy_primitives::B512, keccak256, Address, Bytes, TxKind, B256, U256};
+use specification::eip2::SECP256K1N_HALF;
+use std::{vec, vec::Vec};
+
+/// Magic byte prefixed to the RLP encoding of an EIP-7702 authorization before signing.
+const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+/// Error decoding a signed transaction or recovering its sender.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignedTxError {
+    /// Invalid RLP encoding.
+    Rlp(alloy_rlp::Error),
+    /// Transaction type is not supported.
+    UnsupportedType(u8),
+    /// Signature is invalid or the signer can't be recovered.
+    InvalidSignature,
+}
+
+impl From<alloy_rlp::Error> for SignedTxError {
+    fn from(error: alloy_rlp::Error) -> Self {
+        Self::Rlp(error)
+    }
+}
+
+impl core::error::Error for SignedTxError {}
+
+impl fmt::Display for SignedTxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rlp(error) => write!(f, "rlp: {error}"),
+            Self::UnsupportedType(tx_type) => write!(f, "unsupported transaction type {tx_type}"),
+            Self::InvalidSignature => write!(f, "invalid signature"),
+        }
+    }
+}
+
+/// ECDSA signature of a transaction or an authorization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Signature {
+    /// `v` of legacy transactions, y parity otherwise.
+    pub v: u64,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Signature {
+    /// Returns `true` if `s` is in the lower half of the curve order, required since Homestead.
+    pub fn is_low_s(&self) -> bool {
+        self.s <= SECP256K1N_HALF
+    }
+
+    /// Recovers the signer of `hash`, `y_parity` is the recovery id.
+    fn recover(&self, y_parity: u64, hash: &B256) -> Option<Address> {
+        if y_parity > 1 {
+            return None;
+        }
+        let mut sig = B512::ZERO;
+        sig[..32].copy_from_slice(&self.r.to_be_bytes::<32>());
+        sig[32..].copy_from_slice(&self.s.to_be_bytes::<32>());
+        let signer = ecrecover(&sig, y_parity as u8, hash).ok()?;
+        Some(Address::from_slice(&signer[12..]))
+    }
+}
+
+/// Signed EIP-7702 authorization.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignedAuthorization {
+    pub chain_id: U256,
+    pub address: Address,
+    pub nonce: u64,
+    pub signature: Signature,
+}
+
+impl SignedAuthorization {
+    fn encode_fields(&self, out: &mut Vec<u8>) {
+        self.chain_id.encode(out);
+        self.address.encode(out);
+        self.nonce.encode(out);
+    }
+
+    /// Returns the hash signed by the authority.
+    pub fn signature_hash(&self) -> B256 {
+        let mut payload = Vec::new();
+        self.encode_fields(&mut payload);
+        let mut out = vec![AUTHORIZATION_MAGIC];
+        out.extend_from_slice(&rlp_list(&payload));
+        keccak256(out)
+    }
+
+    /// Recovers the authority, `None` if the signature is invalid.
+    pub fn recover_authority(&self) -> Option<Address> {
+        if !self.signature.is_low_s() {
+            return None;
+        }
+        self.signature
+            .recover(self.signature.v, &self.signature_hash())
+    }
+
+    /// Converts to [`AuthorizationItem`], the authority is `None` if the signature is invalid.
+    pub fn to_item(&self) -> AuthorizationItem {
+        (
+            self.recover_authority(),
+            self.chain_id,
+            self.nonce,
+            self.address,
+        )
+    }
+}
+
+impl Encodable for SignedAuthorization {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        let mut payload = Vec::new();
+        self.encode_fields(&mut payload);
+        self.signature.v.encode(&mut payload);
+        self.signature.r.encode(&mut payload);
+        self.signature.s.encode(&mut payload);
+        out.put_slice(&rlp_list(&payload));
+    }
+}
+
+impl Decodable for SignedAuthorization {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut payload = list_payload(buf)?;
+        let auth = Self {
+            chain_id: Decodable::decode(&mut payload)?,
+            address: Decodable::decode(&mut payload)?,
+            nonce: Decodable::decode(&mut payload)?,
+            signature: Signature {
+                v: Decodable::decode(&mut payload)?,
+                r: Decodable::decode(&mut payload)?,
+                s: Decodable::decode(&mut payload)?,
+            },
+        };
+        finish(payload)?;
+        Ok(auth)
+    }
+}
+
+/// Access list item, the address and its storage keys.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, alloy_rlp::RlpEncodable, alloy_rlp::RlpDecodable,
+)]
+pub struct AccessListItem {
+    pub address: Address,
+    pub storage_keys: Vec<B256>,
+}
+
+/// Signed transaction of any type.
+///
+/// Fields that don't exist in the transaction type are left at their default value.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignedTransaction {
+    pub tx_type: u8,
+    /// Chain id, `None` for legacy transactions before EIP-155.
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    /// Gas price, or max fee per gas since EIP-1559.
+    pub gas_price: u128,
+    pub max_priority_fee_per_gas: u128,
+    pub gas_limit: u64,
+    pub to: TxKind,
+    pub value: U256,
+    pub input: Bytes,
+    pub access_list: Vec<AccessListItem>,
+    pub max_fee_per_blob_gas: u128,
+    pub blob_versioned_hashes: Vec<B256>,
+    pub authorization_list: Vec<SignedAuthorization>,
+    pub signature: Signature,
+}
+
+impl SignedTransaction {
+    /// Encodes the fields of the transaction before the signature.
+    fn encode_fields(&self, out: &mut Vec<u8>) {
+        if self.tx_type != 0 {
+            self.chain_id.unwrap_or_default().encode(out);
+        }
+        self.nonce.encode(out);
+        if self.tx_type >= 2 {
+            self.max_priority_fee_per_gas.encode(out);
+        }
+        self.gas_price.encode(out);
+        self.gas_limit.encode(out);
+        self.to.encode(out);
+        self.value.encode(out);
+        self.input.encode(out);
+        if self.tx_type == 0 {
+            return;
+        }
+        self.access_list.encode(out);
+        if self.tx_type == 3 {
+            self.max_fee_per_blob_gas.encode(out);
+            self.blob_versioned_hashes.encode(out);
+        }
+        if self.tx_type == 4 {
+            self.authorization_list.encode(out);
+        }
+    }
+
+    /// Returns the hash signed by the sender.
+    pub fn signature_hash(&self) -> B256 {
+        let mut payload = Vec::new();
+        self.encode_fields(&mut payload);
+        if self.tx_type == 0 {
+            // EIP-155 replay protection.
+            if let Some(chain_id) = self.chain_id {
+                chain_id.encode(&mut payload);
+                0u8.encode(&mut payload);
+                0u8.encode(&mut payload);
+            }
+            return keccak256(rlp_list(&payload));
+        }
+        let mut out = vec![self.tx_type];
+        out.extend_from_slice(&rlp_list(&payload));
+        keccak256(out)
+    }
+
+    /// Returns the EIP-2718 encoding, the RLP list for legacy transactions.
+    pub fn encoded(&self) -> Vec<u8> {
+        let mut payload = Vec::new();
+        self.encode_fields(&mut payload);
+        self.signature.v.encode(&mut payload);
+        self.signature.r.encode(&mut payload);
+        self.signature.s.encode(&mut payload);
+
+        let mut out = Vec::with_capacity(payload.len() + 5);
+        if self.tx_type != 0 {
+            out.push(self.tx_type);
+        }
+        out.extend_from_slice(&rlp_list(&payload));
+        out
+    }
+
+    /// Returns the transaction hash.
+    pub fn hash(&self) -> B256 {
+        keccak256(self.encoded())
+    }
+
+    /// Decodes an EIP-2718 encoded transaction.
+    pub fn decode(mut buf: &[u8]) -> Result<Self, SignedTxError> {
+        let tx = Self::decode_inner(&mut buf)?;
+        if !buf.is_empty() {
+            return Err(alloy_rlp::Error::UnexpectedLength.into());
+        }
+        Ok(tx)
+    }
+
+    /// Decodes a transaction from an RLP list of transactions.
+    ///
+    /// Legacy transactions are lists, typed transactions are strings with the EIP-2718 encoding.
+    pub fn decode_list_item(buf: &mut &[u8]) -> Result<Self, SignedTxError> {
+        let Some(&first) = buf.first() else {
+            return Err(alloy_rlp::Error::InputTooShort.into());
+        };
+        if first >= alloy_rlp::EMPTY_LIST_CODE {
+            return Self::decode_inner(buf);
+        }
+        let bytes = Header::decode_bytes(buf, false)?;
+        Self::decode(bytes)
+    }
+
+    fn decode_inner(buf: &mut &[u8]) -> Result<Self, SignedTxError> {
+        let Some(&first) = buf.first() else {
+            return Err(alloy_rlp::Error::InputTooShort.into());
+        };
+        let tx_type = if first >= alloy_rlp::EMPTY_LIST_CODE {
+            0
+        } else {
+            if first == 0 || first > 4 {
+                return Err(SignedTxError::UnsupportedType(first));
+            }
+            *buf = &buf[1..];
+            first
+        };
+
+        let mut payload = list_payload(buf)?;
+        // Blob transactions with the sidecar are wrapped in a list, the sidecar is ignored.
+        if tx_type == 3
+            && payload
+                .first()
+                .is_some_and(|b| *b >= alloy_rlp::EMPTY_LIST_CODE)
+        {
+            payload = list_payload(&mut payload)?;
+        }
+        let fields = &mut payload;
+
+        let mut tx = Self {
+            tx_type,
+            ..Default::default()
+        };
+        if tx_type != 0 {
+            tx.chain_id = Some(Decodable::decode(fields)?);
+        }
+        tx.nonce = Decodable::decode(fields)?;
+        if tx_type >= 2 {
+            tx.max_priority_fee_per_gas = Decodable::decode(fields)?;
+        }
+        tx.gas_price = Decodable::decode(fields)?;
+        tx.gas_limit = Decodable::decode(fields)?;
+        tx.to = Decodable::decode(fields)?;
+        tx.value = Decodable::decode(fields)?;
+        tx.input = Decodable::decode(fields)?;
+        if tx_type != 0 {
+            tx.access_list = Decodable::decode(fields)?;
+        }
+        if tx_type == 3 {
+            tx.max_fee_per_blob_gas = Decodable::decode(fields)?;
+            tx.blob_versioned_hashes = Decodable::decode(fields)?;
+        }
+        if tx_type == 4 {
+            tx.authorization_list = Decodable::decode(fields)?;
+        }
+        tx.signature = Signature {
+            v: Decodable::decode(fields)?,
+            r: Decodable::decode(fields)?,
+            s: Decodable::decode(fields)?,
+        };
+        finish(payload)?;
+
+        // Legacy `v` is 27 or 28, or has the chain id since EIP-155.
+        if tx_type == 0 && tx.signature.v >= 35 {
+            tx.chain_id = Some((tx.signature.v - 35) / 2);
+        }
+        Ok(tx)
+    }
+
+    /// Recovers the sender from the signature.
+    pub fn recover_sender(&self) -> Result<Address, SignedTxError> {
+        if !self.signature.is_low_s() {
+            return Err(SignedTxError::InvalidSignature);
+        }
+        self.recover_sender_unchecked()
+    }
+
+    /// Recovers the sender without the EIP-2 check of `s`, as before Homestead.
+    pub fn recover_sender_unchecked(&self) -> Result<Address, SignedTxError> {
+        let y_parity = if self.tx_type != 0 {
+            Some(self.signature.v)
+        } else if let Some(chain_id) = self.chain_id {
+            self.signature.v.checked_sub(35 + 2 * chain_id)
+        } else {
+            self.signature.v.checked_sub(27)
+        };
+        y_parity
+            .and_then(|y_parity| self.signature.recover(y_parity, &self.signature_hash()))
+            .ok_or(SignedTxError::InvalidSignature)
+    }
+
+    /// Returns the transaction environment sent by `caller`.
+    pub fn to_tx_env(&self, caller: Address) -> TxEnv {
+        TxEnv {
+            tx_type: self.tx_type,
+            caller,
+            gas_limit: self.gas_limit,
+            gas_price: self.gas_price,
+            kind: self.to,
+            value: self.value,
+            data: self.input.clone(),
+            nonce: self.nonce,
+            chain_id: self.chain_id,
+            access_list: self
+                .access_list
+                .iter()
+                .map(|item| (item.address, item.storage_keys.clone()))
+                .collect(),
+            gas_priority_fee: (self.tx_type >= 2).then_some(self.max_priority_fee_per_gas),
+            blob_hashes: self.blob_versioned_hashes.clone(),
+            max_fee_per_blob_gas: self.max_fee_per_blob_gas,
+            authorization_list: self
+                .authorization_list
+                .iter()
+                .map(SignedAuthorization::to_item)
+                .collect(),
+        }
+    }
+}
+
+/// Decodes an EIP-2718 encoded transaction and recovers its sender.
+pub fn decode_tx_env(bytes: &[u8]) -> Result<TxEnv, SignedTxError> {
+    let tx = SignedTransaction::decode(bytes)?;
+    let caller = tx.recover_sender()?;
+    Ok(tx.to_tx_env(caller))
+}
+
+/// Decodes an RLP list of transactions, as in a block body.
+pub fn decode_transactions(mut buf: &[u8]) -> Result<Vec<SignedTransaction>, SignedTxError> {
+    let mut payload = list_payload(&mut buf)?;
+    finish(buf)?;
+    let mut transactions = Vec::new();
+    while !payload.is_empty() {
+        transactions.push(SignedTransaction::decode_list_item(&mut payload)?);
+    }
+    Ok(transactions)
+}
+
+/// Encodes transactions as an RLP list, typed transactions are RLP strings.
+pub fn encode_transactions(transactions: &[SignedTransaction]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    for tx in transactions {
+        let encoded = tx.encoded();
+        if tx.tx_type == 0 {
+            payload.extend_from_slice(&encoded);
+        } else {
+            encoded.as_slice().encode(&mut payload);
+        }
+    }
+    rlp_list(&payload)
+}
+
+fn rlp_list(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 5);
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut out);
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Decodes a list header and returns its payload, advancing `buf` past the list.
+fn list_payload<'a>(buf: &mut &'a [u8]) -> alloy_rlp::Result<&'a [u8]> {
+    let header = Header::decode(buf)?;
+    if !header.list {
+        return Err(alloy_rlp::Error::UnexpectedString);
+    }
+    if buf.len() < header.payload_length {
+        return Err(alloy_rlp::Error::InputTooShort);
+    }
+    let (payload, rest) = buf.split_at(header.payload_length);
+    *buf = rest;
+    Ok(payload)
+}
+
+fn finish(rest: &[u8]) -> alloy_rlp::Result<()> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(alloy_rlp::Error::ListLengthMismatch {
+            expected: 0,
+            got: rest.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{address, b256, hex};
+
+    #[test]
+    fn legacy_eip155() {
+        // Example from EIP-155.
+        let raw = hex!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+        let tx = SignedTransaction::decode(&raw).unwrap();
+        assert_eq!(tx.chain_id, Some(1));
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(
+            tx.signature_hash(),
+            b256!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+        assert_eq!(tx.encoded(), raw);
+
+        let tx_env = decode_tx_env(&raw).unwrap();
+        assert_eq!(
+            tx_env.caller,
+            address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")
+        );
+        assert_eq!(tx_env.gas_limit, 21_000);
+        assert_eq!(tx_env.gas_priority_fee, None);
+    }
+
+    #[test]
+    fn high_s() {
+        let raw = hex!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+        let mut tx = SignedTransaction::decode(&raw).unwrap();
+        // Flip the signature to the equivalent one with the high `s`.
+        const N: U256 = U256::from_be_bytes(hex!(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+        ));
+        tx.signature.s = N - tx.signature.s;
+        tx.signature.v = 37 + 38 - tx.signature.v;
+        assert_eq!(tx.recover_sender(), Err(SignedTxError::InvalidSignature));
+        assert_eq!(
+            tx.recover_sender_unchecked(),
+            Ok(address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"))
+        );
+    }
+
+    #[test]
+    fn unsupported_type() {
+        assert_eq!(
+            SignedTransaction::decode(&[0x05, 0xc0]),
+            Err(SignedTxError::UnsupportedType(5))
+        );
+        assert_eq!(
+            SignedTransaction::decode(&[0x00, 0xc0]),
+            Err(SignedTxError::UnsupportedType(0))
+        );
+    }
+
+    #[test]
+    fn transaction_list() {
+        let transactions = (0..=4)
+            .map(|tx_type| SignedTransaction {
+                tx_type,
+                chain_id: Some(1),
+                nonce: tx_type as u64,
+                to: TxKind::Call(Address::with_last_byte(tx_type)),
+                signature: Signature {
+                    v: if tx_type == 0 { 37 } else { 1 },
+                    r: U256::from(1),
+                    s: U256::from(2),
+                },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let encoded = encode_transactions(&transactions);
+        assert_eq!(decode_transactions(&encoded).unwrap(), transactions);
+    }
+}
@@ -0,0 +1,300 @@
+//! Gas schedule used by the interpreter and transaction validation.
+
+use crate::hardfork::SpecId;
+use core::num::NonZeroU64;
+use primitives::Address;
+use std::vec::Vec;
+
+/// Gas costs of dynamically priced opcodes, memory expansion and intrinsic transaction gas.
+///
+/// Values are already resolved for a hardfork, for example [`GasSchedule::sload`] holds `50`,
+/// `200` or `800` depending on the fork. Rules that change the structure of a cost (e.g. warm and
+/// cold accounting introduced in Berlin) are still selected by [`SpecId`].
+///
+/// Mainnet schedules are returned by [`GasSchedule::new`] and [`GasSchedule::for_spec`].
+/// Individual entries can be overridden with struct update syntax:
+///
+/// ```
+/// use revm_specification::{gas_schedule::GasSchedule, hardfork::SpecId};
+///
+/// let schedule = GasSchedule {
+///     cold_sload: 3000,
+///     ..GasSchedule::new(SpecId::PRAGUE)
+/// };
+/// assert_eq!(schedule.cold_sload, 3000);
+/// ```
+///
+/// Static opcode costs (e.g. `ADD` or `PUSH1`) are part of the instruction table. Precompile
+/// prices are defined by the precompile provider unless they are set in
+/// [`GasSchedule::precompiles`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasSchedule {
+    /// Linear cost of a memory word.
+    pub memory: u64,
+    /// Divisor of the quadratic part of memory expansion cost.
+    pub memory_quadratic_divisor: NonZeroU64,
+    /// Cost of copying a word in `*COPY` opcodes.
+    pub copy: u64,
+    /// Base cost of `KECCAK256`.
+    pub keccak256: u64,
+    /// Cost of hashing a word in `KECCAK256` and `CREATE2`.
+    pub keccak256_word: u64,
+    /// Base cost of `EXP`.
+    pub exp: u64,
+    /// Cost of a byte of the `EXP` exponent.
+    ///
+    /// EIP-160: EXP cost increase
+    pub exp_byte: u64,
+    /// Base cost of `LOG*`.
+    pub log: u64,
+    /// Cost of a byte of logged data.
+    pub log_data: u64,
+    /// Cost of a log topic.
+    pub log_topic: u64,
+    /// Cost of `BLOCKHASH`.
+    pub blockhash: u64,
+    /// Cost of `BALANCE` before Berlin.
+    pub balance: u64,
+    /// Cost of `EXTCODESIZE` before Berlin.
+    pub extcodesize: u64,
+    /// Cost of `EXTCODEHASH` before Berlin.
+    pub extcodehash: u64,
+    /// Base cost of `EXTCODECOPY` before Berlin.
+    pub extcodecopy: u64,
+    /// Cost of `SLOAD` before Berlin.
+    pub sload: u64,
+    /// Base cost of `CALL` like opcodes before Berlin.
+    pub call: u64,
+    /// Base cost of `SELFDESTRUCT`.
+    pub selfdestruct: u64,
+    /// EIP-2929: Cost of a cold storage slot access.
+    pub cold_sload: u64,
+    /// EIP-2929: Cost of a cold account access.
+    pub cold_account_access: u64,
+    /// EIP-2929: Cost of a warm storage or account access.
+    pub warm_storage_read: u64,
+    /// Cost of setting a storage slot from zero to non-zero.
+    pub sstore_set: u64,
+    /// Cost of changing a non-zero storage slot.
+    pub sstore_reset: u64,
+    /// Refund for clearing a storage slot.
+    ///
+    /// EIP-3529: Reduction in refunds
+    pub sstore_clears_refund: u64,
+    /// Refund for destroying an account before London.
+    pub selfdestruct_refund: u64,
+    /// Cost of transferring value in a call.
+    pub call_value: u64,
+    /// Gas given to the callee when value is transferred.
+    pub call_stipend: u64,
+    /// Cost of creating a new account.
+    pub new_account: u64,
+    /// Base cost of `CREATE` and `CREATE2`.
+    pub create: u64,
+    /// Cost of a byte of deployed code.
+    pub code_deposit: u64,
+    /// EIP-3860: Cost of a word of initcode.
+    pub initcode_word: u64,
+    /// Base intrinsic cost of a transaction.
+    pub tx_base: u64,
+    /// Additional intrinsic cost of a create transaction.
+    ///
+    /// EIP-2: Homestead Hard-fork Changes
+    pub tx_create: u64,
+    /// Cost of a calldata token. A zero byte is one token.
+    pub tx_token: u64,
+    /// Number of tokens a non-zero calldata byte is counted as.
+    ///
+    /// EIP-2028: Transaction data gas cost reduction
+    pub tx_non_zero_byte_multiplier: u64,
+    /// EIP-2930: Cost of an access list address.
+    pub tx_access_list_address: u64,
+    /// EIP-2930: Cost of an access list storage key.
+    pub tx_access_list_storage_key: u64,
+    /// EIP-7702: Cost of an authorization.
+    pub tx_authorization: u64,
+    /// EIP-7623: Floor cost of a calldata token.
+    pub tx_floor_cost_per_token: u64,
+    /// Prices of precompiles that replace the ones of the precompile provider.
+    ///
+    /// Empty in mainnet schedules.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub precompiles: Vec<(Address, PrecompileGas)>,
+}
+
+/// Price of a precompile call, `base + word * words(input)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrecompileGas {
+    /// Cost of a call.
+    pub base: u64,
+    /// Cost of a 32 byte word of input, rounded up.
+    pub word: u64,
+}
+
+impl PrecompileGas {
+    /// Returns the cost of calling the precompile with `len` bytes of input.
+    pub const fn cost(&self, len: usize) -> u64 {
+        let words = (len as u64).div_ceil(32);
+        self.base.saturating_add(self.word.saturating_mul(words))
+    }
+}
+
+/// Frontier gas schedule.
+pub static FRONTIER_GAS_SCHEDULE: GasSchedule = GasSchedule::new(SpecId::FRONTIER);
+/// Homestead gas schedule.
+pub static HOMESTEAD_GAS_SCHEDULE: GasSchedule = GasSchedule::new(SpecId::HOMESTEAD);
+/// Tangerine Whistle gas schedule.
+pub static TANGERINE_GAS_SCHEDULE: GasSchedule = GasSchedule::new(SpecId::TANGERINE);
+/// Spurious Dragon gas schedule.
+pub static SPURIOUS_DRAGON_GAS_SCHEDULE: GasSchedule = GasSchedule::new(SpecId::SPURIOUS_DRAGON);
+/// Istanbul gas schedule.
+pub static ISTANBUL_GAS_SCHEDULE: GasSchedule = GasSchedule::new(SpecId::ISTANBUL);
+/// London gas schedule.
+pub static LONDON_GAS_SCHEDULE: GasSchedule = GasSchedule::new(SpecId::LONDON);
+
+impl GasSchedule {
+    /// Returns the mainnet gas schedule of the given hardfork.
+    pub const fn new(spec_id: SpecId) -> Self {
+        let is_tangerine = spec_id.is_enabled_in(SpecId::TANGERINE);
+        let is_istanbul = spec_id.is_enabled_in(SpecId::ISTANBUL);
+        Self {
+            memory: 3,
+            memory_quadratic_divisor: match NonZeroU64::new(512) {
+                Some(divisor) => divisor,
+                None => unreachable!(),
+            },
+            copy: 3,
+            keccak256: 30,
+            keccak256_word: 6,
+            exp: 10,
+            exp_byte: if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
+                50
+            } else {
+                10
+            },
+            log: 375,
+            log_data: 8,
+            log_topic: 375,
+            blockhash: 20,
+            // EIP-150 and EIP-1884 repricing.
+            balance: if is_istanbul {
+                700
+            } else if is_tangerine {
+                400
+            } else {
+                20
+            },
+            extcodesize: if is_tangerine { 700 } else { 20 },
+            extcodehash: if is_istanbul { 700 } else { 400 },
+            extcodecopy: if is_tangerine { 700 } else { 20 },
+            sload: if is_istanbul {
+                800
+            } else if is_tangerine {
+                200
+            } else {
+                50
+            },
+            call: if is_tangerine { 700 } else { 40 },
+            selfdestruct: if is_tangerine { 5000 } else { 0 },
+            cold_sload: 2100,
+            cold_account_access: 2600,
+            warm_storage_read: 100,
+            sstore_set: 20000,
+            sstore_reset: 5000,
+            // EIP-3529: `SSTORE_RESET - COLD_SLOAD_COST + ACCESS_LIST_STORAGE_KEY`
+            sstore_clears_refund: if spec_id.is_enabled_in(SpecId::LONDON) {
+                4800
+            } else {
+                15000
+            },
+            selfdestruct_refund: 24000,
+            call_value: 9000,
+            call_stipend: 2300,
+            new_account: 25000,
+            create: 32000,
+            code_deposit: 200,
+            initcode_word: 2,
+            tx_base: 21000,
+            tx_create: if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
+                32000
+            } else {
+                0
+            },
+            tx_token: 4,
+            tx_non_zero_byte_multiplier: if is_istanbul { 4 } else { 17 },
+            tx_access_list_address: 2400,
+            tx_access_list_storage_key: 1900,
+            tx_authorization: crate::eip7702::PER_EMPTY_ACCOUNT_COST,
+            tx_floor_cost_per_token: 10,
+            precompiles: Vec::new(),
+        }
+    }
+
+    /// Returns the price of the precompile at `address` if it is overridden.
+    pub fn precompile_gas(&self, address: &Address) -> Option<PrecompileGas> {
+        self.precompiles
+            .iter()
+            .find(|(precompile, _)| precompile == address)
+            .map(|(_, gas)| *gas)
+    }
+
+    /// Returns a reference to the static mainnet gas schedule of the given hardfork.
+    pub const fn for_spec(spec_id: SpecId) -> &'static Self {
+        if spec_id.is_enabled_in(SpecId::LONDON) {
+            &LONDON_GAS_SCHEDULE
+        } else if spec_id.is_enabled_in(SpecId::ISTANBUL) {
+            &ISTANBUL_GAS_SCHEDULE
+        } else if spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON) {
+            &SPURIOUS_DRAGON_GAS_SCHEDULE
+        } else if spec_id.is_enabled_in(SpecId::TANGERINE) {
+            &TANGERINE_GAS_SCHEDULE
+        } else if spec_id.is_enabled_in(SpecId::HOMESTEAD) {
+            &HOMESTEAD_GAS_SCHEDULE
+        } else {
+            &FRONTIER_GAS_SCHEDULE
+        }
+    }
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self::new(SpecId::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_schedules_match_spec() {
+        for spec_id in (0..=SpecId::OSAKA as u8).filter_map(SpecId::try_from_u8) {
+            assert_eq!(*GasSchedule::for_spec(spec_id), GasSchedule::new(spec_id));
+        }
+        assert_eq!(
+            *GasSchedule::for_spec(SpecId::LATEST),
+            GasSchedule::new(SpecId::LATEST)
+        );
+    }
+
+    #[test]
+    fn precompile_gas_override() {
+        let address = Address::with_last_byte(4);
+        let schedule = GasSchedule {
+            precompiles: vec![(
+                address,
+                PrecompileGas {
+                    base: 100,
+                    word: 10,
+                },
+            )],
+            ..GasSchedule::new(SpecId::PRAGUE)
+        };
+        assert_eq!(schedule.precompile_gas(&Address::with_last_byte(1)), None);
+        let price = schedule.precompile_gas(&address).unwrap();
+        assert_eq!(price.cost(0), 100);
+        assert_eq!(price.cost(33), 120);
+    }
+}
@@ -10,4 +10,5 @@ pub mod eip170;
 pub mod eip2;
 pub mod eip4844;
 pub mod eip7702;
+pub mod gas_schedule;
 pub mod hardfork;
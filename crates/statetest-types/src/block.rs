@@ -0,0 +1,183 @@
+use revm::primitives::{
+    alloy_primitives::{Bloom, B64},
+    Address, Bytes, B256, U256,
+};
+use serde::Deserialize;
+
+use crate::{deserializer::deserialize_maybe_empty, AccessListItem, TestAuthorization};
+
+/// Block of a blockchain test.
+///
+/// Invalid blocks may only have the RLP encoding, or have the decoded fields in `rlp_decoded`.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    /// RLP encoded block.
+    pub rlp: Bytes,
+    /// Exception expected when importing the block, the block is invalid if set.
+    pub expect_exception: Option<String>,
+    #[serde(flatten)]
+    pub decoded: DecodedBlock,
+    /// Decoded fields of an invalid block.
+    #[serde(rename = "rlp_decoded")]
+    pub rlp_decoded: Option<DecodedBlock>,
+}
+
+impl Block {
+    /// Returns the decoded block, `None` if the test only has the RLP encoding.
+    pub fn decoded(&self) -> Option<&DecodedBlock> {
+        self.rlp_decoded
+            .as_ref()
+            .or(Some(&self.decoded))
+            .filter(|block| block.block_header.is_some())
+    }
+}
+
+/// Decoded fields of a block.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedBlock {
+    pub block_header: Option<BlockHeader>,
+    #[serde(default)]
+    pub transactions: Vec<BlockTransaction>,
+    #[serde(default)]
+    pub uncle_headers: Vec<BlockHeader>,
+    /// Withdrawals, present since Shanghai.
+    pub withdrawals: Option<Vec<Withdrawal>>,
+}
+
+/// Block header.
+///
+/// Optional fields are present since the fork that added them.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockHeader {
+    pub parent_hash: B256,
+    pub uncle_hash: B256,
+    pub coinbase: Address,
+    pub state_root: B256,
+    pub transactions_trie: B256,
+    pub receipt_trie: B256,
+    pub bloom: Bloom,
+    pub difficulty: U256,
+    pub number: U256,
+    pub gas_limit: U256,
+    pub gas_used: U256,
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    pub mix_hash: B256,
+    pub nonce: B64,
+    pub base_fee_per_gas: Option<U256>,
+    pub withdrawals_root: Option<B256>,
+    pub blob_gas_used: Option<U256>,
+    pub excess_blob_gas: Option<U256>,
+    pub parent_beacon_block_root: Option<B256>,
+    pub requests_hash: Option<B256>,
+    /// Hash of the header.
+    pub hash: B256,
+}
+
+/// Signed transaction of a block.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTransaction {
+    /// Transaction type, legacy if not present.
+    #[serde(rename = "type", default)]
+    pub tx_type: U256,
+    pub chain_id: Option<U256>,
+    pub nonce: U256,
+    pub gas_price: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub gas_limit: U256,
+    #[serde(default, deserialize_with = "deserialize_maybe_empty")]
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+    pub access_list: Option<Vec<AccessListItem>>,
+    pub authorization_list: Option<Vec<TestAuthorization>>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+    pub max_fee_per_blob_gas: Option<U256>,
+    pub v: U256,
+    pub r: U256,
+    pub s: U256,
+    /// Address recovered from the signature.
+    pub sender: Option<Address>,
+}
+
+/// Withdrawal from the beacon chain, added in Shanghai.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    pub index: U256,
+    pub validator_index: U256,
+    pub address: Address,
+    /// Amount in gwei.
+    pub amount: U256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_invalid_block() {
+        let block = r#"{
+            "rlp": "0xf90200",
+            "expectException": "TransactionException.INSUFFICIENT_ACCOUNT_FUNDS",
+            "rlp_decoded": {
+                "blockHeader": {
+                    "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "uncleHash": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+                    "coinbase": "0x2adc25665018aa1fe0e6bc666dac8fc2697ff9ba",
+                    "stateRoot": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "transactionsTrie": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "receiptTrie": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "bloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                    "difficulty": "0x00",
+                    "number": "0x01",
+                    "gasLimit": "0x016345785d8a0000",
+                    "gasUsed": "0x00",
+                    "timestamp": "0x0c",
+                    "extraData": "0x",
+                    "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "nonce": "0x0000000000000000",
+                    "baseFeePerGas": "0x07",
+                    "withdrawalsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                    "hash": "0x0000000000000000000000000000000000000000000000000000000000000000"
+                },
+                "transactions": [
+                    {
+                        "type": "0x02",
+                        "chainId": "0x01",
+                        "nonce": "0x00",
+                        "maxPriorityFeePerGas": "0x00",
+                        "maxFeePerGas": "0x07",
+                        "gasLimit": "0x5208",
+                        "to": "",
+                        "value": "0x01",
+                        "data": "0x",
+                        "accessList": [],
+                        "v": "0x00",
+                        "r": "0x01",
+                        "s": "0x01",
+                        "sender": "0xa94f5374fce5edbc8e2a8697c15331677e6ebf0b"
+                    }
+                ],
+                "uncleHeaders": [],
+                "withdrawals": []
+            }
+        }"#;
+
+        let block: Block = serde_json::from_str(block).unwrap();
+        let decoded = block.decoded().unwrap();
+        assert_eq!(decoded.transactions[0].to, None);
+        assert_eq!(decoded.transactions[0].tx_type, U256::from(2));
+        assert_eq!(decoded.withdrawals, Some(vec![]));
+
+        let block: Block =
+            serde_json::from_str(r#"{"rlp": "0x00", "expectException": "x"}"#).unwrap();
+        assert!(block.decoded().is_none());
+    }
+}
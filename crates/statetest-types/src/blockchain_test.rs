@@ -0,0 +1,55 @@
+use revm::primitives::{Address, Bytes, HashMap, B256, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::{AccountInfo, Block, BlockHeader, SpecName};
+
+/// The top level blockchain test suite struct
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockchainTestSuite(pub BTreeMap<String, BlockchainTestUnit>);
+
+/// Single blockchain test, a chain of blocks built on top of the genesis block.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockchainTestUnit {
+    /// Fork rules of the chain.
+    pub network: SpecName,
+    /// Genesis block header.
+    pub genesis_block_header: BlockHeader,
+    /// RLP encoded genesis block.
+    #[serde(rename = "genesisRLP")]
+    pub genesis_rlp: Option<Bytes>,
+    /// Blocks to import, in order.
+    pub blocks: Vec<Block>,
+    /// Genesis state.
+    pub pre: HashMap<Address, AccountInfo>,
+    /// State after the last valid block.
+    pub post_state: Option<HashMap<Address, AccountInfo>>,
+    /// State root after the last valid block, used instead of `post_state` for large states.
+    pub post_state_hash: Option<B256>,
+    /// Hash of the last valid block.
+    #[serde(rename = "lastblockhash")]
+    pub last_block_hash: B256,
+    /// Consensus engine, `NoProof` if the proof of work is not valid.
+    pub seal_engine: Option<String>,
+    /// Chain configuration.
+    pub config: Option<BlockchainTestConfig>,
+}
+
+impl BlockchainTestUnit {
+    /// Returns the chain id, mainnet if it is not configured.
+    pub fn chain_id(&self) -> u64 {
+        self.config
+            .as_ref()
+            .and_then(|config| config.chain_id)
+            .map(|chain_id| chain_id.saturating_to())
+            .unwrap_or(1)
+    }
+}
+
+/// Chain configuration of a blockchain test.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct BlockchainTestConfig {
+    #[serde(rename = "chainid")]
+    pub chain_id: Option<U256>,
+}
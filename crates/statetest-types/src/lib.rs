@@ -4,9 +4,12 @@
 //! specifically tailored for use with REVM.
 //!
 //! It includes structures for representing account information, environment settings,
-//! test cases, and transaction data used in Ethereum state tests.
+//! test cases, and transaction data used in Ethereum state tests, the blocks of
+//! blockchain tests and the expected outcomes of transaction tests.
 
 mod account_info;
+mod block;
+mod blockchain_test;
 mod deserializer;
 mod env;
 mod spec;
@@ -15,8 +18,11 @@ mod test_authorization;
 mod test_suite;
 mod test_unit;
 mod transaction;
+mod transaction_test;
 
 pub use account_info::*;
+pub use block::*;
+pub use blockchain_test::*;
 pub use deserializer::*;
 pub use env::*;
 pub use spec::*;
@@ -25,3 +31,4 @@ pub use test_authorization::*;
 pub use test_suite::*;
 pub use test_unit::*;
 pub use transaction::*;
+pub use transaction_test::*;
@@ -2,7 +2,7 @@ use revm::specification::hardfork::SpecId;
 use serde::Deserialize;
 
 /// Ethereum specification names
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Hash)]
 pub enum SpecName {
     Frontier,
     FrontierToHomesteadAt5,
@@ -23,8 +23,11 @@ pub enum SpecName {
     London,
     Paris,
     Merge,
+    ParisToShanghaiAtTime15k,
     Shanghai,
+    ShanghaiToCancunAtTime15k,
     Cancun,
+    CancunToPragueAtTime15k,
     Prague,
     Osaka, // SKIPPED
     #[serde(other)]
@@ -47,9 +50,9 @@ impl SpecName {
             Self::Berlin => SpecId::BERLIN,
             Self::London | Self::BerlinToLondonAt5 => SpecId::LONDON,
             Self::Paris | Self::Merge => SpecId::MERGE,
-            Self::Shanghai => SpecId::SHANGHAI,
-            Self::Cancun => SpecId::CANCUN,
-            Self::Prague => SpecId::PRAGUE,
+            Self::Shanghai | Self::ParisToShanghaiAtTime15k => SpecId::SHANGHAI,
+            Self::Cancun | Self::ShanghaiToCancunAtTime15k => SpecId::CANCUN,
+            Self::Prague | Self::CancunToPragueAtTime15k => SpecId::PRAGUE,
             Self::Osaka => SpecId::OSAKA,
             Self::ByzantiumToConstantinopleAt5 | Self::Constantinople => {
                 panic!("Overridden with PETERSBURG")
@@ -57,4 +60,48 @@ impl SpecName {
             Self::Unknown => panic!("Unknown spec"),
         }
     }
+
+    /// Converts to the [SpecId] active at the given block.
+    ///
+    /// Transition networks switch to the later fork at block 5 or at timestamp 15000, other
+    /// networks return [`to_spec_id`](Self::to_spec_id).
+    pub fn spec_at(&self, number: u64, timestamp: u64) -> SpecId {
+        let (before, after, activated) = match self {
+            Self::FrontierToHomesteadAt5 => (SpecId::FRONTIER, SpecId::HOMESTEAD, number >= 5),
+            Self::HomesteadToEIP150At5 => (SpecId::HOMESTEAD, SpecId::TANGERINE, number >= 5),
+            Self::EIP158ToByzantiumAt5 => (SpecId::SPURIOUS_DRAGON, SpecId::BYZANTIUM, number >= 5),
+            Self::ByzantiumToConstantinopleFixAt5 => {
+                (SpecId::BYZANTIUM, SpecId::PETERSBURG, number >= 5)
+            }
+            Self::BerlinToLondonAt5 => (SpecId::BERLIN, SpecId::LONDON, number >= 5),
+            Self::ParisToShanghaiAtTime15k => {
+                (SpecId::MERGE, SpecId::SHANGHAI, timestamp >= 15_000)
+            }
+            Self::ShanghaiToCancunAtTime15k => {
+                (SpecId::SHANGHAI, SpecId::CANCUN, timestamp >= 15_000)
+            }
+            Self::CancunToPragueAtTime15k => (SpecId::CANCUN, SpecId::PRAGUE, timestamp >= 15_000),
+            _ => return self.to_spec_id(),
+        };
+        if activated {
+            after
+        } else {
+            before
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transition_networks() {
+        let spec: SpecName = serde_json::from_str("\"ShanghaiToCancunAtTime15k\"").unwrap();
+        assert_eq!(spec.spec_at(1, 14_999), SpecId::SHANGHAI);
+        assert_eq!(spec.spec_at(2, 15_000), SpecId::CANCUN);
+        assert_eq!(SpecName::BerlinToLondonAt5.spec_at(4, 0), SpecId::BERLIN);
+        assert_eq!(SpecName::BerlinToLondonAt5.spec_at(5, 0), SpecId::LONDON);
+        assert_eq!(SpecName::Prague.spec_at(0, 0), SpecId::PRAGUE);
+    }
 }
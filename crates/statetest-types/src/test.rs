@@ -20,11 +20,9 @@ pub struct Test {
     /// Logs root
     pub logs: B256,
 
-    /// Output state.
-    ///
-    /// Note: Not used.
+    /// Expected accounts after the execution, empty if the test only has the state hash.
     #[serde(default)]
-    state: HashMap<Address, AccountInfo>,
+    pub state: HashMap<Address, AccountInfo>,
 
     /// Tx bytes
     pub txbytes: Option<Bytes>,
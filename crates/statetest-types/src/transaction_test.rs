@@ -0,0 +1,68 @@
+use revm::primitives::{Address, Bytes, B256, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::SpecName;
+
+/// The top level transaction test suite struct
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct TransactionTestSuite(pub BTreeMap<String, TransactionTestUnit>);
+
+/// Single transaction test, the expected outcome of decoding a transaction in every fork.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+pub struct TransactionTestUnit {
+    /// Expected outcome by fork.
+    pub result: BTreeMap<SpecName, TransactionTestResult>,
+    /// EIP-2718 encoded transaction.
+    pub txbytes: Bytes,
+}
+
+/// Expected outcome of a transaction test in a fork.
+///
+/// Valid transactions have a hash and a sender, invalid ones an exception.
+#[derive(Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionTestResult {
+    pub hash: Option<B256>,
+    pub intrinsic_gas: Option<U256>,
+    pub sender: Option<Address>,
+    pub exception: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_transaction_test() {
+        let suite: TransactionTestSuite = serde_json::from_str(
+            r#"{
+                "test": {
+                    "_info": {},
+                    "result": {
+                        "Berlin": {
+                            "hash": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                            "intrinsicGas": "0x5208",
+                            "sender": "0x0000000000000000000000000000000000000002"
+                        },
+                        "Frontier": {
+                            "exception": "TR_TypeNotSupported"
+                        }
+                    },
+                    "txbytes": "0x01c0"
+                }
+            }"#,
+        )
+        .unwrap();
+        let unit = &suite.0["test"];
+        assert_eq!(unit.txbytes, Bytes::from_static(&[0x01, 0xc0]));
+        assert_eq!(
+            unit.result[&SpecName::Berlin].intrinsic_gas,
+            Some(U256::from(21_000))
+        );
+        assert_eq!(
+            unit.result[&SpecName::Frontier].exception.as_deref(),
+            Some("TR_TypeNotSupported")
+        );
+    }
+}
@@ -237,6 +237,7 @@ impl Journal for Backend {
             spec: _,
             warm_preloaded_addresses: _,
             precompiles: _,
+            analysis_cache: _,
         } = &mut self.journaled_state;
 
         *transient_storage = TransientStorage::default();
@@ -0,0 +1,128 @@
+//! Registers a custom opcode, then disassembles, executes and traces bytecode that uses it.
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+
+use anyhow::bail;
+use database::{BenchmarkDB, EEADDRESS, FFADDRESS};
+use inspector::{
+    inspector_context::InspectorContext, inspector_instruction::InspectorInstructionExecutor,
+    inspectors::TracerEip3155, journal::JournalExtGetter, InspectorCtx, InspectorHandlerImpl,
+};
+use revm::{
+    bytecode::{
+        disassembler::Disassembly,
+        opcode::{custom::CustomOpCodes, immediate_size, stack_io, OpCodeInfo},
+        opcode::{JUMPDEST, PUSH0, SSTORE, STOP},
+        Bytecode,
+    },
+    context_interface::{
+        result::{EVMError, ExecutionResult, HaltReason, InvalidTransaction, ResultAndState},
+        DatabaseGetter,
+    },
+    database_interface::Database,
+    handler::{
+        instructions::{CustomInstructions, EthInstructionExecutor},
+        EthContext, EthFrame, EthHandler, EthPrecompileProvider, MainnetHandler,
+    },
+    interpreter::{
+        gas,
+        interpreter::EthInterpreter,
+        interpreter_types::{Immediates, Jumps, LoopControl},
+        table::make_instruction_table,
+        Host, InstructionResult, Interpreter,
+    },
+    primitives::{TxKind, U256},
+    Context,
+};
+use std::rc::Rc;
+
+/// Opcode of `PUSHB`, not used by any builtin opcode.
+const PUSHB: u8 = 0x0C;
+
+type EvmResult<DB> =
+    Result<ResultAndState<HaltReason>, EVMError<<DB as Database>::Error, InvalidTransaction>>;
+
+/// Information of `PUSHB`, it pushes its one byte immediate.
+fn pushb_info() -> OpCodeInfo {
+    stack_io(immediate_size(OpCodeInfo::new("PUSHB"), 1), 0, 1)
+}
+
+/// Instruction of `PUSHB`, its static gas is charged before it is called.
+fn pushb<H: Host>(interpreter: &mut Interpreter<EthInterpreter>, _host: &mut H) {
+    let value = interpreter.bytecode.read_u8();
+    interpreter.bytecode.relative_jump(1);
+    if !interpreter.stack.push(U256::from(value)) {
+        interpreter
+            .control
+            .set_instruction_result(InstructionResult::StackOverflow);
+    }
+}
+
+/// Executes the transaction with `PUSHB` registered.
+fn execute<CTX: EthContext>(ctx: &mut CTX) -> EvmResult<<CTX as DatabaseGetter>::Database> {
+    let mut executor = EthInstructionExecutor::new();
+    executor
+        .register_opcode(PUSHB, pushb_info(), gas::VERYLOW, pushb::<CTX>)
+        .expect("PUSHB is not a builtin opcode");
+    MainnetHandler::<_, _, EthFrame<CTX, _, _, _>, EthPrecompileProvider<CTX, _>, _>::new(executor)
+        .run(ctx)
+}
+
+/// Inspects the transaction with `PUSHB` registered.
+///
+/// The inspector runs its own instruction table, so the opcode is registered again.
+fn inspect<
+    DB: Database,
+    CTX: EthContext
+        + JournalExtGetter
+        + DatabaseGetter<Database = DB>
+        + InspectorCtx<IT = EthInterpreter>,
+>(
+    ctx: &mut CTX,
+) -> EvmResult<DB> {
+    let mut custom = CustomInstructions::default();
+    custom
+        .register(PUSHB, pushb_info(), gas::VERYLOW, pushb::<CTX>)
+        .expect("PUSHB is not a builtin opcode");
+    InspectorHandlerImpl::<_, _, EthFrame<_, _, _, _>, _, _, EthInterpreter>::new(
+        MainnetHandler::<_, _, _, _, InspectorInstructionExecutor<EthInterpreter, CTX>>::default(),
+        make_instruction_table(),
+    )
+    .with_custom_instructions(Rc::new(custom))
+    .run(ctx)
+}
+
+fn main() -> anyhow::Result<()> {
+    // PUSHB 0x5b, PUSH0, SSTORE, STOP. The immediate of `PUSHB` is not a `JUMPDEST`.
+    let bytecode = Bytecode::new_legacy([PUSHB, JUMPDEST, PUSH0, SSTORE, STOP].into());
+    let mut ctx = Context::default()
+        .with_db(BenchmarkDB::new_bytecode(bytecode.clone()))
+        .modify_tx_chained(|tx| {
+            tx.gas_limit = 100_000;
+            tx.caller = EEADDRESS;
+            tx.kind = TxKind::Call(FFADDRESS);
+        });
+
+    let mut custom_opcodes = CustomOpCodes::new();
+    custom_opcodes.register(PUSHB, pushb_info())?;
+
+    println!("Disassembly:");
+    let disassembly = Disassembly::legacy_with_custom(bytecode.original_bytes(), &custom_opcodes);
+    print!("{disassembly}");
+
+    let ResultAndState { result, state } = execute(&mut ctx)?;
+    let ExecutionResult::Success { gas_used, .. } = result else {
+        bail!("Execution failed: {result:#?}");
+    };
+    println!(
+        "Stored {} using {gas_used} gas",
+        state[&FFADDRESS].storage[&U256::ZERO].present_value()
+    );
+
+    println!("Trace:");
+    let tracer = TracerEip3155::new(Box::new(std::io::stdout()))
+        .without_summary()
+        .with_custom_opcodes(custom_opcodes);
+    inspect(&mut InspectorContext::new(&mut ctx, tracer))?;
+
+    Ok(())
+}